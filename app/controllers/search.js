@@ -30,6 +30,7 @@ export default class SearchController extends Controller {
   }
 
   @reads('model.meta.total') totalItems;
+  @reads('model.meta.suggestions') suggestions;
 
   @pagination() pagination;
 
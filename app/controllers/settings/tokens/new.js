@@ -23,7 +23,15 @@ export default class NewTokenController extends Controller {
   @tracked scopesInvalid;
   @tracked crateScopes;
 
-  ENDPOINT_SCOPES = ['change-owners', 'publish-new', 'publish-update', 'yank'];
+  ENDPOINT_SCOPES = [
+    'change-owners',
+    'follow',
+    'manage-tokens',
+    'publish-new',
+    'publish-update',
+    'read-private',
+    'yank',
+  ];
 
   scopeDescription = scopeDescription;
 
@@ -154,7 +162,7 @@ class CratePattern {
   }
 
   get hasWildcard() {
-    return this.pattern.endsWith('*');
+    return this.pattern.split('@')[0].endsWith('*');
   }
 
   get description() {
@@ -188,6 +196,15 @@ function isValidIdent(pattern) {
 
 function isValidPattern(pattern) {
   if (!pattern) return false;
+
+  // Patterns can be restricted to some versions with a `@<version requirement>`
+  // or `@prerelease` suffix.
+  let versionsIndex = pattern.indexOf('@');
+  if (versionsIndex !== -1) {
+    let versions = pattern.slice(versionsIndex + 1);
+    return isValidVersions(versions) && isValidPattern(pattern.slice(0, versionsIndex));
+  }
+
   if (pattern === '*') return true;
 
   if (pattern.endsWith('*')) {
@@ -197,6 +214,13 @@ function isValidPattern(pattern) {
   return isValidIdent(pattern);
 }
 
+const VERSION_COMPARATOR = /^\s*(?:[=<>~^]|>=|<=)?\s*(?:\d+|[*xX])(?:\.(?:\d+|[*xX])){0,2}(?:-[\dA-Za-z.-]+)?(?:\+[\dA-Za-z.-]+)?\s*$/;
+
+function isValidVersions(versions) {
+  if (versions === 'prerelease') return true;
+  return versions.split(',').every(comparator => VERSION_COMPARATOR.test(comparator));
+}
+
 function isAsciiAlphanumeric(c) {
   return (c >= '0' && c <= '9') || (c >= 'A' && c <= 'Z') || (c >= 'a' && c <= 'z');
 }
@@ -2,8 +2,11 @@ import { htmlSafe } from '@ember/template';
 
 const DESCRIPTIONS = {
   'change-owners': 'Invite new crate owners or remove existing ones',
+  follow: 'Follow and unfollow crates',
+  'manage-tokens': 'List and revoke your API tokens',
   'publish-new': 'Publish new crates',
   'publish-update': 'Publish new versions of existing crates',
+  'read-private': 'Read your private account data, like your pending ownership invitations',
   yank: 'Yank and unyank crate versions',
 };
 
@@ -12,6 +15,17 @@ export function scopeDescription(scope) {
 }
 
 export function patternDescription(pattern) {
+  let versionsIndex = pattern.indexOf('@');
+  if (versionsIndex !== -1) {
+    let cratesDescription = patternDescription(pattern.slice(0, versionsIndex));
+    let versions = pattern.slice(versionsIndex + 1);
+    let versionsDescription =
+      versions === 'prerelease'
+        ? 'but only their prerelease versions'
+        : `but only versions matching <strong>${escapeHtml(versions)}</strong>`;
+    return htmlSafe(`${cratesDescription}, ${versionsDescription}`);
+  }
+
   if (pattern === '*') {
     return 'Matches all crates on crates.io';
   } else if (pattern.endsWith('*')) {
@@ -20,3 +34,7 @@ export function patternDescription(pattern) {
     return htmlSafe(`Matches only the <strong>${pattern}</strong> crate`);
   }
 }
+
+function escapeHtml(text) {
+  return text.replaceAll('&', '&amp;').replaceAll('<', '&lt;').replaceAll('>', '&gt;');
+}
@@ -24,6 +24,10 @@ pub struct Crate {
     pub links: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub rust_version: Option<String>,
+    /// The SHA256 checksum of the signed release attestation that was
+    /// uploaded together with the `.crate` file, if there is one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attestation: Option<String>,
     /// The schema version for this entry.
     ///
     /// If this is None, it defaults to version 1. Entries with unknown
@@ -16,8 +16,13 @@ impl<'a> MarkdownRenderer<'a> {
     /// Creates a new renderer instance.
     ///
     /// Per `text_to_html`, `base_url` is the base URL prepended to any
-    /// relative links in the input document.  See that function for more detail.
-    fn new(base_url: Option<&'a str>, base_dir: &'a str) -> MarkdownRenderer<'a> {
+    /// relative links in the input document and `vcs_revision` is the commit
+    /// these links are resolved against.  See that function for more detail.
+    fn new(
+        base_url: Option<&'a str>,
+        base_dir: &'a str,
+        vcs_revision: Option<&'a str>,
+    ) -> MarkdownRenderer<'a> {
         let allowed_classes = hashmap(&[
             (
                 "code",
@@ -42,7 +47,8 @@ impl<'a> MarkdownRenderer<'a> {
             ),
             ("section", hashset(&["footnotes"])),
         ]);
-        let sanitize_url = UrlRelative::Custom(Box::new(SanitizeUrl::new(base_url, base_dir)));
+        let sanitize_url =
+            UrlRelative::Custom(Box::new(SanitizeUrl::new(base_url, base_dir, vcs_revision)));
 
         let mut html_sanitizer = Builder::default();
         html_sanitizer
@@ -133,10 +139,11 @@ fn canon_base_url(mut base_url: String) -> String {
 struct SanitizeUrl {
     base_url: Option<String>,
     base_dir: String,
+    revision: String,
 }
 
 impl SanitizeUrl {
-    fn new(base_url: Option<&str>, base_dir: &str) -> Self {
+    fn new(base_url: Option<&str>, base_dir: &str, vcs_revision: Option<&str>) -> Self {
         let base_url = base_url
             .and_then(|base_url| Url::parse(base_url).ok())
             .and_then(|url| match url.host_str() {
@@ -145,9 +152,16 @@ impl SanitizeUrl {
                 }
                 _ => None,
             });
+        // Only accept commit hashes, so that the revision can't be used to
+        // inject arbitrary path segments into the URL.
+        let revision = vcs_revision
+            .filter(|rev| !rev.is_empty() && rev.chars().all(|c| c.is_ascii_hexdigit()))
+            .unwrap_or("HEAD");
+
         Self {
             base_url,
             base_dir: base_dir.to_owned(),
+            revision: revision.to_owned(),
         }
     }
 }
@@ -212,7 +226,8 @@ impl UrlRelativeEvaluate for SanitizeUrl {
                 is_media,
                 add_sanitize_query,
             } = is_media_url(url);
-            new_url += if is_media { "raw/HEAD" } else { "blob/HEAD" };
+            new_url += if is_media { "raw/" } else { "blob/" };
+            new_url += &self.revision;
             if !self.base_dir.is_empty() {
                 new_url += "/";
                 new_url += &self.base_dir;
@@ -233,9 +248,14 @@ impl UrlRelativeEvaluate for SanitizeUrl {
 }
 
 /// Renders Markdown text to sanitized HTML with a given `base_url`.
-/// See `text_to_html` for the interpretation of `base_url`.
-fn markdown_to_html(text: &str, base_url: Option<&str>, base_dir: &str) -> String {
-    let renderer = MarkdownRenderer::new(base_url, base_dir);
+/// See `text_to_html` for the interpretation of `base_url` and `vcs_revision`.
+fn markdown_to_html(
+    text: &str,
+    base_url: Option<&str>,
+    base_dir: &str,
+    vcs_revision: Option<&str>,
+) -> String {
+    let renderer = MarkdownRenderer::new(base_url, base_dir, vcs_revision);
     renderer.to_html(text)
 }
 
@@ -255,13 +275,17 @@ static MARKDOWN_EXTENSIONS: [&str; 7] =
 /// supplied URL will be used as a directory base whether or not the relative link is
 /// prefixed with '/'.  If `None` is passed, relative links will be omitted.
 ///
+/// The `vcs_revision` parameter is the commit hash the package was published from, as
+/// recorded in the `.cargo_vcs_info.json` file.  If it is given, relative links will point
+/// to this exact commit instead of the default branch (`HEAD`) of the repository.
+///
 /// # Examples
 ///
 /// ```
 /// use crates_io_markdown::text_to_html;
 ///
 /// let text = "[Rust](https://rust-lang.org/) is an awesome *systems programming* language!";
-/// let rendered = text_to_html(text, "README.md", None, None);
+/// let rendered = text_to_html(text, "README.md", None, None, None);
 /// assert_eq!(rendered, "<p><a href=\"https://rust-lang.org/\" rel=\"nofollow noopener noreferrer\">Rust</a> is an awesome <em>systems programming</em> language!</p>\n");
 /// ```
 pub fn text_to_html<P: AsRef<Path>>(
@@ -269,6 +293,7 @@ pub fn text_to_html<P: AsRef<Path>>(
     readme_path_in_pkg: P,
     base_url: Option<&str>,
     pkg_path_in_vcs: Option<P>,
+    vcs_revision: Option<&str>,
 ) -> String {
     let path_in_vcs = match pkg_path_in_vcs {
         None => readme_path_in_pkg.as_ref().to_path_buf(),
@@ -278,12 +303,12 @@ pub fn text_to_html<P: AsRef<Path>>(
     let base_dir = path_in_vcs.parent().and_then(|p| p.to_str()).unwrap_or("");
 
     if path_in_vcs.extension().is_none() {
-        return markdown_to_html(text, base_url, base_dir);
+        return markdown_to_html(text, base_url, base_dir, vcs_revision);
     }
 
     if let Some(ext) = path_in_vcs.extension().and_then(|ext| ext.to_str()) {
         if MARKDOWN_EXTENSIONS.contains(&ext.to_lowercase().as_str()) {
-            return markdown_to_html(text, base_url, base_dir);
+            return markdown_to_html(text, base_url, base_dir, vcs_revision);
         }
     }
 
@@ -315,13 +340,13 @@ mod tests {
     #[test]
     fn empty_text() {
         let text = "";
-        assert_eq!(markdown_to_html(text, None, ""), "");
+        assert_eq!(markdown_to_html(text, None, "", None), "");
     }
 
     #[test]
     fn text_with_script_tag() {
         let text = "foo_readme\n\n<script>alert('Hello World')</script>";
-        assert_snapshot!(markdown_to_html(text, None, ""), @r###"
+        assert_snapshot!(markdown_to_html(text, None, "", None), @r###"
         <p>foo_readme</p>
         &lt;script&gt;alert('Hello World')&lt;/script&gt;
         "###);
@@ -330,7 +355,7 @@ mod tests {
     #[test]
     fn text_with_iframe_tag() {
         let text = "foo_readme\n\n<iframe>alert('Hello World')</iframe>";
-        assert_snapshot!(markdown_to_html(text, None, ""), @r###"
+        assert_snapshot!(markdown_to_html(text, None, "", None), @r###"
         <p>foo_readme</p>
         &lt;iframe&gt;alert('Hello World')&lt;/iframe&gt;
         "###);
@@ -339,7 +364,7 @@ mod tests {
     #[test]
     fn text_with_unknown_tag() {
         let text = "foo_readme\n\n<unknown>alert('Hello World')</unknown>";
-        assert_snapshot!(markdown_to_html(text, None, ""), @r###"
+        assert_snapshot!(markdown_to_html(text, None, "", None), @r###"
         <p>foo_readme</p>
         <p>alert('Hello World')</p>
         "###);
@@ -348,7 +373,7 @@ mod tests {
     #[test]
     fn text_with_kbd_tag() {
         let text = "foo_readme\n\nHello <kbd>alert('Hello World')</kbd>";
-        assert_snapshot!(markdown_to_html(text, None, ""), @r###"
+        assert_snapshot!(markdown_to_html(text, None, "", None), @r###"
         <p>foo_readme</p>
         <p>Hello <kbd>alert('Hello World')</kbd></p>
         "###);
@@ -357,7 +382,7 @@ mod tests {
     #[test]
     fn text_with_inline_javascript() {
         let text = r#"foo_readme\n\n<a href="https://crates.io/crates/cargo-registry" onclick="window.alert('Got you')">Crate page</a>"#;
-        assert_snapshot!(markdown_to_html(text, None, ""), @r###"
+        assert_snapshot!(markdown_to_html(text, None, "", None), @r###"
         <p>foo_readme\n\n<a href="https://crates.io/crates/cargo-registry" rel="nofollow noopener noreferrer">Crate page</a></p>
         "###);
     }
@@ -367,7 +392,7 @@ mod tests {
     #[test]
     fn text_with_fancy_single_quotes() {
         let text = "wb’";
-        assert_snapshot!(markdown_to_html(text, None, ""), @r###"
+        assert_snapshot!(markdown_to_html(text, None, "", None), @r###"
         <p>wb’</p>
         "###);
     }
@@ -375,7 +400,7 @@ mod tests {
     #[test]
     fn code_block_with_syntax_highlighting() {
         let code_block = "```rust\nprintln!(\"Hello World\");\n```";
-        assert_snapshot!(markdown_to_html(code_block, None, ""), @r###"
+        assert_snapshot!(markdown_to_html(code_block, None, "", None), @r###"
         <pre><code class="language-rust">println!("Hello World");
         </code></pre>
         "###);
@@ -384,7 +409,7 @@ mod tests {
     #[test]
     fn code_block_with_mermaid_highlighting() {
         let code_block = "```mermaid\ngraph LR\nA --> C\nC --> A\n```";
-        assert_snapshot!(markdown_to_html(code_block, None, ""), @r###"
+        assert_snapshot!(markdown_to_html(code_block, None, "", None), @r###"
         <pre><code class="language-mermaid">graph LR
         A --&gt; C
         C --&gt; A
@@ -395,7 +420,7 @@ mod tests {
     #[test]
     fn code_block_with_syntax_highlighting_even_if_annot_has_no_run() {
         let code_block = "```rust, no_run\nprintln!(\"Hello World\");\n```";
-        assert_snapshot!(markdown_to_html(code_block, None, ""), @r###"
+        assert_snapshot!(markdown_to_html(code_block, None, "", None), @r###"
         <pre><code class="language-rust">println!("Hello World");
         </code></pre>
         "###);
@@ -404,7 +429,7 @@ mod tests {
     #[test]
     fn text_with_forbidden_class_attribute() {
         let text = "<p class='bad-class'>Hello World!</p>";
-        assert_snapshot!(markdown_to_html(text, None, ""), @r###"
+        assert_snapshot!(markdown_to_html(text, None, "", None), @r###"
         <p>Hello World!</p>
         "###);
     }
@@ -412,7 +437,7 @@ mod tests {
     #[test]
     fn text_with_footnote() {
         let text = "Hello World![^1]\n\n[^1]: Hello Ferris, actually!";
-        assert_snapshot!(markdown_to_html(text, None, ""), @r###"
+        assert_snapshot!(markdown_to_html(text, None, "", None), @r###"
         <p>Hello World!<sup><a href="#user-content-fn-1" id="user-content-fnref-1" rel="nofollow noopener noreferrer">1</a></sup></p>
         <section class="footnotes">
         <ol>
@@ -440,7 +465,7 @@ There can also be some text in between!
 
     Add as many paragraphs as you like."#;
 
-        assert_snapshot!(markdown_to_html(text, None, ""), @r###"
+        assert_snapshot!(markdown_to_html(text, None, "", None), @r###"
         <p>Here's a simple footnote,<sup><a href="#user-content-fn-1" id="user-content-fnref-1" rel="nofollow noopener noreferrer">1</a></sup> and here's a longer one.<sup><a href="#user-content-fn-bignote" id="user-content-fnref-bignote" rel="nofollow noopener noreferrer">2</a></sup></p>
         <p>There can also be some text in between!</p>
         <section class="footnotes">
@@ -476,7 +501,7 @@ There can also be some text in between!
                     if extra_slash { "/" } else { "" },
                 );
 
-                let result = markdown_to_html(absolute, Some(&url), "");
+                let result = markdown_to_html(absolute, Some(&url), "", None);
                 assert_eq!(
                     result,
                     format!(
@@ -484,7 +509,7 @@ There can also be some text in between!
                     )
                 );
 
-                let result = markdown_to_html(relative, Some(&url), "");
+                let result = markdown_to_html(relative, Some(&url), "", None);
                 assert_eq!(
                     result,
                     format!(
@@ -492,7 +517,7 @@ There can also be some text in between!
                     )
                 );
 
-                let result = markdown_to_html(image, Some(&url), "");
+                let result = markdown_to_html(image, Some(&url), "", None);
                 assert_eq!(
                     result,
                     format!(
@@ -500,7 +525,7 @@ There can also be some text in between!
                     )
                 );
 
-                let result = markdown_to_html(html_image, Some(&url), "");
+                let result = markdown_to_html(html_image, Some(&url), "", None);
                 assert_eq!(
                     result,
                     format!(
@@ -508,7 +533,7 @@ There can also be some text in between!
                     )
                 );
 
-                let result = markdown_to_html(svg, Some(&url), "");
+                let result = markdown_to_html(svg, Some(&url), "", None);
                 assert_eq!(
                     result,
                     format!(
@@ -516,7 +541,7 @@ There can also be some text in between!
                     )
                 );
 
-                let result = markdown_to_html(svg, Some(&url), "subdir");
+                let result = markdown_to_html(svg, Some(&url), "subdir", None);
                 assert_eq!(
                     result,
                     format!(
@@ -524,7 +549,7 @@ There can also be some text in between!
                     )
                 );
 
-                let result = markdown_to_html(svg, Some(&url), "subdir1/subdir2");
+                let result = markdown_to_html(svg, Some(&url), "subdir1/subdir2", None);
                 assert_eq!(
                     result,
                     format!(
@@ -534,19 +559,59 @@ There can also be some text in between!
             }
         }
 
-        let result = markdown_to_html(absolute, Some("https://google.com/"), "");
+        let result = markdown_to_html(absolute, Some("https://google.com/"), "", None);
         assert_eq!(
             result,
             "<p><a rel=\"nofollow noopener noreferrer\">hi</a></p>\n"
         );
     }
 
+    #[test]
+    fn relative_links_with_vcs_revision() {
+        let sha = "0123456789abcdef0123456789abcdef01234567";
+        let relative = "[there](there)";
+        let image = "![alt](img.png)";
+
+        for host in &["github.com", "gitlab.com", "bitbucket.org"] {
+            let url = format!("https://{host}/rust-lang/test");
+
+            let result = markdown_to_html(relative, Some(&url), "", Some(sha));
+            assert_eq!(
+                result,
+                format!(
+                    "<p><a href=\"https://{host}/rust-lang/test/blob/{sha}/there\" rel=\"nofollow noopener noreferrer\">there</a></p>\n"
+                )
+            );
+
+            let result = markdown_to_html(image, Some(&url), "subdir", Some(sha));
+            assert_eq!(
+                result,
+                format!(
+                    "<p><img src=\"https://{host}/rust-lang/test/raw/{sha}/subdir/img.png\" alt=\"alt\"></p>\n",
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn relative_links_with_invalid_vcs_revision() {
+        let url = "https://github.com/rust-lang/test";
+
+        for revision in ["", "../../evil", "main?x=y"] {
+            let result = markdown_to_html("[there](there)", Some(url), "", Some(revision));
+            assert_eq!(
+                result,
+                "<p><a href=\"https://github.com/rust-lang/test/blob/HEAD/there\" rel=\"nofollow noopener noreferrer\">there</a></p>\n"
+            );
+        }
+    }
+
     #[test]
     fn absolute_links_dont_get_resolved() {
         let text =
             "[![crates.io](https://img.shields.io/crates/v/clap.svg)](https://crates.io/crates/clap)";
         let repository = "https://github.com/kbknapp/clap-rs/";
-        assert_snapshot!(markdown_to_html(text, Some(repository), ""), @r###"
+        assert_snapshot!(markdown_to_html(text, Some(repository), "", None), @r###"
         <p><a href="https://crates.io/crates/clap" rel="nofollow noopener noreferrer"><img src="https://img.shields.io/crates/v/clap.svg" alt="crates.io"></a></p>
         "###);
     }
@@ -555,11 +620,11 @@ There can also be some text in between!
     fn rustdoc_links() {
         let repository = "https://github.com/foo/bar/";
 
-        assert_snapshot!(markdown_to_html("[stylish](::stylish)", Some(repository), ""), @r###"
+        assert_snapshot!(markdown_to_html("[stylish](::stylish)", Some(repository), "", None), @r###"
         <p><a rel="nofollow noopener noreferrer">stylish</a></p>
         "###);
 
-        assert_snapshot!(markdown_to_html("[Display](stylish::Display)", Some(repository), ""), @r###"
+        assert_snapshot!(markdown_to_html("[Display](stylish::Display)", Some(repository), "", None), @r###"
         <p><a rel="nofollow noopener noreferrer">Display</a></p>
         "###);
     }
@@ -575,33 +640,42 @@ There can also be some text in between!
             "s1/s2/readme.md",
         ] {
             assert_eq!(
-                text_to_html("*lobster*", f, None, None),
+                text_to_html("*lobster*", f, None, None, None),
                 "<p><em>lobster</em></p>\n"
             );
         }
 
-        assert_snapshot!(text_to_html("*[lobster](docs/lobster)*", "readme.md", Some("https://github.com/rust-lang/test"), None), @r###"
+        assert_snapshot!(text_to_html("*[lobster](docs/lobster)*", "readme.md", Some("https://github.com/rust-lang/test"), None, None), @r###"
         <p><em><a href="https://github.com/rust-lang/test/blob/HEAD/docs/lobster" rel="nofollow noopener noreferrer">lobster</a></em></p>
         "###);
-        assert_snapshot!(text_to_html("*[lobster](docs/lobster)*", "s/readme.md", Some("https://github.com/rust-lang/test"), None), @r###"
+        assert_snapshot!(text_to_html("*[lobster](docs/lobster)*", "s/readme.md", Some("https://github.com/rust-lang/test"), None, None), @r###"
         <p><em><a href="https://github.com/rust-lang/test/blob/HEAD/s/docs/lobster" rel="nofollow noopener noreferrer">lobster</a></em></p>
         "###);
-        assert_snapshot!(text_to_html("*[lobster](docs/lobster)*", "s1/s2/readme.md", Some("https://github.com/rust-lang/test"), None), @r###"
+        assert_snapshot!(text_to_html("*[lobster](docs/lobster)*", "s1/s2/readme.md", Some("https://github.com/rust-lang/test"), None, None), @r###"
         <p><em><a href="https://github.com/rust-lang/test/blob/HEAD/s1/s2/docs/lobster" rel="nofollow noopener noreferrer">lobster</a></em></p>
         "###);
-        assert_snapshot!(text_to_html("*[lobster](docs/lobster)*", "s1/s2/readme.md", Some("https://github.com/rust-lang/test"), Some("path/in/vcs/")), @r###"
+        assert_snapshot!(text_to_html("*[lobster](docs/lobster)*", "s1/s2/readme.md", Some("https://github.com/rust-lang/test"), Some("path/in/vcs/"), None), @r###"
         <p><em><a href="https://github.com/rust-lang/test/blob/HEAD/path/in/vcs/s1/s2/docs/lobster" rel="nofollow noopener noreferrer">lobster</a></em></p>
         "###);
-        assert_snapshot!(text_to_html("*[lobster](docs/lobster)*", "s1/s2/readme.md", Some("https://github.com/rust-lang/test"), Some("path/in/vcs")), @r###"
+        assert_snapshot!(text_to_html("*[lobster](docs/lobster)*", "s1/s2/readme.md", Some("https://github.com/rust-lang/test"), Some("path/in/vcs"), None), @r###"
         <p><em><a href="https://github.com/rust-lang/test/blob/HEAD/path/in/vcs/s1/s2/docs/lobster" rel="nofollow noopener noreferrer">lobster</a></em></p>
         "###);
+        assert_snapshot!(text_to_html("*[lobster](docs/lobster)*", "s1/s2/readme.md", Some("https://github.com/rust-lang/test"), Some("path/in/vcs"), Some("0123456789abcdef0123456789abcdef01234567")), @r###"
+        <p><em><a href="https://github.com/rust-lang/test/blob/0123456789abcdef0123456789abcdef01234567/path/in/vcs/s1/s2/docs/lobster" rel="nofollow noopener noreferrer">lobster</a></em></p>
+        "###);
     }
 
     #[test]
     fn text_to_html_renders_other_things() {
         for f in &["readme.exe", "readem.org", "blah.adoc"] {
             assert_eq!(
-                text_to_html("<script>lobster</script>\n\nis my friend\n", f, None, None),
+                text_to_html(
+                    "<script>lobster</script>\n\nis my friend\n",
+                    f,
+                    None,
+                    None,
+                    None
+                ),
                 "&lt;script&gt;lobster&lt;/script&gt;<br>\n<br>\nis my friend<br>\n"
             );
         }
@@ -610,7 +684,7 @@ There can also be some text in between!
     #[test]
     fn header_has_tags() {
         let text = "# My crate\n\nHello, world!\n";
-        assert_snapshot!(markdown_to_html(text, None, ""), @r###"
+        assert_snapshot!(markdown_to_html(text, None, "", None), @r###"
         <h1><a href="#my-crate" id="user-content-my-crate" rel="nofollow noopener noreferrer"></a>My crate</h1>
         <p>Hello, world!</p>
         "###);
@@ -620,7 +694,7 @@ There can also be some text in between!
     fn manual_anchor_is_sanitized() {
         let text =
             "<h1><a href=\"#my-crate\" id=\"my-crate\"></a>My crate</h1>\n<p>Hello, world!</p>\n";
-        assert_snapshot!(markdown_to_html(text, None, ""), @r###"
+        assert_snapshot!(markdown_to_html(text, None, "", None), @r###"
         <h1><a href="#my-crate" id="user-content-my-crate" rel="nofollow noopener noreferrer"></a>My crate</h1>
         <p>Hello, world!</p>
         "###);
@@ -629,7 +703,7 @@ There can also be some text in between!
     #[test]
     fn tables_with_rowspan_and_colspan() {
         let text = "<table><tr><th rowspan=\"1\" colspan=\"2\">Target</th></tr></table>\n";
-        assert_snapshot!(markdown_to_html(text, None, ""), @r###"
+        assert_snapshot!(markdown_to_html(text, None, "", None), @r###"
         <table><tbody><tr><th rowspan="1" colspan="2">Target</th></tr></tbody></table>
         "###);
     }
@@ -637,7 +711,7 @@ There can also be some text in between!
     #[test]
     fn text_alignment() {
         let text = "<h1 align=\"center\">foo-bar</h1>\n<h5 align=\"center\">Hello World!</h5>\n";
-        assert_snapshot!(markdown_to_html(text, None, ""), @r###"
+        assert_snapshot!(markdown_to_html(text, None, "", None), @r###"
         <h1 align="center">foo-bar</h1>
         <h5 align="center">Hello World!</h5>
         "###);
@@ -647,7 +721,7 @@ There can also be some text in between!
     fn image_alignment() {
         let text =
             "<p align=\"center\"><img src=\"https://img.shields.io/crates/v/clap.svg\" alt=\"\"></p>\n";
-        assert_snapshot!(markdown_to_html(text, None, ""), @r###"
+        assert_snapshot!(markdown_to_html(text, None, "", None), @r###"
         <p align="center"><img src="https://img.shields.io/crates/v/clap.svg" alt=""></p>
         "###);
     }
@@ -0,0 +1,252 @@
+use crate::limit_reader::LimitErrorReader;
+use crate::TarballError;
+use flate2::read::GzDecoder;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::Path;
+use tracing::instrument;
+
+/// Lists the regular files of an already published tarball, as paths relative
+/// to the package root mapped to their uncompressed size in bytes.
+///
+/// In contrast to [`process_tarball`](crate::process_tarball) this does not
+/// validate the tarball, since it was already validated when it was published,
+/// and legacy tarballs might not pass the current validation rules anymore.
+#[instrument(skip_all, fields(%pkg_name))]
+pub fn list_files<R: Read>(
+    pkg_name: &str,
+    tarball: R,
+    max_unpack: u64,
+) -> Result<BTreeMap<String, u64>, TarballError> {
+    let decoder = LimitErrorReader::new(GzDecoder::new(tarball), max_unpack);
+    let mut archive = tar::Archive::new(decoder);
+
+    let pkg_root = Path::new(pkg_name);
+
+    let mut files = BTreeMap::new();
+    for entry in archive.entries()? {
+        let entry = entry.map_err(TarballError::Malformed)?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+
+        let entry_path = entry.path()?;
+        if let Ok(relative_path) = entry_path.strip_prefix(pkg_root) {
+            let relative_path = relative_path.to_string_lossy().into_owned();
+            files.insert(relative_path, entry.size());
+        }
+    }
+
+    Ok(files)
+}
+
+/// A regular file of an already published tarball.
+#[derive(Debug)]
+pub struct SourceFile {
+    /// The uncompressed size of the file in bytes.
+    pub size: u64,
+    /// The SHA256 checksum of the file contents.
+    pub checksum: [u8; 32],
+    /// The file contents, or `None` if the file is larger than the limit that
+    /// was passed to [`read_files`].
+    pub contents: Option<Vec<u8>>,
+}
+
+/// Reads all regular files of an already published tarball, as paths relative
+/// to the package root mapped to their size and contents.
+///
+/// The contents of files larger than `max_file_size` are not read, and
+/// tarballs with more than `max_files` regular files are rejected.
+#[instrument(skip_all, fields(%pkg_name))]
+pub fn read_files<R: Read>(
+    pkg_name: &str,
+    tarball: R,
+    max_unpack: u64,
+    max_file_size: u64,
+    max_files: usize,
+) -> Result<BTreeMap<String, SourceFile>, TarballError> {
+    let decoder = LimitErrorReader::new(GzDecoder::new(tarball), max_unpack);
+    let mut archive = tar::Archive::new(decoder);
+
+    let pkg_root = Path::new(pkg_name);
+
+    let mut files = BTreeMap::new();
+    for entry in archive.entries()? {
+        let mut entry = entry.map_err(TarballError::Malformed)?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+
+        let entry_path = entry.path()?;
+        let Ok(relative_path) = entry_path.strip_prefix(pkg_root) else {
+            continue;
+        };
+        let relative_path = relative_path.to_string_lossy().into_owned();
+
+        if files.len() >= max_files {
+            return Err(TarballError::TooManyFiles(max_files));
+        }
+
+        // The checksum is calculated for all files, so that changes to large
+        // files can be detected without keeping their contents in memory.
+        let size = entry.size();
+        let mut hasher = Sha256::new();
+        let mut contents = (size <= max_file_size).then(|| Vec::with_capacity(size as usize));
+
+        let mut buffer = [0; 8192];
+        loop {
+            let len = entry.read(&mut buffer).map_err(TarballError::Malformed)?;
+            if len == 0 {
+                break;
+            }
+
+            hasher.update(&buffer[..len]);
+            if let Some(contents) = &mut contents {
+                contents.extend_from_slice(&buffer[..len]);
+            }
+        }
+
+        let checksum = hasher.finalize().into();
+        let file = SourceFile {
+            size,
+            checksum,
+            contents,
+        };
+
+        files.insert(relative_path, file);
+    }
+
+    Ok(files)
+}
+
+/// Reads the contents of a single regular file from an already published
+/// tarball.
+///
+/// The `path` is relative to the package root. `Ok(None)` is returned if the
+/// tarball does not contain a regular file at that path.
+#[instrument(skip(tarball, max_unpack))]
+pub fn read_file<R: Read>(
+    pkg_name: &str,
+    tarball: R,
+    path: &str,
+    max_unpack: u64,
+) -> Result<Option<Vec<u8>>, TarballError> {
+    let decoder = LimitErrorReader::new(GzDecoder::new(tarball), max_unpack);
+    let mut archive = tar::Archive::new(decoder);
+    let mut entries = archive.entries()?;
+
+    let path = Path::new(pkg_name).join(path);
+    let Some(mut entry) = find_entry_by_path(&mut entries, &path) else {
+        return Ok(None);
+    };
+
+    if !entry.header().entry_type().is_file() {
+        return Ok(None);
+    }
+
+    let mut contents = Vec::with_capacity(entry.size() as usize);
+    entry
+        .read_to_end(&mut contents)
+        .map_err(TarballError::Malformed)?;
+
+    Ok(Some(contents))
+}
+
+/// Search an entry by its path in a tar archive.
+///
+/// Entries that can't be read are skipped, which makes this usable for legacy
+/// tarballs that contain e.g. invalid headers.
+pub fn find_entry_by_path<'a, R: Read>(
+    entries: &mut tar::Entries<'a, R>,
+    path: &Path,
+) -> Option<tar::Entry<'a, R>> {
+    entries
+        .filter_map(|entry| entry.ok())
+        .find(|file| match file.path() {
+            Ok(p) => p == path,
+            Err(_) => false,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{list_files, read_file, read_files};
+    use crate::TarballBuilder;
+    use sha2::{Digest, Sha256};
+
+    const LIMIT: u64 = 512 * 1024 * 1024;
+
+    fn tarball() -> Vec<u8> {
+        TarballBuilder::new()
+            .add_file("foo-0.0.1/Cargo.toml", b"[package]\nname = \"foo\"\n")
+            .add_file("foo-0.0.1/src/lib.rs", b"pub fn foo() {}\n")
+            .add_file("foo-0.0.1/README.md", b"# foo\n")
+            .build()
+    }
+
+    #[test]
+    fn list_files_test() {
+        let tarball = tarball();
+        let files = assert_ok!(list_files("foo-0.0.1", &*tarball, LIMIT));
+        let files = files
+            .iter()
+            .map(|(path, size)| (path.as_str(), *size))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            files,
+            vec![("Cargo.toml", 23), ("README.md", 6), ("src/lib.rs", 16)]
+        );
+    }
+
+    #[test]
+    fn list_files_test_unpack_limit() {
+        let tarball = tarball();
+        assert_err!(list_files("foo-0.0.1", &*tarball, 100));
+    }
+
+    #[test]
+    fn read_files_test() {
+        let tarball = tarball();
+        let files = assert_ok!(read_files("foo-0.0.1", &*tarball, LIMIT, 10, 10));
+
+        // Checksums are also calculated for files that are too large to be read
+        let manifest = assert_some!(files.get("Cargo.toml"));
+        let expected: [u8; 32] = Sha256::digest(b"[package]\nname = \"foo\"\n").into();
+        assert_eq!(manifest.checksum, expected);
+
+        let files = files
+            .iter()
+            .map(|(path, file)| (path.as_str(), file.size, file.contents.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            files,
+            vec![
+                ("Cargo.toml", 23, None),
+                ("README.md", 6, Some(&b"# foo\n"[..])),
+                ("src/lib.rs", 16, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn read_files_test_file_limit() {
+        let tarball = tarball();
+        assert_ok!(read_files("foo-0.0.1", &*tarball, LIMIT, 10, 3));
+        assert_err!(read_files("foo-0.0.1", &*tarball, LIMIT, 10, 2));
+    }
+
+    #[test]
+    fn read_file_test() {
+        let tarball = tarball();
+
+        let contents = assert_ok!(read_file("foo-0.0.1", &*tarball, "src/lib.rs", LIMIT));
+        assert_some_eq!(contents, b"pub fn foo() {}\n");
+
+        let contents = assert_ok!(read_file("foo-0.0.1", &*tarball, "src/main.rs", LIMIT));
+        assert_none!(contents);
+
+        let contents = assert_ok!(read_file("foo-0.0.1", &*tarball, "src", LIMIT));
+        assert_none!(contents);
+    }
+}
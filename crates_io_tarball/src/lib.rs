@@ -4,9 +4,10 @@ extern crate claims;
 
 #[cfg(any(feature = "builder", test))]
 pub use crate::builder::TarballBuilder;
+pub use crate::files::{find_entry_by_path, list_files, read_file, read_files, SourceFile};
 use crate::limit_reader::LimitErrorReader;
 use crate::manifest::validate_manifest;
-pub use crate::vcs_info::CargoVcsInfo;
+pub use crate::vcs_info::{CargoVcsGitInfo, CargoVcsInfo};
 pub use cargo_manifest::{Manifest, StringOrBool};
 use flate2::read::GzDecoder;
 use std::collections::BTreeMap;
@@ -17,6 +18,7 @@ use tracing::instrument;
 
 #[cfg(any(feature = "builder", test))]
 mod builder;
+mod files;
 mod limit_reader;
 mod manifest;
 mod vcs_info;
@@ -25,6 +27,9 @@ mod vcs_info;
 pub struct TarballInfo {
     pub manifest: Manifest,
     pub vcs_info: Option<CargoVcsInfo>,
+    /// Files in the package, as paths relative to the package root
+    /// mapped to their uncompressed size in bytes.
+    pub files: BTreeMap<String, u64>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -43,6 +48,8 @@ pub enum TarballError {
     IncorrectlyCasedManifest(PathBuf),
     #[error("more than one Cargo.toml manifest in tarball: {0:?}")]
     TooManyManifests(Vec<PathBuf>),
+    #[error("tarball contains more than {0} files")]
+    TooManyFiles(usize),
     #[error(transparent)]
     IO(#[from] std::io::Error),
 }
@@ -67,6 +74,7 @@ pub fn process_tarball<R: Read>(
 
     let mut vcs_info = None;
     let mut manifests = BTreeMap::new();
+    let mut files = BTreeMap::new();
 
     for entry in archive.entries()? {
         let mut entry = entry.map_err(TarballError::Malformed)?;
@@ -93,6 +101,13 @@ pub fn process_tarball<R: Read>(
             ));
         }
 
+        if entry_type.is_file() {
+            if let Ok(relative_path) = entry_path.strip_prefix(pkg_root) {
+                let relative_path = relative_path.to_string_lossy().into_owned();
+                files.insert(relative_path, entry.size());
+            }
+        }
+
         // Let's go hunting for the VCS info and crate manifest. The only valid place for these is
         // in the package root in the tarball.
         if entry_path.parent() == Some(pkg_root) {
@@ -136,7 +151,11 @@ pub fn process_tarball<R: Read>(
         return Err(TarballError::IncorrectlyCasedManifest(file.into()));
     }
 
-    Ok(TarballInfo { manifest, vcs_info })
+    Ok(TarballInfo {
+        manifest,
+        vcs_info,
+        files,
+    })
 }
 
 #[cfg(test)]
@@ -157,6 +176,11 @@ mod tests {
 
         let tarball_info = assert_ok!(process_tarball("foo-0.0.1", &*tarball, limit));
         assert_none!(tarball_info.vcs_info);
+        assert_eq!(tarball_info.files.len(), 1);
+        assert_eq!(
+            tarball_info.files.get("Cargo.toml"),
+            Some(&(manifest.len() as u64))
+        );
 
         assert_err!(process_tarball("bar-0.0.1", &*tarball, limit));
     }
@@ -271,6 +295,35 @@ mod tests {
         assert_matches!(package.readme, Some(MaybeInherited::Local(StringOrBool::Bool(b))) if !b);
     }
 
+    #[test]
+    fn process_tarball_test_files() {
+        let manifest = b"[package]\nname = \"foo\"\nversion = \"0.0.1\"\n";
+        let tarball = TarballBuilder::new()
+            .add_file("foo-0.0.1/Cargo.toml", manifest)
+            .add_file("foo-0.0.1/build.rs", b"fn main() {}")
+            .add_file("foo-0.0.1/src/lib.rs", b"")
+            .add_file("foo-0.0.1/src/bin/cli.rs", b"fn main() {}\n")
+            .build();
+
+        let limit = 512 * 1024 * 1024;
+
+        let tarball_info = assert_ok!(process_tarball("foo-0.0.1", &*tarball, limit));
+        let files = tarball_info
+            .files
+            .iter()
+            .map(|(path, size)| (path.as_str(), *size))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            files,
+            vec![
+                ("Cargo.toml", manifest.len() as u64),
+                ("build.rs", 12),
+                ("src/bin/cli.rs", 13),
+                ("src/lib.rs", 0),
+            ]
+        );
+    }
+
     #[test]
     fn process_tarball_test_lowercase_manifest() {
         let manifest = br#"
@@ -7,6 +7,20 @@ pub struct CargoVcsInfo {
     /// Path to the package within repo (empty string if root). / not \
     #[serde(default)]
     pub path_in_vcs: String,
+    /// Git information about the commit the package was published from
+    #[serde(default)]
+    pub git: Option<CargoVcsGitInfo>,
+}
+
+/// Represents the `git` object of the .cargo_vcs_info.json file
+#[derive(Debug, Deserialize, Eq, PartialEq)]
+pub struct CargoVcsGitInfo {
+    /// Hash of the commit the package was published from
+    pub sha1: String,
+    /// Whether the working directory had uncommitted changes when the package
+    /// was published with `--allow-dirty`
+    #[serde(default)]
+    pub dirty: bool,
 }
 
 impl CargoVcsInfo {
@@ -17,7 +31,7 @@ impl CargoVcsInfo {
 
 #[cfg(test)]
 mod tests {
-    use super::CargoVcsInfo;
+    use super::{CargoVcsGitInfo, CargoVcsInfo};
 
     #[test]
     fn test_cargo_vcs_info() {
@@ -25,19 +39,48 @@ mod tests {
         assert_eq!(
             CargoVcsInfo::from_contents("{}").unwrap(),
             CargoVcsInfo {
-                path_in_vcs: "".into()
+                path_in_vcs: "".into(),
+                git: None,
             }
         );
         assert_eq!(
             CargoVcsInfo::from_contents(r#"{"path_in_vcs": "hi"}"#).unwrap(),
             CargoVcsInfo {
-                path_in_vcs: "hi".into()
+                path_in_vcs: "hi".into(),
+                git: None,
             }
         );
         assert_eq!(
             CargoVcsInfo::from_contents(r#"{"path_in_vcs": "hi", "future": "field"}"#).unwrap(),
             CargoVcsInfo {
-                path_in_vcs: "hi".into()
+                path_in_vcs: "hi".into(),
+                git: None,
+            }
+        );
+        assert_eq!(
+            CargoVcsInfo::from_contents(
+                r#"{"git": {"sha1": "0123456789abcdef0123456789abcdef01234567"}, "path_in_vcs": ""}"#
+            )
+            .unwrap(),
+            CargoVcsInfo {
+                path_in_vcs: "".into(),
+                git: Some(CargoVcsGitInfo {
+                    sha1: "0123456789abcdef0123456789abcdef01234567".into(),
+                    dirty: false,
+                }),
+            }
+        );
+        assert_eq!(
+            CargoVcsInfo::from_contents(
+                r#"{"git": {"sha1": "0123456789abcdef0123456789abcdef01234567", "dirty": true}, "path_in_vcs": ""}"#
+            )
+            .unwrap(),
+            CargoVcsInfo {
+                path_in_vcs: "".into(),
+                git: Some(CargoVcsGitInfo {
+                    sha1: "0123456789abcdef0123456789abcdef01234567".into(),
+                    dirty: true,
+                }),
             }
         );
     }
@@ -0,0 +1,19 @@
+CREATE TABLE version_metadata (
+    version_id INTEGER NOT NULL PRIMARY KEY REFERENCES versions(id) ON DELETE CASCADE,
+    edition VARCHAR NOT NULL,
+    has_build_script BOOLEAN NOT NULL,
+    lib JSONB,
+    bins JSONB NOT NULL,
+    badges JSONB NOT NULL,
+    layout JSONB NOT NULL,
+    files JSONB NOT NULL
+);
+
+COMMENT ON TABLE version_metadata IS 'Package metadata extracted from the `Cargo.toml` file and the file list of the published tarball.';
+COMMENT ON COLUMN version_metadata.edition IS 'The Rust edition of the package. Manifests without an explicit edition use `2015`.';
+COMMENT ON COLUMN version_metadata.has_build_script IS 'Whether the package runs a build script, either declared via `package.build` or an implicit `build.rs` file.';
+COMMENT ON COLUMN version_metadata.lib IS 'The `[lib]` target declared in the manifest, or NULL if the manifest has no explicit `[lib]` section.';
+COMMENT ON COLUMN version_metadata.bins IS 'The `[[bin]]` targets declared in the manifest.';
+COMMENT ON COLUMN version_metadata.badges IS 'The `[badges]` section of the manifest.';
+COMMENT ON COLUMN version_metadata.layout IS 'The `autobins`, `autoexamples`, `autotests` and `autobenches` target auto-discovery settings of the manifest.';
+COMMENT ON COLUMN version_metadata.files IS 'Array of `{"path": ..., "size": ...}` objects for all files in the published tarball, relative to the package root.';
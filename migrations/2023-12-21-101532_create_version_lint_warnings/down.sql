@@ -0,0 +1 @@
+DROP TABLE version_lint_warnings;
@@ -0,0 +1,12 @@
+CREATE TABLE version_lint_warnings (
+    id SERIAL PRIMARY KEY,
+    version_id INTEGER NOT NULL REFERENCES versions(id) ON DELETE CASCADE,
+    lint VARCHAR NOT NULL,
+    message VARCHAR NOT NULL
+);
+
+CREATE INDEX version_lint_warnings_version_id_idx ON version_lint_warnings (version_id);
+
+COMMENT ON TABLE version_lint_warnings IS 'Findings of the lints that were run against the published tarball of a version.';
+COMMENT ON COLUMN version_lint_warnings.lint IS 'The name of the lint that produced the warning, e.g. `missing_repository`.';
+COMMENT ON COLUMN version_lint_warnings.message IS 'The warning message that was returned to cargo on publish.';
@@ -0,0 +1,7 @@
+ALTER TABLE version_metadata
+    DROP COLUMN vcs_sha1,
+    DROP COLUMN vcs_dirty,
+    DROP COLUMN path_in_vcs,
+    DROP COLUMN vcs_verification_status,
+    DROP COLUMN vcs_verification_message,
+    DROP COLUMN vcs_verified_at;
@@ -0,0 +1,14 @@
+ALTER TABLE version_metadata
+    ADD COLUMN vcs_sha1 VARCHAR,
+    ADD COLUMN vcs_dirty BOOLEAN,
+    ADD COLUMN path_in_vcs VARCHAR,
+    ADD COLUMN vcs_verification_status VARCHAR,
+    ADD COLUMN vcs_verification_message VARCHAR,
+    ADD COLUMN vcs_verified_at TIMESTAMP;
+
+COMMENT ON COLUMN version_metadata.vcs_sha1 IS 'The git commit hash that was recorded in the `.cargo_vcs_info.json` file of the published tarball, or NULL if the tarball does not contain git information.';
+COMMENT ON COLUMN version_metadata.vcs_dirty IS 'Whether the package was published with uncommitted changes on top of `vcs_sha1`, or NULL if the tarball does not contain git information.';
+COMMENT ON COLUMN version_metadata.path_in_vcs IS 'The path of the package within its repository, or NULL if the tarball does not contain a `.cargo_vcs_info.json` file. An empty string means the repository root.';
+COMMENT ON COLUMN version_metadata.vcs_verification_status IS 'The result of comparing the tarball contents with the `vcs_sha1` commit of the declared repository: `verified`, `mismatch` or `failed`. NULL if the version has not been verified.';
+COMMENT ON COLUMN version_metadata.vcs_verification_message IS 'A human-readable explanation of a `mismatch` or `failed` verification status.';
+COMMENT ON COLUMN version_metadata.vcs_verified_at IS 'The time at which the tarball contents were last compared with the `vcs_sha1` commit.';
@@ -0,0 +1,11 @@
+CREATE TABLE version_attestations (
+    version_id INTEGER NOT NULL PRIMARY KEY REFERENCES versions(id) ON DELETE CASCADE,
+    signer VARCHAR NOT NULL,
+    checksum CHAR(64) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+COMMENT ON TABLE version_attestations IS 'Signed release attestations that were uploaded together with the `.crate` file of a version.';
+COMMENT ON COLUMN version_attestations.signer IS 'The identity of the trusted signer that signed the attestation.';
+COMMENT ON COLUMN version_attestations.checksum IS 'The SHA256 checksum of the attestation document, as hex string.';
+COMMENT ON COLUMN version_attestations.created_at IS 'The time at which the attestation was uploaded.';
@@ -0,0 +1 @@
+ALTER TABLE crates DROP COLUMN ownership_invitations_expiration_days;
@@ -0,0 +1,4 @@
+ALTER TABLE crates
+    ADD COLUMN ownership_invitations_expiration_days INTEGER;
+
+COMMENT ON COLUMN crates.ownership_invitations_expiration_days IS 'Overrides the number of days after which ownership invitations for this crate expire. The server-wide default is used if this is NULL.';
@@ -0,0 +1,3 @@
+ALTER TABLE crate_owners DROP COLUMN can_publish;
+
+DROP TABLE crate_publish_policies;
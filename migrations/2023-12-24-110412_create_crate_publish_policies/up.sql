@@ -0,0 +1,20 @@
+CREATE TABLE crate_publish_policies
+(
+    crate_id                  INTEGER   NOT NULL PRIMARY KEY REFERENCES crates (id) ON DELETE CASCADE,
+    require_exact_crate_scope BOOLEAN   NOT NULL DEFAULT FALSE,
+    forbid_dirty_vcs          BOOLEAN   NOT NULL DEFAULT FALSE,
+    require_rust_version      BOOLEAN   NOT NULL DEFAULT FALSE,
+    updated_at                TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE crate_publish_policies IS 'Restrictions that the owners of a crate put on the publishing of new versions.';
+COMMENT ON COLUMN crate_publish_policies.crate_id IS 'The crate that the policy applies to.';
+COMMENT ON COLUMN crate_publish_policies.require_exact_crate_scope IS 'Only allow publishing with API tokens that have a crate scope naming this crate exactly, without wildcards.';
+COMMENT ON COLUMN crate_publish_policies.forbid_dirty_vcs IS 'Only allow publishing from a clean VCS working directory, as recorded in `.cargo_vcs_info.json`.';
+COMMENT ON COLUMN crate_publish_policies.require_rust_version IS 'Only allow publishing versions that declare a `rust_version`.';
+COMMENT ON COLUMN crate_publish_policies.updated_at IS 'The time when the policy was last changed.';
+
+ALTER TABLE crate_owners
+    ADD COLUMN can_publish BOOLEAN NOT NULL DEFAULT TRUE;
+
+COMMENT ON COLUMN crate_owners.can_publish IS 'Whether this owner may publish new versions of the crate. Owners that may not publish can still yank versions and manage the crate.';
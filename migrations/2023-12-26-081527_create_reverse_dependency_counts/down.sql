@@ -0,0 +1,2 @@
+DROP FUNCTION refresh_reverse_dependency_counts();
+DROP MATERIALIZED VIEW reverse_dependency_counts;
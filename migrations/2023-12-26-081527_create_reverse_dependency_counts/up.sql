@@ -0,0 +1,17 @@
+-- The number of distinct crates that depend on a crate in any of their
+-- unyanked versions, excluding dev-dependencies. This is used as a popularity
+-- signal for the relevance ranking of search results.
+CREATE MATERIALIZED VIEW reverse_dependency_counts (crate_id, dependents) AS
+  SELECT dependencies.crate_id, COUNT(DISTINCT versions.crate_id) FROM dependencies
+    INNER JOIN versions
+      ON dependencies.version_id = versions.id
+    WHERE NOT versions.yanked
+      AND dependencies.kind <> 2
+    GROUP BY dependencies.crate_id;
+CREATE UNIQUE INDEX reverse_dependency_counts_crate_id ON reverse_dependency_counts (crate_id);
+
+COMMENT ON MATERIALIZED VIEW reverse_dependency_counts IS 'The number of crates that depend on each crate. Refreshed by the daily database maintenance job.';
+
+CREATE FUNCTION refresh_reverse_dependency_counts() RETURNS VOID AS $$
+  REFRESH MATERIALIZED VIEW CONCURRENTLY reverse_dependency_counts;
+$$ LANGUAGE SQL;
@@ -0,0 +1 @@
+DROP TABLE typosquat_findings;
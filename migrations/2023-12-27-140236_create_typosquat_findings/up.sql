@@ -0,0 +1,23 @@
+CREATE TABLE typosquat_findings (
+    id SERIAL PRIMARY KEY,
+    crate_id INTEGER NOT NULL REFERENCES crates (id) ON DELETE CASCADE,
+    squatted_crate VARCHAR NOT NULL,
+    message VARCHAR NOT NULL,
+    status INTEGER NOT NULL DEFAULT 0,
+    holds_index BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    reviewed_at TIMESTAMP,
+    reviewed_by VARCHAR,
+    review_note VARCHAR,
+    UNIQUE (crate_id, squatted_crate, message)
+);
+
+CREATE INDEX typosquat_findings_pending ON typosquat_findings (created_at) WHERE status = 0;
+
+COMMENT ON TABLE typosquat_findings IS 'Potential typosquatting of popular crates by new crates, found by the `check_typosquat` background job.';
+COMMENT ON COLUMN typosquat_findings.crate_id IS 'The new crate that may be typosquatting another crate.';
+COMMENT ON COLUMN typosquat_findings.squatted_crate IS 'The name of the popular crate that may be typosquatted.';
+COMMENT ON COLUMN typosquat_findings.message IS 'A description of the check that found the similarity.';
+COMMENT ON COLUMN typosquat_findings.status IS 'The review status: 0 = pending, 1 = approved, 2 = rejected.';
+COMMENT ON COLUMN typosquat_findings.holds_index IS 'If true, the crate is kept out of the index until the finding is approved.';
+COMMENT ON COLUMN typosquat_findings.reviewed_by IS 'The name of the reviewer that approved or rejected the finding.';
@@ -0,0 +1,16 @@
+CREATE TABLE version_scan_findings (
+    id SERIAL PRIMARY KEY,
+    version_id INTEGER NOT NULL REFERENCES versions(id) ON DELETE CASCADE,
+    rule VARCHAR NOT NULL,
+    path VARCHAR NOT NULL,
+    message VARCHAR NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX version_scan_findings_version_id_idx ON version_scan_findings (version_id);
+
+COMMENT ON TABLE version_scan_findings IS 'Findings of the rules that scan the files of new versions for signs of malicious code.';
+COMMENT ON COLUMN version_scan_findings.rule IS 'The name of the rule that produced the finding, e.g. `build_script_network_access`.';
+COMMENT ON COLUMN version_scan_findings.path IS 'The path of the offending file, relative to the package root.';
+COMMENT ON COLUMN version_scan_findings.message IS 'A description of the finding for the reviewers.';
+COMMENT ON COLUMN version_scan_findings.created_at IS 'The time at which the version was scanned.';
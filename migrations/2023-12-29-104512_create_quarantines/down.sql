@@ -0,0 +1,3 @@
+DROP TABLE quarantine_actions;
+DROP TABLE version_quarantines;
+DROP TABLE crate_quarantines;
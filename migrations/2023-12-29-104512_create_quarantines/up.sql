@@ -0,0 +1,39 @@
+CREATE TABLE crate_quarantines (
+    crate_id INTEGER PRIMARY KEY REFERENCES crates(id) ON DELETE CASCADE,
+    reason VARCHAR NOT NULL,
+    created_by VARCHAR NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+COMMENT ON TABLE crate_quarantines IS 'Crates that are hidden from search, the index and downloads while they are investigated.';
+COMMENT ON COLUMN crate_quarantines.reason IS 'Why the crate was quarantined. This is sent to the owners of the crate.';
+COMMENT ON COLUMN crate_quarantines.created_by IS 'The admin who quarantined the crate.';
+
+CREATE TABLE version_quarantines (
+    version_id INTEGER PRIMARY KEY REFERENCES versions(id) ON DELETE CASCADE,
+    reason VARCHAR NOT NULL,
+    created_by VARCHAR NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+COMMENT ON TABLE version_quarantines IS 'Versions that are hidden from the index and downloads while they are investigated.';
+COMMENT ON COLUMN version_quarantines.reason IS 'Why the version was quarantined. This is sent to the owners of the crate.';
+COMMENT ON COLUMN version_quarantines.created_by IS 'The admin who quarantined the version.';
+
+CREATE TABLE quarantine_actions (
+    id SERIAL PRIMARY KEY,
+    crate_id INTEGER NOT NULL REFERENCES crates(id) ON DELETE CASCADE,
+    version_id INTEGER REFERENCES versions(id) ON DELETE CASCADE,
+    action INTEGER NOT NULL,
+    reason VARCHAR,
+    actor VARCHAR NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX quarantine_actions_crate_id_idx ON quarantine_actions (crate_id);
+
+COMMENT ON TABLE quarantine_actions IS 'The audit trail of quarantining crates and versions, and of lifting quarantines.';
+COMMENT ON COLUMN quarantine_actions.version_id IS 'The quarantined version, or NULL if the whole crate was quarantined.';
+COMMENT ON COLUMN quarantine_actions.action IS '0 = quarantine, 1 = lift';
+COMMENT ON COLUMN quarantine_actions.reason IS 'Why the crate or version was quarantined, or why the quarantine was lifted.';
+COMMENT ON COLUMN quarantine_actions.actor IS 'The admin who performed the action.';
@@ -0,0 +1,3 @@
+DROP TABLE admin_actions;
+DROP TABLE admin_tokens;
+ALTER TABLE users DROP COLUMN is_admin;
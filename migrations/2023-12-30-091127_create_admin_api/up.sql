@@ -0,0 +1,34 @@
+ALTER TABLE users ADD COLUMN is_admin BOOLEAN NOT NULL DEFAULT FALSE;
+
+COMMENT ON COLUMN users.is_admin IS 'Whether the user may use the admin API with an admin token.';
+
+CREATE TABLE admin_tokens (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR NOT NULL,
+    token BYTEA NOT NULL UNIQUE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_used_at TIMESTAMP,
+    expired_at TIMESTAMP,
+    revoked BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE INDEX admin_tokens_user_id_idx ON admin_tokens (user_id);
+
+COMMENT ON TABLE admin_tokens IS 'Tokens for the admin API. They are separate from the API tokens, which can''t be used for the admin API.';
+COMMENT ON COLUMN admin_tokens.token IS 'The SHA-256 hash of the token.';
+
+CREATE TABLE admin_actions (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id),
+    admin_token_id INTEGER NOT NULL REFERENCES admin_tokens(id),
+    action VARCHAR NOT NULL,
+    target VARCHAR NOT NULL,
+    details JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+COMMENT ON TABLE admin_actions IS 'The audit log of the actions performed through the admin API.';
+COMMENT ON COLUMN admin_actions.action IS 'The name of the action, e.g. `delete_crate`.';
+COMMENT ON COLUMN admin_actions.target IS 'What the action was performed on, e.g. the name of a crate.';
+COMMENT ON COLUMN admin_actions.details IS 'The parameters of the action, e.g. the reason of a quarantine.';
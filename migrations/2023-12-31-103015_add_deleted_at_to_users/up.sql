@@ -0,0 +1,3 @@
+ALTER TABLE users ADD COLUMN deleted_at TIMESTAMP;
+
+COMMENT ON COLUMN users.deleted_at IS 'When the user deleted their account. The personal data of deleted accounts is removed, but the row is kept for the versions they published.';
@@ -0,0 +1,2 @@
+DROP TABLE crate_adoption_requests;
+DROP TABLE crate_transfers;
@@ -0,0 +1,35 @@
+CREATE TABLE crate_transfers (
+    crate_id INTEGER PRIMARY KEY REFERENCES crates(id) ON DELETE CASCADE,
+    proposed_by INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    recipient_user_id INTEGER REFERENCES users(id) ON DELETE CASCADE,
+    recipient_team_id INTEGER REFERENCES teams(id) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CHECK ((recipient_user_id IS NULL) <> (recipient_team_id IS NULL))
+);
+
+COMMENT ON TABLE crate_transfers IS 'Pending handovers of crates, which replace all owners of the crate once the recipient accepts them.';
+COMMENT ON COLUMN crate_transfers.proposed_by IS 'The owner who proposed the handover.';
+COMMENT ON COLUMN crate_transfers.recipient_user_id IS 'The user that the crate is handed over to, if it is not handed over to a team.';
+COMMENT ON COLUMN crate_transfers.recipient_team_id IS 'The team that the crate is handed over to, if it is not handed over to a user.';
+
+CREATE TABLE crate_adoption_requests (
+    id SERIAL PRIMARY KEY,
+    crate_id INTEGER NOT NULL REFERENCES crates(id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    reason VARCHAR NOT NULL,
+    status INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    approvable_at TIMESTAMP NOT NULL,
+    decided_by VARCHAR,
+    decided_at TIMESTAMP,
+    decision_reason VARCHAR
+);
+
+CREATE INDEX crate_adoption_requests_crate_id_idx ON crate_adoption_requests (crate_id);
+CREATE UNIQUE INDEX crate_adoption_requests_pending_idx ON crate_adoption_requests (crate_id, user_id) WHERE status = 0;
+
+COMMENT ON TABLE crate_adoption_requests IS 'Requests of users to become an owner of an abandoned crate, which are decided by the crates.io team.';
+COMMENT ON COLUMN crate_adoption_requests.reason IS 'Why the user wants to adopt the crate. This is sent to the current owners.';
+COMMENT ON COLUMN crate_adoption_requests.status IS '0 = pending, 1 = approved, 2 = rejected, 3 = withdrawn';
+COMMENT ON COLUMN crate_adoption_requests.approvable_at IS 'The end of the waiting period, in which the current owners can object. The request can''t be approved before.';
+COMMENT ON COLUMN crate_adoption_requests.decided_by IS 'The admin who approved or rejected the request, or the owner who rejected it.';
@@ -0,0 +1,92 @@
+use crate::admin::dialoguer;
+use crate::email::Emails;
+use crate::models::User;
+use crate::{config, db};
+use anyhow::Result;
+use chrono::NaiveDate;
+
+#[derive(clap::Parser, Debug)]
+#[command(
+    name = "account-lock",
+    about = "Lock or unlock user accounts and notify the users",
+    rename_all = "snake_case"
+)]
+pub enum Command {
+    /// Lock the account of a user, so that they can neither sign in nor use their API tokens
+    Lock {
+        /// GitHub login of the user
+        login: String,
+        /// Why the account is locked. This is shown to the user.
+        #[arg(long)]
+        reason: String,
+        /// Lock the account until the start of this day (UTC), e.g. `2024-01-31`, instead of
+        /// indefinitely
+        #[arg(long)]
+        until: Option<NaiveDate>,
+        /// Don't ask for confirmation: yes, we are sure. Best for scripting.
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Unlock the account of a user
+    Unlock {
+        /// GitHub login of the user
+        login: String,
+        /// Don't ask for confirmation: yes, we are sure. Best for scripting.
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+pub fn run(command: Command) -> Result<()> {
+    let conn = &mut db::oneoff_connection()?;
+    let emails = Emails::from_environment(&config::Server::from_environment()?);
+
+    match command {
+        Command::Lock {
+            login,
+            reason,
+            until,
+            yes,
+        } => {
+            let user = User::find_by_login(conn, &login)?;
+            let until = until.map(|until| until.and_hms_opt(0, 0, 0).unwrap());
+
+            let duration = match until {
+                Some(until) => format!("until {until}"),
+                None => "indefinitely".to_string(),
+            };
+            let prompt = format!(
+                "Are you sure you want to lock the account of {} (id={}) {duration}?",
+                user.gh_login, user.id
+            );
+            if !yes && !dialoguer::confirm(&prompt) {
+                return Ok(());
+            }
+
+            let user = user.lock(&reason, until, conn)?;
+            user.notify_account_lock(&emails, conn)?;
+            println!("locked the account of {login} {duration}");
+        }
+        Command::Unlock { login, yes } => {
+            let user = User::find_by_login(conn, &login)?;
+            if user.account_lock_reason.is_none() {
+                println!("the account of {login} is not locked");
+                return Ok(());
+            }
+
+            let prompt = format!(
+                "Are you sure you want to unlock the account of {} (id={})?",
+                user.gh_login, user.id
+            );
+            if !yes && !dialoguer::confirm(&prompt) {
+                return Ok(());
+            }
+
+            let user = user.unlock(conn)?;
+            user.notify_account_lock(&emails, conn)?;
+            println!("unlocked the account of {login}");
+        }
+    }
+
+    Ok(())
+}
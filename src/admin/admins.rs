@@ -0,0 +1,154 @@
+use crate::admin::dialoguer;
+use crate::db;
+use crate::models::{AdminToken, CreatedAdminToken, User};
+use crate::schema::{admin_tokens, users};
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use secrecy::ExposeSecret;
+
+#[derive(clap::Parser, Debug)]
+#[command(
+    name = "admins",
+    about = "Manage the users with the admin role and their tokens for the admin API",
+    rename_all = "snake_case"
+)]
+pub enum Command {
+    /// List the users with the admin role and their active tokens
+    List,
+    /// Give the admin role to a user
+    Grant {
+        /// GitHub login of the user
+        login: String,
+    },
+    /// Take the admin role away from a user, which also disables their tokens
+    Revoke {
+        /// GitHub login of the user
+        login: String,
+    },
+    /// Create a token for the admin API and print it
+    CreateToken {
+        /// GitHub login of the user, who must have the admin role
+        login: String,
+        /// Name of the token, e.g. the purpose or the machine it is used on
+        #[arg(long)]
+        name: String,
+        /// Number of days until the token expires
+        #[arg(long, default_value_t = 90)]
+        expires_in_days: i64,
+    },
+    /// Revoke a token for the admin API
+    RevokeToken {
+        /// ID of the token
+        id: i32,
+    },
+}
+
+pub fn run(command: Command) -> Result<()> {
+    let conn = &mut db::oneoff_connection()?;
+
+    match command {
+        Command::List => list(conn),
+        Command::Grant { login } => set_admin(&login, true, conn),
+        Command::Revoke { login } => set_admin(&login, false, conn),
+        Command::CreateToken {
+            login,
+            name,
+            expires_in_days,
+        } => {
+            let user = find_user(&login, conn)?;
+            let token = create_token(&user, &name, expires_in_days, conn)?;
+            println!("Created admin token #{} for {login}:", token.model.id);
+            println!("{}", token.plaintext.expose_secret());
+            Ok(())
+        }
+        Command::RevokeToken { id } => {
+            if AdminToken::revoke(conn, id)? {
+                println!("Revoked admin token #{id}");
+            } else {
+                println!("Admin token #{id} does not exist or is already revoked");
+            }
+            Ok(())
+        }
+    }
+}
+
+fn list(conn: &mut PgConnection) -> Result<()> {
+    let admins: Vec<User> = users::table
+        .filter(users::is_admin.eq(true))
+        .order(users::gh_login)
+        .load(conn)?;
+
+    if admins.is_empty() {
+        println!("No users have the admin role");
+        return Ok(());
+    }
+
+    let tokens: Vec<AdminToken> = AdminToken::belonging_to(&admins)
+        .filter(admin_tokens::revoked.eq(false))
+        .select(AdminToken::as_select())
+        .order(admin_tokens::id)
+        .load(conn)?;
+    let tokens = tokens.grouped_by(&admins);
+
+    for (admin, tokens) in admins.into_iter().zip(tokens) {
+        println!("{} (id={})", admin.gh_login, admin.id);
+        for token in tokens {
+            let expires = token
+                .expired_at
+                .map(|expired_at| expired_at.to_string())
+                .unwrap_or_else(|| "never".into());
+            println!("    #{} {} (expires: {expires})", token.id, token.name);
+        }
+    }
+
+    Ok(())
+}
+
+fn find_user(login: &str, conn: &mut PgConnection) -> Result<User> {
+    users::table
+        .filter(users::gh_login.eq(login))
+        .first(conn)
+        .optional()?
+        .ok_or_else(|| anyhow::anyhow!("user `{login}` not found"))
+}
+
+fn set_admin(login: &str, is_admin: bool, conn: &mut PgConnection) -> Result<()> {
+    let user = find_user(login, conn)?;
+
+    if is_admin {
+        let prompt = format!(
+            "Are you sure you want to give the admin role to {} (id={})?",
+            user.gh_login, user.id
+        );
+        if !dialoguer::confirm(&prompt) {
+            return Ok(());
+        }
+    }
+
+    diesel::update(&user)
+        .set(users::is_admin.eq(is_admin))
+        .execute(conn)?;
+
+    if is_admin {
+        println!("{login} is now an admin");
+    } else {
+        println!("{login} is no longer an admin");
+    }
+
+    Ok(())
+}
+
+fn create_token(
+    user: &User,
+    name: &str,
+    expires_in_days: i64,
+    conn: &mut PgConnection,
+) -> Result<CreatedAdminToken> {
+    if !user.is_admin {
+        anyhow::bail!("{} does not have the admin role", user.gh_login);
+    }
+
+    let expired_at = Utc::now().naive_utc() + Duration::days(expires_in_days);
+    Ok(AdminToken::insert(conn, user.id, name, Some(expired_at))?)
+}
@@ -93,16 +93,27 @@ pub fn run(opts: Opts) -> anyhow::Result<()> {
             warn!(%name, ?error, "Failed to enqueue index sync jobs");
         }
 
-        info!(%name, "Deleting crate files from S3");
-        if let Err(error) = rt.block_on(store.delete_all_crate_files(name)) {
-            warn!(%name, ?error, "Failed to delete crate files from S3");
-        }
-
-        info!(%name, "Deleting readme files from S3");
-        if let Err(error) = rt.block_on(store.delete_all_readmes(name)) {
-            warn!(%name, ?error, "Failed to delete readme files from S3");
-        }
+        rt.block_on(delete_crate_files(&store, name));
     }
 
     Ok(())
 }
+
+/// Deletes all files of a crate from the storage. Failures are only logged,
+/// since the crate is already gone from the database and the index.
+pub async fn delete_crate_files(store: &Storage, name: &str) {
+    info!(%name, "Deleting crate files from S3");
+    if let Err(error) = store.delete_all_crate_files(name).await {
+        warn!(%name, ?error, "Failed to delete crate files from S3");
+    }
+
+    info!(%name, "Deleting readme files from S3");
+    if let Err(error) = store.delete_all_readmes(name).await {
+        warn!(%name, ?error, "Failed to delete readme files from S3");
+    }
+
+    info!(%name, "Deleting cached diffs from S3");
+    if let Err(error) = store.delete_all_diffs(name).await {
+        warn!(%name, ?error, "Failed to delete cached diffs from S3");
+    }
+}
@@ -80,14 +80,32 @@ pub fn run(opts: Opts) -> anyhow::Result<()> {
         .build()
         .context("Failed to initialize tokio runtime")?;
 
-    for version in &opts.versions {
+    rt.block_on(delete_version_files(&store, crate_name, &opts.versions));
+
+    Ok(())
+}
+
+/// Deletes the files of the versions of a crate from the storage. Failures are
+/// only logged, since the versions are already gone from the database and the
+/// index.
+pub async fn delete_version_files(store: &Storage, crate_name: &str, versions: &[String]) {
+    for version in versions {
         debug!(%crate_name, %version, "Deleting crate file from S3");
-        if let Err(error) = rt.block_on(store.delete_crate_file(crate_name, version)) {
+        if let Err(error) = store.delete_crate_file(crate_name, version).await {
             warn!(%crate_name, %version, ?error, "Failed to delete crate file from S3");
         }
 
+        debug!(%crate_name, %version, "Deleting attestation from S3");
+        match store.delete_attestation(crate_name, version).await {
+            Err(object_store::Error::NotFound { .. }) => {}
+            Err(error) => {
+                warn!(%crate_name, %version, ?error, "Failed to delete attestation from S3")
+            }
+            Ok(_) => {}
+        }
+
         debug!(%crate_name, %version, "Deleting readme file from S3");
-        match rt.block_on(store.delete_readme(crate_name, version)) {
+        match store.delete_readme(crate_name, version).await {
             Err(object_store::Error::NotFound { .. }) => {}
             Err(error) => {
                 warn!(%crate_name, %version, ?error, "Failed to delete readme file from S3")
@@ -96,5 +114,10 @@ pub fn run(opts: Opts) -> anyhow::Result<()> {
         }
     }
 
-    Ok(())
+    // Cached diffs are keyed by both versions, so we can't easily find the
+    // ones involving the deleted versions and delete all of them instead.
+    debug!(%crate_name, "Deleting cached diffs from S3");
+    if let Err(error) = store.delete_all_diffs(crate_name).await {
+        warn!(%crate_name, ?error, "Failed to delete cached diffs from S3");
+    }
 }
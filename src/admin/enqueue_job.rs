@@ -36,6 +36,22 @@ pub fn run(command: Command) -> Result<()> {
     let conn = &mut db::oneoff_connection()?;
     println!("Enqueueing background job: {command:?}");
 
+    // Only the `update_downloads` job is skipped if it's already in progress
+    if !enqueue(command, conn)? {
+        println!(
+            "Did not enqueue {}, existing job already in progress",
+            jobs::UpdateDownloads::JOB_NAME
+        );
+    }
+
+    Ok(())
+}
+
+/// Adds the job to the queue. Returns `false` if the job wasn't enqueued
+/// because the same job is already in progress.
+///
+/// This is shared with the admin API.
+pub fn enqueue(command: Command, conn: &mut PgConnection) -> Result<bool> {
     match command {
         Command::UpdateDownloads => {
             let count: i64 = background_jobs::table
@@ -44,13 +60,10 @@ pub fn run(command: Command) -> Result<()> {
                 .get_result(conn)?;
 
             if count > 0 {
-                println!(
-                    "Did not enqueue {}, existing job already in progress",
-                    jobs::UpdateDownloads::JOB_NAME
-                );
-            } else {
-                jobs::UpdateDownloads.enqueue(conn)?;
+                return Ok(false);
             }
+
+            jobs::UpdateDownloads.enqueue(conn)?;
         }
         Command::DumpDb {
             database_url,
@@ -84,5 +97,5 @@ pub fn run(command: Command) -> Result<()> {
         }
     };
 
-    Ok(())
+    Ok(true)
 }
@@ -0,0 +1,138 @@
+use crate::db;
+use crate::models::Crate;
+use crate::schema::crates;
+use anyhow::{bail, Result};
+use diesel::prelude::*;
+use std::ops::RangeInclusive;
+
+/// The allowed number of days after which the ownership invitations of a
+/// crate expire.
+const VALID_DAYS: RangeInclusive<i32> = 1..=365;
+
+#[derive(clap::Parser, Debug)]
+#[command(
+    name = "invitation-expiration",
+    about = "Change after how many days the ownership invitations of a crate expire",
+    rename_all = "snake_case"
+)]
+pub enum Command {
+    /// Show after how many days the ownership invitations of a crate expire
+    Show {
+        /// Name of the crate
+        crate_name: String,
+    },
+    /// Override the number of days after which the ownership invitations of a crate expire
+    Set {
+        /// Name of the crate
+        crate_name: String,
+        /// Number of days, between 1 and 365
+        days: i32,
+    },
+    /// Use the default expiration of the server configuration for a crate again
+    Reset {
+        /// Name of the crate
+        crate_name: String,
+    },
+}
+
+pub fn run(command: Command) -> Result<()> {
+    let conn = &mut db::oneoff_connection()?;
+
+    match command {
+        Command::Show { crate_name } => {
+            let krate: Crate = Crate::by_name(&crate_name).first(conn)?;
+            match krate.ownership_invitations_expiration_days {
+                Some(days) => println!("invitations for {} expire after {days} days", krate.name),
+                None => println!("invitations for {} use the default expiration", krate.name),
+            }
+        }
+        Command::Set { crate_name, days } => {
+            let krate = set(&crate_name, Some(days), conn)?;
+            println!(
+                "invitations for {} now expire after {days} days",
+                krate.name
+            );
+        }
+        Command::Reset { crate_name } => {
+            let krate = set(&crate_name, None, conn)?;
+            println!(
+                "invitations for {} now use the default expiration",
+                krate.name
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Overrides the number of days after which the ownership invitations of a
+/// crate expire, or removes the override if `days` is `None`.
+///
+/// Pending invitations are affected too, since their expiration is calculated
+/// when they are used.
+fn set(crate_name: &str, days: Option<i32>, conn: &mut PgConnection) -> Result<Crate> {
+    if let Some(days) = days {
+        if !VALID_DAYS.contains(&days) {
+            bail!(
+                "the expiration must be between {} and {} days",
+                VALID_DAYS.start(),
+                VALID_DAYS.end()
+            );
+        }
+    }
+
+    let krate: Crate = Crate::by_name(crate_name).first(conn)?;
+    let krate = diesel::update(&krate)
+        .set(crates::ownership_invitations_expiration_days.eq(days))
+        .returning(Crate::as_returning())
+        .get_result(conn)?;
+
+    Ok(krate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::pg_connection;
+    use crate::typosquat::test_util::Faker;
+
+    #[test]
+    fn set_and_reset() -> anyhow::Result<()> {
+        let mut faker = Faker::new(pg_connection());
+        let user = faker.user("a")?;
+        faker.crate_and_version("mycrate", "description", &user, 0)?;
+        let mut conn = faker.into_conn();
+        let conn = &mut conn;
+
+        let krate = set("mycrate", Some(7), conn)?;
+        assert_eq!(krate.ownership_invitations_expiration_days, Some(7));
+
+        let krate = set("mycrate", Some(365), conn)?;
+        assert_eq!(krate.ownership_invitations_expiration_days, Some(365));
+
+        let krate = set("mycrate", None, conn)?;
+        assert_eq!(krate.ownership_invitations_expiration_days, None);
+
+        assert_err!(set("missing", Some(7), conn));
+
+        Ok(())
+    }
+
+    #[test]
+    fn bounds() -> anyhow::Result<()> {
+        let mut faker = Faker::new(pg_connection());
+        let user = faker.user("a")?;
+        faker.crate_and_version("mycrate", "description", &user, 0)?;
+        let mut conn = faker.into_conn();
+        let conn = &mut conn;
+
+        for days in [-1, 0, 366] {
+            assert_err!(set("mycrate", Some(days), conn));
+        }
+
+        let krate: Crate = Crate::by_name("mycrate").first(conn)?;
+        assert_eq!(krate.ownership_invitations_expiration_days, None);
+
+        Ok(())
+    }
+}
@@ -1,14 +1,20 @@
+pub mod account_lock;
+pub mod admins;
 pub mod delete_crate;
 pub mod delete_version;
 pub mod dialoguer;
 pub mod enqueue_job;
 pub mod git_import;
+pub mod invitation_expiration;
 pub mod migrate;
 pub mod on_call;
 pub mod populate;
+pub mod quarantine;
 pub mod render_readmes;
+pub mod scan;
 pub mod test_pagerduty;
 pub mod transfer_crates;
+pub mod typosquat;
 pub mod upload_index;
 pub mod verify_token;
 pub mod yank_version;
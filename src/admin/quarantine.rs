@@ -0,0 +1,373 @@
+use crate::admin::dialoguer;
+use crate::email::Emails;
+use crate::models::{Crate, QuarantineAction, QuarantineActionKind, QuarantineTarget, Version};
+use crate::schema::{crate_quarantines, crates, version_quarantines, versions};
+use crate::worker::jobs;
+use crate::{config, db};
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+
+#[derive(clap::Parser, Debug)]
+#[command(
+    name = "quarantine",
+    about = "Hide crates or versions from search, the index and downloads while they are investigated",
+    rename_all = "snake_case"
+)]
+pub enum Command {
+    /// List the quarantined crates and versions, or the quarantine history of a crate
+    List {
+        /// Show the quarantine history of this crate
+        #[arg(long = "crate")]
+        crate_name: Option<String>,
+    },
+    /// Quarantine a crate or a single version and notify its owners
+    Add(AddOpts),
+    /// Lift the quarantine of a crate or a single version and notify its owners
+    Lift(LiftOpts),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct TargetOpts {
+    /// Name of the crate
+    pub(super) crate_name: String,
+    /// Only affect this version instead of the whole crate
+    #[arg(long)]
+    pub(super) version: Option<String>,
+    /// Name of the admin, which is recorded in the audit trail
+    #[arg(long, env = "USER")]
+    pub(super) actor: String,
+    /// Don't ask for confirmation: yes, we are sure. Best for scripting.
+    #[arg(short, long)]
+    pub(super) yes: bool,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct AddOpts {
+    #[command(flatten)]
+    target: TargetOpts,
+    /// Why the crate or version is quarantined. This is sent to the owners of the crate.
+    #[arg(long)]
+    reason: String,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct LiftOpts {
+    #[command(flatten)]
+    target: TargetOpts,
+    /// Why the quarantine is lifted
+    #[arg(long)]
+    reason: Option<String>,
+}
+
+pub fn run(command: Command) -> Result<()> {
+    let conn = &mut db::oneoff_connection()?;
+
+    match command {
+        Command::List { crate_name } => match crate_name {
+            Some(crate_name) => history(&crate_name, conn),
+            None => list(conn),
+        },
+        Command::Add(opts) => {
+            let emails = Emails::from_environment(&config::Server::from_environment()?);
+            let action = QuarantineActionKind::Quarantine;
+            apply(
+                action,
+                &opts.target,
+                Some(&opts.reason),
+                false,
+                &emails,
+                conn,
+            )?;
+            Ok(())
+        }
+        Command::Lift(opts) => {
+            let emails = Emails::from_environment(&config::Server::from_environment()?);
+            let action = QuarantineActionKind::Lift;
+            apply(
+                action,
+                &opts.target,
+                opts.reason.as_deref(),
+                false,
+                &emails,
+                conn,
+            )?;
+            Ok(())
+        }
+    }
+}
+
+fn list(conn: &mut PgConnection) -> Result<()> {
+    let quarantined_crates: Vec<(String, String, String, NaiveDateTime)> = crate_quarantines::table
+        .inner_join(crates::table)
+        .select((
+            crates::name,
+            crate_quarantines::reason,
+            crate_quarantines::created_by,
+            crate_quarantines::created_at,
+        ))
+        .order(crate_quarantines::created_at)
+        .load(conn)?;
+
+    let quarantined_versions: Vec<(String, String, String, String, NaiveDateTime)> =
+        version_quarantines::table
+            .inner_join(versions::table.inner_join(crates::table))
+            .select((
+                crates::name,
+                versions::num,
+                version_quarantines::reason,
+                version_quarantines::created_by,
+                version_quarantines::created_at,
+            ))
+            .order(version_quarantines::created_at)
+            .load(conn)?;
+
+    if quarantined_crates.is_empty() && quarantined_versions.is_empty() {
+        println!("Nothing is quarantined");
+        return Ok(());
+    }
+
+    for (crate_name, reason, created_by, created_at) in quarantined_crates {
+        println!("{crate_name} (by {created_by}, {created_at}): {reason}");
+    }
+
+    for (crate_name, version, reason, created_by, created_at) in quarantined_versions {
+        println!("{crate_name}@{version} (by {created_by}, {created_at}): {reason}");
+    }
+
+    Ok(())
+}
+
+fn history(crate_name: &str, conn: &mut PgConnection) -> Result<()> {
+    let krate: Crate = Crate::by_name(crate_name).first(conn)?;
+
+    let actions = QuarantineAction::for_crate(krate.id, conn)?;
+    if actions.is_empty() {
+        println!("Crate {crate_name} has never been quarantined");
+        return Ok(());
+    }
+
+    let version_ids = actions.iter().filter_map(|action| action.version_id);
+    let version_nums: Vec<(i32, String)> = versions::table
+        .filter(versions::id.eq_any(version_ids.collect::<Vec<_>>()))
+        .select((versions::id, versions::num))
+        .load(conn)?;
+
+    for action in actions {
+        let target = match action.version_id {
+            Some(version_id) => {
+                let num = version_nums
+                    .iter()
+                    .find(|(id, _)| *id == version_id)
+                    .map(|(_, num)| num.as_str())
+                    .unwrap_or("?");
+                format!("{crate_name}@{num}")
+            }
+            None => crate_name.to_string(),
+        };
+
+        println!(
+            "#{id} {action:?} {target} (by {actor}, {created_at}): {reason}",
+            id = action.id,
+            action = action.action,
+            actor = action.actor,
+            created_at = action.created_at,
+            reason = action.reason.as_deref().unwrap_or("no reason given"),
+        );
+    }
+
+    Ok(())
+}
+
+/// Quarantines or lifts the quarantine of a crate or version, syncs the crate
+/// to the index and notifies the owners of the crate.
+///
+/// If `yank` is set, a quarantined version is also yanked in the same
+/// transaction, so that it stays yanked after the quarantine is lifted.
+///
+/// Returns `false` if nothing was changed, e.g. because the crate is already
+/// quarantined.
+pub(super) fn apply(
+    action: QuarantineActionKind,
+    opts: &TargetOpts,
+    reason: Option<&str>,
+    yank: bool,
+    emails: &Emails,
+    conn: &mut PgConnection,
+) -> Result<bool> {
+    let krate: Crate = Crate::by_name(&opts.crate_name).first(conn)?;
+    let version: Option<Version> = opts
+        .version
+        .as_ref()
+        .map(|version| {
+            Version::belonging_to(&krate)
+                .filter(versions::num.eq(version))
+                .first(conn)
+        })
+        .transpose()?;
+
+    let target = match &version {
+        Some(version) => QuarantineTarget::Version(&krate, version),
+        None => QuarantineTarget::Crate(&krate),
+    };
+
+    let description = match &version {
+        Some(version) => format!("{}@{}", krate.name, version.num),
+        None => krate.name.clone(),
+    };
+
+    if !opts.yes {
+        let prompt = match action {
+            QuarantineActionKind::Quarantine => {
+                format!("Are you sure you want to quarantine {description}?")
+            }
+            QuarantineActionKind::Lift => {
+                format!("Are you sure you want to lift the quarantine of {description}?")
+            }
+        };
+        if !dialoguer::confirm(&prompt) {
+            return Ok(false);
+        }
+    }
+
+    let (changed, yanked) = conn.transaction(|conn| {
+        let changed = match action {
+            QuarantineActionKind::Quarantine => {
+                let reason = reason.expect("quarantines always have a reason");
+                target.quarantine(reason, &opts.actor, conn)?
+            }
+            QuarantineActionKind::Lift => target.lift(reason, &opts.actor, conn)?,
+        };
+
+        let yanked = match &version {
+            Some(version) if yank && action == QuarantineActionKind::Quarantine => {
+                diesel::update(version)
+                    .filter(versions::yanked.eq(false))
+                    .set(versions::yanked.eq(true))
+                    .execute(conn)?
+                    > 0
+            }
+            _ => false,
+        };
+
+        if changed || yanked {
+            jobs::enqueue_sync_to_index(&krate.name, conn)?;
+        }
+
+        if changed {
+            jobs::SyncQuarantinedFiles::new(&krate.name).enqueue(conn)?;
+        }
+
+        Ok::<_, anyhow::Error>((changed, yanked))
+    })?;
+
+    if yanked {
+        println!("yanked {description}");
+    }
+
+    if !changed {
+        match action {
+            QuarantineActionKind::Quarantine => println!("{description} is already quarantined"),
+            QuarantineActionKind::Lift => println!("{description} is not quarantined"),
+        }
+        return Ok(false);
+    }
+
+    match action {
+        QuarantineActionKind::Quarantine => println!("quarantined {description}"),
+        QuarantineActionKind::Lift => println!("lifted the quarantine of {description}"),
+    }
+
+    target.notify_owners(action, reason, emails, conn)?;
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{is_crate_quarantined, is_version_quarantined};
+    use crate::schema::background_jobs;
+    use crate::test_util::pg_connection;
+    use crate::typosquat::test_util::Faker;
+
+    fn opts(version: Option<&str>) -> TargetOpts {
+        TargetOpts {
+            crate_name: "mycrate".into(),
+            version: version.map(Into::into),
+            actor: "admin".into(),
+            yes: true,
+        }
+    }
+
+    #[test]
+    fn quarantine_and_lift() -> anyhow::Result<()> {
+        let mut faker = Faker::new(pg_connection());
+        let user = faker.user("a")?;
+        let (krate, version) = faker.crate_and_version("mycrate", "description", &user, 0)?;
+        let mut conn = faker.into_conn();
+        let conn = &mut conn;
+        let emails = Emails::new_in_memory();
+
+        let quarantine = QuarantineActionKind::Quarantine;
+        let lift = QuarantineActionKind::Lift;
+        let reason = Some("malware");
+
+        let version_opts = opts(Some("1.0.0"));
+        assert!(apply(
+            quarantine,
+            &version_opts,
+            reason,
+            false,
+            &emails,
+            conn
+        )?);
+        assert!(!apply(
+            quarantine,
+            &version_opts,
+            reason,
+            false,
+            &emails,
+            conn
+        )?);
+        assert!(is_version_quarantined(krate.id, version.id, conn)?);
+        assert!(!is_crate_quarantined(krate.id, conn)?);
+
+        assert!(apply(lift, &version_opts, None, false, &emails, conn)?);
+        assert!(!apply(lift, &version_opts, None, false, &emails, conn)?);
+        assert!(!is_version_quarantined(krate.id, version.id, conn)?);
+
+        let crate_opts = opts(None);
+        assert!(apply(
+            quarantine,
+            &crate_opts,
+            reason,
+            false,
+            &emails,
+            conn
+        )?);
+        assert!(is_crate_quarantined(krate.id, conn)?);
+
+        let actions = QuarantineAction::for_crate(krate.id, conn)?;
+        assert_eq!(actions.len(), 3);
+        assert!(actions.iter().all(|action| action.actor == "admin"));
+
+        let sync_jobs: i64 = background_jobs::table
+            .filter(background_jobs::job_type.eq(jobs::SyncToGitIndex::JOB_NAME))
+            .count()
+            .get_result(conn)?;
+        assert!(sync_jobs > 0);
+
+        assert_err!(apply(
+            quarantine,
+            &opts(Some("2.0.0")),
+            reason,
+            false,
+            &emails,
+            conn
+        ));
+
+        Ok(())
+    }
+}
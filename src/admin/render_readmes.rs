@@ -10,7 +10,7 @@ use std::{io::Read, path::Path, sync::Arc, thread};
 use crate::storage::Storage;
 use chrono::{NaiveDateTime, Utc};
 use crates_io_markdown::text_to_html;
-use crates_io_tarball::{Manifest, StringOrBool};
+use crates_io_tarball::{find_entry_by_path, CargoVcsInfo, Manifest, StringOrBool};
 use diesel::prelude::*;
 use flate2::read::GzDecoder;
 use reqwest::{blocking::Client, header};
@@ -172,10 +172,20 @@ fn get_readme(
 fn render_pkg_readme<R: Read>(mut archive: Archive<R>, pkg_name: &str) -> anyhow::Result<String> {
     let mut entries = archive.entries().context("Invalid tar archive entries")?;
 
+    // The `.cargo_vcs_info.json` file is picked up while searching for the other files, since
+    // the entries of the archive can only be read once.
+    let vcs_info_path = Path::new(pkg_name).join(".cargo_vcs_info.json");
+    let mut vcs_info = None;
+
     let manifest: Manifest = {
         let path = format!("{pkg_name}/Cargo.toml");
-        let contents = find_file_by_path(&mut entries, Path::new(&path))
-            .context("Failed to read Cargo.toml file")?;
+        let contents = find_file_by_path(
+            &mut entries,
+            Path::new(&path),
+            &vcs_info_path,
+            &mut vcs_info,
+        )
+        .context("Failed to read Cargo.toml file")?;
 
         Manifest::from_str(&contents).context("Failed to parse manifest file")?
 
@@ -197,12 +207,21 @@ fn render_pkg_readme<R: Read>(mut archive: Archive<R>, pkg_name: &str) -> anyhow
         };
 
         let path = Path::new(pkg_name).join(&readme_path);
-        let contents = find_file_by_path(&mut entries, Path::new(&path))
+        let contents = find_file_by_path(&mut entries, &path, &vcs_info_path, &mut vcs_info)
             .with_context(|| format!("Failed to read {} file", readme_path.display()))?;
 
-        // pkg_path_in_vcs Unsupported from admin::render_readmes. See #4095
-        // Would need access to cargo_vcs_info
-        let pkg_path_in_vcs = None;
+        if vcs_info.is_none() {
+            vcs_info = find_entry_by_path(&mut entries, &vcs_info_path)
+                .and_then(|entry| read_vcs_info(entry).ok());
+        }
+
+        let (pkg_path_in_vcs, vcs_sha1) = match vcs_info {
+            Some(info) => (
+                Some(PathBuf::from(info.path_in_vcs)),
+                info.git.map(|git| git.sha1),
+            ),
+            None => (None, None),
+        };
 
         let repository = manifest
             .package
@@ -211,29 +230,54 @@ fn render_pkg_readme<R: Read>(mut archive: Archive<R>, pkg_name: &str) -> anyhow
             .and_then(|r| r.as_ref().as_local())
             .map(|s| s.as_str());
 
-        text_to_html(&contents, &readme_path, repository, pkg_path_in_vcs)
+        text_to_html(
+            &contents,
+            &readme_path,
+            repository,
+            pkg_path_in_vcs.as_ref(),
+            vcs_sha1.as_deref(),
+        )
     };
     Ok(rendered)
 }
 
-/// Search an entry by its path in a Tar archive.
+/// Search an entry by its path in a Tar archive and read it into a string.
+///
+/// If the `.cargo_vcs_info.json` file at `vcs_info_path` is passed on the way,
+/// it is parsed into `vcs_info`.
 fn find_file_by_path<R: Read>(
     entries: &mut tar::Entries<'_, R>,
     path: &Path,
+    vcs_info_path: &Path,
+    vcs_info: &mut Option<CargoVcsInfo>,
 ) -> anyhow::Result<String> {
-    let mut file = entries
-        .filter_map(|entry| entry.ok())
-        .find(|file| match file.path() {
-            Ok(p) => p == path,
-            Err(_) => false,
-        })
-        .ok_or_else(|| anyhow!("Failed to find tarball entry: {}", path.display()))?;
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let Ok(entry_path) = entry.path() else {
+            continue;
+        };
 
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)
-        .context("Failed to read file contents")?;
+        if entry_path == path {
+            let mut file = entry;
+            let mut contents = String::new();
+            file.read_to_string(&mut contents)
+                .context("Failed to read file contents")?;
+
+            return Ok(contents);
+        }
 
-    Ok(contents)
+        if entry_path == vcs_info_path {
+            *vcs_info = read_vcs_info(entry).ok();
+        }
+    }
+
+    Err(anyhow!("Failed to find tarball entry: {}", path.display()))
+}
+
+/// Read and parse a `.cargo_vcs_info.json` entry of a Tar archive.
+fn read_vcs_info<R: Read>(mut entry: tar::Entry<'_, R>) -> anyhow::Result<CargoVcsInfo> {
+    let mut contents = String::new();
+    entry.read_to_string(&mut contents)?;
+    Ok(CargoVcsInfo::from_contents(&contents)?)
 }
 
 #[cfg(test)]
@@ -319,6 +363,33 @@ repository = "https://github.com/foo/foo"
         assert!(result.contains("\"https://github.com/foo/foo/blob/HEAD/./Other.md\""))
     }
 
+    #[test]
+    fn test_render_pkg_readme_w_vcs_info() {
+        let serialized_archive = TarballBuilder::new()
+            .add_file(
+                "foo-0.0.1/.cargo_vcs_info.json",
+                br#"{"git": {"sha1": "0123456789abcdef0123456789abcdef01234567"}, "path_in_vcs": "foo"}"#,
+            )
+            .add_file(
+                "foo-0.0.1/Cargo.toml",
+                br#"
+[package]
+name = "foo"
+version = "0.0.1"
+readme = "README.md"
+repository = "https://github.com/foo/foo"
+"#,
+            )
+            .add_file("foo-0.0.1/README.md", b"readme [link](./Other.md)")
+            .build_unzipped();
+
+        let result =
+            render_pkg_readme(tar::Archive::new(&*serialized_archive), "foo-0.0.1").unwrap();
+        assert!(result.contains(
+            "\"https://github.com/foo/foo/blob/0123456789abcdef0123456789abcdef01234567/foo/./Other.md\""
+        ))
+    }
+
     #[test]
     fn test_render_pkg_readme_not_at_root() {
         let serialized_archive = TarballBuilder::new()
@@ -0,0 +1,185 @@
+use crate::admin::quarantine;
+use crate::email::Emails;
+use crate::models::{Crate, QuarantineActionKind, Version, VersionScanFinding};
+use crate::schema::{crates, version_scan_findings, versions};
+use crate::{config, db};
+use anyhow::Result;
+use diesel::prelude::*;
+
+/// The number of findings that are listed if no crate is given.
+const MAX_LISTED_FINDINGS: i64 = 100;
+
+#[derive(clap::Parser, Debug)]
+#[command(
+    name = "scan",
+    about = "Review the findings of the scans of new versions for malicious code",
+    rename_all = "snake_case"
+)]
+pub enum Command {
+    /// List the most recent findings, or all findings of a crate
+    List {
+        /// Only list the findings of this crate
+        #[arg(long = "crate")]
+        crate_name: Option<String>,
+    },
+    /// Quarantine and yank a version with scan findings and notify the owners of the crate
+    Quarantine {
+        /// Name of the crate
+        crate_name: String,
+        /// Version number that should be quarantined
+        version: String,
+        /// Why the version is quarantined. Defaults to a list of the rules that found something.
+        #[arg(long)]
+        reason: Option<String>,
+        /// Name of the admin, which is recorded in the audit trail
+        #[arg(long, env = "USER")]
+        actor: String,
+        /// Don't ask for confirmation: yes, we are sure. Best for scripting.
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+pub fn run(command: Command) -> Result<()> {
+    let conn = &mut db::oneoff_connection()?;
+
+    match command {
+        Command::List { crate_name } => list(crate_name.as_deref(), conn),
+        Command::Quarantine {
+            crate_name,
+            version,
+            reason,
+            actor,
+            yes,
+        } => {
+            let opts = quarantine::TargetOpts {
+                crate_name,
+                version: Some(version),
+                actor,
+                yes,
+            };
+            let emails = Emails::from_environment(&config::Server::from_environment()?);
+            scan_quarantine(opts, reason, &emails, conn)
+        }
+    }
+}
+
+fn list(crate_name: Option<&str>, conn: &mut PgConnection) -> Result<()> {
+    let mut query = version_scan_findings::table
+        .inner_join(versions::table.inner_join(crates::table))
+        .select((VersionScanFinding::as_select(), crates::name, versions::num))
+        .order(version_scan_findings::id.desc())
+        .into_boxed();
+
+    query = match crate_name {
+        Some(crate_name) => query.filter(crates::name.eq(crate_name)),
+        None => query.limit(MAX_LISTED_FINDINGS),
+    };
+
+    let findings: Vec<(VersionScanFinding, String, String)> = query.load(conn)?;
+    if findings.is_empty() {
+        println!("No findings");
+        return Ok(());
+    }
+
+    for (finding, crate_name, version) in findings {
+        println!(
+            "#{id} {crate_name}@{version} ({created_at}): {rule} in `{path}`: {message}",
+            id = finding.id,
+            created_at = finding.created_at,
+            rule = finding.rule,
+            path = finding.path,
+            message = finding.message,
+        );
+    }
+
+    Ok(())
+}
+
+fn scan_quarantine(
+    opts: quarantine::TargetOpts,
+    reason: Option<String>,
+    emails: &Emails,
+    conn: &mut PgConnection,
+) -> Result<()> {
+    let crate_name = &opts.crate_name;
+    let version = opts.version.as_deref().unwrap_or_default();
+
+    let krate: Crate = Crate::by_name(crate_name).first(conn)?;
+    let v: Version = Version::belonging_to(&krate)
+        .filter(versions::num.eq(version))
+        .first(conn)?;
+
+    let findings = VersionScanFinding::for_version(v.id, conn)?;
+    if findings.is_empty() {
+        println!("Version {version} of crate {crate_name} has no scan findings");
+    }
+
+    let reason = reason.unwrap_or_else(|| {
+        let mut rules = findings.iter().map(|f| f.rule.as_str()).collect::<Vec<_>>();
+        rules.dedup();
+        format!(
+            "Scanning found signs of malicious code ({})",
+            rules.join(", ")
+        )
+    });
+
+    // The version is yanked too, so that it isn't picked up by new lockfiles
+    // after the quarantine has been lifted by mistake.
+    let action = QuarantineActionKind::Quarantine;
+    quarantine::apply(action, &opts, Some(&reason), true, emails, conn)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::is_version_quarantined;
+    use crate::scanning::Finding;
+    use crate::schema::background_jobs;
+    use crate::test_util::pg_connection;
+    use crate::typosquat::test_util::Faker;
+    use crate::worker::jobs;
+    use crates_io_worker::BackgroundJob;
+
+    #[test]
+    fn quarantine_yanks_version() -> anyhow::Result<()> {
+        let mut faker = Faker::new(pg_connection());
+        let user = faker.user("a")?;
+        let (krate, version) = faker.crate_and_version("mycrate", "description", &user, 0)?;
+        let mut conn = faker.into_conn();
+        let conn = &mut conn;
+        let emails = Emails::new_in_memory();
+
+        let finding = Finding {
+            rule: "build-script-download",
+            path: "build.rs".into(),
+            message: "downloads a file".into(),
+        };
+        VersionScanFinding::insert_all(version.id, &[finding], conn)?;
+
+        let opts = quarantine::TargetOpts {
+            crate_name: "mycrate".into(),
+            version: Some("1.0.0".into()),
+            actor: "admin".into(),
+            yes: true,
+        };
+        scan_quarantine(opts, None, &emails, conn)?;
+
+        assert!(is_version_quarantined(krate.id, version.id, conn)?);
+        let yanked: bool = versions::table
+            .find(version.id)
+            .select(versions::yanked)
+            .get_result(conn)?;
+        assert!(yanked);
+
+        let sync_jobs: i64 = background_jobs::table
+            .filter(background_jobs::job_type.eq(jobs::SyncToGitIndex::JOB_NAME))
+            .count()
+            .get_result(conn)?;
+        assert!(sync_jobs > 0);
+
+        Ok(())
+    }
+}
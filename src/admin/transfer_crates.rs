@@ -35,6 +35,10 @@ fn transfer(opts: Opts, conn: &mut PgConnection) -> anyhow::Result<()> {
         .filter(users::gh_login.eq(opts.to_user))
         .first(conn)?;
 
+    if from.id == to.id {
+        anyhow::bail!("cannot transfer crates to the same user");
+    }
+
     if from.gh_id != to.gh_id {
         println!("====================================================");
         println!("WARNING");
@@ -67,15 +71,42 @@ fn transfer(opts: Opts, conn: &mut PgConnection) -> anyhow::Result<()> {
         }
     }
 
-    diesel::update(crate_owners)
-        .set(crate_owners::owner_id.eq(to.id))
-        .execute(conn)?;
+    transfer_all(&from, &to, conn)?;
 
     get_confirm("commit?");
 
     Ok(())
 }
 
+/// Makes `to` the owner of all crates that are owned by `from`, returning the
+/// number of crates that were transferred. This is shared with the admin API.
+///
+/// Crates that `to` already owns are skipped, and the ownerships of `from` are
+/// removed from them. The users must be different.
+pub fn transfer_all(from: &User, to: &User, conn: &mut PgConnection) -> QueryResult<usize> {
+    let owned_by = |user: &User| {
+        crate_owners::table
+            .filter(crate_owners::owner_id.eq(user.id))
+            .filter(crate_owners::owner_kind.eq(OwnerKind::User))
+    };
+
+    let owned_by_to: Vec<i32> = owned_by(to)
+        .filter(crate_owners::deleted.eq(false))
+        .select(crate_owners::crate_id)
+        .load(conn)?;
+    diesel::delete(owned_by(from).filter(crate_owners::crate_id.eq_any(owned_by_to)))
+        .execute(conn)?;
+
+    // Former ownerships of `to` would conflict with the transferred ones
+    let owned_by_from: Vec<i32> = owned_by(from).select(crate_owners::crate_id).load(conn)?;
+    diesel::delete(owned_by(to).filter(crate_owners::crate_id.eq_any(owned_by_from)))
+        .execute(conn)?;
+
+    diesel::update(owned_by(from))
+        .set(crate_owners::owner_id.eq(to.id))
+        .execute(conn)
+}
+
 fn get_confirm(msg: &str) {
     if !dialoguer::confirm(msg) {
         exit(0);
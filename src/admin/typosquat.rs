@@ -0,0 +1,217 @@
+use crate::db;
+use crate::models::{TyposquatFinding, TyposquatFindingStatus};
+use crate::schema::{crates, typosquat_findings};
+use crate::worker::jobs;
+use anyhow::Result;
+use diesel::prelude::*;
+
+#[derive(clap::Parser, Debug)]
+#[command(
+    name = "typosquat",
+    about = "Review the potential typosquatting found by the `check_typosquat` job",
+    rename_all = "snake_case"
+)]
+pub enum Command {
+    /// List the findings that await review, or all findings of a crate
+    List {
+        /// Only list the findings of this crate, regardless of their status
+        #[arg(long = "crate")]
+        crate_name: Option<String>,
+    },
+    /// Approve findings, which releases the crates that are held from the index
+    Approve(ReviewOpts),
+    /// Reject findings, which keeps the crates that are held out of the index
+    Reject(ReviewOpts),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ReviewOpts {
+    /// IDs of the findings
+    #[arg(required = true)]
+    ids: Vec<i32>,
+    /// A note on the review, e.g. why the crate is not typosquatting
+    #[arg(long)]
+    note: Option<String>,
+    /// Name of the reviewer
+    #[arg(long, env = "USER")]
+    reviewer: String,
+}
+
+pub fn run(command: Command) -> Result<()> {
+    let conn = &mut db::oneoff_connection()?;
+
+    match command {
+        Command::List { crate_name } => list(crate_name.as_deref(), conn),
+        Command::Approve(opts) => conn.transaction(|conn| {
+            review(opts, TyposquatFindingStatus::Approved, conn)?;
+            Ok(())
+        }),
+        Command::Reject(opts) => conn.transaction(|conn| {
+            review(opts, TyposquatFindingStatus::Rejected, conn)?;
+            Ok(())
+        }),
+    }
+}
+
+fn list(crate_name: Option<&str>, conn: &mut PgConnection) -> Result<()> {
+    let mut query = typosquat_findings::table
+        .inner_join(crates::table)
+        .select((TyposquatFinding::as_select(), crates::name))
+        .order(typosquat_findings::id)
+        .into_boxed();
+
+    query = match crate_name {
+        Some(crate_name) => query.filter(crates::name.eq(crate_name)),
+        None => query.filter(typosquat_findings::status.eq(TyposquatFindingStatus::Pending)),
+    };
+
+    let findings: Vec<(TyposquatFinding, String)> = query.load(conn)?;
+    if findings.is_empty() {
+        println!("No findings to review");
+        return Ok(());
+    }
+
+    for (finding, crate_name) in findings {
+        let held = if finding.holds_index { ", held" } else { "" };
+        println!(
+            "#{id} {crate_name} ({status:?}{held}, {created_at}): {message}",
+            id = finding.id,
+            status = finding.status,
+            created_at = finding.created_at,
+            message = finding.message,
+        );
+        if let (Some(reviewer), Some(note)) = (&finding.reviewed_by, &finding.review_note) {
+            println!("    {reviewer}: {note}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Records the review of the findings and syncs the affected crates to the
+/// index, which adds the crates that are no longer held.
+fn review(
+    opts: ReviewOpts,
+    status: TyposquatFindingStatus,
+    conn: &mut PgConnection,
+) -> Result<Vec<TyposquatFinding>> {
+    let findings = TyposquatFinding::review(
+        &opts.ids,
+        status,
+        &opts.reviewer,
+        opts.note.as_deref(),
+        conn,
+    )?;
+
+    for id in &opts.ids {
+        if !findings.iter().any(|finding| finding.id == *id) {
+            anyhow::bail!("finding #{id} does not exist");
+        }
+    }
+
+    let mut held_crate_ids = findings
+        .iter()
+        .filter(|finding| finding.holds_index)
+        .map(|finding| finding.crate_id)
+        .collect::<Vec<_>>();
+    held_crate_ids.dedup();
+
+    let held_crates: Vec<String> = crates::table
+        .filter(crates::id.eq_any(held_crate_ids))
+        .select(crates::name)
+        .load(conn)?;
+
+    for crate_name in held_crates {
+        jobs::enqueue_sync_to_index(&crate_name, conn)?;
+    }
+
+    println!("{status:?} {} findings", findings.len());
+    Ok(findings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::NewTyposquatFinding;
+    use crate::schema::background_jobs;
+    use crate::test_util::pg_connection;
+    use crate::typosquat::test_util::Faker;
+    use crates_io_worker::BackgroundJob;
+
+    fn opts(ids: Vec<i32>) -> ReviewOpts {
+        ReviewOpts {
+            ids,
+            note: Some("looks fine".into()),
+            reviewer: "admin".into(),
+        }
+    }
+
+    #[test]
+    fn review_findings() -> anyhow::Result<()> {
+        let mut faker = Faker::new(pg_connection());
+        let user = faker.user("a")?;
+        let (krate, _) = faker.crate_and_version("mycrate", "description", &user, 0)?;
+        let mut conn = faker.into_conn();
+        let conn = &mut conn;
+
+        NewTyposquatFinding::insert_all(
+            &[
+                NewTyposquatFinding {
+                    crate_id: krate.id,
+                    squatted_crate: "my-crate",
+                    message: "removes the - separator",
+                    holds_index: true,
+                },
+                NewTyposquatFinding {
+                    crate_id: krate.id,
+                    squatted_crate: "mycrate-rs",
+                    message: "removes the -rs suffix",
+                    holds_index: true,
+                },
+            ],
+            conn,
+        )?;
+
+        let findings = TyposquatFinding::for_crate(krate.id, conn)?;
+        let ids = findings.iter().map(|f| f.id).collect::<Vec<_>>();
+        assert!(TyposquatFinding::holds_index_of(krate.id, conn)?);
+
+        // The crate stays held while any finding is not approved
+        let reviewed = review(opts(vec![ids[0]]), TyposquatFindingStatus::Approved, conn)?;
+        assert_eq!(reviewed[0].status, TyposquatFindingStatus::Approved);
+        assert_eq!(reviewed[0].reviewed_by.as_deref(), Some("admin"));
+        assert!(TyposquatFinding::holds_index_of(krate.id, conn)?);
+
+        review(opts(vec![ids[1]]), TyposquatFindingStatus::Rejected, conn)?;
+        assert!(TyposquatFinding::holds_index_of(krate.id, conn)?);
+
+        review(opts(vec![ids[1]]), TyposquatFindingStatus::Approved, conn)?;
+        assert!(!TyposquatFinding::holds_index_of(krate.id, conn)?);
+
+        let sync_jobs: i64 = background_jobs::table
+            .filter(background_jobs::job_type.eq(jobs::SyncToGitIndex::JOB_NAME))
+            .count()
+            .get_result(conn)?;
+        assert!(sync_jobs > 0);
+
+        assert_err!(review(
+            opts(vec![-1]),
+            TyposquatFindingStatus::Approved,
+            conn
+        ));
+
+        // Findings are only recorded once
+        let inserted = NewTyposquatFinding::insert_all(
+            &[NewTyposquatFinding {
+                crate_id: krate.id,
+                squatted_crate: "my-crate",
+                message: "removes the - separator",
+                holds_index: true,
+            }],
+            conn,
+        )?;
+        assert_eq!(inserted, 0);
+
+        Ok(())
+    }
+}
@@ -2,6 +2,7 @@
 
 use crate::config;
 use crate::db::{ConnectionConfig, DieselPool, DieselPooledConn, PoolError};
+use std::collections::BTreeMap;
 use std::ops::Deref;
 use std::sync::atomic::AtomicUsize;
 use std::sync::Arc;
@@ -11,9 +12,11 @@ use crate::email::Emails;
 use crate::metrics::{InstanceMetrics, ServiceMetrics};
 use crate::rate_limiter::RateLimiter;
 use crate::storage::Storage;
+use crate::typosquat::TopCratesCache;
 use axum::extract::{FromRef, FromRequestParts, State};
 use crates_io_github::GitHubClient;
 use diesel::r2d2;
+use hyper::body::Bytes;
 use moka::future::{Cache, CacheBuilder};
 use oauth2::basic::BasicClient;
 use scheduled_thread_pool::ScheduledThreadPool;
@@ -42,6 +45,24 @@ pub struct App {
     /// `version_id` is only cached under the canonical spelling of the crate name.
     pub(crate) version_id_cacher: Cache<(String, String), i32>,
 
+    /// Cache the list of files in the `.crate` file of a `crate_name:semver` pair
+    ///
+    /// This is used by the source browsing endpoints to avoid downloading and unpacking the
+    /// whole tarball for every request.
+    pub(crate) source_index_cacher: Cache<(String, String), Arc<BTreeMap<String, u64>>>,
+
+    /// Cache the contents of single files in the `.crate` file of a `crate_name:semver` pair
+    ///
+    /// The cache is weighed by the size of the files, which are at most `max_source_file_size`
+    /// bytes large.
+    pub(crate) source_file_cacher: Cache<(String, String, String), Bytes>,
+
+    /// Cache the popular crates for the typosquatting check on publish
+    ///
+    /// Loading them takes two queries over thousands of crates, which are too slow to run for
+    /// every new crate.
+    pub(crate) typosquat_top_crates: TopCratesCache,
+
     /// Count downloads and periodically persist them in the database
     pub downloads_counter: DownloadsCounter,
 
@@ -145,12 +166,24 @@ impl App {
             .time_to_live(config.version_id_cache_ttl)
             .build();
 
+        let source_index_cacher = CacheBuilder::new(config.source_index_cache_size)
+            .time_to_live(config.source_index_cache_ttl)
+            .build();
+
+        let source_file_cacher = CacheBuilder::new(config.source_file_cache_size)
+            .weigher(|_, contents: &Bytes| contents.len().try_into().unwrap_or(u32::MAX))
+            .time_to_live(config.source_index_cache_ttl)
+            .build();
+
         App {
             primary_database,
             read_only_replica_database: replica_database,
             github,
             github_oauth,
             version_id_cacher,
+            source_index_cacher,
+            source_file_cacher,
+            typosquat_top_crates: TopCratesCache::new(config.typosquat_top_crates_ttl),
             downloads_counter: DownloadsCounter::new(),
             emails,
             storage: Arc::new(Storage::from_config(&config.storage)),
@@ -0,0 +1,264 @@
+//! Signed release attestations that can be uploaded together with a `.crate`
+//! file.
+//!
+//! An attestation is a JSON document with a detached ECDSA P-256 signature
+//! over the raw bytes of the `.crate` file:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "signer": "https://github.com/rust-lang/foo/.github/workflows/release.yml",
+//!   "signature": "<base64 encoded ASN.1 DER signature>"
+//! }
+//! ```
+//!
+//! The `signer` identity has to be listed in the [`TrustRoot`] of the
+//! instance, which maps identities to the public keys that are used to verify
+//! their signatures. In addition, the signer has to be a GitHub workflow of
+//! an account or organization that owns the crate, see
+//! [`Attestation::is_signed_by_owner()`].
+
+use crate::models::Owner;
+use base64::{engine::general_purpose, Engine};
+use p256::ecdsa::signature::Verifier;
+use p256::ecdsa::{Signature, VerifyingKey};
+use p256::pkcs8::DecodePublicKey;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// The maximum size of an attestation document in bytes.
+pub const MAX_ATTESTATION_SIZE: usize = 64 * 1024;
+
+/// The only attestation format version that is currently supported.
+const ATTESTATION_VERSION: u32 = 1;
+
+/// The signer identities that are trusted to sign attestations, and their
+/// public keys.
+#[derive(Clone, Debug, Default)]
+pub struct TrustRoot {
+    signers: HashMap<String, VerifyingKey>,
+}
+
+impl TrustRoot {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_signer(mut self, identity: impl Into<String>, key: VerifyingKey) -> Self {
+        self.signers.insert(identity.into(), key);
+        self
+    }
+
+    /// Parses and verifies an attestation for the given `.crate` file
+    /// contents.
+    pub fn verify(&self, attestation: &[u8], tarball: &[u8]) -> Result<Attestation, Error> {
+        if attestation.len() > MAX_ATTESTATION_SIZE {
+            return Err(Error::TooLarge);
+        }
+
+        let attestation: Attestation =
+            serde_json::from_slice(attestation).map_err(Error::InvalidFormat)?;
+
+        if attestation.version != ATTESTATION_VERSION {
+            return Err(Error::UnsupportedVersion(attestation.version));
+        }
+
+        let signature = general_purpose::STANDARD
+            .decode(&attestation.signature)
+            .map_err(|_| Error::InvalidSignatureEncoding)?;
+        let signature =
+            Signature::from_der(&signature).map_err(|_| Error::InvalidSignatureEncoding)?;
+
+        let key = self
+            .signers
+            .get(&attestation.signer)
+            .ok_or_else(|| Error::UntrustedSigner(attestation.signer.clone()))?;
+
+        key.verify(tarball, &signature)
+            .map_err(|_| Error::InvalidSignature)?;
+
+        Ok(attestation)
+    }
+}
+
+/// Parses a comma separated list of `identity:key` pairs, where `key` is a
+/// base64 encoded DER `SubjectPublicKeyInfo` of a P-256 public key.
+///
+/// The identity is separated from the key by the last colon, so identities
+/// may contain colons themselves (e.g. URLs).
+impl FromStr for TrustRoot {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut trust_root = Self::new();
+        for entry in s.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let (identity, key) = entry
+                .rsplit_once(':')
+                .ok_or_else(|| anyhow::anyhow!("invalid trust root entry: {entry}"))?;
+
+            let key = general_purpose::STANDARD.decode(key)?;
+            let key = VerifyingKey::from_public_key_der(&key)
+                .map_err(|error| anyhow::anyhow!("invalid public key for {identity}: {error}"))?;
+
+            trust_root = trust_root.with_signer(identity, key);
+        }
+
+        Ok(trust_root)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Attestation {
+    pub version: u32,
+    pub signer: String,
+    pub signature: String,
+}
+
+impl Attestation {
+    /// Returns the GitHub account or organization of the signer, e.g. `foo`
+    /// for `https://github.com/foo/bar/.github/workflows/release.yml`.
+    pub fn signer_account(&self) -> Option<&str> {
+        let path = self.signer.strip_prefix("https://github.com/")?;
+        path.split('/').next().filter(|account| !account.is_empty())
+    }
+
+    /// Checks whether the signer belongs to the GitHub account of a user
+    /// owner, or to the GitHub organization of a team owner of the crate.
+    pub fn is_signed_by_owner(&self, owners: &[Owner]) -> bool {
+        let Some(account) = self.signer_account() else {
+            return false;
+        };
+
+        owners.iter().any(|owner| {
+            let owner_account = match owner {
+                Owner::User(user) => Some(user.gh_login.as_str()),
+                // Team logins have the format `github:org:team`
+                Owner::Team(team) => team.login.split(':').nth(1),
+            };
+            owner_account.is_some_and(|owner_account| owner_account.eq_ignore_ascii_case(account))
+        })
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("attestation is larger than {MAX_ATTESTATION_SIZE} bytes")]
+    TooLarge,
+    #[error("invalid attestation: {0}")]
+    InvalidFormat(serde_json::Error),
+    #[error("unsupported attestation version: {0}")]
+    UnsupportedVersion(u32),
+    #[error("attestation signature is not a base64 encoded ASN.1 DER signature")]
+    InvalidSignatureEncoding,
+    #[error("attestation signer `{0}` is not trusted")]
+    UntrustedSigner(String),
+    #[error("attestation signature does not match the uploaded crate file")]
+    InvalidSignature,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use p256::ecdsa::signature::Signer;
+    use p256::ecdsa::SigningKey;
+    use p256::pkcs8::EncodePublicKey;
+
+    const SIGNER: &str = "https://github.com/foo/foo/.github/workflows/release.yml";
+
+    fn signing_key() -> SigningKey {
+        SigningKey::from_slice(&[1; 32]).unwrap()
+    }
+
+    fn sign(signer: &str, key: &SigningKey, contents: &[u8]) -> Vec<u8> {
+        let signature: Signature = key.sign(contents);
+        let signature = general_purpose::STANDARD.encode(signature.to_der());
+        let attestation = json!({ "version": 1, "signer": signer, "signature": signature });
+        serde_json::to_vec(&attestation).unwrap()
+    }
+
+    fn trust_root() -> TrustRoot {
+        TrustRoot::new().with_signer(SIGNER, *signing_key().verifying_key())
+    }
+
+    #[test]
+    fn valid_attestation() {
+        let attestation = sign(SIGNER, &signing_key(), b"crate");
+        let attestation = assert_ok!(trust_root().verify(&attestation, b"crate"));
+        assert_eq!(attestation.signer, SIGNER);
+    }
+
+    #[test]
+    fn invalid_signature() {
+        let attestation = sign(SIGNER, &signing_key(), b"crate");
+        let error = assert_err!(trust_root().verify(&attestation, b"other crate"));
+        assert!(matches!(error, Error::InvalidSignature));
+
+        let other_key = SigningKey::from_slice(&[2; 32]).unwrap();
+        let attestation = sign(SIGNER, &other_key, b"crate");
+        let error = assert_err!(trust_root().verify(&attestation, b"crate"));
+        assert!(matches!(error, Error::InvalidSignature));
+    }
+
+    #[test]
+    fn untrusted_signer() {
+        let attestation = sign("someone else", &signing_key(), b"crate");
+        let error = assert_err!(trust_root().verify(&attestation, b"crate"));
+        assert!(matches!(error, Error::UntrustedSigner(signer) if signer == "someone else"));
+    }
+
+    #[test]
+    fn invalid_format() {
+        let error = assert_err!(trust_root().verify(b"foo", b"crate"));
+        assert!(matches!(error, Error::InvalidFormat(_)));
+
+        let attestation = br#"{"version": 2, "signer": "foo", "signature": ""}"#;
+        let error = assert_err!(trust_root().verify(attestation, b"crate"));
+        assert!(matches!(error, Error::UnsupportedVersion(2)));
+
+        let attestation = br#"{"version": 1, "signer": "foo", "signature": "foo"}"#;
+        let error = assert_err!(trust_root().verify(attestation, b"crate"));
+        assert!(matches!(error, Error::InvalidSignatureEncoding));
+
+        let attestation = vec![b' '; MAX_ATTESTATION_SIZE + 1];
+        let error = assert_err!(trust_root().verify(&attestation, b"crate"));
+        assert!(matches!(error, Error::TooLarge));
+    }
+
+    #[test]
+    fn signer_account() {
+        let attestation = |signer: &str| Attestation {
+            version: 1,
+            signer: signer.into(),
+            signature: String::new(),
+        };
+
+        assert_eq!(attestation(SIGNER).signer_account(), Some("foo"));
+        assert_eq!(
+            attestation("https://github.com/bar").signer_account(),
+            Some("bar")
+        );
+        assert_eq!(attestation("https://github.com/").signer_account(), None);
+        assert_eq!(
+            attestation("https://gitlab.com/foo/foo").signer_account(),
+            None
+        );
+        assert_eq!(attestation("foo").signer_account(), None);
+    }
+
+    #[test]
+    fn parse_trust_root() {
+        let key = signing_key().verifying_key().to_public_key_der().unwrap();
+        let key = general_purpose::STANDARD.encode(key.as_bytes());
+
+        let trust_root: TrustRoot = assert_ok!(format!("{SIGNER}:{key}, foo:{key}").parse());
+        assert_eq!(trust_root.signers.len(), 2);
+        assert!(trust_root.signers.contains_key(SIGNER));
+        assert!(trust_root.signers.contains_key("foo"));
+
+        let trust_root: TrustRoot = assert_ok!("".parse());
+        assert!(trust_root.signers.is_empty());
+
+        assert_err!("foo".parse::<TrustRoot>());
+        assert_err!("foo:bar".parse::<TrustRoot>());
+    }
+}
@@ -3,19 +3,23 @@ use crate::controllers::util::RequestPartsExt;
 use crate::middleware::log_request::RequestLogExt;
 use crate::middleware::session::RequestSession;
 use crate::models::token::{CrateScope, EndpointScope};
-use crate::models::{ApiToken, User};
+use crate::models::{AdminToken, ApiToken, NewAdminAction, User};
 use crate::util::errors::{
-    account_locked, forbidden, internal, AppError, AppResult, InsecurelyGeneratedTokenRevoked,
+    account_locked, bad_request, forbidden, internal, AppError, AppResult, BoxedAppError,
+    InsecurelyGeneratedTokenRevoked,
 };
 use chrono::Utc;
-use diesel::PgConnection;
+use diesel::{PgConnection, QueryResult};
 use http::header;
+use semver::Version;
+use serde_json::Value;
 
 #[derive(Debug, Clone)]
 pub struct AuthCheck {
     allow_token: bool,
     endpoint_scope: Option<EndpointScope>,
     crate_name: Option<String>,
+    crate_version: Option<Version>,
 }
 
 impl AuthCheck {
@@ -27,6 +31,7 @@ impl AuthCheck {
             allow_token: true,
             endpoint_scope: None,
             crate_name: None,
+            crate_version: None,
         }
     }
 
@@ -36,14 +41,21 @@ impl AuthCheck {
             allow_token: false,
             endpoint_scope: None,
             crate_name: None,
+            crate_version: None,
         }
     }
 
+    /// Allows API tokens with the given endpoint scope to use the endpoint.
+    ///
+    /// For [`AuthCheck::only_cookie()`] endpoints this only lets through
+    /// tokens that were explicitly created with this endpoint scope, while
+    /// legacy tokens without any endpoint scopes are still rejected.
     pub fn with_endpoint_scope(&self, endpoint_scope: EndpointScope) -> Self {
         Self {
             allow_token: self.allow_token,
             endpoint_scope: Some(endpoint_scope),
             crate_name: self.crate_name.clone(),
+            crate_version: self.crate_version.clone(),
         }
     }
 
@@ -52,6 +64,18 @@ impl AuthCheck {
             allow_token: self.allow_token,
             endpoint_scope: self.endpoint_scope,
             crate_name: Some(crate_name.to_string()),
+            crate_version: self.crate_version.clone(),
+        }
+    }
+
+    /// Restricts the check to a single version of the crate, which lets
+    /// through tokens with crate scopes that only match some versions.
+    pub fn for_version(&self, version: &Version) -> Self {
+        Self {
+            allow_token: self.allow_token,
+            endpoint_scope: self.endpoint_scope,
+            crate_name: self.crate_name.clone(),
+            crate_version: Some(version.clone()),
         }
     }
 
@@ -64,7 +88,7 @@ impl AuthCheck {
         let auth = authenticate(request, conn)?;
 
         if let Some(token) = auth.api_token() {
-            if !self.allow_token {
+            if !self.allow_token && !self.explicitly_allows(token.endpoint_scopes.as_ref()) {
                 let error_message =
                     "API Token authentication was explicitly disallowed for this API";
                 return Err(internal(error_message).chain(forbidden()));
@@ -97,6 +121,15 @@ impl AuthCheck {
         }
     }
 
+    /// Whether the token was explicitly given the endpoint scope of this
+    /// check, which lets it through even if tokens are otherwise disallowed.
+    fn explicitly_allows(&self, token_scopes: Option<&Vec<EndpointScope>>) -> bool {
+        match (token_scopes, &self.endpoint_scope) {
+            (Some(token_scopes), Some(endpoint_scope)) => token_scopes.contains(endpoint_scope),
+            _ => false,
+        }
+    }
+
     fn crate_scope_matches(&self, token_scopes: Option<&Vec<CrateScope>>) -> bool {
         match (&token_scopes, &self.crate_name) {
             // The token is a legacy token.
@@ -105,13 +138,21 @@ impl AuthCheck {
             // The token does not have any crate scopes.
             (Some(token_scopes), _) if token_scopes.is_empty() => true,
 
-            // The token has crate scopes, but the endpoint does not deal with crates.
-            (Some(_), None) => false,
+            // The token has crate scopes, but the endpoint does not deal with crates. Only
+            // endpoints that read the data of the user are allowed in that case.
+            (Some(_), None) => self
+                .endpoint_scope
+                .is_some_and(|scope| scope.is_read_only()),
 
             // The token is NOT a legacy token, and the endpoint allows a certain endpoint scope or a legacy token.
-            (Some(token_scopes), Some(crate_name)) => token_scopes
-                .iter()
-                .any(|token_scope| token_scope.matches(crate_name)),
+            (Some(token_scopes), Some(crate_name)) => {
+                token_scopes
+                    .iter()
+                    .any(|token_scope| match &self.crate_version {
+                        Some(version) => token_scope.matches_version(crate_name, version),
+                        None => token_scope.matches(crate_name),
+                    })
+            }
         }
     }
 }
@@ -174,6 +215,11 @@ fn authenticate_via_cookie<T: RequestPartsExt>(
     let user = User::find(conn, id)
         .map_err(|err| err.chain(internal("user_id from cookie not found in database")))?;
 
+    // Sessions of other browsers outlive the deletion of the account
+    if user.deleted_at.is_some() {
+        return Err(internal("user_id from cookie belongs to a deleted account").chain(forbidden()));
+    }
+
     ensure_not_locked(&user)?;
 
     req.request_log().add("uid", id);
@@ -234,6 +280,116 @@ fn authenticate<T: RequestPartsExt>(req: &T, conn: &mut PgConnection) -> AppResu
     return Err(internal("no cookie session or auth header found").chain(forbidden()));
 }
 
+/// The authentication of a request to the admin API.
+///
+/// Only admin tokens are accepted, and only while their user still has the
+/// admin role, so that revoking the role takes effect immediately.
+#[derive(Debug)]
+pub struct AdminAuthentication {
+    token: AdminToken,
+    user: User,
+}
+
+impl AdminAuthentication {
+    #[instrument(name = "auth.admin", skip_all)]
+    pub fn check<T: RequestPartsExt>(req: &T, conn: &mut PgConnection) -> AppResult<Self> {
+        controllers::util::verify_origin(req)?;
+
+        let header_value = req
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|h| h.to_str().ok())
+            .ok_or_else(|| internal("no admin token found").chain(forbidden()))?;
+
+        let token = AdminToken::find_by_admin_token(conn, header_value).map_err(|e| {
+            warn!(route = %req.uri().path(), "Rejected invalid admin token");
+            e.chain(internal("invalid admin token")).chain(forbidden())
+        })?;
+
+        let user = User::find(conn, token.user_id)
+            .map_err(|err| err.chain(internal("user_id from token not found in database")))?;
+
+        if let Err(error) = ensure_not_locked(&user) {
+            audit_rejection(&token, req, "the account is locked", conn);
+            return Err(error);
+        }
+
+        if !user.is_admin {
+            audit_rejection(&token, req, "the user is not an admin", conn);
+            return Err(internal("user is not an admin").chain(forbidden()));
+        }
+
+        req.request_log().add("uid", token.user_id);
+        req.request_log().add("admintokenid", token.id);
+
+        Ok(Self { token, user })
+    }
+
+    pub fn user(&self) -> &User {
+        &self.user
+    }
+
+    pub fn token(&self) -> &AdminToken {
+        &self.token
+    }
+
+    /// Records an action in the audit log of the admin API.
+    pub fn audit(
+        &self,
+        action: &str,
+        target: &str,
+        details: Value,
+        conn: &mut PgConnection,
+    ) -> QueryResult<()> {
+        NewAdminAction {
+            user_id: self.user.id,
+            admin_token_id: self.token.id,
+            action,
+            target,
+            details,
+        }
+        .insert(conn)?;
+
+        Ok(())
+    }
+
+    /// Records the rejected request in the audit log and returns a
+    /// `400 Bad Request` error with the reason.
+    pub fn reject<T: RequestPartsExt>(
+        &self,
+        req: &T,
+        reason: &str,
+        conn: &mut PgConnection,
+    ) -> BoxedAppError {
+        audit_rejection(&self.token, req, reason, conn);
+        bad_request(reason)
+    }
+}
+
+/// Records a request with a valid admin token that was rejected in the audit
+/// log. The request is rejected regardless of whether this succeeds.
+fn audit_rejection<T: RequestPartsExt>(
+    token: &AdminToken,
+    req: &T,
+    reason: &str,
+    conn: &mut PgConnection,
+) {
+    let route = req.uri().path();
+    let details = json!({ "method": req.method().as_str(), "reason": reason });
+
+    let action = NewAdminAction {
+        user_id: token.user_id,
+        admin_token_id: token.id,
+        action: "rejected",
+        target: route,
+        details,
+    };
+
+    if let Err(error) = action.insert(conn) {
+        warn!(%error, route, "Failed to record rejected admin request");
+    }
+}
+
 fn ensure_not_locked(user: &User) -> AppResult<()> {
     if let Some(reason) = &user.account_lock_reason {
         let still_locked = user
@@ -347,4 +503,52 @@ mod tests {
         assert!(!auth_check.crate_scope_matches(Some(&vec![cs("anyhow")])));
         assert!(!auth_check.crate_scope_matches(Some(&vec![cs("actix-*")])));
     }
+
+    #[test]
+    fn private_data_endpoint() {
+        let auth_check = AuthCheck::only_cookie().with_endpoint_scope(EndpointScope::ReadPrivate);
+
+        assert!(auth_check.explicitly_allows(Some(&vec![EndpointScope::ReadPrivate])));
+        assert!(!auth_check.explicitly_allows(Some(&vec![EndpointScope::Follow])));
+        assert!(!auth_check.explicitly_allows(None));
+        assert!(
+            !AuthCheck::only_cookie().explicitly_allows(Some(&vec![EndpointScope::ReadPrivate]))
+        );
+    }
+
+    #[test]
+    fn crate_scoped_tokens_without_crate() {
+        let read_private = AuthCheck::only_cookie().with_endpoint_scope(EndpointScope::ReadPrivate);
+        assert!(read_private.crate_scope_matches(Some(&vec![cs("tokio-console")])));
+
+        let manage_tokens =
+            AuthCheck::only_cookie().with_endpoint_scope(EndpointScope::ManageTokens);
+        assert!(!manage_tokens.crate_scope_matches(Some(&vec![cs("tokio-console")])));
+
+        assert!(!AuthCheck::default().crate_scope_matches(Some(&vec![cs("tokio-console")])));
+    }
+
+    #[test]
+    fn version_restricted_crate_scopes() {
+        let version = Version::parse("1.2.0").unwrap();
+        let prerelease = Version::parse("2.0.0-rc.1").unwrap();
+
+        let crate_check = AuthCheck::default()
+            .with_endpoint_scope(EndpointScope::ChangeOwners)
+            .for_crate("tokio-console");
+        let version_check = AuthCheck::default()
+            .with_endpoint_scope(EndpointScope::Yank)
+            .for_crate("tokio-console")
+            .for_version(&version);
+        let prerelease_check = version_check.for_version(&prerelease);
+
+        assert!(!crate_check.crate_scope_matches(Some(&vec![cs("tokio-console@^1")])));
+        assert!(version_check.crate_scope_matches(Some(&vec![cs("tokio-console@^1")])));
+        assert!(version_check.crate_scope_matches(Some(&vec![cs("tokio-*@>=1.2")])));
+        assert!(version_check.crate_scope_matches(Some(&vec![cs("tokio-console")])));
+        assert!(!version_check.crate_scope_matches(Some(&vec![cs("tokio-console@^2")])));
+        assert!(!version_check.crate_scope_matches(Some(&vec![cs("tokio-*@prerelease")])));
+        assert!(prerelease_check.crate_scope_matches(Some(&vec![cs("tokio-*@prerelease")])));
+        assert!(!prerelease_check.crate_scope_matches(Some(&vec![cs("tokio-console@^1")])));
+    }
 }
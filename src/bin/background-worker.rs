@@ -20,7 +20,7 @@ use crates_io::fastly::Fastly;
 use crates_io::storage::Storage;
 use crates_io::worker::{Environment, RunnerExt};
 use crates_io::{config, Emails};
-use crates_io::{db, ssh};
+use crates_io::{db, scanning, ssh};
 use crates_io_env_vars::var;
 use crates_io_index::RepositoryConfig;
 use crates_io_worker::Runner;
@@ -77,6 +77,7 @@ fn main() -> anyhow::Result<()> {
 
     let emails = Emails::from_environment(&config);
     let fastly = Fastly::from_environment(client);
+    let scanning = scanning::Config::from_environment()?;
 
     let connection_pool = r2d2::Pool::builder()
         .max_size(10)
@@ -90,6 +91,7 @@ fn main() -> anyhow::Result<()> {
         .storage(storage)
         .connection_pool(DieselPool::new_background_worker(connection_pool.clone()))
         .emails(emails)
+        .scanning(scanning)
         .build()?;
 
     let environment = Arc::new(environment);
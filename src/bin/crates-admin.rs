@@ -2,8 +2,9 @@
 extern crate tracing;
 
 use crates_io::admin::{
-    delete_crate, delete_version, enqueue_job, git_import, migrate, populate, render_readmes,
-    test_pagerduty, transfer_crates, upload_index, verify_token, yank_version,
+    account_lock, admins, delete_crate, delete_version, enqueue_job, git_import,
+    invitation_expiration, migrate, populate, quarantine, render_readmes, scan, test_pagerduty,
+    transfer_crates, typosquat, upload_index, verify_token, yank_version,
 };
 
 #[derive(clap::Parser, Debug)]
@@ -22,6 +23,18 @@ enum Command {
     GitImport(git_import::Opts),
     #[clap(subcommand)]
     EnqueueJob(enqueue_job::Command),
+    #[clap(subcommand)]
+    Typosquat(typosquat::Command),
+    #[clap(subcommand)]
+    Scan(scan::Command),
+    #[clap(subcommand)]
+    Quarantine(quarantine::Command),
+    #[clap(subcommand)]
+    Admins(admins::Command),
+    #[clap(subcommand)]
+    AccountLock(account_lock::Command),
+    #[clap(subcommand)]
+    InvitationExpiration(invitation_expiration::Command),
 }
 
 fn main() -> anyhow::Result<()> {
@@ -49,6 +62,12 @@ fn main() -> anyhow::Result<()> {
         Command::YankVersion(opts) => yank_version::run(opts),
         Command::GitImport(opts) => git_import::run(opts),
         Command::EnqueueJob(command) => enqueue_job::run(command),
+        Command::Typosquat(command) => typosquat::run(command),
+        Command::Scan(command) => scan::run(command),
+        Command::Quarantine(command) => quarantine::run(command),
+        Command::Admins(command) => admins::run(command),
+        Command::AccountLock(command) => account_lock::run(command),
+        Command::InvitationExpiration(command) => invitation_expiration::run(command),
     }
 }
 
@@ -1,11 +1,13 @@
 mod balance_capacity;
 mod base;
 mod database_pools;
+mod search_relevance;
 mod sentry;
 mod server;
 
 pub use self::balance_capacity::BalanceCapacityConfig;
 pub use self::base::Base;
 pub use self::database_pools::{DatabasePools, DbPoolConfig};
+pub use self::search_relevance::SearchRelevanceConfig;
 pub use self::sentry::SentryConfig;
 pub use self::server::Server;
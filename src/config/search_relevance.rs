@@ -0,0 +1,59 @@
+use crates_io_env_vars::var_parsed;
+
+/// The weights of the signals that are blended into the relevance score of
+/// search results, see `controllers::krate::search::relevance`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SearchRelevanceConfig {
+    /// Weight of the normalized full text search rank, which is in `[0, 1)`.
+    pub text_weight: f64,
+    /// Weight of `ln(1 + downloads)` over the last 90 days.
+    pub recent_downloads_weight: f64,
+    /// Weight of `ln(1 + n)`, where `n` is the number of crates that depend
+    /// on the crate.
+    pub reverse_dependencies_weight: f64,
+    /// Weight of the recency of the last release, which halves every
+    /// `recency_half_life_days`.
+    pub recency_weight: f64,
+    pub recency_half_life_days: f64,
+    /// Subtracted from the score of crates whose versions are all yanked.
+    pub yanked_penalty: f64,
+}
+
+impl Default for SearchRelevanceConfig {
+    fn default() -> Self {
+        Self {
+            text_weight: 1.0,
+            recent_downloads_weight: 0.03,
+            reverse_dependencies_weight: 0.05,
+            recency_weight: 0.1,
+            recency_half_life_days: 365.0,
+            yanked_penalty: 0.5,
+        }
+    }
+}
+
+impl SearchRelevanceConfig {
+    pub fn from_environment() -> anyhow::Result<Self> {
+        let default = Self::default();
+
+        let config = Self {
+            text_weight: var_parsed("SEARCH_WEIGHT_TEXT")?.unwrap_or(default.text_weight),
+            recent_downloads_weight: var_parsed("SEARCH_WEIGHT_RECENT_DOWNLOADS")?
+                .unwrap_or(default.recent_downloads_weight),
+            reverse_dependencies_weight: var_parsed("SEARCH_WEIGHT_REVERSE_DEPENDENCIES")?
+                .unwrap_or(default.reverse_dependencies_weight),
+            recency_weight: var_parsed("SEARCH_WEIGHT_RECENCY")?.unwrap_or(default.recency_weight),
+            recency_half_life_days: var_parsed("SEARCH_RECENCY_HALF_LIFE_DAYS")?
+                .unwrap_or(default.recency_half_life_days),
+            yanked_penalty: var_parsed("SEARCH_YANKED_PENALTY")?.unwrap_or(default.yanked_penalty),
+        };
+
+        if config.recency_half_life_days <= 0.0 {
+            return Err(anyhow::anyhow!(
+                "SEARCH_RECENCY_HALF_LIFE_DAYS must be a positive number of days"
+            ));
+        }
+
+        Ok(config)
+    }
+}
@@ -2,12 +2,14 @@ use anyhow::{anyhow, Context};
 use ipnetwork::IpNetwork;
 use oauth2::{ClientId, ClientSecret};
 
+use crate::attestation::TrustRoot;
 use crate::rate_limiter::{LimitedAction, RateLimiterConfig};
 use crate::Env;
 
 use super::base::Base;
 use super::database_pools::DatabasePools;
 use crate::config::balance_capacity::BalanceCapacityConfig;
+use crate::config::search_relevance::SearchRelevanceConfig;
 use crate::middleware::cargo_compat::StatusCodeConfig;
 use crate::storage::StorageConfig;
 use crates_io_env_vars::{required_var, var, var_parsed};
@@ -19,6 +21,16 @@ use std::time::Duration;
 const DEFAULT_VERSION_ID_CACHE_SIZE: u64 = 10_000;
 const DEFAULT_VERSION_ID_CACHE_TTL: u64 = 5 * 60; // 5 minutes
 
+const DEFAULT_SOURCE_INDEX_CACHE_SIZE: u64 = 1_000;
+const DEFAULT_SOURCE_INDEX_CACHE_TTL: u64 = 60 * 60; // 1 hour
+const DEFAULT_SOURCE_FILE_CACHE_SIZE: u64 = 64 * 1024 * 1024; // 64 MB
+
+const DEFAULT_MAX_SOURCE_FILE_SIZE: u64 = 1024 * 1024; // 1 MB
+const DEFAULT_MAX_DIFF_UNPACK_SIZE: u64 = 64 * 1024 * 1024; // 64 MB
+const DEFAULT_MAX_DIFF_FILES: usize = 10_000;
+
+const DEFAULT_TYPOSQUAT_TOP_CRATES_TTL: u64 = 60 * 60; // 1 hour
+
 /// Maximum number of features a crate can have or that a feature itself can
 /// enable. This value can be overridden in the database on a per-crate basis.
 const DEFAULT_MAX_FEATURES: usize = 300;
@@ -35,6 +47,15 @@ pub struct Server {
     pub gh_client_secret: ClientSecret,
     pub max_upload_size: u64,
     pub max_unpack_size: u64,
+    /// Maximum size of a single file that is served by the source browsing
+    /// endpoints.
+    pub max_source_file_size: u64,
+    /// Maximum number of bytes that are unpacked from both `.crate` files
+    /// when two versions are compared.
+    pub max_diff_unpack_size: u64,
+    /// Maximum number of files in both `.crate` files when two versions are
+    /// compared.
+    pub max_diff_files: usize,
     pub max_features: usize,
     pub rate_limiter: HashMap<LimitedAction, RateLimiterConfig>,
     pub new_version_rate_limit: Option<u32>,
@@ -52,10 +73,32 @@ pub struct Server {
     pub instance_metrics_log_every_seconds: Option<u64>,
     pub force_unconditional_redirects: bool,
     pub blocked_routes: HashSet<String>,
+    /// The names of the lints in [`crate::lints`] that are run on publish.
+    pub enabled_publish_lints: HashSet<String>,
+    /// Whether published versions are compared with the git commit they were
+    /// published from by a background job.
+    pub verify_vcs_commits: bool,
+    /// Whether the files of published versions are scanned for signs of
+    /// malicious code by a background job.
+    pub scan_new_versions: bool,
+    /// The signers that are trusted to sign release attestations.
+    pub attestation_trust_root: TrustRoot,
     pub version_id_cache_size: u64,
     pub version_id_cache_ttl: Duration,
+    pub source_index_cache_size: u64,
+    pub source_index_cache_ttl: Duration,
+    /// Total size in bytes of the source files that are cached by the source
+    /// browsing endpoints. Cached files expire after `source_index_cache_ttl`.
+    pub source_file_cache_size: u64,
     pub cdn_user_agent: String,
     pub balance_capacity: BalanceCapacityConfig,
+    /// The weights of the relevance score of search results.
+    pub search_relevance: SearchRelevanceConfig,
+    /// The configuration of the typosquatting checks, which is read from the file in the
+    /// `TYPOSQUAT_CONFIG` environment variable.
+    pub typosquat: crate::typosquat::Config,
+    /// How long the popular crates for the typosquatting check on publish are cached.
+    pub typosquat_top_crates_ttl: Duration,
 
     /// Instructs the `cargo_compat` middleware whether to adjust response
     /// status codes to `200 OK` for all endpoints that are relevant for cargo.
@@ -103,6 +146,19 @@ impl Server {
     ///   endpoint even with a healthy database pool.
     /// - `BLOCKED_ROUTES`: A comma separated list of HTTP route patterns that are manually blocked
     ///   by an operator (e.g. `/crates/:crate_id/:version/download`).
+    /// - `PUBLISH_LINTS`: A comma separated list of the lints that are run on publish. If not
+    ///   set, all lints are enabled. Unknown lint names are rejected.
+    /// - `VERIFY_VCS_COMMITS`: If set, published versions are compared with the git commit that is
+    ///   recorded in their `.cargo_vcs_info.json` file.
+    /// - `ATTESTATION_TRUST_ROOT`: A comma separated list of `identity:key` pairs of the signers
+    ///   that are trusted to sign release attestations. `key` is a base64 encoded DER P-256
+    ///   public key.
+    /// - `MAX_SOURCE_FILE_SIZE`: The maximum size in bytes of a single file that is served by the
+    ///   source browsing endpoints. Defaults to 1 MB.
+    /// - `SOURCE_FILE_CACHE_SIZE`: The total size in bytes of the source files that are cached in
+    ///   memory by the source browsing endpoints. Defaults to 64 MB.
+    /// - `TYPOSQUAT_TOP_CRATES_TTL`: How many seconds the popular crates for the typosquatting
+    ///   check on publish are cached. Defaults to one hour.
     ///
     /// # Panics
     ///
@@ -197,6 +253,11 @@ impl Server {
             gh_client_secret: ClientSecret::new(required_var("GH_CLIENT_SECRET")?),
             max_upload_size: 10 * 1024 * 1024, // 10 MB default file upload size limit
             max_unpack_size: 512 * 1024 * 1024, // 512 MB max when decompressed
+            max_source_file_size: var_parsed("MAX_SOURCE_FILE_SIZE")?
+                .unwrap_or(DEFAULT_MAX_SOURCE_FILE_SIZE),
+            max_diff_unpack_size: var_parsed("MAX_DIFF_UNPACK_SIZE")?
+                .unwrap_or(DEFAULT_MAX_DIFF_UNPACK_SIZE),
+            max_diff_files: var_parsed("MAX_DIFF_FILES")?.unwrap_or(DEFAULT_MAX_DIFF_FILES),
             max_features: DEFAULT_MAX_FEATURES,
             rate_limiter,
             new_version_rate_limit: var_parsed("MAX_NEW_VERSIONS_DAILY")?,
@@ -218,14 +279,36 @@ impl Server {
             blocked_routes: var("BLOCKED_ROUTES")?
                 .map(|routes| routes.split(',').map(|s| s.into()).collect())
                 .unwrap_or_default(),
+            enabled_publish_lints: var("PUBLISH_LINTS")?
+                .map(|lints| crate::lints::parse_lint_names(&lints))
+                .transpose()?
+                .unwrap_or_else(crate::lints::all_lint_names),
+            verify_vcs_commits: var("VERIFY_VCS_COMMITS")?.is_some(),
+            scan_new_versions: var("SCAN_NEW_VERSIONS")?.is_some(),
+            attestation_trust_root: var("ATTESTATION_TRUST_ROOT")?
+                .map(|trust_root| trust_root.parse())
+                .transpose()?
+                .unwrap_or_default(),
             version_id_cache_size: var_parsed("VERSION_ID_CACHE_SIZE")?
                 .unwrap_or(DEFAULT_VERSION_ID_CACHE_SIZE),
             version_id_cache_ttl: Duration::from_secs(
                 var_parsed("VERSION_ID_CACHE_TTL")?.unwrap_or(DEFAULT_VERSION_ID_CACHE_TTL),
             ),
+            source_index_cache_size: var_parsed("SOURCE_INDEX_CACHE_SIZE")?
+                .unwrap_or(DEFAULT_SOURCE_INDEX_CACHE_SIZE),
+            source_index_cache_ttl: Duration::from_secs(
+                var_parsed("SOURCE_INDEX_CACHE_TTL")?.unwrap_or(DEFAULT_SOURCE_INDEX_CACHE_TTL),
+            ),
+            source_file_cache_size: var_parsed("SOURCE_FILE_CACHE_SIZE")?
+                .unwrap_or(DEFAULT_SOURCE_FILE_CACHE_SIZE),
             cdn_user_agent: var("WEB_CDN_USER_AGENT")?
                 .unwrap_or_else(|| "Amazon CloudFront".into()),
             balance_capacity: BalanceCapacityConfig::from_environment()?,
+            search_relevance: SearchRelevanceConfig::from_environment()?,
+            typosquat: crate::typosquat::Config::from_env()?,
+            typosquat_top_crates_ttl: Duration::from_secs(
+                var_parsed("TYPOSQUAT_TOP_CRATES_TTL")?.unwrap_or(DEFAULT_TYPOSQUAT_TOP_CRATES_TTL),
+            ),
             cargo_compat_status_code_config: var_parsed("CARGO_COMPAT_STATUS_CODES")?
                 .unwrap_or(StatusCodeConfig::AdjustAll),
             serve_dist: true,
@@ -0,0 +1,577 @@
+//! The admin API, which offers the operator actions of the `crates-admin` CLI
+//! to users with the admin role, so that they can respond to incidents without
+//! shell access to production.
+//!
+//! All endpoints only accept admin tokens, and every action is recorded in the
+//! audit log in the same transaction as the action itself.
+
+use crate::admin::delete_crate::delete_crate_files;
+use crate::admin::delete_version::delete_version_files;
+use crate::admin::{enqueue_job, transfer_crates};
+use crate::auth::AdminAuthentication;
+use crate::controllers::frontend_prelude::*;
+use crate::models::{
+    AdminAction, AdoptionRequest, Crate, QuarantineActionKind, QuarantineTarget, User, Version,
+};
+use crate::schema::{crates, users, versions};
+use crate::util::BytesRequest;
+use crate::views::EncodableAdoptionRequest;
+use crate::worker::jobs;
+use chrono::NaiveDateTime;
+use crates_io_worker::BackgroundJob;
+
+/// The number of entries of the audit log that are returned.
+const MAX_LISTED_ACTIONS: i64 = 100;
+
+#[derive(Serialize)]
+struct EncodableAdminAction {
+    #[serde(flatten)]
+    action: AdminAction,
+    user: String,
+}
+
+/// Handles the `GET /api/private/admin/actions` route.
+pub async fn list_actions(app: AppState, req: Parts) -> AppResult<Json<Value>> {
+    spawn_blocking(move || {
+        let conn = &mut *app.db_write()?;
+        AdminAuthentication::check(&req, conn)?;
+
+        let actions = AdminAction::recent(MAX_LISTED_ACTIONS, conn)?
+            .into_iter()
+            .map(|(action, user)| EncodableAdminAction { action, user })
+            .collect::<Vec<_>>();
+
+        Ok(Json(json!({ "actions": actions })))
+    })
+    .await
+}
+
+/// Handles the `DELETE /api/private/admin/crates/:crate_id` route.
+pub async fn delete_crate(
+    app: AppState,
+    Path(crate_name): Path<String>,
+    req: Parts,
+) -> AppResult<Response> {
+    let crate_name = spawn_blocking({
+        let app = app.clone();
+        move || {
+            let conn = &mut *app.db_write()?;
+            let auth = AdminAuthentication::check(&req, conn)?;
+
+            conn.transaction(|conn| {
+                let krate: Crate = Crate::by_name(&crate_name).first(conn)?;
+
+                info!(name = %krate.name, "Deleting crate from the database");
+                diesel::delete(crates::table.find(krate.id)).execute(conn)?;
+                jobs::enqueue_sync_to_index(&krate.name, conn)?;
+
+                auth.audit("delete_crate", &krate.name, json!({}), conn)?;
+
+                Ok::<_, BoxedAppError>(krate.name)
+            })
+        }
+    })
+    .await?;
+
+    delete_crate_files(&app.storage, &crate_name).await;
+
+    ok_true()
+}
+
+/// Handles the `DELETE /api/private/admin/crates/:crate_id/:version` route.
+pub async fn delete_version(
+    app: AppState,
+    Path((crate_name, version)): Path<(String, String)>,
+    req: Parts,
+) -> AppResult<Response> {
+    let crate_name = spawn_blocking({
+        let app = app.clone();
+        let version = version.clone();
+        move || {
+            let conn = &mut *app.db_write()?;
+            let auth = AdminAuthentication::check(&req, conn)?;
+
+            conn.transaction(|conn| {
+                let krate: Crate = Crate::by_name(&crate_name).first(conn)?;
+                let version = krate.find_version(conn, &version)?;
+
+                let (name, num) = (&krate.name, &version.num);
+                info!(%name, %num, "Deleting version from the database");
+                diesel::delete(versions::table.find(version.id)).execute(conn)?;
+                jobs::enqueue_sync_to_index(&krate.name, conn)?;
+
+                let target = format!("{}@{}", krate.name, version.num);
+                auth.audit("delete_version", &target, json!({}), conn)?;
+
+                Ok::<_, BoxedAppError>(krate.name)
+            })
+        }
+    })
+    .await?;
+
+    delete_version_files(&app.storage, &crate_name, &[version]).await;
+
+    ok_true()
+}
+
+/// Handles the `PUT /api/private/admin/crates/:crate_id/:version/yank` route.
+pub async fn yank_version(
+    app: AppState,
+    Path((crate_name, version)): Path<(String, String)>,
+    req: Parts,
+) -> AppResult<Response> {
+    spawn_blocking(move || {
+        let conn = &mut *app.db_write()?;
+        let auth = AdminAuthentication::check(&req, conn)?;
+
+        conn.transaction(|conn| {
+            let krate: Crate = Crate::by_name(&crate_name).first(conn)?;
+            let version = krate.find_version(conn, &version)?;
+
+            if version.yanked {
+                return ok_true();
+            }
+
+            diesel::update(&version)
+                .set(versions::yanked.eq(true))
+                .execute(conn)?;
+            jobs::enqueue_sync_to_index(&krate.name, conn)?;
+
+            let target = format!("{}@{}", krate.name, version.num);
+            auth.audit("yank_version", &target, json!({}), conn)?;
+
+            ok_true()
+        })
+    })
+    .await
+}
+
+#[derive(Deserialize, Default)]
+struct QuarantineRequest {
+    reason: Option<String>,
+}
+
+/// Handles the `PUT /api/private/admin/crates/:crate_id/quarantine` route.
+pub async fn quarantine_crate(
+    app: AppState,
+    Path(crate_name): Path<String>,
+    req: BytesRequest,
+) -> AppResult<Response> {
+    let action = QuarantineActionKind::Quarantine;
+    let version = None;
+    spawn_blocking(move || change_quarantine(&app, &req, action, &crate_name, version)).await
+}
+
+/// Handles the `DELETE /api/private/admin/crates/:crate_id/quarantine` route.
+pub async fn lift_crate_quarantine(
+    app: AppState,
+    Path(crate_name): Path<String>,
+    req: BytesRequest,
+) -> AppResult<Response> {
+    let action = QuarantineActionKind::Lift;
+    let version = None;
+    spawn_blocking(move || change_quarantine(&app, &req, action, &crate_name, version)).await
+}
+
+/// Handles the `PUT /api/private/admin/crates/:crate_id/:version/quarantine` route.
+pub async fn quarantine_version(
+    app: AppState,
+    Path((crate_name, version)): Path<(String, String)>,
+    req: BytesRequest,
+) -> AppResult<Response> {
+    let action = QuarantineActionKind::Quarantine;
+    let version = Some(version);
+    spawn_blocking(move || change_quarantine(&app, &req, action, &crate_name, version)).await
+}
+
+/// Handles the `DELETE /api/private/admin/crates/:crate_id/:version/quarantine` route.
+pub async fn lift_version_quarantine(
+    app: AppState,
+    Path((crate_name, version)): Path<(String, String)>,
+    req: BytesRequest,
+) -> AppResult<Response> {
+    let action = QuarantineActionKind::Lift;
+    let version = Some(version);
+    spawn_blocking(move || change_quarantine(&app, &req, action, &crate_name, version)).await
+}
+
+/// Quarantines a crate or version, or lifts its quarantine, like the
+/// `crates-admin quarantine` command does.
+fn change_quarantine(
+    app: &AppState,
+    req: &BytesRequest,
+    action: QuarantineActionKind,
+    crate_name: &str,
+    version: Option<String>,
+) -> AppResult<Response> {
+    let conn = &mut *app.db_write()?;
+    let auth = AdminAuthentication::check(req, conn)?;
+
+    let request: QuarantineRequest = if req.body().is_empty() {
+        QuarantineRequest::default()
+    } else {
+        serde_json::from_slice(req.body()).map_err(|_| bad_request("invalid json request"))?
+    };
+    let reason = request.reason.as_deref();
+
+    let krate: Crate = Crate::by_name(crate_name).first(conn)?;
+    let version: Option<Version> = version
+        .map(|version| krate.find_version(conn, &version))
+        .transpose()?;
+
+    let (target, description) = match &version {
+        Some(version) => (
+            QuarantineTarget::Version(&krate, version),
+            format!("{}@{}", krate.name, version.num),
+        ),
+        None => (QuarantineTarget::Crate(&krate), krate.name.clone()),
+    };
+
+    let changed = conn.transaction(|conn| {
+        let (changed, audit_action) = match action {
+            QuarantineActionKind::Quarantine => {
+                let reason = reason.ok_or_else(|| bad_request("a reason is required"))?;
+                let changed = target.quarantine(reason, &auth.user().gh_login, conn)?;
+                (changed, "quarantine")
+            }
+            QuarantineActionKind::Lift => {
+                let changed = target.lift(reason, &auth.user().gh_login, conn)?;
+                (changed, "lift_quarantine")
+            }
+        };
+
+        if changed {
+            jobs::enqueue_sync_to_index(&krate.name, conn)?;
+            jobs::SyncQuarantinedFiles::new(&krate.name).enqueue(conn)?;
+            auth.audit(
+                audit_action,
+                &description,
+                json!({ "reason": reason }),
+                conn,
+            )?;
+        }
+
+        Ok::<_, BoxedAppError>(changed)
+    })?;
+
+    if changed {
+        target.notify_owners(action, reason, &app.emails, conn)?;
+    }
+
+    ok_true()
+}
+
+#[derive(Deserialize)]
+struct LockAccountRequest {
+    reason: String,
+    #[serde(default)]
+    until: Option<NaiveDateTime>,
+}
+
+/// Handles the `PUT /api/private/admin/users/:login/lock` route.
+pub async fn lock_account(
+    app: AppState,
+    Path(login): Path<String>,
+    req: BytesRequest,
+) -> AppResult<Response> {
+    spawn_blocking(move || {
+        let conn = &mut *app.db_write()?;
+        let auth = AdminAuthentication::check(&req, conn)?;
+
+        let request: LockAccountRequest =
+            serde_json::from_slice(req.body()).map_err(|_| bad_request("invalid json request"))?;
+
+        let user = User::find_by_login(conn, &login)?;
+
+        let user = conn.transaction(|conn| {
+            let user = user.lock(&request.reason, request.until, conn)?;
+
+            let details = json!({ "reason": request.reason, "until": request.until });
+            auth.audit("lock_account", &user.gh_login, details, conn)?;
+
+            Ok::<_, BoxedAppError>(user)
+        })?;
+
+        user.notify_account_lock(&app.emails, conn)?;
+
+        ok_true()
+    })
+    .await
+}
+
+/// Handles the `DELETE /api/private/admin/users/:login/lock` route.
+pub async fn unlock_account(
+    app: AppState,
+    Path(login): Path<String>,
+    req: Parts,
+) -> AppResult<Response> {
+    spawn_blocking(move || {
+        let conn = &mut *app.db_write()?;
+        let auth = AdminAuthentication::check(&req, conn)?;
+
+        let user = User::find_by_login(conn, &login)?;
+        if user.account_lock_reason.is_none() {
+            return ok_true();
+        }
+
+        let user = conn.transaction(|conn| {
+            let user = user.unlock(conn)?;
+            auth.audit("unlock_account", &user.gh_login, json!({}), conn)?;
+            Ok::<_, BoxedAppError>(user)
+        })?;
+
+        user.notify_account_lock(&app.emails, conn)?;
+
+        ok_true()
+    })
+    .await
+}
+
+#[derive(Deserialize)]
+struct TransferCratesRequest {
+    from: String,
+    to: String,
+    /// Has to be set if the users have different GitHub IDs, since they may
+    /// not be the same person then.
+    #[serde(default)]
+    confirm_different_account: bool,
+}
+
+/// Handles the `POST /api/private/admin/transfer_crates` route.
+pub async fn transfer_crates(app: AppState, req: BytesRequest) -> AppResult<Json<Value>> {
+    spawn_blocking(move || {
+        let conn = &mut *app.db_write()?;
+        let auth = AdminAuthentication::check(&req, conn)?;
+
+        let request: TransferCratesRequest =
+            serde_json::from_slice(req.body()).map_err(|_| bad_request("invalid json request"))?;
+
+        let find_user = |conn: &mut PgConnection, login: &str| {
+            users::table
+                .filter(users::gh_login.eq(login))
+                .first::<User>(conn)
+                .optional()?
+                .ok_or_else(|| bad_request(format_args!("user `{login}` not found")))
+        };
+
+        let from = find_user(conn, &request.from)?;
+        let to = find_user(conn, &request.to)?;
+
+        if from.id == to.id {
+            return Err(auth.reject(&req, "cannot transfer crates to the same user", conn));
+        }
+        if from.gh_id != to.gh_id && !request.confirm_different_account {
+            let reason = "the users have different GitHub IDs and may not be the same person, \
+                set `confirm_different_account` to `true` to transfer the crates anyway";
+            return Err(auth.reject(&req, reason, conn));
+        }
+
+        let transferred = conn.transaction(|conn| {
+            let transferred = transfer_crates::transfer_all(&from, &to, conn)?;
+
+            let details = json!({ "to": to.gh_login, "crates": transferred });
+            auth.audit("transfer_crates", &from.gh_login, details, conn)?;
+
+            Ok::<_, BoxedAppError>(transferred)
+        })?;
+
+        Ok(Json(json!({ "ok": true, "transferred": transferred })))
+    })
+    .await
+}
+
+/// Handles the `GET /api/private/admin/adoption_requests` route.
+pub async fn list_adoption_requests(app: AppState, req: Parts) -> AppResult<Json<Value>> {
+    spawn_blocking(move || {
+        let conn = &mut *app.db_write()?;
+        AdminAuthentication::check(&req, conn)?;
+
+        let requests = AdoptionRequest::pending(conn)?
+            .into_iter()
+            .map(|(request, crate_name, user)| {
+                EncodableAdoptionRequest::from(request, crate_name, user)
+            })
+            .collect::<Vec<_>>();
+
+        Ok(Json(json!({ "adoption_requests": requests })))
+    })
+    .await
+}
+
+/// Handles the `PUT /api/private/admin/adoption_requests/:id/approve` route.
+pub async fn approve_adoption_request(
+    app: AppState,
+    Path(id): Path<i32>,
+    req: Parts,
+) -> AppResult<Response> {
+    spawn_blocking(move || {
+        let conn = &mut *app.db_write()?;
+        let auth = AdminAuthentication::check(&req, conn)?;
+
+        let (request, crate_name) = conn.transaction(|conn| {
+            // The request is locked, so that the owners can't reject it at
+            // the same time
+            let request = AdoptionRequest::find_for_update(id, conn)?;
+            if !request.is_pending() {
+                return Err(bad_request("the adoption request has already been decided"));
+            }
+            if !request.is_approvable() {
+                return Err(bad_request(format_args!(
+                    "the adoption request can't be approved before {}",
+                    request.approvable_at
+                )));
+            }
+
+            // The owners may have published a new version during the
+            // waiting period
+            let krate: Crate = Crate::all().find(request.crate_id).first(conn)?;
+            if !AdoptionRequest::is_abandoned(&krate, conn)? {
+                return Err(bad_request(
+                    "the crate is not abandoned anymore and can't be adopted",
+                ));
+            }
+
+            let request = request.approve(&auth.user().gh_login, conn)?;
+
+            let details = json!({ "adoption_request": request.id });
+            auth.audit("approve_adoption", &krate.name, details, conn)?;
+
+            Ok::<_, BoxedAppError>((request, krate.name))
+        })?;
+
+        request.notify_user(&crate_name, &app.emails, conn)?;
+
+        ok_true()
+    })
+    .await
+}
+
+#[derive(Deserialize)]
+struct RejectAdoptionRequest {
+    reason: String,
+}
+
+/// Handles the `PUT /api/private/admin/adoption_requests/:id/reject` route.
+pub async fn reject_adoption_request(
+    app: AppState,
+    Path(id): Path<i32>,
+    req: BytesRequest,
+) -> AppResult<Response> {
+    spawn_blocking(move || {
+        let conn = &mut *app.db_write()?;
+        let auth = AdminAuthentication::check(&req, conn)?;
+
+        let body: RejectAdoptionRequest =
+            serde_json::from_slice(req.body()).map_err(|_| bad_request("invalid json request"))?;
+
+        let (request, crate_name) = conn.transaction(|conn| {
+            let request = AdoptionRequest::find_for_update(id, conn)?;
+            if !request.is_pending() {
+                return Err(bad_request("the adoption request has already been decided"));
+            }
+
+            let request = request.reject(&auth.user().gh_login, Some(&body.reason), conn)?;
+            let crate_name: String = crates::table
+                .find(request.crate_id)
+                .select(crates::name)
+                .first(conn)?;
+
+            let details = json!({ "adoption_request": request.id, "reason": body.reason });
+            auth.audit("reject_adoption", &crate_name, details, conn)?;
+
+            Ok::<_, BoxedAppError>((request, crate_name))
+        })?;
+
+        request.notify_user(&crate_name, &app.emails, conn)?;
+
+        ok_true()
+    })
+    .await
+}
+
+/// The jobs that can be enqueued through the admin API. The `dump_db` job is
+/// left out, since it needs the URL of the database.
+#[derive(Deserialize, Serialize)]
+#[serde(tag = "job", rename_all = "snake_case")]
+enum EnqueueJobRequest {
+    UpdateDownloads,
+    DailyDbMaintenance,
+    SquashIndex,
+    NormalizeIndex {
+        #[serde(default)]
+        dry_run: bool,
+    },
+    CheckTyposquat {
+        name: String,
+    },
+}
+
+impl From<EnqueueJobRequest> for enqueue_job::Command {
+    fn from(request: EnqueueJobRequest) -> Self {
+        match request {
+            EnqueueJobRequest::UpdateDownloads => Self::UpdateDownloads,
+            EnqueueJobRequest::DailyDbMaintenance => Self::DailyDbMaintenance,
+            EnqueueJobRequest::SquashIndex => Self::SquashIndex,
+            EnqueueJobRequest::NormalizeIndex { dry_run } => Self::NormalizeIndex { dry_run },
+            EnqueueJobRequest::CheckTyposquat { name } => Self::CheckTyposquat { name },
+        }
+    }
+}
+
+/// Handles the `POST /api/private/admin/jobs` route.
+pub async fn enqueue_job(app: AppState, req: BytesRequest) -> AppResult<Json<Value>> {
+    spawn_blocking(move || {
+        let conn = &mut *app.db_write()?;
+        let auth = AdminAuthentication::check(&req, conn)?;
+
+        let details: Value =
+            serde_json::from_slice(req.body()).map_err(|_| bad_request("invalid json request"))?;
+        let request: EnqueueJobRequest = serde_json::from_value(details.clone())
+            .map_err(|e| bad_request(format!("invalid job: {e}")))?;
+        let job_name = details["job"].as_str().unwrap_or_default().to_string();
+
+        let enqueued = conn.transaction(|conn| {
+            let enqueued = enqueue_job::enqueue(request.into(), conn)
+                .map_err(|error| bad_request(error.to_string()))?;
+
+            if enqueued {
+                auth.audit("enqueue_job", &job_name, details, conn)?;
+            }
+
+            Ok::<_, BoxedAppError>(enqueued)
+        })?;
+
+        Ok(Json(json!({ "ok": true, "enqueued": enqueued })))
+    })
+    .await
+}
+
+#[derive(Deserialize)]
+struct VerifyTokenRequest {
+    api_token: String,
+}
+
+/// Handles the `POST /api/private/admin/verify_token` route.
+///
+/// Looks up the user of an API token, which is used to verify someone's
+/// identity by having them hand over an API token.
+pub async fn verify_token(app: AppState, req: BytesRequest) -> AppResult<Json<Value>> {
+    spawn_blocking(move || {
+        let conn = &mut *app.db_write()?;
+        let auth = AdminAuthentication::check(&req, conn)?;
+
+        let request: VerifyTokenRequest =
+            serde_json::from_slice(req.body()).map_err(|_| bad_request("invalid json request"))?;
+
+        let user = User::find_by_api_token(conn, &request.api_token)
+            .map_err(|_| bad_request("no user found for the API token"))?;
+
+        // The token itself is not recorded, since it is still valid.
+        auth.audit("verify_token", &user.gh_login, json!({}), conn)?;
+
+        Ok(Json(
+            json!({ "user": { "id": user.id, "login": user.gh_login } }),
+        ))
+    })
+    .await
+}
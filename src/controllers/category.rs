@@ -1,9 +1,10 @@
 use super::helpers::pagination::*;
 use super::prelude::*;
 
-use crate::models::Category;
+use crate::models::{Category, ToplevelSeek};
 use crate::schema::categories;
 use crate::views::{EncodableCategory, EncodableCategoryWithSubcategories};
+use indexmap::IndexMap;
 
 /// Handles the `GET /categories` route.
 pub async fn index(app: AppState, req: Parts) -> AppResult<Json<Value>> {
@@ -12,12 +13,46 @@ pub async fn index(app: AppState, req: Parts) -> AppResult<Json<Value>> {
         // FIXME: There are 69 categories, 47 top level. This isn't going to
         // grow by an OoM. We need a limit for /summary, but we don't need
         // to paginate this.
-        let options = PaginationOptions::builder().gather(&req)?;
+        let options = PaginationOptions::builder()
+            .enable_seek(true)
+            .gather(&req)?;
         let offset = options.offset().unwrap_or_default();
         let sort = query.get("sort").map_or("alpha", String::as_str);
 
         let conn = &mut app.db_read()?;
-        let categories = Category::toplevel(conn, sort, options.per_page, offset)?;
+        let categories = match options.page {
+            Page::Seek(ref seek) => {
+                let seek = if sort == "crates" {
+                    let (crates_cnt, id) = seek.decode()?;
+                    ToplevelSeek::CratesCnt(crates_cnt, id)
+                } else {
+                    ToplevelSeek::Category(seek.decode()?)
+                };
+                Category::toplevel_after(conn, &seek, options.per_page)?
+            }
+            _ => Category::toplevel(conn, sort, options.per_page, offset)?,
+        };
+
+        // Offset-based pagination is kept for explicitly requested pages, see the
+        // `GET /crates` route for details.
+        let next_page = match categories.last() {
+            Some(last)
+                if categories.len() == options.per_page as usize
+                    && !matches!(options.page, Page::Numeric(_)) =>
+            {
+                let seek = if sort == "crates" {
+                    encode_seek((last.crates_cnt, last.id))?
+                } else {
+                    encode_seek(&last.category)?
+                };
+
+                let mut params = IndexMap::new();
+                params.insert("seek".into(), seek);
+                Some(req.query_with_params(params))
+            }
+            _ => None,
+        };
+
         let categories = categories
             .into_iter()
             .map(Category::into)
@@ -28,7 +63,7 @@ pub async fn index(app: AppState, req: Parts) -> AppResult<Json<Value>> {
 
         Ok(Json(json!({
             "categories": categories,
-            "meta": { "total": total },
+            "meta": { "total": total, "next_page": next_page },
         })))
     })
     .await
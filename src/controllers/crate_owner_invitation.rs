@@ -3,28 +3,34 @@ use super::frontend_prelude::*;
 use crate::auth::AuthCheck;
 use crate::auth::Authentication;
 use crate::controllers::helpers::pagination::{Page, PaginationOptions};
+use crate::models::token::EndpointScope;
 use crate::models::{Crate, CrateOwnerInvitation, Rights, User};
+use crate::rate_limiter::LimitedAction;
 use crate::schema::{crate_owner_invitations, crates, users};
 use crate::util::errors::{forbidden, internal};
 use crate::views::{
     EncodableCrateOwnerInvitation, EncodableCrateOwnerInvitationV1, EncodablePublicUser,
     InvitationResponse,
 };
-use chrono::{Duration, Utc};
+use diesel::helper_types::InnerJoinQuerySource;
 use diesel::{pg::Pg, sql_types::Bool};
 use indexmap::IndexMap;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 use tokio::runtime::Handle;
 
 /// Handles the `GET /api/v1/me/crate_owner_invitations` route.
 pub async fn list(app: AppState, req: Parts) -> AppResult<Json<Value>> {
     spawn_blocking(move || {
         let conn = &mut app.db_read()?;
-        let auth = AuthCheck::only_cookie().check(&req, conn)?;
+        let auth = AuthCheck::only_cookie()
+            .with_endpoint_scope(EndpointScope::ReadPrivate)
+            .check(&req, conn)?;
         let user_id = auth.user_id();
 
         let PrivateListResponse {
-            invitations, users, ..
+            invitations,
+            users,
+            meta,
         } = prepare_list(&app, &req, auth, ListFilter::InviteeId(user_id), conn)?;
 
         // The schema for the private endpoints is converted to the schema used by v1 endpoints.
@@ -51,6 +57,7 @@ pub async fn list(app: AppState, req: Parts) -> AppResult<Json<Value>> {
         Ok(Json(json!({
             "crate_owner_invitations": crate_owner_invitations,
             "users": users,
+            "meta": meta,
         })))
     })
     .await
@@ -60,12 +67,16 @@ pub async fn list(app: AppState, req: Parts) -> AppResult<Json<Value>> {
 pub async fn private_list(app: AppState, req: Parts) -> AppResult<Json<PrivateListResponse>> {
     spawn_blocking(move || {
         let conn = &mut app.db_read()?;
-        let auth = AuthCheck::only_cookie().check(&req, conn)?;
+        let auth = AuthCheck::only_cookie()
+            .with_endpoint_scope(EndpointScope::ReadPrivate)
+            .check(&req, conn)?;
 
         let filter = if let Some(crate_name) = req.query().get("crate_name") {
             ListFilter::CrateName(crate_name.clone())
         } else if let Some(id) = req.query().get("invitee_id").and_then(|i| i.parse().ok()) {
             ListFilter::InviteeId(id)
+        } else if let Some(id) = req.query().get("inviter_id").and_then(|i| i.parse().ok()) {
+            ListFilter::InviterId(id)
         } else {
             return Err(bad_request("missing or invalid filter"));
         };
@@ -76,11 +87,135 @@ pub async fn private_list(app: AppState, req: Parts) -> AppResult<Json<PrivateLi
     .await
 }
 
+/// Handles the `GET /api/v1/crates/:crate_id/owner_invitations` route.
+///
+/// Lists the pending invitations to become an owner of the crate, which only
+/// the owners of the crate are allowed to see.
+pub async fn crate_list(
+    app: AppState,
+    Path(crate_name): Path<String>,
+    req: Parts,
+) -> AppResult<Json<PrivateListResponse>> {
+    spawn_blocking(move || {
+        let conn = &mut app.db_read()?;
+        let auth = AuthCheck::default()
+            .with_endpoint_scope(EndpointScope::ChangeOwners)
+            .for_crate(&crate_name)
+            .check(&req, conn)?;
+
+        let list = prepare_list(&app, &req, auth, ListFilter::CrateName(crate_name), conn)?;
+        Ok(Json(list))
+    })
+    .await
+}
+
+/// Handles the `DELETE /api/v1/crates/:crate_id/owner_invitations/:login` route.
+pub async fn revoke(
+    app: AppState,
+    Path((crate_name, login)): Path<(String, String)>,
+    req: Parts,
+) -> AppResult<Json<Value>> {
+    spawn_blocking(move || {
+        let conn = &mut app.db_write()?;
+        let (_, krate, invitation) = find_sent_invitation(&app, &crate_name, &login, &req, conn)?;
+
+        invitation.revoke(conn)?;
+
+        let msg = format!(
+            "the invitation of user {login} to be an owner of crate {} has been revoked",
+            krate.name
+        );
+        Ok(Json(json!({ "ok": true, "msg": msg })))
+    })
+    .await
+}
+
+/// Handles the `POST /api/v1/crates/:crate_id/owner_invitations/:login/resend` route.
+///
+/// Sends the invitation email again and restarts the expiration period of the
+/// invitation. This intentionally also works for invitations that already
+/// expired, so that owners don't have to revoke them and invite the user
+/// again. Resending is rate limited per user, so that owners can't flood the
+/// inbox of the invited user.
+pub async fn resend(
+    app: AppState,
+    Path((crate_name, login)): Path<(String, String)>,
+    req: Parts,
+) -> AppResult<Json<Value>> {
+    spawn_blocking(move || {
+        let conn = &mut app.db_write()?;
+        let (auth, krate, invitation) =
+            find_sent_invitation(&app, &crate_name, &login, &req, conn)?;
+
+        app.rate_limiter
+            .check_rate_limit(auth.user_id(), LimitedAction::ResendInvitation, conn)?;
+
+        let invitation = invitation.renew(conn)?;
+        let invitee = User::find(conn, invitation.invited_user_id)?;
+        krate.send_owner_invite_email(&app, conn, auth.user(), &invitee, invitation.token);
+
+        let msg = format!(
+            "the invitation of user {} to be an owner of crate {} has been resent",
+            invitee.gh_login, krate.name
+        );
+        Ok(Json(json!({ "ok": true, "msg": msg })))
+    })
+    .await
+}
+
+/// Finds the pending invitation of a user to become an owner of a crate,
+/// after checking that the authenticated user is allowed to manage it.
+fn find_sent_invitation(
+    app: &AppState,
+    crate_name: &str,
+    login: &str,
+    req: &Parts,
+    conn: &mut PgConnection,
+) -> AppResult<(Authentication, Crate, CrateOwnerInvitation)> {
+    let auth = AuthCheck::default()
+        .with_endpoint_scope(EndpointScope::ChangeOwners)
+        .for_crate(crate_name)
+        .check(req, conn)?;
+
+    let krate: Crate = Crate::by_name(crate_name).first(conn)?;
+    let owners = krate.owners(conn)?;
+    match Handle::current().block_on(auth.user().rights(app, &owners))? {
+        Rights::Full => {}
+        Rights::Publish => {
+            return Err(cargo_err(
+                "team members don't have permission to manage owner invitations",
+            ));
+        }
+        Rights::None => {
+            return Err(cargo_err(
+                "only owners have permission to manage owner invitations",
+            ));
+        }
+    }
+
+    let invitee = User::find_by_login(conn, login)
+        .optional()?
+        .ok_or_else(|| cargo_err(format_args!("could not find user with login `{login}`")))?;
+    let invitation = CrateOwnerInvitation::find_by_id(invitee.id, krate.id, conn)
+        .optional()?
+        .ok_or_else(|| {
+            cargo_err(format_args!(
+                "user {login} has no pending invitation to be an owner of crate {}",
+                krate.name
+            ))
+        })?;
+
+    Ok((auth, krate, invitation))
+}
+
 enum ListFilter {
     CrateName(String),
     InviteeId(i32),
+    InviterId(i32),
 }
 
+type InvitationsWithCrates = InnerJoinQuerySource<crate_owner_invitations::table, crates::table>;
+
 fn prepare_list(
     state: &AppState,
     req: &Parts,
@@ -97,11 +232,10 @@ fn prepare_list(
 
     let config = &state.config;
 
-    let mut crate_names = HashMap::new();
     let mut users = IndexMap::new();
     users.insert(user.id, user.clone());
 
-    let sql_filter: Box<dyn BoxableExpression<crate_owner_invitations::table, Pg, SqlType = Bool>> =
+    let sql_filter: Box<dyn BoxableExpression<InvitationsWithCrates, Pg, SqlType = Bool>> =
         match filter {
             ListFilter::CrateName(crate_name) => {
                 // Only allow crate owners to query pending invitations for their crate.
@@ -111,9 +245,6 @@ fn prepare_list(
                     return Err(forbidden());
                 }
 
-                // Cache the crate name to avoid querying it from the database again
-                crate_names.insert(krate.id, krate.name.clone());
-
                 Box::new(crate_owner_invitations::crate_id.eq(krate.id))
             }
             ListFilter::InviteeId(invitee_id) => {
@@ -122,13 +253,25 @@ fn prepare_list(
                 }
                 Box::new(crate_owner_invitations::invited_user_id.eq(invitee_id))
             }
+            ListFilter::InviterId(inviter_id) => {
+                if inviter_id != user.id {
+                    return Err(forbidden());
+                }
+                Box::new(crate_owner_invitations::invited_by_user_id.eq(inviter_id))
+            }
         };
 
-    // Load all the non-expired invitations matching the filter.
-    let expire_cutoff = Duration::days(config.ownership_invitations_expiration_days as i64);
+    // Load all the non-expired invitations matching the filter, together with the name and the
+    // invitation expiration override of their crates.
     let query = crate_owner_invitations::table
+        .inner_join(crates::table)
         .filter(sql_filter)
-        .filter(crate_owner_invitations::created_at.gt((Utc::now() - expire_cutoff).naive_utc()))
+        .filter(CrateOwnerInvitation::not_expired(config))
+        .select((
+            crate_owner_invitations::all_columns,
+            crates::name,
+            crates::ownership_invitations_expiration_days,
+        ))
         .order_by((
             crate_owner_invitations::crate_id,
             crate_owner_invitations::invited_user_id,
@@ -137,28 +280,29 @@ fn prepare_list(
         .limit(pagination.per_page + 1);
 
     // Load and paginate the results.
-    let mut raw_invitations: Vec<CrateOwnerInvitation> = match pagination.page {
-        Page::Unspecified => query.load(conn)?,
-        Page::Seek(s) => {
-            let seek_key: (i32, i32) = s.decode()?;
-            query
-                .filter(
-                    crate_owner_invitations::crate_id.gt(seek_key.0).or(
-                        crate_owner_invitations::crate_id
-                            .eq(seek_key.0)
-                            .and(crate_owner_invitations::invited_user_id.gt(seek_key.1)),
-                    ),
-                )
-                .load(conn)?
-        }
-        Page::Numeric(_) => unreachable!("page-based pagination is disabled"),
-    };
+    let mut raw_invitations: Vec<(CrateOwnerInvitation, String, Option<i32>)> =
+        match pagination.page {
+            Page::Unspecified => query.load(conn)?,
+            Page::Seek(s) => {
+                let seek_key: (i32, i32) = s.decode()?;
+                query
+                    .filter(
+                        crate_owner_invitations::crate_id.gt(seek_key.0).or(
+                            crate_owner_invitations::crate_id
+                                .eq(seek_key.0)
+                                .and(crate_owner_invitations::invited_user_id.gt(seek_key.1)),
+                        ),
+                    )
+                    .load(conn)?
+            }
+            Page::Numeric(_) => unreachable!("page-based pagination is disabled"),
+        };
     let next_page = if raw_invitations.len() > pagination.per_page as usize {
         // We fetch `per_page + 1` to check if there are records for the next page. Since the last
         // element is not what the user wanted it's discarded.
         raw_invitations.pop();
 
-        if let Some(last) = raw_invitations.last() {
+        if let Some((last, _, _)) = raw_invitations.last() {
             let mut params = IndexMap::new();
             params.insert(
                 "seek".into(),
@@ -175,26 +319,10 @@ fn prepare_list(
         None
     };
 
-    // Load all the related crates.
-    let missing_crate_names = raw_invitations
-        .iter()
-        .map(|i| i.crate_id)
-        .filter(|id| !crate_names.contains_key(id))
-        .collect::<Vec<_>>();
-    if !missing_crate_names.is_empty() {
-        let new_names: Vec<(i32, String)> = crates::table
-            .select((crates::id, crates::name))
-            .filter(crates::id.eq_any(missing_crate_names))
-            .load(conn)?;
-        for (id, name) in new_names.into_iter() {
-            crate_names.insert(id, name);
-        }
-    }
-
     // Load all the related users.
     let missing_users = raw_invitations
         .iter()
-        .flat_map(|invite| {
+        .flat_map(|(invite, _, _)| {
             std::iter::once(invite.invited_user_id)
                 .chain(std::iter::once(invite.invited_by_user_id))
         })
@@ -210,20 +338,17 @@ fn prepare_list(
     }
 
     // Turn `CrateOwnerInvitation`s into `EncodablePrivateCrateOwnerInvitation`.
-    let config = &state.config;
     let mut invitations = Vec::new();
     let mut users_in_response = HashSet::new();
-    for invitation in raw_invitations.into_iter() {
+    for (invitation, crate_name, crate_expiration_days) in raw_invitations.into_iter() {
+        let expiration_days = CrateOwnerInvitation::expiration_days(config, crate_expiration_days);
         invitations.push(EncodableCrateOwnerInvitation {
             invitee_id: invitation.invited_user_id,
             inviter_id: invitation.invited_by_user_id,
             crate_id: invitation.crate_id,
-            crate_name: crate_names
-                .get(&invitation.crate_id)
-                .ok_or_else(|| internal(format!("missing crate with id {}", invitation.crate_id)))?
-                .clone(),
+            crate_name,
             created_at: invitation.created_at,
-            expires_at: invitation.expires_at(config),
+            expires_at: invitation.expires_at(expiration_days),
         });
         users_in_response.insert(invitation.invited_user_id);
         users_in_response.insert(invitation.invited_by_user_id);
@@ -3,10 +3,11 @@ use crate::app::AppState;
 use axum::extract::{Path, Query};
 use axum::Json;
 
-use crate::controllers::helpers::pagination::PaginationOptions;
+use crate::controllers::helpers::pagination::{encode_seek, Page, PaginationOptions};
 use crate::controllers::helpers::{pagination::Paginated, Paginate};
 use crate::models::Keyword;
 use crate::views::EncodableKeyword;
+use indexmap::IndexMap;
 
 #[derive(Deserialize)]
 pub struct IndexQuery {
@@ -18,25 +19,73 @@ pub async fn index(state: AppState, qp: Query<IndexQuery>, req: Parts) -> AppRes
     spawn_blocking(move || {
         use crate::schema::keywords;
 
+        let sort_by_crates = matches!(&qp.sort, Some(sort) if sort == "crates");
+
         let mut query = keywords::table.into_boxed();
 
-        query = match &qp.sort {
-            Some(sort) if sort == "crates" => query.order(keywords::crates_cnt.desc()),
-            _ => query.order(keywords::keyword.asc()),
+        query = if sort_by_crates {
+            query.order((keywords::crates_cnt.desc(), keywords::id.desc()))
+        } else {
+            query.order(keywords::keyword.asc())
         };
 
-        let query = query.pages_pagination(PaginationOptions::builder().gather(&req)?);
+        let pagination = PaginationOptions::builder()
+            .enable_seek(true)
+            .gather(&req)?;
         let conn = &mut state.db_read()?;
-        let data: Paginated<Keyword> = query.load(conn)?;
-        let total = data.total();
-        let kws = data
+
+        // Seek-based pagination is used unless an explicit page was requested, see the
+        // `GET /crates` route for details.
+        let (kws, total, next_page) = if let Page::Numeric(_) = pagination.page {
+            let data: Paginated<Keyword> = query.pages_pagination(pagination).load(conn)?;
+            let total = data.total();
+            let next_page = data.next_page_params().map(|p| req.query_with_params(p));
+            (data.into_iter().collect::<Vec<_>>(), total, next_page)
+        } else {
+            query = query.limit(pagination.per_page);
+            if let Page::Seek(ref seek) = pagination.page {
+                query = if sort_by_crates {
+                    let (crates_cnt, id): (i32, i32) = seek.decode()?;
+                    query.filter(
+                        keywords::crates_cnt
+                            .lt(crates_cnt)
+                            .or(keywords::crates_cnt.eq(crates_cnt).and(keywords::id.lt(id))),
+                    )
+                } else {
+                    let keyword: String = seek.decode()?;
+                    query.filter(keywords::keyword.gt(keyword))
+                };
+            }
+
+            let total = keywords::table.count().get_result(conn)?;
+            let kws: Vec<Keyword> = query.load(conn)?;
+
+            let next_page = match kws.last() {
+                Some(last) if kws.len() == pagination.per_page as usize => {
+                    let seek = if sort_by_crates {
+                        encode_seek((last.crates_cnt, last.id))?
+                    } else {
+                        encode_seek(&last.keyword)?
+                    };
+
+                    let mut params = IndexMap::new();
+                    params.insert("seek".into(), seek);
+                    Some(req.query_with_params(params))
+                }
+                _ => None,
+            };
+
+            (kws, total, next_page)
+        };
+
+        let kws = kws
             .into_iter()
             .map(Keyword::into)
             .collect::<Vec<EncodableKeyword>>();
 
         Ok(Json(json!({
             "keywords": kws,
-            "meta": { "total": total },
+            "meta": { "total": total, "next_page": next_page },
         })))
     })
     .await
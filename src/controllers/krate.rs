@@ -1,6 +1,12 @@
+pub mod adoption;
+pub mod audit;
 pub mod downloads;
 pub mod follow;
+pub mod lookup;
 pub mod metadata;
 pub mod owners;
 pub mod publish;
+pub mod publish_policy;
+pub mod resolve;
 pub mod search;
+pub mod transfer;
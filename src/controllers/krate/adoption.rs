@@ -0,0 +1,104 @@
+//! Endpoints for requests to adopt abandoned crates
+//!
+//! The current owners are notified about new requests and can reject them
+//! during a waiting period. After that, the crates.io team decides about the
+//! request via the admin API.
+
+use crate::auth::AuthCheck;
+use crate::controllers::frontend_prelude::*;
+use crate::models::{AdoptionRequest, Crate, Rights};
+use crate::util::errors::{forbidden, not_found};
+use crate::views::EncodableAdoptionRequest;
+use tokio::runtime::Handle;
+
+#[derive(Deserialize)]
+struct NewAdoptionRequest {
+    /// Why the user wants to adopt the crate. This is shown to the owners.
+    reason: String,
+}
+
+/// Handles the `POST /crates/:crate_id/adoption_requests` route.
+pub async fn create(
+    app: AppState,
+    Path(crate_name): Path<String>,
+    req: BytesRequest,
+) -> AppResult<Json<Value>> {
+    spawn_blocking(move || {
+        let request: NewAdoptionRequest =
+            serde_json::from_slice(req.body()).map_err(|_| bad_request("invalid json request"))?;
+
+        let reason = request.reason.trim();
+        if reason.is_empty() {
+            return Err(bad_request("please explain why you want to adopt the crate"));
+        }
+
+        let conn = &mut *app.db_write()?;
+        let auth = AuthCheck::only_cookie().check(&req, conn)?;
+        let user = auth.user();
+
+        let krate: Crate = Crate::by_name(&crate_name).first(conn)?;
+
+        let owners = krate.owners(conn)?;
+        if Handle::current().block_on(user.rights(&app, &owners))? == Rights::Full {
+            return Err(bad_request("you are already an owner of this crate"));
+        }
+        if !AdoptionRequest::is_abandoned(&krate, conn)? {
+            return Err(bad_request(
+                "only crates without owners or without a new version in the last year can be adopted",
+            ));
+        }
+        if AdoptionRequest::find_pending(krate.id, user.id, conn)?.is_some() {
+            return Err(bad_request(
+                "you already have a pending request to adopt this crate",
+            ));
+        }
+
+        let request = AdoptionRequest::create(&krate, user, reason, conn)?;
+        request.notify_owners(&krate, user, &app.emails, conn)?;
+
+        let request = EncodableAdoptionRequest::from(request, krate.name, user.gh_login.clone());
+        Ok(Json(json!({ "adoption_request": request })))
+    })
+    .await
+}
+
+/// Handles the `DELETE /crates/:crate_id/adoption_requests/:id` route.
+///
+/// The user who asked to adopt the crate can withdraw the request, and the
+/// owners of the crate can reject it.
+pub async fn delete(
+    app: AppState,
+    Path((crate_name, id)): Path<(String, i32)>,
+    req: Parts,
+) -> AppResult<Response> {
+    spawn_blocking(move || {
+        let conn = &mut *app.db_write()?;
+        let auth = AuthCheck::only_cookie().check(&req, conn)?;
+        let user = auth.user();
+
+        let krate: Crate = Crate::by_name(&crate_name).first(conn)?;
+        let request = AdoptionRequest::find(id, conn)?;
+        if request.crate_id != krate.id {
+            return Err(not_found());
+        }
+        if !request.is_pending() {
+            return Err(bad_request("the adoption request has already been decided"));
+        }
+
+        if request.user_id == user.id {
+            request.withdraw(conn)?;
+            return ok_true();
+        }
+
+        let owners = krate.owners(conn)?;
+        if Handle::current().block_on(user.rights(&app, &owners))? != Rights::Full {
+            return Err(forbidden());
+        }
+
+        let request = request.reject(&user.gh_login, None, conn)?;
+        request.notify_user(&krate.name, &app.emails, conn)?;
+
+        ok_true()
+    })
+    .await
+}
@@ -0,0 +1,209 @@
+//! Endpoint for auditing the registry dependencies of a `Cargo.lock` file
+
+use std::collections::{BTreeSet, HashMap};
+
+use semver::{Version as SemverVersion, VersionReq};
+
+use crate::auth::AuthCheck;
+use crate::controllers::frontend_prelude::*;
+use crate::licenses::{parse_license_expr, NON_STANDARD_LICENSE};
+use crate::lockfile;
+use crate::rate_limiter::LimitedAction;
+use crate::schema::{crates, versions};
+
+/// The maximum number of registry packages that can be audited in a single
+/// request.
+const MAX_AUDIT_PACKAGES: usize = 2000;
+
+/// How crates that only have a `license-file` are reported.
+const CUSTOM_LICENSE_FILE: &str = "custom license file";
+
+#[derive(Serialize, Default)]
+struct AuditSummary {
+    /// The number of packages that could not be found on crates.io.
+    missing: usize,
+    yanked: usize,
+    invalid_licenses: usize,
+    /// The number of packages for which a newer semver-compatible version is
+    /// available.
+    outdated: usize,
+}
+
+#[derive(Serialize)]
+struct AuditedPackage {
+    name: String,
+    version: String,
+    /// Whether this version of the crate exists on crates.io. The remaining
+    /// fields are only filled in for versions that do exist.
+    found: bool,
+    yanked: bool,
+    /// The license expression, or "custom license file" for crates that only
+    /// specify a `license-file`.
+    license: Option<String>,
+    /// The reason why the license expression is invalid, if it is.
+    license_error: Option<String>,
+    rust_version: Option<String>,
+    /// Unyanked versions that are newer than, but semver-compatible with, the
+    /// locked version, highest first.
+    newer_compatible_versions: Vec<String>,
+}
+
+#[derive(Queryable)]
+struct VersionRow {
+    crate_name: String,
+    num: String,
+    yanked: bool,
+    license: Option<String>,
+    rust_version: Option<String>,
+}
+
+struct VersionInfo {
+    num: SemverVersion,
+    yanked: bool,
+    license: Option<String>,
+    rust_version: Option<String>,
+}
+
+/// Handles the `POST /audit/crates` route.
+///
+/// Takes the contents of a `Cargo.lock` file as the request body and reports
+/// the yank status, license, MSRV and newer compatible versions of all the
+/// packages that come from a registry.
+pub async fn audit(app: AppState, req: BytesRequest) -> AppResult<Json<Value>> {
+    spawn_blocking(move || {
+        let lockfile = std::str::from_utf8(req.body())
+            .map_err(|_| bad_request("the lockfile must be valid UTF-8"))?;
+        let packages = lockfile::registry_packages(lockfile)
+            .map_err(|e| bad_request(format!("invalid lockfile: {e}")))?;
+
+        if packages.len() > MAX_AUDIT_PACKAGES {
+            return Err(bad_request(format!(
+                "cannot audit more than {MAX_AUDIT_PACKAGES} packages at once"
+            )));
+        }
+
+        let conn = &mut *app.db_write()?;
+        let auth = AuthCheck::default().check(&req, conn)?;
+        app.rate_limiter
+            .check_rate_limit(auth.user_id(), LimitedAction::Audit, conn)?;
+
+        let names = packages
+            .iter()
+            .map(|package| package.name.as_str())
+            .collect::<BTreeSet<_>>();
+        let rows: Vec<VersionRow> = versions::table
+            .inner_join(crates::table)
+            .filter(crates::name.eq_any(names))
+            .select((
+                crates::name,
+                versions::num,
+                versions::yanked,
+                versions::license,
+                versions::rust_version,
+            ))
+            .load(conn)?;
+
+        let mut versions_by_crate: HashMap<String, Vec<VersionInfo>> = HashMap::new();
+        for row in rows {
+            if let Ok(num) = SemverVersion::parse(&row.num) {
+                versions_by_crate
+                    .entry(row.crate_name)
+                    .or_default()
+                    .push(VersionInfo {
+                        num,
+                        yanked: row.yanked,
+                        license: row.license,
+                        rust_version: row.rust_version,
+                    });
+            }
+        }
+        for versions in versions_by_crate.values_mut() {
+            versions.sort_by(|a, b| b.num.cmp(&a.num));
+        }
+
+        let mut summary = AuditSummary::default();
+        let packages = packages
+            .into_iter()
+            .map(|package| {
+                let versions = versions_by_crate
+                    .get(&package.name)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+                let audited = audit_package(package.name, package.version, versions);
+
+                if !audited.found {
+                    summary.missing += 1;
+                }
+                if audited.yanked {
+                    summary.yanked += 1;
+                }
+                if audited.license_error.is_some() {
+                    summary.invalid_licenses += 1;
+                }
+                if !audited.newer_compatible_versions.is_empty() {
+                    summary.outdated += 1;
+                }
+
+                audited
+            })
+            .collect::<Vec<_>>();
+
+        Ok(Json(json!({
+            "packages": packages,
+            "summary": summary,
+        })))
+    })
+    .await
+}
+
+/// Audits a single locked package against the known versions of its crate,
+/// which are sorted from highest to lowest.
+fn audit_package(name: String, version: String, versions: &[VersionInfo]) -> AuditedPackage {
+    let locked = SemverVersion::parse(&version).ok();
+    let info = locked
+        .as_ref()
+        .and_then(|locked| versions.iter().find(|v| &v.num == locked));
+
+    let Some((locked, info)) = locked.zip(info) else {
+        return AuditedPackage {
+            name,
+            version,
+            found: false,
+            yanked: false,
+            license: None,
+            license_error: None,
+            rust_version: None,
+            newer_compatible_versions: vec![],
+        };
+    };
+
+    let (license, license_error) = match info.license.as_deref() {
+        Some(NON_STANDARD_LICENSE) => (Some(CUSTOM_LICENSE_FILE.to_string()), None),
+        Some(license) => {
+            let error = parse_license_expr(license).err().map(|e| e.to_string());
+            (Some(license.to_string()), error)
+        }
+        None => (None, None),
+    };
+
+    let newer_compatible_versions = VersionReq::parse(&format!("^{locked}"))
+        .map(|req| {
+            versions
+                .iter()
+                .filter(|v| !v.yanked && v.num > locked && req.matches(&v.num))
+                .map(|v| v.num.to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    AuditedPackage {
+        name,
+        version,
+        found: true,
+        yanked: info.yanked,
+        license,
+        license_error,
+        rust_version: info.rust_version.clone(),
+        newer_compatible_versions,
+    }
+}
@@ -4,6 +4,7 @@ use crate::auth::AuthCheck;
 use diesel::associations::Identifiable;
 
 use crate::controllers::frontend_prelude::*;
+use crate::models::token::EndpointScope;
 use crate::models::{Crate, Follow};
 use crate::schema::*;
 
@@ -20,7 +21,10 @@ pub async fn follow(
 ) -> AppResult<Response> {
     spawn_blocking(move || {
         let conn = &mut *app.db_write()?;
-        let user_id = AuthCheck::default().check(&req, conn)?.user_id();
+        let user_id = AuthCheck::default()
+            .with_endpoint_scope(EndpointScope::Follow)
+            .check(&req, conn)?
+            .user_id();
         let follow = follow_target(&crate_name, conn, user_id)?;
         diesel::insert_into(follows::table)
             .values(&follow)
@@ -40,7 +44,10 @@ pub async fn unfollow(
 ) -> AppResult<Response> {
     spawn_blocking(move || {
         let conn = &mut *app.db_write()?;
-        let user_id = AuthCheck::default().check(&req, conn)?.user_id();
+        let user_id = AuthCheck::default()
+            .with_endpoint_scope(EndpointScope::Follow)
+            .check(&req, conn)?
+            .user_id();
         let follow = follow_target(&crate_name, conn, user_id)?;
         diesel::delete(&follow).execute(conn)?;
 
@@ -59,7 +66,10 @@ pub async fn following(
         use diesel::dsl::exists;
 
         let conn = &mut *app.db_read_prefer_primary()?;
-        let user_id = AuthCheck::only_cookie().check(&req, conn)?.user_id();
+        let user_id = AuthCheck::only_cookie()
+            .with_endpoint_scope(EndpointScope::Follow)
+            .check(&req, conn)?
+            .user_id();
         let follow = follow_target(&crate_name, conn, user_id)?;
         let following =
             diesel::select(exists(follows::table.find(follow.id()))).get_result::<bool>(conn)?;
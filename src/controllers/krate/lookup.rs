@@ -0,0 +1,236 @@
+//! Endpoint for looking up the metadata of many crates and versions at once
+
+use std::collections::{BTreeSet, HashMap};
+
+use chrono::NaiveDateTime;
+use semver::{Version as SemverVersion, VersionReq};
+
+use crate::auth::AuthCheck;
+use crate::controllers::frontend_prelude::*;
+use crate::lockfile;
+use crate::models::{Crate, TopVersions, User, Version, VersionOwnerAction};
+use crate::rate_limiter::LimitedAction;
+use crate::schema::{crates, users, versions};
+use crate::sql::canon_crate_name;
+use crate::views::{EncodableCrate, EncodableVersion};
+
+/// The maximum number of crates that can be looked up in a single request.
+const MAX_LOOKUP_ENTRIES: usize = 1000;
+
+#[derive(Deserialize)]
+struct LookupRequest {
+    #[serde(default)]
+    crates: Vec<LookupEntry>,
+    /// The contents of a `Cargo.lock` file, whose registry packages are
+    /// looked up in addition to `crates`.
+    lockfile: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct LookupEntry {
+    /// The name of the crate, which is matched like crate names are matched
+    /// when publishing, i.e. ignoring case and the difference between `-`
+    /// and `_`.
+    name: String,
+    /// Either an exact version or a version requirement. A bare version like
+    /// `1.2.3` only matches exactly that version, like in `Cargo.lock`, and
+    /// unlike in `Cargo.toml`, where it would mean `^1.2.3`.
+    version: String,
+}
+
+#[derive(Serialize)]
+struct LookupResult {
+    name: String,
+    version: String,
+    /// The name of the crate, if it exists.
+    #[serde(rename = "crate")]
+    krate: Option<String>,
+    /// The IDs of the matching versions, highest first.
+    versions: Vec<i32>,
+}
+
+enum Requirement {
+    Exact(SemverVersion),
+    Req(VersionReq),
+}
+
+impl Requirement {
+    fn parse(name: &str, version: &str) -> AppResult<Self> {
+        if let Ok(version) = SemverVersion::parse(version) {
+            return Ok(Self::Exact(version));
+        }
+
+        VersionReq::parse(version).map(Self::Req).map_err(|_| {
+            bad_request(format!(
+                "invalid version requirement `{version}` for crate `{name}`"
+            ))
+        })
+    }
+
+    fn matches(&self, version: &SemverVersion) -> bool {
+        match self {
+            Self::Exact(exact) => exact == version,
+            Self::Req(req) => req.matches(version),
+        }
+    }
+}
+
+/// Handles the `POST /lookup/crates` route.
+///
+/// Looks up a list of crates and version requirements (or the registry
+/// packages of a `Cargo.lock` file) and returns the matching crates and
+/// versions, including their yank status and licenses.
+///
+/// Bare versions like `1.2.3` are treated as exact versions, so that locked
+/// versions can be looked up as they are. Use `^1.2.3` to look up all
+/// semver-compatible versions instead.
+pub async fn lookup(app: AppState, req: BytesRequest) -> AppResult<Json<Value>> {
+    spawn_blocking(move || {
+        let request: LookupRequest = serde_json::from_slice(req.body())
+            .map_err(|e| bad_request(format!("invalid lookup request: {e}")))?;
+
+        let mut entries = request.crates;
+        if let Some(lockfile) = &request.lockfile {
+            let packages = lockfile::registry_packages(lockfile)
+                .map_err(|e| bad_request(format!("invalid lockfile: {e}")))?;
+
+            let packages = packages.into_iter().map(|package| LookupEntry {
+                name: package.name,
+                version: package.version,
+            });
+            entries.extend(packages);
+        }
+
+        if entries.len() > MAX_LOOKUP_ENTRIES {
+            return Err(bad_request(format!(
+                "cannot look up more than {MAX_LOOKUP_ENTRIES} crates at once"
+            )));
+        }
+
+        let requirements = entries
+            .iter()
+            .map(|entry| Requirement::parse(&entry.name, &entry.version))
+            .collect::<AppResult<Vec<_>>>()?;
+
+        let conn = &mut *app.db_write()?;
+        let auth = AuthCheck::default().check(&req, conn)?;
+        app.rate_limiter
+            .check_rate_limit(auth.user_id(), LimitedAction::BulkLookup, conn)?;
+
+        let names = entries
+            .iter()
+            .map(|entry| canonical_name(&entry.name))
+            .collect::<BTreeSet<_>>();
+        let krates: Vec<Crate> = Crate::all()
+            .filter(canon_crate_name(crates::name).eq_any(names))
+            .load(conn)?;
+
+        // Only the version numbers are loaded for the matching, to avoid
+        // loading all the details of versions that didn't match.
+        let crate_ids = krates.iter().map(|krate| krate.id).collect::<Vec<_>>();
+        let all_versions: Vec<(i32, i32, String, NaiveDateTime, bool)> = versions::table
+            .filter(versions::crate_id.eq_any(crate_ids))
+            .select((
+                versions::id,
+                versions::crate_id,
+                versions::num,
+                versions::created_at,
+                versions::yanked,
+            ))
+            .load(conn)?;
+
+        let mut versions_by_crate: HashMap<i32, Vec<(i32, SemverVersion)>> = HashMap::new();
+        let mut unyanked_by_crate: HashMap<i32, Vec<(NaiveDateTime, String)>> = HashMap::new();
+        for (id, crate_id, num, created_at, yanked) in all_versions {
+            if let Ok(semver) = SemverVersion::parse(&num) {
+                versions_by_crate
+                    .entry(crate_id)
+                    .or_default()
+                    .push((id, semver));
+            }
+            if !yanked {
+                let unyanked = unyanked_by_crate.entry(crate_id).or_default();
+                unyanked.push((created_at, num));
+            }
+        }
+        for versions in versions_by_crate.values_mut() {
+            versions.sort_by(|(_, a), (_, b)| b.cmp(a));
+        }
+
+        let krates_by_name = krates
+            .iter()
+            .map(|krate| (canonical_name(&krate.name), krate))
+            .collect::<HashMap<_, _>>();
+
+        let mut matched_ids = BTreeSet::new();
+        let mut results = Vec::with_capacity(entries.len());
+        for (entry, requirement) in entries.iter().zip(&requirements) {
+            let krate = krates_by_name.get(&canonical_name(&entry.name));
+            let versions = krate
+                .and_then(|krate| versions_by_crate.get(&krate.id))
+                .map(|versions| {
+                    versions
+                        .iter()
+                        .filter(|(_, num)| requirement.matches(num))
+                        .map(|(id, _)| *id)
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+
+            matched_ids.extend(versions.iter().copied());
+            results.push(LookupResult {
+                name: entry.name.clone(),
+                version: entry.version.clone(),
+                krate: krate.map(|krate| krate.name.clone()),
+                versions,
+            });
+        }
+
+        let versions_and_publishers: Vec<(Version, Option<User>)> = versions::table
+            .filter(versions::id.eq_any(matched_ids))
+            .left_outer_join(users::table)
+            .select((versions::all_columns, users::all_columns.nullable()))
+            .order(versions::id)
+            .load(conn)?;
+
+        let crate_names = krates
+            .iter()
+            .map(|krate| (krate.id, krate.name.as_str()))
+            .collect::<HashMap<_, _>>();
+        let versions = versions_and_publishers
+            .iter()
+            .map(|(v, _)| v)
+            .cloned()
+            .collect::<Vec<_>>();
+        let versions = versions_and_publishers
+            .into_iter()
+            .zip(VersionOwnerAction::for_versions(conn, &versions)?)
+            .map(|((version, published_by), actions)| {
+                let crate_name = crate_names[&version.crate_id];
+                EncodableVersion::from(version, crate_name, published_by, actions)
+            })
+            .collect::<Vec<_>>();
+
+        let crates = krates
+            .into_iter()
+            .map(|krate| {
+                let unyanked = unyanked_by_crate.remove(&krate.id).unwrap_or_default();
+                let top_versions = TopVersions::from_date_version_pairs(unyanked);
+                EncodableCrate::from_minimal(krate, Some(&top_versions), None, false, None)
+            })
+            .collect::<Vec<_>>();
+
+        Ok(Json(json!({
+            "results": results,
+            "crates": crates,
+            "versions": versions,
+        })))
+    })
+    .await
+}
+
+/// Canonicalizes a crate name the same way as the `canon_crate_name` SQL
+/// function.
+fn canonical_name(name: &str) -> String {
+    name.to_lowercase().replace('-', "_")
+}
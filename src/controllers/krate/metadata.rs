@@ -4,17 +4,23 @@
 //! index or cached metadata which was extracted (client side) from the
 //! `Cargo.toml` file.
 
+use chrono::NaiveDateTime;
+use indexmap::IndexMap;
 use std::cmp::Reverse;
+use std::collections::HashMap;
 use std::str::FromStr;
 
 use crate::controllers::frontend_prelude::*;
-use crate::controllers::helpers::pagination::PaginationOptions;
+use crate::controllers::helpers::pagination::{encode_seek, Page, PaginationOptions};
 
+use crate::controllers::version::version_and_crate;
 use crate::models::{
-    Category, Crate, CrateCategory, CrateKeyword, CrateVersions, Keyword, RecentCrateDownloads,
-    TopVersions, User, Version, VersionOwnerAction,
+    is_compatible_rust_version, is_crate_quarantined, parse_rust_version,
+    without_quarantined_versions, Category, Crate, CrateCategory, CrateKeyword, CrateVersions,
+    Keyword, RecentCrateDownloads, TopVersions, User, Version, VersionOwnerAction,
 };
 use crate::schema::*;
+use crate::util::errors::Quarantined;
 use crate::views::{
     EncodableCategory, EncodableCrate, EncodableDependency, EncodableKeyword, EncodableVersion,
 };
@@ -141,13 +147,14 @@ pub async fn show(app: AppState, Path(name): Path<String>, req: Parts) -> AppRes
 
         let conn = &mut *app.db_read()?;
         let krate: Crate = Crate::by_name(&name).first(conn)?;
+        ensure_not_quarantined(&krate, conn)?;
 
         let versions_publishers_and_audit_actions = if include.versions {
-            let mut versions_and_publishers: Vec<(Version, Option<User>)> = krate
-                .all_versions()
-                .left_outer_join(users::table)
-                .select((versions::all_columns, users::all_columns.nullable()))
-                .load(conn)?;
+            let mut versions_and_publishers: Vec<(Version, Option<User>)> =
+                without_quarantined_versions(krate.all_versions())
+                    .left_outer_join(users::table)
+                    .select((versions::all_columns, users::all_columns.nullable()))
+                    .load(conn)?;
             versions_and_publishers.sort_by_cached_key(|(version, _)| {
                 Reverse(semver::Version::parse(&version.num).ok())
             });
@@ -305,35 +312,101 @@ impl FromStr for ShowIncludeMode {
     }
 }
 
+/// Returns an error if the crate is quarantined.
+fn ensure_not_quarantined(krate: &Crate, conn: &mut PgConnection) -> AppResult<()> {
+    if is_crate_quarantined(krate.id, conn)? {
+        return Err(Box::new(Quarantined {
+            crate_name: krate.name.clone(),
+            version: None,
+        }));
+    }
+
+    Ok(())
+}
+
 /// Handles the `GET /crates/:crate_id/:version/readme` route.
 pub async fn readme(
     app: AppState,
     Path((crate_name, version)): Path<(String, String)>,
     req: Parts,
-) -> Response {
+) -> AppResult<Response> {
+    let (crate_name, version) = spawn_blocking({
+        let app = app.clone();
+        move || {
+            let conn = &mut *app.db_read()?;
+            let (version, krate) = version_and_crate(conn, &crate_name, &version)?;
+            Ok::<_, BoxedAppError>((krate.name, version.num))
+        }
+    })
+    .await?;
+
     let redirect_url = app.storage.readme_location(&crate_name, &version);
     if req.wants_json() {
-        Json(json!({ "url": redirect_url })).into_response()
+        Ok(Json(json!({ "url": redirect_url })).into_response())
     } else {
-        redirect(redirect_url)
+        Ok(redirect(redirect_url))
     }
 }
 
 /// Handles the `GET /crates/:crate_id/versions` route.
+///
+/// All versions are returned unless `per_page` or `seek` is used, in which case
+/// the results are paginated using seek keys. The versions are sorted by their
+/// semver version by default, or by their publish date with `sort=date`.
+///
+/// The versions can be filtered with a semver requirement (`req=^1.2`), by
+/// excluding prereleases (`include_prerelease=no`) or yanked versions
+/// (`include_yanked=no`), and by the Rust version they support
+/// (`rust_version=1.70`).
 // FIXME: Not sure why this is necessary since /crates/:crate_id returns
 // this information already, but ember is definitely requesting it
-pub async fn versions(state: AppState, Path(crate_name): Path<String>) -> AppResult<Json<Value>> {
+pub async fn versions(
+    state: AppState,
+    Path(crate_name): Path<String>,
+    req: Parts,
+) -> AppResult<Json<Value>> {
     spawn_blocking(move || {
+        let params = req.query();
+        let paginated = ["page", "per_page", "seek"];
+        let pagination = if paginated.iter().any(|key| params.contains_key(*key)) {
+            let options = PaginationOptions::builder()
+                .enable_pages(false)
+                .enable_seek(true)
+                .gather(&req)?;
+            Some(options)
+        } else {
+            None
+        };
+
+        let filter = VersionsFilter::from_params(&params)?;
+        let sort = match params.get("sort") {
+            Some(sort) if sort == "date" => VersionsSort::Date,
+            _ => VersionsSort::Semver,
+        };
+
         let conn = &mut *state.db_read()?;
         let krate: Crate = Crate::by_name(&crate_name).first(conn)?;
-        let mut versions_and_publishers: Vec<(Version, Option<User>)> = krate
-            .all_versions()
-            .left_outer_join(users::table)
-            .select((versions::all_columns, users::all_columns.nullable()))
+        ensure_not_quarantined(&krate, conn)?;
+
+        let rows: Vec<VersionRow> = without_quarantined_versions(krate.all_versions())
+            .select((
+                versions::id,
+                versions::num,
+                versions::created_at,
+                versions::yanked,
+                versions::rust_version,
+            ))
             .load(conn)?;
 
-        versions_and_publishers
-            .sort_by_cached_key(|(version, _)| Reverse(semver::Version::parse(&version.num).ok()));
+        let top_versions = encode_top_versions(&rows, filter.rust_version.as_ref());
+        let (versions_and_publishers, total, next_seek) =
+            list_versions(rows, &filter, sort, pagination.as_ref(), conn)?;
+
+        let next_page = next_seek.map(|seek| {
+            let mut params = IndexMap::new();
+            params.insert("seek".into(), seek);
+            req.query_with_params(params)
+        });
 
         let versions = versions_and_publishers
             .iter()
@@ -346,11 +419,196 @@ pub async fn versions(state: AppState, Path(crate_name): Path<String>) -> AppRes
             .map(|((v, pb), aas)| EncodableVersion::from(v, &crate_name, pb, aas))
             .collect::<Vec<_>>();
 
-        Ok(Json(json!({ "versions": versions })))
+        Ok(Json(json!({
+            "versions": versions,
+            "meta": {
+                "total": total,
+                "next_page": next_page,
+                "top_versions": top_versions,
+            },
+        })))
     })
     .await
 }
 
+#[derive(Queryable)]
+struct VersionRow {
+    id: i32,
+    num: String,
+    created_at: NaiveDateTime,
+    yanked: bool,
+    rust_version: Option<String>,
+}
+
+#[derive(Debug)]
+struct VersionsFilter {
+    req: Option<semver::VersionReq>,
+    include_prerelease: bool,
+    include_yanked: bool,
+    /// Only include versions whose `rust_version` is at most this version.
+    rust_version: Option<semver::Version>,
+}
+
+impl VersionsFilter {
+    fn from_params(params: &IndexMap<String, String>) -> AppResult<Self> {
+        let req = params
+            .get("req")
+            .map(|req| {
+                semver::VersionReq::parse(req)
+                    .map_err(|_| bad_request(format!("invalid version requirement: {req}")))
+            })
+            .transpose()?;
+
+        let rust_version = params
+            .get("rust_version")
+            .map(|rust_version| {
+                parse_rust_version(rust_version)
+                    .ok_or_else(|| bad_request(format!("invalid Rust version: {rust_version}")))
+            })
+            .transpose()?;
+
+        Ok(Self {
+            req,
+            include_prerelease: params.get("include_prerelease").map_or(true, |s| s != "no"),
+            include_yanked: params.get("include_yanked").map_or(true, |s| s != "no"),
+            rust_version,
+        })
+    }
+
+    fn matches(&self, num: Option<&semver::Version>, row: &VersionRow) -> bool {
+        if !self.include_yanked && row.yanked {
+            return false;
+        }
+
+        if !self.include_prerelease && num.is_some_and(|num| !num.pre.is_empty()) {
+            return false;
+        }
+
+        if let Some(req) = &self.req {
+            // `VersionReq` only matches prereleases that are explicitly
+            // mentioned in the requirement, just like cargo does.
+            if !num.is_some_and(|num| req.matches(num)) {
+                return false;
+            }
+        }
+
+        if let Some(rust_version) = &self.rust_version {
+            if !is_compatible_rust_version(row.rust_version.as_deref(), rust_version) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum VersionsSort {
+    /// Highest semver version first
+    Semver,
+    /// Most recently published version first
+    Date,
+}
+
+/// Encodes the highest, highest stable, newest and (if a Rust version was
+/// requested) highest compatible versions among the unyanked versions.
+fn encode_top_versions(rows: &[VersionRow], rust_version: Option<&semver::Version>) -> Value {
+    let unyanked = rows.iter().filter(|row| !row.yanked);
+
+    let mut top_versions = TopVersions::from_date_version_pairs(
+        unyanked.clone().map(|r| (r.created_at, r.num.clone())),
+    );
+    if let Some(rust_version) = rust_version {
+        top_versions = top_versions.with_highest_compatible(
+            unyanked.map(|r| (r.num.as_str(), r.rust_version.as_deref())),
+            rust_version,
+        );
+    }
+
+    let encode = |version: Option<semver::Version>| version.map(|v| v.to_string());
+    json!({
+        "highest": encode(top_versions.highest),
+        "highest_stable": encode(top_versions.highest_stable),
+        "highest_compatible": encode(top_versions.highest_compatible),
+        "newest": encode(top_versions.newest),
+    })
+}
+
+type VersionsPage = (Vec<(Version, Option<User>)>, i64, Option<String>);
+
+/// Filters and sorts the versions of a crate, and loads the versions on the
+/// requested page.
+///
+/// Since the semver order and the filters can't be expressed in SQL, only
+/// the few columns that are needed for them are loaded for all versions, and
+/// the full versions on the requested page are loaded afterwards. The seek key
+/// is the version number of the last version on the page when sorting by
+/// semver, or its publish date and ID when sorting by date.
+fn list_versions(
+    rows: Vec<VersionRow>,
+    filter: &VersionsFilter,
+    sort: VersionsSort,
+    options: Option<&PaginationOptions>,
+    conn: &mut PgConnection,
+) -> AppResult<VersionsPage> {
+    let mut rows = rows
+        .into_iter()
+        .map(|row| (semver::Version::parse(&row.num).ok(), row))
+        .filter(|(num, row)| filter.matches(num.as_ref(), row))
+        .collect::<Vec<_>>();
+
+    match sort {
+        VersionsSort::Semver => rows.sort_by(|(a, _), (b, _)| b.cmp(a)),
+        VersionsSort::Date => rows.sort_by_key(|(_, row)| Reverse((row.created_at, row.id))),
+    }
+
+    let total = rows.len() as i64;
+
+    let mut next_seek = None;
+    if let Some(options) = options {
+        let start = match (&options.page, sort) {
+            (Page::Seek(seek), VersionsSort::Semver) => {
+                let seek_key = semver::Version::parse(&seek.decode::<String>()?).ok();
+                rows.partition_point(|(num, _)| Reverse(num) <= Reverse(&seek_key))
+            }
+            (Page::Seek(seek), VersionsSort::Date) => {
+                let seek_key: (NaiveDateTime, i32) = seek.decode()?;
+                rows.partition_point(|(_, row)| (row.created_at, row.id) >= seek_key)
+            }
+            _ => 0,
+        };
+        let end = rows
+            .len()
+            .min(start.saturating_add(options.per_page as usize));
+
+        if end < rows.len() && end > start {
+            let (_, last) = &rows[end - 1];
+            next_seek = Some(match sort {
+                VersionsSort::Semver => encode_seek(&last.num)?,
+                VersionsSort::Date => encode_seek((last.created_at, last.id))?,
+            });
+        }
+
+        rows = rows.drain(start..end).collect();
+    }
+
+    let positions = rows
+        .iter()
+        .enumerate()
+        .map(|(position, (_, row))| (row.id, position))
+        .collect::<HashMap<_, _>>();
+
+    let mut versions_and_publishers: Vec<(Version, Option<User>)> = versions::table
+        .filter(versions::id.eq_any(positions.keys().copied().collect::<Vec<_>>()))
+        .left_outer_join(users::table)
+        .select((versions::all_columns, users::all_columns.nullable()))
+        .load(conn)?;
+
+    versions_and_publishers.sort_by_key(|(version, _)| positions.get(&version.id).copied());
+
+    Ok((versions_and_publishers, total, next_seek))
+}
+
 /// Handles the `GET /crates/:crate_id/reverse_dependencies` route.
 pub async fn reverse_dependencies(
     app: AppState,
@@ -358,10 +616,37 @@ pub async fn reverse_dependencies(
     req: Parts,
 ) -> AppResult<Json<Value>> {
     spawn_blocking(move || {
-        let pagination_options = PaginationOptions::builder().gather(&req)?;
+        let pagination_options = PaginationOptions::builder()
+            .enable_seek(true)
+            .gather(&req)?;
+        let per_page = pagination_options.per_page;
+        let explicit_page = matches!(pagination_options.page, Page::Numeric(_));
+        let after: Option<(i32, String)> = match pagination_options.page {
+            Page::Seek(ref seek) => Some(seek.decode()?),
+            _ => None,
+        };
+
         let conn = &mut *app.db_read()?;
         let krate: Crate = Crate::by_name(&name).first(conn)?;
-        let (rev_deps, total) = krate.reverse_dependencies(conn, pagination_options)?;
+        let after = after
+            .as_ref()
+            .map(|(downloads, name)| (*downloads, name.as_str()));
+        let (rev_deps, total) = krate.reverse_dependencies(conn, pagination_options, after)?;
+
+        // Offset-based pagination is kept for explicitly requested pages, see the
+        // `GET /crates` route for details.
+        let next_page = match rev_deps.last() {
+            Some(last) if rev_deps.len() == per_page as usize && !explicit_page => {
+                let mut params = IndexMap::new();
+                params.insert(
+                    "seek".into(),
+                    encode_seek((last.crate_downloads, &last.name))?,
+                );
+                Some(req.query_with_params(params))
+            }
+            _ => None,
+        };
+
         let rev_deps: Vec<_> = rev_deps
             .into_iter()
             .map(|dep| EncodableDependency::from_reverse_dep(dep, &krate.name))
@@ -395,7 +680,7 @@ pub async fn reverse_dependencies(
         Ok(Json(json!({
             "dependencies": rev_deps,
             "versions": versions,
-            "meta": { "total": total },
+            "meta": { "total": total, "next_page": next_page },
         })))
     })
     .await
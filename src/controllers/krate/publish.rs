@@ -17,16 +17,19 @@ use url::Url;
 
 use crate::controllers::cargo_prelude::*;
 use crate::models::{
-    insert_version_owner_action, Category, Crate, DependencyKind, Keyword, NewCrate, NewVersion,
-    Rights, VersionAction,
+    insert_version_owner_action, Category, Crate, CratePublishPolicy, DependencyKind, Keyword,
+    NewCrate, NewVersion, NewVersionAttestation, NewVersionMetadata, Rights, Team, VersionAction,
+    VersionLintWarning,
 };
 
-use crate::licenses::parse_license_expr;
+use crate::licenses::{parse_license_expr, NON_STANDARD_LICENSE};
+use crate::lints;
 use crate::middleware::log_request::RequestLogExt;
 use crate::models::token::EndpointScope;
 use crate::rate_limiter::LimitedAction;
 use crate::schema::*;
 use crate::sql::canon_crate_name;
+use crate::typosquat::{check_new_crate, Severity};
 use crate::util::errors::{cargo_err, internal, AppResult};
 use crate::util::Maximums;
 use crate::views::{
@@ -47,7 +50,7 @@ const MISSING_RIGHTS_ERROR_MESSAGE: &str = "this crate exists but you don't seem
 /// --status` command, via crates.io's front end, or email.
 pub async fn publish(app: AppState, req: BytesRequest) -> AppResult<Json<GoodCrate>> {
     let (req, bytes) = req.0.into_parts();
-    let (json_bytes, tarball_bytes) = split_body(bytes)?;
+    let (json_bytes, tarball_bytes, attestation_bytes) = split_body(bytes)?;
 
     let metadata: PublishMetadata = serde_json::from_slice(&json_bytes)
         .map_err(|e| cargo_err(format_args!("invalid upload request: {e}")))?;
@@ -89,6 +92,7 @@ pub async fn publish(app: AppState, req: BytesRequest) -> AppResult<Json<GoodCra
         let auth = AuthCheck::default()
             .with_endpoint_scope(endpoint_scope)
             .for_crate(&metadata.name)
+            .for_version(&version)
             .check(&req, conn)?;
 
         let api_token_id = auth.api_token_id();
@@ -129,6 +133,21 @@ pub async fn publish(app: AppState, req: BytesRequest) -> AppResult<Json<GoodCra
         let pkg_name = format!("{}-{}", &*metadata.name, &version_string);
         let tarball_info = process_tarball(&pkg_name, &*tarball_bytes, maximums.max_unpack_size)?;
 
+        let version_metadata = NewVersionMetadata::new(&tarball_info)?;
+        let lint_warnings = lints::run(&tarball_info, &app.config.enabled_publish_lints);
+
+        let attestation = attestation_bytes
+            .map(|bytes| {
+                let trust_root = &app.config.attestation_trust_root;
+                let attestation = trust_root
+                    .verify(&bytes, &tarball_bytes)
+                    .map_err(|error| cargo_err(error.to_string()))?;
+
+                let checksum: String = Sha256::digest(&bytes).encode_hex();
+                Ok::<_, BoxedAppError>((attestation, checksum, bytes))
+            })
+            .transpose()?;
+
         // `unwrap()` is safe here since `process_tarball()` validates that
         // we only accept manifests with a `package` section and without
         // inheritance.
@@ -176,7 +195,7 @@ pub async fn publish(app: AppState, req: BytesRequest) -> AppResult<Json<GoodCra
             // If no license is given, but a license file is given, flag this
             // crate as having a nonstandard license. Note that we don't
             // actually do anything else with license_file currently.
-            license = Some(String::from("non-standard"));
+            license = Some(String::from(NON_STANDARD_LICENSE));
         }
 
         validate_url(homepage.as_deref(), "homepage")?;
@@ -257,6 +276,38 @@ pub async fn publish(app: AppState, req: BytesRequest) -> AppResult<Json<GoodCra
             }
         }
 
+        // New crates with names that are easily mistaken for the name of a popular crate are
+        // either rejected or published with a warning, depending on the configuration.
+        let typosquat_config = &app.config.typosquat;
+        let typosquat_warnings = match (&existing_crate, typosquat_config.publish_check) {
+            (Some(_), _) | (None, Severity::Off) => vec![],
+            (None, severity) => {
+                let top = app.typosquat_top_crates.get(typosquat_config, conn)?;
+                let squats = check_new_crate(
+                    &metadata.name,
+                    user.id,
+                    &top,
+                    typosquat_config,
+                    |team_id| {
+                        let team: Team = teams::table.find(team_id).first(conn)?;
+                        Handle::current().block_on(team.contains_user(&app, user))
+                    },
+                )?;
+                if severity == Severity::Reject && !squats.is_empty() {
+                    return Err(cargo_err(format!(
+                        "the name `{}` is too similar to the name of a popular crate:\n\
+                        {}\n\
+                        \n\
+                        Please choose a different name. If you believe this is a mistake, \
+                        please send us an email to help@crates.io.",
+                        metadata.name,
+                        squats.join("\n"),
+                    )));
+                }
+                squats
+            }
+        };
+
 
         // Create a transaction on the database, if there are no errors,
         // commit the transactions to record a new or updated crate.
@@ -275,6 +326,7 @@ pub async fn publish(app: AppState, req: BytesRequest) -> AppResult<Json<GoodCra
                 repository: repository.as_deref(),
                 max_upload_size: None,
                 max_features: None,
+                ownership_invitations_expiration_days: None,
             };
 
             if is_reserved_name(persist.name, conn)? {
@@ -293,6 +345,29 @@ pub async fn publish(app: AppState, req: BytesRequest) -> AppResult<Json<GoodCra
                 return Err(cargo_err(MISSING_RIGHTS_ERROR_MESSAGE));
             }
 
+            // Some of the owners might only be allowed to yank, in which case the rights have to
+            // be checked again against the owners that are allowed to publish.
+            let publishing_owners = krate.publishing_owners(conn)?;
+            if publishing_owners.len() < owners.len()
+                && Handle::current().block_on(user.rights(&app, &publishing_owners))? < Rights::Publish
+            {
+                return Err(cargo_err(format_args!(
+                    "the owners of `{}` only allow some of them to publish new versions, \
+                    and you are not one of them",
+                    krate.name
+                )));
+            }
+
+            // The publish policy is only checked after the publish rights, so that users that
+            // aren't owners of the crate don't learn about it from the error messages.
+            if let Some(policy) = CratePublishPolicy::find(krate.id, conn)? {
+                let crate_scopes = auth.api_token().and_then(|token| token.crate_scopes.as_deref());
+                let vcs_dirty = tarball_info.vcs_info.as_ref()
+                    .and_then(|vcs_info| vcs_info.git.as_ref())
+                    .map(|git| git.dirty);
+                policy.check(&krate.name, crate_scopes, vcs_dirty, rust_version.as_deref())?;
+            }
+
             if krate.name != *name {
                 return Err(cargo_err(format_args!(
                     "crate was previously named `{}`",
@@ -300,6 +375,16 @@ pub async fn publish(app: AppState, req: BytesRequest) -> AppResult<Json<GoodCra
                 )));
             }
 
+            if let Some((attestation, _, _)) = &attestation {
+                if !attestation.is_signed_by_owner(&owners) {
+                    return Err(cargo_err(format_args!(
+                        "attestation signer `{}` does not belong to the GitHub account or \
+                        organization of any owner of `{}`",
+                        attestation.signer, krate.name
+                    )));
+                }
+            }
+
             if let Some(daily_version_limit) = app.config.new_version_rate_limit {
                 let published_today = count_versions_published_today(krate.id, conn)?;
                 if published_today >= daily_version_limit as i64 {
@@ -328,6 +413,18 @@ pub async fn publish(app: AppState, req: BytesRequest) -> AppResult<Json<GoodCra
             )?
             .save(conn, &verified_email_address)?;
 
+            version_metadata.save(version.id, conn)?;
+            VersionLintWarning::insert_all(version.id, &lint_warnings, conn)?;
+
+            if let Some((attestation, checksum, _)) = &attestation {
+                NewVersionAttestation {
+                    version_id: version.id,
+                    signer: &attestation.signer,
+                    checksum,
+                }
+                .insert(conn)?;
+            }
+
             insert_version_owner_action(
                 conn,
                 version.id,
@@ -359,7 +456,35 @@ pub async fn publish(app: AppState, req: BytesRequest) -> AppResult<Json<GoodCra
 
             let top_versions = krate.top_versions(conn)?;
 
-            let pkg_path_in_vcs = tarball_info.vcs_info.map(|info| info.path_in_vcs);
+            let (pkg_path_in_vcs, vcs_sha1) = match tarball_info.vcs_info {
+                Some(info) => (Some(info.path_in_vcs), info.git.map(|git| git.sha1)),
+                None => (None, None),
+            };
+
+            if app.config.verify_vcs_commits {
+                if let (Some(repository), Some(sha1)) = (&repository, &vcs_sha1) {
+                    jobs::VerifyVcsCommit::new(
+                        version.id,
+                        krate.name.clone(),
+                        version_string.clone(),
+                        repository.clone(),
+                        pkg_path_in_vcs.clone().unwrap_or_default(),
+                        sha1.clone(),
+                        maximums.max_unpack_size,
+                    )
+                    .enqueue(conn)?;
+                }
+            }
+
+            if app.config.scan_new_versions {
+                jobs::ScanVersion::new(
+                    version.id,
+                    krate.name.clone(),
+                    version_string.clone(),
+                    maximums.max_unpack_size,
+                )
+                .enqueue(conn)?;
+            }
 
             if let Some(readme) = metadata.readme {
                 if !readme.is_empty() {
@@ -371,6 +496,7 @@ pub async fn publish(app: AppState, req: BytesRequest) -> AppResult<Json<GoodCra
                             .unwrap_or_else(|| String::from("README.md")),
                         repository,
                         pkg_path_in_vcs,
+                        vcs_sha1,
                     )
                     .enqueue(conn)?;
                 }
@@ -385,6 +511,16 @@ pub async fn publish(app: AppState, req: BytesRequest) -> AppResult<Json<GoodCra
                 ))
                 .map_err(|e| internal(format!("failed to upload crate: {e}")))?;
 
+            if let Some((_, _, bytes)) = attestation {
+                Handle::current()
+                    .block_on(app.storage.upload_attestation(
+                        &krate.name,
+                        &version_string,
+                        bytes,
+                    ))
+                    .map_err(|e| internal(format!("failed to upload attestation: {e}")))?;
+            }
+
             jobs::enqueue_sync_to_index(&krate.name, conn)?;
 
             // Experiment: check new crates for potential typosquatting.
@@ -392,13 +528,18 @@ pub async fn publish(app: AppState, req: BytesRequest) -> AppResult<Json<GoodCra
                 CheckTyposquat::new(&krate.name).enqueue(conn)?;
             }
 
-            // The `other` field on `PublishWarnings` was introduced to handle a temporary warning
-            // that is no longer needed. As such, crates.io currently does not return any `other`
-            // warnings at this time, but if we need to, the field is available.
+            // The findings of the publish lints and the typosquatting check are returned as
+            // `other` warnings, which cargo displays to the user as-is.
+            let other = lint_warnings
+                .into_iter()
+                .map(|warning| warning.message)
+                .chain(typosquat_warnings)
+                .collect();
+
             let warnings = PublishWarnings {
                 invalid_categories: ignored_invalid_categories,
                 invalid_badges: vec![],
-                other: vec![],
+                other,
             };
 
             Ok(Json(GoodCrate {
@@ -423,13 +564,15 @@ fn count_versions_published_today(crate_id: i32, conn: &mut PgConnection) -> Que
 }
 
 #[instrument(skip_all)]
-fn split_body(mut bytes: Bytes) -> AppResult<(Bytes, Bytes)> {
+fn split_body(mut bytes: Bytes) -> AppResult<(Bytes, Bytes, Option<Bytes>)> {
     // The format of the req.body() of a publish request is as follows:
     //
     // metadata length
     // metadata in JSON about the crate being published
     // .crate tarball length
     // .crate tarball file
+    // attestation length (optional)
+    // attestation file (optional)
 
     if bytes.len() < 4 {
         // Avoid panic in `get_u32_le()` if there is not enough remaining data
@@ -459,7 +602,32 @@ fn split_body(mut bytes: Bytes) -> AppResult<(Bytes, Bytes)> {
 
     let tarball_bytes = bytes.split_to(tarball_len);
 
-    Ok((json_bytes, tarball_bytes))
+    if bytes.is_empty() {
+        return Ok((json_bytes, tarball_bytes, None));
+    }
+
+    if bytes.len() < 4 {
+        // Avoid panic in `get_u32_le()` if there is not enough remaining data
+        return Err(cargo_err("invalid attestation length"));
+    }
+
+    let attestation_len = bytes.get_u32_le() as usize;
+    if attestation_len > bytes.len() {
+        return Err(cargo_err(format!(
+            "invalid attestation length for remaining payload: {attestation_len}"
+        )));
+    }
+
+    let attestation_bytes = bytes.split_to(attestation_len);
+
+    if !bytes.is_empty() {
+        return Err(cargo_err(format!(
+            "unexpected {} bytes after the attestation",
+            bytes.len()
+        )));
+    }
+
+    Ok((json_bytes, tarball_bytes, Some(attestation_bytes)))
 }
 
 fn is_reserved_name(name: &str, conn: &mut PgConnection) -> QueryResult<bool> {
@@ -705,6 +873,9 @@ impl From<TarballError> for BoxedAppError {
             TarballError::InvalidManifest(err) => cargo_err(format!(
                 "failed to parse `Cargo.toml` manifest file\n\n{err}"
             )),
+            TarballError::TooManyFiles(max_files) => {
+                cargo_err(format!("uploaded tarball contains more than {max_files} files"))
+            }
         }
     }
 }
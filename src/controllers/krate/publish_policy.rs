@@ -0,0 +1,134 @@
+//! Endpoints for managing the publish policy of a crate
+
+use crate::auth::AuthCheck;
+use crate::controllers::prelude::*;
+use crate::models::token::EndpointScope;
+use crate::models::{Crate, CratePublishPolicy, NewCratePublishPolicy, Owner, Rights};
+use tokio::runtime::Handle;
+
+#[derive(Serialize, Deserialize)]
+struct EncodablePublishPolicy {
+    #[serde(default)]
+    require_exact_crate_scope: bool,
+    #[serde(default)]
+    forbid_dirty_vcs: bool,
+    #[serde(default)]
+    require_rust_version: bool,
+    /// The logins of the owners that are only allowed to yank versions, but
+    /// not to publish new ones.
+    #[serde(default)]
+    yank_only_owners: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct PublishPolicyRequest {
+    publish_policy: EncodablePublishPolicy,
+}
+
+/// Handles the `GET /crates/:crate_id/publish_policy` route.
+pub async fn show(state: AppState, Path(crate_name): Path<String>) -> AppResult<Json<Value>> {
+    spawn_blocking(move || {
+        let conn = &mut *state.db_read()?;
+        let krate: Crate = Crate::by_name(&crate_name).first(conn)?;
+
+        let policy = encode_policy(&krate, conn)?;
+        Ok(Json(json!({ "publish_policy": policy })))
+    })
+    .await
+}
+
+/// Handles the `PUT /crates/:crate_id/publish_policy` route.
+pub async fn update(
+    state: AppState,
+    Path(crate_name): Path<String>,
+    req: BytesRequest,
+) -> AppResult<Json<Value>> {
+    spawn_blocking(move || {
+        let request: PublishPolicyRequest =
+            serde_json::from_slice(req.body()).map_err(|_| cargo_err("invalid json request"))?;
+        let request = request.publish_policy;
+
+        let conn = &mut *state.db_write()?;
+        let auth = AuthCheck::default()
+            .with_endpoint_scope(EndpointScope::ChangeOwners)
+            .for_crate(&crate_name)
+            .check(&req, conn)?;
+
+        conn.transaction(|conn| {
+            let krate: Crate = Crate::by_name(&crate_name).first(conn)?;
+            let owners = krate.owners(conn)?;
+
+            match Handle::current().block_on(auth.user().rights(&state, &owners))? {
+                Rights::Full => {}
+                Rights::Publish => {
+                    return Err(cargo_err(
+                        "team members don't have permission to change the publish policy",
+                    ));
+                }
+                Rights::None => {
+                    return Err(cargo_err(
+                        "only owners have permission to change the publish policy",
+                    ));
+                }
+            }
+
+            let is_yank_only = |owner: &Owner| {
+                let login = owner.login().to_lowercase();
+                request
+                    .yank_only_owners
+                    .iter()
+                    .any(|yank_only| yank_only.to_lowercase() == login)
+            };
+
+            for login in &request.yank_only_owners {
+                let lowercase_login = login.to_lowercase();
+                if !owners
+                    .iter()
+                    .any(|o| o.login().to_lowercase() == lowercase_login)
+                {
+                    return Err(cargo_err(format_args!(
+                        "`{login}` is not an owner of this crate"
+                    )));
+                }
+            }
+
+            if owners.iter().all(is_yank_only) {
+                return Err(cargo_err(
+                    "at least one owner must be allowed to publish new versions",
+                ));
+            }
+
+            for owner in &owners {
+                krate.set_owner_can_publish(owner, !is_yank_only(owner), conn)?;
+            }
+
+            NewCratePublishPolicy {
+                crate_id: krate.id,
+                require_exact_crate_scope: request.require_exact_crate_scope,
+                forbid_dirty_vcs: request.forbid_dirty_vcs,
+                require_rust_version: request.require_rust_version,
+            }
+            .save(conn)?;
+
+            let policy = encode_policy(&krate, conn)?;
+            Ok(Json(json!({ "publish_policy": policy })))
+        })
+    })
+    .await
+}
+
+fn encode_policy(krate: &Crate, conn: &mut PgConnection) -> AppResult<EncodablePublishPolicy> {
+    let policy = CratePublishPolicy::find(krate.id, conn)?;
+    let yank_only_owners = krate
+        .yank_only_owners(conn)?
+        .iter()
+        .map(|owner| owner.login().to_string())
+        .collect();
+
+    Ok(EncodablePublishPolicy {
+        require_exact_crate_scope: policy.as_ref().is_some_and(|p| p.require_exact_crate_scope),
+        forbid_dirty_vcs: policy.as_ref().is_some_and(|p| p.forbid_dirty_vcs),
+        require_rust_version: policy.as_ref().is_some_and(|p| p.require_rust_version),
+        yank_only_owners,
+    })
+}
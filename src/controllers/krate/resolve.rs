@@ -0,0 +1,181 @@
+//! Endpoint for previewing the dependency graph of a set of requirements
+
+use std::collections::BTreeSet;
+
+use semver::VersionReq;
+
+use crate::auth::AuthCheck;
+use crate::controllers::frontend_prelude::*;
+use crate::models::{parse_rust_version, DependencyKind};
+use crate::rate_limiter::LimitedAction;
+use crate::resolver::{self, DatabaseRegistry, Requirement};
+
+/// The maximum number of root requirements that can be resolved in a single
+/// request.
+const MAX_REQUIREMENTS: usize = 100;
+
+#[derive(Deserialize)]
+struct ResolveRequest {
+    dependencies: Vec<RootDependency>,
+    /// Only select versions that support this Rust version.
+    #[serde(default)]
+    rust_version: Option<String>,
+    /// Whether to include the dev-dependencies of the root packages.
+    #[serde(default)]
+    dev_dependencies: bool,
+}
+
+#[derive(Deserialize)]
+struct RootDependency {
+    name: String,
+    #[serde(default = "default_req")]
+    req: String,
+    #[serde(default)]
+    features: Vec<String>,
+    #[serde(default = "default_true")]
+    default_features: bool,
+}
+
+fn default_req() -> String {
+    "*".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Serialize)]
+struct EncodableResolvedPackage {
+    /// The `name@version` identifier of the package, which is used to refer
+    /// to it in the dependency edges.
+    id: String,
+    name: String,
+    version: String,
+    license: Option<String>,
+    rust_version: Option<String>,
+    features: BTreeSet<String>,
+}
+
+#[derive(Serialize)]
+struct EncodableResolvedDependency {
+    /// The `id` of the dependent package, or `None` for root requirements.
+    from: Option<String>,
+    to: String,
+    req: String,
+    kind: DependencyKind,
+    target: Option<String>,
+    optional: bool,
+}
+
+/// Handles the `POST /resolve/crates` route.
+///
+/// Takes a list of root requirements and resolves their dependency graph
+/// using the versions and dependencies that are known to crates.io, see the
+/// [`crate::resolver`] module for the differences to cargo's resolver.
+pub async fn resolve(app: AppState, req: BytesRequest) -> AppResult<Json<Value>> {
+    spawn_blocking(move || {
+        let request: ResolveRequest =
+            serde_json::from_slice(req.body()).map_err(|e| bad_request(format!("{e}")))?;
+
+        if request.dependencies.is_empty() {
+            return Err(bad_request("at least one dependency is required"));
+        }
+        if request.dependencies.len() > MAX_REQUIREMENTS {
+            return Err(bad_request(format!(
+                "cannot resolve more than {MAX_REQUIREMENTS} dependencies at once"
+            )));
+        }
+
+        let requirements = request
+            .dependencies
+            .into_iter()
+            .map(|dependency| {
+                let req = VersionReq::parse(&dependency.req).map_err(|_| {
+                    bad_request(format!(
+                        "invalid version requirement `{}` for `{}`",
+                        dependency.req, dependency.name
+                    ))
+                })?;
+
+                Ok(Requirement {
+                    name: dependency.name,
+                    req,
+                    features: dependency.features,
+                    default_features: dependency.default_features,
+                })
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+
+        let rust_version = request
+            .rust_version
+            .map(|rust_version| {
+                parse_rust_version(&rust_version)
+                    .ok_or_else(|| bad_request(format!("invalid Rust version: {rust_version}")))
+            })
+            .transpose()?;
+
+        // The authentication and the rate limit need the primary database,
+        // but the resolution itself only reads and can use the replica.
+        {
+            let conn = &mut *app.db_write()?;
+            let auth = AuthCheck::default().check(&req, conn)?;
+            app.rate_limiter
+                .check_rate_limit(auth.user_id(), LimitedAction::Resolve, conn)?;
+        }
+
+        let conn = &mut *app.db_read()?;
+        let mut registry = DatabaseRegistry::new(conn);
+        let resolve = resolver::resolve(
+            &mut registry,
+            &requirements,
+            rust_version.as_ref(),
+            request.dev_dependencies,
+        )?;
+
+        let ids = resolve
+            .packages
+            .iter()
+            .map(|package| format!("{}@{}", package.name, package.version))
+            .collect::<Vec<_>>();
+
+        let licenses = resolve
+            .packages
+            .iter()
+            .filter_map(|package| package.license.clone())
+            .collect::<BTreeSet<_>>();
+
+        let packages = resolve
+            .packages
+            .into_iter()
+            .zip(&ids)
+            .map(|(package, id)| EncodableResolvedPackage {
+                id: id.clone(),
+                name: package.name,
+                version: package.version.to_string(),
+                license: package.license,
+                rust_version: package.rust_version,
+                features: package.features,
+            })
+            .collect::<Vec<_>>();
+
+        let dependencies = resolve
+            .dependencies
+            .into_iter()
+            .map(|dependency| EncodableResolvedDependency {
+                from: dependency.from.map(|from| ids[from].clone()),
+                to: ids[dependency.to].clone(),
+                req: dependency.req,
+                kind: dependency.kind,
+                target: dependency.target,
+                optional: dependency.optional,
+            })
+            .collect::<Vec<_>>();
+
+        Ok(Json(json!({
+            "packages": packages,
+            "dependencies": dependencies,
+            "licenses": licenses,
+        })))
+    })
+    .await
+}
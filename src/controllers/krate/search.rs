@@ -1,8 +1,11 @@
 //! Endpoint for searching and discovery functionality
 
 use crate::auth::AuthCheck;
+use chrono::{NaiveDateTime, Utc};
 use diesel::dsl::*;
-use diesel::sql_types::Array;
+use diesel::pg::Pg;
+use diesel::sql_types::{Array, Bool, Double, Integer, Text};
+use diesel_full_text_search::configuration::TsConfigurationByName;
 use diesel_full_text_search::*;
 use indexmap::IndexMap;
 
@@ -13,9 +16,18 @@ use crate::schema::*;
 use crate::util::errors::bad_request;
 use crate::views::EncodableCrate;
 
-use crate::controllers::helpers::pagination::{Page, Paginated, PaginationOptions};
+use crate::controllers::helpers::pagination::{
+    encode_seek, Page, Paginated, PaginationOptions, RawSeekPayload,
+};
 use crate::models::krate::ALL_COLUMNS;
-use crate::sql::{array_agg, canon_crate_name, lower};
+use crate::sql::{array_agg, canon_crate_name, lower, plainto_tsquery_with_search_config};
+
+use self::query::{Operator, SearchQuery};
+use self::relevance::Relevance;
+
+mod query;
+mod relevance;
+mod suggestions;
 
 /// Handles the `GET /crates` route.
 /// Returns a list of crates. Called in a variety of scenarios in the
@@ -40,8 +52,6 @@ use crate::sql::{array_agg, canon_crate_name, lower};
 /// for them.
 pub async fn search(app: AppState, req: Parts) -> AppResult<Json<Value>> {
     spawn_blocking(move || {
-        use diesel::sql_types::{Bool, Text};
-
         let params = req.query();
         let sort = params.get("sort").map(|s| &**s);
         let include_yanked = params
@@ -52,55 +62,262 @@ pub async fn search(app: AppState, req: Parts) -> AppResult<Json<Value>> {
         // Remove 0x00 characters from the query string because Postgres can not
         // handle them and will return an error, which would cause us to throw
         // an Internal Server Error ourselves.
-        let q_string = params.get("q").map(|q| q.replace('\u{0}', ""));
+        let search_query = params
+            .get("q")
+            .map(|q| SearchQuery::parse(&q.replace('\u{0}', "")))
+            .transpose()?
+            .unwrap_or_default();
+
+        // Only the free text of the query is used for the full text search,
+        // while the qualifiers are turned into filters.
+        let q_string = search_query.text.clone();
+
+        let conn = &mut *app.db_read()?;
+
+        let following = if params.get("following").is_some() {
+            Some(AuthCheck::default().check(&req, conn)?.user_id())
+        } else {
+            None
+        };
+
+        let ids = if params.get("ids[]").is_some() {
+            let query_bytes = req.uri.query().unwrap_or("").as_bytes();
+            let ids: Vec<_> = url::form_urlencoded::parse(query_bytes)
+                .filter(|(key, _)| key == "ids[]")
+                .map(|(_, value)| value.to_string())
+                .collect();
+            Some(ids)
+        } else {
+            None
+        };
 
-        let selection = (
+        let filter_params = FilterParams {
+            q_string: q_string.as_deref(),
+            search_query: &search_query,
+            include_yanked,
+            category: params.get("category").map(|s| &**s),
+            all_keywords: params.get("all_keywords").map(|s| &**s),
+            keyword: params.get("keyword").map(|s| &**s),
+            letter: params.get("letter").map(|s| &**s),
+            user_id: params.get("user_id").and_then(|s| s.parse().ok()),
+            team_id: params.get("team_id").and_then(|s| s.parse().ok()),
+            following,
+            ids: ids.as_deref(),
+        };
+
+        let sort = SortOrder::new(q_string.is_some(), sort);
+
+        let pagination: PaginationOptions = PaginationOptions::builder()
+            .limit_page_numbers()
+            .enable_seek(true)
+            .gather(&req)?;
+
+        // The recency of the last release is part of the relevance score, so
+        // it is measured from the time of the first page for all pages.
+        let reference_time = match &pagination.page {
+            Page::Seek(seek) if sort == SortOrder::Relevance => {
+                let (_, _, _, reference_time): RelevanceSeekKey = seek.decode()?;
+                reference_time
+            }
+            _ => Utc::now().naive_utc(),
+        };
+
+        let relevance = q_string.as_deref().map(|q_string| Relevance {
+            config: &app.config.search_relevance,
+            q_string,
+            reference_time,
+        });
+
+        let mut query = filter_params.make_query()?;
+        if let Page::Seek(ref seek) = pagination.page {
+            query = sort.seek(query, seek, relevance.as_ref(), conn)?;
+        }
+
+        let mut query = query.select((
             ALL_COLUMNS,
             false.into_sql::<Bool>(),
             recent_crate_downloads::downloads.nullable(),
-        );
-        let mut query = crates::table
-            .left_join(recent_crate_downloads::table)
-            .select(selection)
-            .into_boxed();
-
-        let mut supports_seek = true;
-
-        if let Some(q_string) = &q_string {
-            // Searching with a query string always puts the exact match at the start of the results,
-            // so we can't support seek-based pagination with it.
-            supports_seek = false;
-
-            if !q_string.is_empty() {
-                let sort = params.get("sort").map(|s| &**s).unwrap_or("relevance");
-
-                let q = sql::<TsQuery>("plainto_tsquery('english', ")
-                    .bind::<Text, _>(q_string)
-                    .sql(")");
-                query = query.filter(
-                    q.clone()
-                        .matches(crates::textsearchable_index_col)
-                        .or(Crate::loosly_matches_name(q_string)),
-                );
-
-                query = query.select((
-                    ALL_COLUMNS,
-                    Crate::with_name(q_string),
-                    recent_crate_downloads::downloads.nullable(),
-                ));
-                query = query.order(Crate::with_name(q_string).desc());
-
-                if sort == "relevance" {
-                    let rank = ts_rank_cd(crates::textsearchable_index_col, q);
-                    query = query.then_order_by(rank.desc())
-                }
+            0_f64.into_sql::<Double>(),
+        ));
+
+        if let Some(relevance) = &relevance {
+            let q_string = relevance.q_string;
+            query = query.select((
+                ALL_COLUMNS,
+                Crate::with_name(q_string),
+                recent_crate_downloads::downloads.nullable(),
+                relevance.score(),
+            ));
+            query = query.order(Crate::with_name(q_string).desc());
+
+            if sort == SortOrder::Relevance {
+                query = query.then_order_by(relevance.score().desc())
             }
         }
 
-        if let Some(cat) = params.get("category") {
-            // Calculating the total number of results with filters is not supported yet.
-            supports_seek = false;
+        // Any sort other than 'relevance' (default) would ignore exact crate name matches.
+        // The crate ID is used as a tie-breaker to make the order stable for seek-based pagination.
+        query = match sort {
+            SortOrder::Downloads => query.order((crates::downloads.desc(), crates::id.desc())),
+            SortOrder::RecentDownloads => query.order((
+                recent_crate_downloads::downloads.desc().nulls_last(),
+                crates::id.desc(),
+            )),
+            SortOrder::RecentUpdates => query.order((crates::updated_at.desc(), crates::id.desc())),
+            SortOrder::New => query.order((crates::created_at.desc(), crates::id.desc())),
+            SortOrder::Name | SortOrder::Query | SortOrder::Relevance => {
+                query.then_order_by(crates::name.asc())
+            }
+        };
+
+        // To avoid breaking existing users, seek-based pagination is only used if an explicit page has
+        // not been provided. This way clients relying on meta.next_page will use the faster seek-based
+        // paginations, while client hardcoding pages handling will use the slower offset-based code.
+        let (total, next_page, prev_page, data, conn) = if let Page::Numeric(_) = pagination.page {
+            let query = query.pages_pagination(pagination);
+            let data: Paginated<SearchRow> =
+                info_span!("db.query", message = "SELECT ..., COUNT(*) FROM crates")
+                    .in_scope(|| query.load(conn))?;
+            (
+                data.total(),
+                data.next_page_params().map(|p| req.query_with_params(p)),
+                data.prev_page_params().map(|p| req.query_with_params(p)),
+                data.into_iter().collect::<Vec<_>>(),
+                conn,
+            )
+        } else {
+            query = query.limit(pagination.per_page);
+
+            // Without any filters this does a full index-only scan over the crates table to gather
+            // how many crates were published. Unfortunately on PostgreSQL counting the rows in a
+            // table requires scanning the table, and the `total` field is part of the stable
+            // registries API.
+            //
+            // If this becomes a problem in the future the crates count could be denormalized, at least
+            // for the filterless happy path.
+            let count_query = filter_params.make_query()?.count();
+            let total: i64 = info_span!("db.query", message = "SELECT COUNT(*) FROM crates")
+                .in_scope(|| count_query.get_result(conn))?;
+
+            let results: Vec<SearchRow> =
+                info_span!("db.query", message = "SELECT ... FROM crates")
+                    .in_scope(|| query.load(conn))?;
+
+            let next_page = if let Some(last) = results.last() {
+                let mut params = IndexMap::new();
+                params.insert("seek".into(), sort.encode_seek(last, reference_time)?);
+                Some(req.query_with_params(params))
+            } else {
+                None
+            };
+
+            (total, next_page, None, results, conn)
+        };
+
+        let perfect_matches = data.iter().map(|&(_, b, _, _)| b).collect::<Vec<_>>();
+        let recent_downloads = data
+            .iter()
+            .map(|&(_, _, s, _)| s.unwrap_or(0))
+            .collect::<Vec<_>>();
+        let crates = data.into_iter().map(|(c, _, _, _)| c).collect::<Vec<_>>();
+
+        let suggestions = match &q_string {
+            Some(q_string) => info_span!("db.query", message = "SELECT ... FROM crates")
+                .in_scope(|| suggestions::suggest_names(q_string, conn))?,
+            None => Vec::new(),
+        };
+
+        let versions: Vec<Version> = info_span!("db.query", message = "SELECT ... FROM versions")
+            .in_scope(|| crates.versions().load(conn))?;
+        let versions = versions
+            .grouped_by(&crates)
+            .into_iter()
+            .map(TopVersions::from_versions);
+
+        let crates = versions
+            .zip(crates)
+            .zip(perfect_matches)
+            .zip(recent_downloads)
+            .map(
+                |(((max_version, krate), perfect_match), recent_downloads)| {
+                    EncodableCrate::from_minimal(
+                        krate,
+                        Some(&max_version),
+                        Some(vec![]),
+                        perfect_match,
+                        Some(recent_downloads),
+                    )
+                },
+            )
+            .collect::<Vec<_>>();
+
+        Ok(Json(json!({
+            "crates": crates,
+            "meta": {
+                "total": total,
+                "next_page": next_page,
+                "prev_page": prev_page,
+                "suggestions": suggestions,
+            },
+        })))
+    })
+    .await
+}
+
+/// A crate, whether its name is an exact match for the search query, its
+/// recent downloads and its relevance score.
+type SearchRow = (Crate, bool, Option<i64>, f64);
+
+/// The seek key of the relevance order: whether the name is an exact match,
+/// the relevance score, the name and the reference time of the score.
+type RelevanceSeekKey = (bool, f64, String, NaiveDateTime);
+
+type BoxedCrates<'a> = IntoBoxed<'a, LeftJoin<crates::table, recent_crate_downloads::table>, Pg>;
+
+fn plainto_tsquery(
+    q_string: &str,
+) -> plainto_tsquery_with_search_config::HelperType<TsConfigurationByName, &str> {
+    plainto_tsquery_with_search_config(TsConfigurationByName("english"), q_string)
+}
+
+/// The filters of a search request.
+///
+/// These are kept separately from the rest of the query so that the same
+/// filters can be used both for loading the results and for counting the total
+/// number of results.
+struct FilterParams<'a> {
+    q_string: Option<&'a str>,
+    search_query: &'a SearchQuery,
+    include_yanked: bool,
+    category: Option<&'a str>,
+    all_keywords: Option<&'a str>,
+    keyword: Option<&'a str>,
+    letter: Option<&'a str>,
+    user_id: Option<i32>,
+    team_id: Option<i32>,
+    following: Option<i32>,
+    ids: Option<&'a [String]>,
+}
 
+impl<'a> FilterParams<'a> {
+    fn make_query(&self) -> AppResult<BoxedCrates<'a>> {
+        let mut query = crates::table
+            .left_join(recent_crate_downloads::table)
+            .into_boxed()
+            .filter(not(exists(
+                crate_quarantines::table.filter(crate_quarantines::crate_id.eq(crates::id)),
+            )));
+
+        if let Some(q_string) = self.q_string {
+            query = query.filter(
+                plainto_tsquery(q_string)
+                    .matches(crates::textsearchable_index_col)
+                    .or(Crate::loosly_matches_name(q_string)),
+            );
+        }
+
+        let categories = self.category.into_iter();
+        for cat in categories.chain(self.search_query.categories.iter().map(|s| &**s)) {
             query = query.filter(
                 crates::id.eq_any(
                     crates_categories::table
@@ -108,19 +325,14 @@ pub async fn search(app: AppState, req: Parts) -> AppResult<Json<Value>> {
                         .inner_join(categories::table)
                         .filter(
                             categories::slug
-                                .eq(cat)
+                                .eq(cat.to_string())
                                 .or(categories::slug.like(format!("{cat}::%"))),
                         ),
                 ),
             );
         }
 
-        let conn = &mut *app.db_read()?;
-
-        if let Some(kws) = params.get("all_keywords") {
-            // Calculating the total number of results with filters is not supported yet.
-            supports_seek = false;
-
+        if let Some(kws) = self.all_keywords {
             let names: Vec<_> = kws
                 .split_whitespace()
                 .map(|name| name.to_lowercase())
@@ -138,10 +350,7 @@ pub async fn search(app: AppState, req: Parts) -> AppResult<Json<Value>> {
                     names.into_sql::<Array<Text>>(),
                 ),
             );
-        } else if let Some(kw) = params.get("keyword") {
-            // Calculating the total number of results with filters is not supported yet.
-            supports_seek = false;
-
+        } else if let Some(kw) = self.keyword {
             query = query.filter(
                 crates::id.eq_any(
                     crates_keywords::table
@@ -150,10 +359,20 @@ pub async fn search(app: AppState, req: Parts) -> AppResult<Json<Value>> {
                         .filter(lower(keywords::keyword).eq(lower(kw))),
                 ),
             );
-        } else if let Some(letter) = params.get("letter") {
-            // Calculating the total number of results with filters is not supported yet.
-            supports_seek = false;
+        }
+
+        for kw in &self.search_query.keywords {
+            query = query.filter(
+                crates::id.eq_any(
+                    crates_keywords::table
+                        .select(crates_keywords::crate_id)
+                        .inner_join(keywords::table)
+                        .filter(lower(keywords::keyword).eq(kw.clone())),
+                ),
+            );
+        }
 
+        if let Some(letter) = self.letter {
             let pattern = format!(
                 "{}%",
                 letter
@@ -164,10 +383,9 @@ pub async fn search(app: AppState, req: Parts) -> AppResult<Json<Value>> {
                     .collect::<String>()
             );
             query = query.filter(canon_crate_name(crates::name).like(pattern));
-        } else if let Some(user_id) = params.get("user_id").and_then(|s| s.parse::<i32>().ok()) {
-            // Calculating the total number of results with filters is not supported yet.
-            supports_seek = false;
+        }
 
+        if let Some(user_id) = self.user_id {
             query = query.filter(
                 crates::id.eq_any(
                     CrateOwner::by_owner_kind(OwnerKind::User)
@@ -175,10 +393,9 @@ pub async fn search(app: AppState, req: Parts) -> AppResult<Json<Value>> {
                         .filter(crate_owners::owner_id.eq(user_id)),
                 ),
             );
-        } else if let Some(team_id) = params.get("team_id").and_then(|s| s.parse::<i32>().ok()) {
-            // Calculating the total number of results with filters is not supported yet.
-            supports_seek = false;
+        }
 
+        if let Some(team_id) = self.team_id {
             query = query.filter(
                 crates::id.eq_any(
                     CrateOwner::by_owner_kind(OwnerKind::Team)
@@ -186,12 +403,9 @@ pub async fn search(app: AppState, req: Parts) -> AppResult<Json<Value>> {
                         .filter(crate_owners::owner_id.eq(team_id)),
                 ),
             );
-        } else if params.get("following").is_some() {
-            // Calculating the total number of results with filters is not supported yet.
-            supports_seek = false;
-
-            let user_id = AuthCheck::default().check(&req, conn)?.user_id();
+        }
 
+        if let Some(user_id) = self.following {
             query = query.filter(
                 crates::id.eq_any(
                     follows::table
@@ -199,161 +413,260 @@ pub async fn search(app: AppState, req: Parts) -> AppResult<Json<Value>> {
                         .filter(follows::user_id.eq(user_id)),
                 ),
             );
-        } else if params.get("ids[]").is_some() {
-            // Calculating the total number of results with filters is not supported yet.
-            supports_seek = false;
-
-            let query_bytes = req.uri.query().unwrap_or("").as_bytes();
-            let ids: Vec<_> = url::form_urlencoded::parse(query_bytes)
-                .filter(|(key, _)| key == "ids[]")
-                .map(|(_, value)| value.to_string())
-                .collect();
+        }
 
+        if let Some(ids) = self.ids {
             query = query.filter(crates::name.eq_any(ids));
         }
 
-        if !include_yanked {
-            // Calculating the total number of results with filters is not supported yet.
-            supports_seek = false;
-
-            query = query.filter(exists(
-                versions::table
-                    .filter(versions::crate_id.eq(crates::id))
-                    .filter(versions::yanked.eq(false)),
-            ));
+        for owner in &self.search_query.owners {
+            let login = owner.to_lowercase();
+            query = query.filter(
+                crates::id
+                    .eq_any(
+                        CrateOwner::by_owner_kind(OwnerKind::User)
+                            .select(crate_owners::crate_id)
+                            .filter(
+                                crate_owners::owner_id.eq_any(
+                                    users::table
+                                        .select(users::id)
+                                        .filter(lower(users::gh_login).eq(login.clone())),
+                                ),
+                            ),
+                    )
+                    .or(crates::id.eq_any(
+                        CrateOwner::by_owner_kind(OwnerKind::Team)
+                            .select(crate_owners::crate_id)
+                            .filter(
+                                crate_owners::owner_id.eq_any(
+                                    teams::table
+                                        .select(teams::id)
+                                        .filter(lower(teams::login).eq(login)),
+                                ),
+                            ),
+                    )),
+            );
         }
 
-        // Any sort other than 'relevance' (default) would ignore exact crate name matches
-        if sort == Some("downloads") {
-            // Custom sorting is not supported yet with seek.
-            supports_seek = false;
-
-            query = query.order(crates::downloads.desc())
-        } else if sort == Some("recent-downloads") {
-            // Custom sorting is not supported yet with seek.
-            supports_seek = false;
+        // The license and MSRV qualifiers apply to the most recently
+        // published version that is not yanked.
+        for license in &self.search_query.licenses {
+            let pattern = format!(
+                r"(^|[\s()/]){}($|[\s()/])",
+                license.replace('.', r"\.").replace('+', r"\+")
+            );
+            query = query.filter(
+                sql::<Bool>(
+                    "(SELECT versions.license FROM versions \
+                    WHERE versions.crate_id = crates.id AND NOT versions.yanked \
+                    ORDER BY versions.created_at DESC LIMIT 1) ~* ",
+                )
+                .bind::<Text, _>(pattern),
+            );
+        }
 
-            query = query.order(recent_crate_downloads::downloads.desc().nulls_last())
-        } else if sort == Some("recent-updates") {
-            // Custom sorting is not supported yet with seek.
-            supports_seek = false;
+        for comparison in &self.search_query.rust_versions {
+            let version = &comparison.value;
+            let version = [version.major, version.minor, version.patch]
+                .map(|part| i32::try_from(part).unwrap_or(i32::MAX))
+                .to_vec();
+            query = query.filter(
+                sql::<Bool>(
+                    "(SELECT ((string_to_array(versions.rust_version || '.0.0', '.'))[1:3])::int[] \
+                    FROM versions \
+                    WHERE versions.crate_id = crates.id AND NOT versions.yanked \
+                    ORDER BY versions.created_at DESC LIMIT 1) ",
+                )
+                .sql(comparison.op.as_sql())
+                .sql(" ")
+                .bind::<Array<Integer>, _>(version),
+            );
+        }
 
-            query = query.order(crates::updated_at.desc());
-        } else if sort == Some("new") {
-            // Custom sorting is not supported yet with seek.
-            supports_seek = false;
+        for comparison in &self.search_query.updated {
+            // The dates refer to whole days, from the start of the day up to
+            // the start of the next day
+            let updated_at = crates::updated_at;
+            let start = comparison.value;
+            let end = start + chrono::Duration::days(1);
+            query = match comparison.op {
+                Operator::Lt => query.filter(updated_at.lt(start)),
+                Operator::Le => query.filter(updated_at.lt(end)),
+                Operator::Eq => query.filter(updated_at.ge(start).and(updated_at.lt(end))),
+                Operator::Ge => query.filter(updated_at.ge(start)),
+                Operator::Gt => query.filter(updated_at.ge(end)),
+            };
+        }
 
-            query = query.order(crates::created_at.desc());
+        // Crates whose versions are all quarantined are hidden, just like
+        // quarantined crates
+        let quarantined = exists(
+            version_quarantines::table.filter(version_quarantines::version_id.eq(versions::id)),
+        );
+        if self.include_yanked {
+            query = query.filter(exists(
+                versions::table
+                    .filter(versions::crate_id.eq(crates::id))
+                    .filter(not(quarantined)),
+            ));
         } else {
-            query = query.then_order_by(crates::name.asc())
+            query = query.filter(exists(
+                versions::table
+                    .filter(versions::crate_id.eq(crates::id))
+                    .filter(versions::yanked.eq(false))
+                    .filter(not(quarantined)),
+            ));
         }
 
-        let pagination: PaginationOptions = PaginationOptions::builder()
-            .limit_page_numbers()
-            .enable_seek(supports_seek)
-            .gather(&req)?;
+        Ok(query)
+    }
+}
 
-        let (explicit_page, seek) = match pagination.page {
-            Page::Numeric(_) => (true, None),
-            Page::Seek(ref s) => (false, Some(s.decode::<i32>()?)),
-            Page::Unspecified => (false, None),
-        };
+/// The order of the search results.
+///
+/// Each order has its own seek key, which contains the values of the columns
+/// the results are sorted by, so that the next page can continue right after
+/// the last result of the previous one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortOrder {
+    /// Sorted by name. The seek key is the ID of the last crate.
+    Name,
+    /// Exact name matches first, then sorted by name.
+    Query,
+    /// Exact name matches first, then sorted by relevance score and name.
+    Relevance,
+    Downloads,
+    RecentDownloads,
+    RecentUpdates,
+    New,
+}
 
-        // To avoid breaking existing users, seek-based pagination is only used if an explicit page has
-        // not been provided. This way clients relying on meta.next_page will use the faster seek-based
-        // paginations, while client hardcoding pages handling will use the slower offset-based code.
-        let (total, next_page, prev_page, data, conn) = if supports_seek && !explicit_page {
-            // Equivalent of:
-            // `WHERE name > (SELECT name FROM crates WHERE id = $1) LIMIT $2`
-            query = query.limit(pagination.per_page);
-            if let Some(seek) = seek {
+impl SortOrder {
+    fn new(has_query: bool, sort: Option<&str>) -> Self {
+        match sort {
+            Some("downloads") => Self::Downloads,
+            Some("recent-downloads") => Self::RecentDownloads,
+            Some("recent-updates") => Self::RecentUpdates,
+            Some("new") => Self::New,
+            None | Some("relevance") if has_query => Self::Relevance,
+            _ if has_query => Self::Query,
+            _ => Self::Name,
+        }
+    }
+
+    /// Restricts the query to the results after the given seek key.
+    fn seek<'a>(
+        self,
+        query: BoxedCrates<'a>,
+        seek: &RawSeekPayload,
+        relevance: Option<&Relevance<'a>>,
+        conn: &mut PgConnection,
+    ) -> AppResult<BoxedCrates<'a>> {
+        let query = match self {
+            Self::Name => {
+                // Equivalent of:
+                // `WHERE name > (SELECT name FROM crates WHERE id = $1) LIMIT $2`
+                let id: i32 = seek.decode()?;
                 let crate_name: String = crates::table
-                    .find(seek)
+                    .find(id)
                     .select(crates::name)
                     .get_result(conn)?;
-                query = query.filter(crates::name.gt(crate_name));
+                query.filter(crates::name.gt(crate_name))
             }
-
-            // This does a full index-only scan over the crates table to gather how many crates were
-            // published. Unfortunately on PostgreSQL counting the rows in a table requires scanning
-            // the table, and the `total` field is part of the stable registries API.
-            //
-            // If this becomes a problem in the future the crates count could be denormalized, at least
-            // for the filterless happy path.
-            let total: i64 = info_span!("db.query", message = "SELECT COUNT(*) FROM crates")
-                .in_scope(|| crates::table.count().get_result(conn))?;
-
-            let results: Vec<(Crate, bool, Option<i64>)> =
-                info_span!("db.query", message = "SELECT ... FROM crates")
-                    .in_scope(|| query.load(conn))?;
-
-            let next_page = if let Some(last) = results.last() {
-                let mut params = IndexMap::new();
-                params.insert(
-                    "seek".into(),
-                    crate::controllers::helpers::pagination::encode_seek(last.0.id)?,
-                );
-                Some(req.query_with_params(params))
-            } else {
-                None
-            };
-
-            (total, next_page, None, results, conn)
-        } else {
-            let query = query.pages_pagination(pagination);
-            let data: Paginated<(Crate, bool, Option<i64>)> =
-                info_span!("db.query", message = "SELECT ..., COUNT(*) FROM crates")
-                    .in_scope(|| query.load(conn))?;
-            (
-                data.total(),
-                data.next_page_params().map(|p| req.query_with_params(p)),
-                data.prev_page_params().map(|p| req.query_with_params(p)),
-                data.into_iter().collect::<Vec<_>>(),
-                conn,
-            )
-        };
-
-        let perfect_matches = data.iter().map(|&(_, b, _)| b).collect::<Vec<_>>();
-        let recent_downloads = data
-            .iter()
-            .map(|&(_, _, s)| s.unwrap_or(0))
-            .collect::<Vec<_>>();
-        let crates = data.into_iter().map(|(c, _, _)| c).collect::<Vec<_>>();
-
-        let versions: Vec<Version> = info_span!("db.query", message = "SELECT ... FROM versions")
-            .in_scope(|| crates.versions().load(conn))?;
-        let versions = versions
-            .grouped_by(&crates)
-            .into_iter()
-            .map(TopVersions::from_versions);
-
-        let crates = versions
-            .zip(crates)
-            .zip(perfect_matches)
-            .zip(recent_downloads)
-            .map(
-                |(((max_version, krate), perfect_match), recent_downloads)| {
-                    EncodableCrate::from_minimal(
-                        krate,
-                        Some(&max_version),
-                        Some(vec![]),
-                        perfect_match,
-                        Some(recent_downloads),
+            Self::Query => {
+                let (exact, name): (bool, String) = seek.decode()?;
+                let q_string = relevance.map(|r| r.q_string).unwrap_or_default();
+                let after_name = crates::name.gt(name);
+                if exact {
+                    query.filter(
+                        not(Crate::with_name(q_string))
+                            .or(Crate::with_name(q_string).and(after_name)),
                     )
-                },
-            )
-            .collect::<Vec<_>>();
+                } else {
+                    query
+                        .filter(not(Crate::with_name(q_string)))
+                        .filter(after_name)
+                }
+            }
+            Self::Relevance => {
+                let (exact, score, name, _): RelevanceSeekKey = seek.decode()?;
+                let Some(relevance) = relevance else {
+                    return Err(bad_request("invalid seek parameter"));
+                };
+                let q_string = relevance.q_string;
+                let after_rank = relevance
+                    .score()
+                    .lt(score)
+                    .or(relevance.score().eq(score).and(crates::name.gt(name)));
+                if exact {
+                    query.filter(
+                        not(Crate::with_name(q_string))
+                            .or(Crate::with_name(q_string).and(after_rank)),
+                    )
+                } else {
+                    query
+                        .filter(not(Crate::with_name(q_string)))
+                        .filter(after_rank)
+                }
+            }
+            Self::Downloads => {
+                let (downloads, id): (i32, i32) = seek.decode()?;
+                query.filter(
+                    crates::downloads
+                        .lt(downloads)
+                        .or(crates::downloads.eq(downloads).and(crates::id.lt(id))),
+                )
+            }
+            Self::RecentDownloads => {
+                let (downloads, id): (Option<i64>, i32) = seek.decode()?;
+                let recent = recent_crate_downloads::downloads;
+                match downloads {
+                    Some(downloads) => query.filter(
+                        recent
+                            .lt(downloads)
+                            .or(recent.is_null())
+                            .or(recent.eq(downloads).and(crates::id.lt(id))),
+                    ),
+                    None => query.filter(recent.is_null()).filter(crates::id.lt(id)),
+                }
+            }
+            Self::RecentUpdates => {
+                let (updated_at, id): (NaiveDateTime, i32) = seek.decode()?;
+                query.filter(
+                    crates::updated_at
+                        .lt(updated_at)
+                        .or(crates::updated_at.eq(updated_at).and(crates::id.lt(id))),
+                )
+            }
+            Self::New => {
+                let (created_at, id): (NaiveDateTime, i32) = seek.decode()?;
+                query.filter(
+                    crates::created_at
+                        .lt(created_at)
+                        .or(crates::created_at.eq(created_at).and(crates::id.lt(id))),
+                )
+            }
+        };
 
-        Ok(Json(json!({
-            "crates": crates,
-            "meta": {
-                "total": total,
-                "next_page": next_page,
-                "prev_page": prev_page,
-            },
-        })))
-    })
-    .await
+        Ok(query)
+    }
+
+    /// Encodes the seek key pointing right after the given result.
+    fn encode_seek(
+        self,
+        (krate, exact, recent_downloads, score): &SearchRow,
+        reference_time: NaiveDateTime,
+    ) -> AppResult<String> {
+        match self {
+            Self::Name => encode_seek(krate.id),
+            Self::Query => encode_seek((exact, &krate.name)),
+            Self::Relevance => encode_seek((exact, score, &krate.name, reference_time)),
+            Self::Downloads => encode_seek((krate.downloads, krate.id)),
+            Self::RecentDownloads => encode_seek((recent_downloads, krate.id)),
+            Self::RecentUpdates => encode_seek((krate.updated_at, krate.id)),
+            Self::New => encode_seek((krate.created_at, krate.id)),
+        }
+    }
 }
 
 diesel::infix_operator!(Contains, "@>");
@@ -0,0 +1,225 @@
+//! Parsing of the structured search query syntax
+//!
+//! A search query consists of free text and qualifiers, which restrict the
+//! results further, for example:
+//!
+//! ```text
+//! http client keyword:async category:web-programming license:MIT owner:foo msrv:<=1.70 updated:>2024-01-01
+//! ```
+//!
+//! All qualifiers can be repeated and all of them have to match. Words that
+//! look like qualifiers with an unknown name are treated as free text. Dates
+//! refer to whole days, so `updated:2024-01-01` matches all crates that were
+//! updated on that day.
+
+use chrono::{NaiveDate, NaiveDateTime};
+
+use crate::models::parse_rust_version;
+use crate::util::errors::{bad_request, AppResult};
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SearchQuery {
+    /// The free text of the query, without the qualifiers.
+    pub text: Option<String>,
+    pub keywords: Vec<String>,
+    /// Category slugs, which also match the subcategories.
+    pub categories: Vec<String>,
+    /// SPDX license identifiers that must appear in the license expression
+    /// of the most recent version.
+    pub licenses: Vec<String>,
+    /// Logins of users or teams that own the crate.
+    pub owners: Vec<String>,
+    /// Restrictions on the `rust_version` of the most recent version.
+    pub rust_versions: Vec<Comparison<semver::Version>>,
+    /// Restrictions on the last update of the crate.
+    pub updated: Vec<Comparison<NaiveDateTime>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operator {
+    Lt,
+    Le,
+    Eq,
+    Ge,
+    Gt,
+}
+
+impl Operator {
+    /// The SQL operator for this comparison.
+    pub fn as_sql(self) -> &'static str {
+        match self {
+            Operator::Lt => "<",
+            Operator::Le => "<=",
+            Operator::Eq => "=",
+            Operator::Ge => ">=",
+            Operator::Gt => ">",
+        }
+    }
+
+    /// Splits the operator off the beginning of a qualifier value. Values
+    /// without an operator are compared for equality.
+    fn parse(value: &str) -> (Self, &str) {
+        let operators = [
+            ("<=", Operator::Le),
+            (">=", Operator::Ge),
+            ("<", Operator::Lt),
+            (">", Operator::Gt),
+            ("=", Operator::Eq),
+        ];
+
+        operators
+            .into_iter()
+            .find_map(|(prefix, op)| value.strip_prefix(prefix).map(|rest| (op, rest)))
+            .unwrap_or((Operator::Eq, value))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Comparison<T> {
+    pub op: Operator,
+    pub value: T,
+}
+
+impl SearchQuery {
+    pub fn parse(query: &str) -> AppResult<Self> {
+        let mut search_query = SearchQuery::default();
+        let mut text = Vec::new();
+
+        for word in query.split_whitespace() {
+            let Some((name, value)) = word.split_once(':') else {
+                text.push(word);
+                continue;
+            };
+
+            match name {
+                "keyword" | "category" | "license" | "owner" | "msrv" | "updated"
+                    if value.is_empty() =>
+                {
+                    return Err(bad_request(format!("missing value for `{name}:`")));
+                }
+                "keyword" => search_query.keywords.push(value.to_lowercase()),
+                "category" => search_query.categories.push(value.to_string()),
+                "license" => search_query.licenses.push(parse_license(value)?),
+                "owner" => search_query.owners.push(value.to_string()),
+                "msrv" => {
+                    let (op, value) = Operator::parse(value);
+                    let value = parse_rust_version(value)
+                        .ok_or_else(|| bad_request(format!("invalid Rust version in `{word}`")))?;
+                    search_query.rust_versions.push(Comparison { op, value });
+                }
+                "updated" => {
+                    let (op, value) = Operator::parse(value);
+                    let value = NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+                        bad_request(format!("invalid date in `{word}`, expected YYYY-MM-DD"))
+                    })?;
+                    let value = value.and_hms_opt(0, 0, 0).unwrap_or_default();
+                    search_query.updated.push(Comparison { op, value });
+                }
+                _ => text.push(word),
+            }
+        }
+
+        if !text.is_empty() {
+            search_query.text = Some(text.join(" "));
+        }
+
+        Ok(search_query)
+    }
+}
+
+/// License identifiers are matched with a regular expression in the database,
+/// so only the characters that can appear in SPDX identifiers are allowed.
+fn parse_license(value: &str) -> AppResult<String> {
+    let is_valid = value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+'));
+
+    if !is_valid {
+        return Err(bad_request(format!("invalid license identifier `{value}`")));
+    }
+
+    Ok(value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(date: &str) -> NaiveDateTime {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn free_text() {
+        let query = assert_ok!(SearchQuery::parse("  http   client "));
+        assert_eq!(query.text.as_deref(), Some("http client"));
+
+        let query = assert_ok!(SearchQuery::parse(""));
+        assert_eq!(query, SearchQuery::default());
+    }
+
+    #[test]
+    fn qualifiers() {
+        let query = assert_ok!(SearchQuery::parse(
+            "http keyword:Async category:web-programming license:MIT owner:foo \
+            msrv:<=1.70 updated:>2024-01-01 keyword:client client"
+        ));
+
+        assert_eq!(
+            query,
+            SearchQuery {
+                text: Some("http client".into()),
+                keywords: vec!["async".into(), "client".into()],
+                categories: vec!["web-programming".into()],
+                licenses: vec!["MIT".into()],
+                owners: vec!["foo".into()],
+                rust_versions: vec![Comparison {
+                    op: Operator::Le,
+                    value: semver::Version::new(1, 70, 0),
+                }],
+                updated: vec![Comparison {
+                    op: Operator::Gt,
+                    value: date("2024-01-01"),
+                }],
+            }
+        );
+    }
+
+    #[test]
+    fn operators() {
+        let msrv = |query: &str| SearchQuery::parse(query).unwrap().rust_versions[0].op;
+        assert_eq!(msrv("msrv:1.70"), Operator::Eq);
+        assert_eq!(msrv("msrv:=1.70"), Operator::Eq);
+        assert_eq!(msrv("msrv:<1.70"), Operator::Lt);
+        assert_eq!(msrv("msrv:<=1.70"), Operator::Le);
+        assert_eq!(msrv("msrv:>1.70"), Operator::Gt);
+        assert_eq!(msrv("msrv:>=1.70"), Operator::Ge);
+    }
+
+    #[test]
+    fn owners_with_colons() {
+        let query = assert_ok!(SearchQuery::parse("owner:github:rust-lang:core"));
+        assert_eq!(query.owners, vec!["github:rust-lang:core"]);
+    }
+
+    #[test]
+    fn unknown_qualifiers_are_text() {
+        let query = assert_ok!(SearchQuery::parse("foo:bar baz"));
+        assert_eq!(query.text.as_deref(), Some("foo:bar baz"));
+        assert!(query.keywords.is_empty());
+
+        let query = assert_ok!(SearchQuery::parse("serde: foo:"));
+        assert_eq!(query.text.as_deref(), Some("serde: foo:"));
+    }
+
+    #[test]
+    fn invalid_qualifiers() {
+        assert_err!(SearchQuery::parse("keyword:"));
+        assert_err!(SearchQuery::parse("msrv:<=latest"));
+        assert_err!(SearchQuery::parse("updated:yesterday"));
+        assert_err!(SearchQuery::parse("license:MIT)"));
+    }
+}
@@ -0,0 +1,84 @@
+//! Relevance ranking of search results
+//!
+//! The full text search rank alone favours crates that repeat the search
+//! terms in their name, description and keywords, so it is blended with
+//! popularity and maintenance signals:
+//!
+//! ```text
+//! score = text_weight * text_rank
+//!       + recent_downloads_weight * ln(1 + recent_downloads)
+//!       + reverse_dependencies_weight * ln(1 + reverse_dependencies)
+//!       + recency_weight * 0.5 ^ (days_since_last_release / recency_half_life_days)
+//!       - yanked_penalty, if all versions are yanked
+//! ```
+//!
+//! `text_rank` is the `ts_rank_cd` of the query, normalized to `[0, 1)`. The
+//! weights are configured with [`SearchRelevanceConfig`].
+//!
+//! The `routes::crates::relevance` tests contain an offline evaluation
+//! harness, which ranks a fixture corpus through the search endpoint and
+//! records the resulting order in a snapshot, so that changes to the weights
+//! show up as reviewable rank changes.
+
+use chrono::NaiveDateTime;
+use diesel::dsl::sql;
+use diesel::expression::is_aggregate;
+use diesel::expression::BoxableExpression;
+use diesel::pg::Pg;
+use diesel::sql_types::{Double, Text, Timestamp};
+
+use crate::config::SearchRelevanceConfig;
+
+/// The relevance score of the crates of a search query.
+#[derive(Clone, Copy, Debug)]
+pub struct Relevance<'a> {
+    pub config: &'a SearchRelevanceConfig,
+    pub q_string: &'a str,
+    /// The point in time that the recency of the last release is measured
+    /// from. It is kept in the seek key, so that the scores don't change
+    /// between the pages of the results.
+    pub reference_time: NaiveDateTime,
+}
+
+/// A score expression that can be used in the `SELECT`, `ORDER BY` and
+/// `WHERE` clauses of the search query, which has to include the
+/// `recent_crate_downloads` table.
+pub type ScoreExpression<'a, QS> =
+    Box<dyn BoxableExpression<QS, Pg, (), is_aggregate::Never, SqlType = Double> + 'a>;
+
+impl<'a> Relevance<'a> {
+    pub fn score<QS: 'a>(&self) -> ScoreExpression<'a, QS> {
+        let config = self.config;
+
+        Box::new(
+            sql::<Double>("(")
+                .bind::<Double, _>(config.text_weight)
+                .sql(" * ts_rank_cd(crates.textsearchable_index_col, plainto_tsquery('english', ")
+                .bind::<Text, _>(self.q_string.to_string())
+                .sql("), 32) + ")
+                .bind::<Double, _>(config.recent_downloads_weight)
+                .sql(" * ln(1 + COALESCE(recent_crate_downloads.downloads, 0)::float8) + ")
+                .bind::<Double, _>(config.reverse_dependencies_weight)
+                .sql(
+                    " * ln(1 + COALESCE((SELECT reverse_dependency_counts.dependents \
+                    FROM reverse_dependency_counts \
+                    WHERE reverse_dependency_counts.crate_id = crates.id), 0)::float8) + ",
+                )
+                // The age of the last release is `NULL` if all versions are
+                // yanked, in which case the penalty is applied instead.
+                .sql("COALESCE(")
+                .bind::<Double, _>(config.recency_weight)
+                .sql(" * power(0.5, (SELECT GREATEST(EXTRACT(EPOCH FROM ")
+                .bind::<Timestamp, _>(self.reference_time)
+                .sql(
+                    " - MAX(versions.created_at)), 0)::float8 / 86400 FROM versions \
+                    WHERE versions.crate_id = crates.id AND NOT versions.yanked \
+                    HAVING COUNT(*) > 0) / ",
+                )
+                .bind::<Double, _>(config.recency_half_life_days)
+                .sql("), -")
+                .bind::<Double, _>(config.yanked_penalty)
+                .sql("))"),
+        )
+    }
+}
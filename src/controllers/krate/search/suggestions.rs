@@ -0,0 +1,84 @@
+//! "Did you mean" suggestions for misspelled crate names
+
+use diesel::dsl::{exists, not};
+use diesel::prelude::*;
+use diesel::sql_types::Text;
+
+use crate::models::Crate;
+use crate::schema::{crate_quarantines, crates};
+use crate::sql::canon_crate_name;
+use crate::typosquat::typo_distance;
+
+/// The maximum number of suggestions in a search response.
+const MAX_SUGGESTIONS: usize = 5;
+
+/// The number of crates with similar names that are ranked by their edit
+/// distance to the query.
+const MAX_CANDIDATES: i64 = 50;
+
+/// Queries longer than this can't be crate names.
+const MAX_NAME_LENGTH: usize = 64;
+
+diesel::infix_operator!(Similar, " % ");
+sql_function!(fn similarity(x: Text, y: Text) -> Float);
+
+/// Returns the names of existing crates that the query might be a misspelling
+/// of, or nothing if the query is the name of an existing crate. Quarantined
+/// crates are never suggested.
+///
+/// The candidates are the crates with the most trigrams in common with the
+/// query, which uses the trigram index on the crate names. They are then
+/// ranked by their [`typo_distance`] to the query, so that confusable
+/// characters and omitted separators count as smaller differences, and by
+/// their downloads.
+pub fn suggest_names(q_string: &str, conn: &mut PgConnection) -> QueryResult<Vec<String>> {
+    let is_name = !q_string.is_empty()
+        && q_string.len() <= MAX_NAME_LENGTH
+        && q_string
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+    if !is_name {
+        return Ok(Vec::new());
+    }
+
+    let exact_match = diesel::select(exists(Crate::by_name(q_string))).get_result(conn)?;
+    if exact_match {
+        return Ok(Vec::new());
+    }
+
+    let candidates: Vec<(String, i32)> = crates::table
+        .filter(not(exists(
+            crate_quarantines::table.filter(crate_quarantines::crate_id.eq(crates::id)),
+        )))
+        .filter(Similar::new(
+            canon_crate_name(crates::name),
+            canon_crate_name(q_string),
+        ))
+        .order(similarity(canon_crate_name(crates::name), canon_crate_name(q_string)).desc())
+        .then_order_by(crates::name.asc())
+        .select((crates::name, crates::downloads))
+        .limit(MAX_CANDIDATES)
+        .load(conn)?;
+
+    // Allow about one edit for every four characters
+    let max_distance = (q_string.len() / 4).clamp(1, 3) as f64;
+
+    let mut suggestions = candidates
+        .into_iter()
+        .map(|(name, downloads)| (typo_distance(q_string, &name), downloads, name))
+        .filter(|(distance, _, _)| *distance <= max_distance)
+        .collect::<Vec<_>>();
+
+    suggestions.sort_by(|a, b| {
+        a.0.total_cmp(&b.0)
+            .then(b.1.cmp(&a.1))
+            .then_with(|| a.2.cmp(&b.2))
+    });
+
+    Ok(suggestions
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, _, name)| name)
+        .collect())
+}
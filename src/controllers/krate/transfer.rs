@@ -0,0 +1,207 @@
+//! Endpoints for handing a crate over to a new owner
+//!
+//! An owner proposes the transfer to a user or a team, and once the recipient
+//! accepts it, all current owners are replaced by the recipient at once.
+
+use crate::auth::AuthCheck;
+use crate::controllers::frontend_prelude::*;
+use crate::models::token::EndpointScope;
+use crate::models::{
+    notify_previous_owners, Crate, CrateTransfer, CrateTransferProposedEmail, Owner, Rights, User,
+};
+use crate::util::errors::forbidden;
+use crate::views::EncodableCrateTransfer;
+use tokio::runtime::Handle;
+
+#[derive(Deserialize)]
+struct TransferRequest {
+    /// The login of the user or the name of the team, e.g. `github:org:team`.
+    to: String,
+}
+
+/// Handles the `PUT /crates/:crate_id/transfer` route.
+pub async fn propose(
+    app: AppState,
+    Path(crate_name): Path<String>,
+    req: BytesRequest,
+) -> AppResult<Json<Value>> {
+    spawn_blocking(move || {
+        let request: TransferRequest =
+            serde_json::from_slice(req.body()).map_err(|_| cargo_err("invalid json request"))?;
+
+        let conn = &mut *app.db_write()?;
+        let auth = AuthCheck::default()
+            .with_endpoint_scope(EndpointScope::ChangeOwners)
+            .for_crate(&crate_name)
+            .check(&req, conn)?;
+        let user = auth.user();
+
+        let krate: Crate = Crate::by_name(&crate_name).first(conn)?;
+        ensure_full_rights(&app, user, &krate, conn)?;
+
+        let recipient = Owner::find_or_create_by_login(&app, conn, user, &request.to)?;
+        if matches!(&recipient, Owner::User(recipient) if recipient.id == user.id) {
+            return Err(cargo_err("cannot transfer a crate to yourself"));
+        }
+
+        let transfer = CrateTransfer::propose(&krate, user, &recipient, conn)?;
+
+        if let Owner::User(recipient) = &recipient {
+            if let Some(email_address) = recipient.verified_email(conn)? {
+                let email = CrateTransferProposedEmail {
+                    domain: &app.emails.domain,
+                    proposed_by: &user.gh_login,
+                    crate_name: &krate.name,
+                    expires_at: transfer.expires_at(&app.config, &krate),
+                };
+                if let Err(error) = app.emails.send(&email_address, email) {
+                    error!(?error, "Failed to send crate transfer email");
+                }
+            }
+        }
+
+        let msg = format!(
+            "{} has been asked to accept the transfer of crate {}",
+            recipient.login(),
+            krate.name
+        );
+        Ok(Json(json!({ "ok": true, "msg": msg })))
+    })
+    .await
+}
+
+/// Handles the `DELETE /crates/:crate_id/transfer` route.
+///
+/// Owners can cancel the transfer, and the recipient can decline it.
+pub async fn cancel(
+    app: AppState,
+    Path(crate_name): Path<String>,
+    req: Parts,
+) -> AppResult<Response> {
+    spawn_blocking(move || {
+        let conn = &mut *app.db_write()?;
+        let auth = AuthCheck::default()
+            .with_endpoint_scope(EndpointScope::ChangeOwners)
+            .for_crate(&crate_name)
+            .check(&req, conn)?;
+        let user = auth.user();
+
+        let krate: Crate = Crate::by_name(&crate_name).first(conn)?;
+        let transfer = CrateTransfer::find(krate.id, conn)?
+            .ok_or_else(|| cargo_err("there is no pending transfer for this crate"))?;
+
+        if !is_recipient(&app, user, &transfer, conn)? {
+            ensure_full_rights(&app, user, &krate, conn)?;
+        }
+
+        transfer.cancel(conn)?;
+
+        ok_true()
+    })
+    .await
+}
+
+/// Handles the `PUT /crates/:crate_id/transfer/accept` route.
+pub async fn accept(
+    app: AppState,
+    Path(crate_name): Path<String>,
+    req: Parts,
+) -> AppResult<Response> {
+    spawn_blocking(move || {
+        let conn = &mut *app.db_write()?;
+        let auth = AuthCheck::default()
+            .with_endpoint_scope(EndpointScope::ChangeOwners)
+            .for_crate(&crate_name)
+            .check(&req, conn)?;
+        let user = auth.user();
+
+        let krate: Crate = Crate::by_name(&crate_name).first(conn)?;
+        let transfer = CrateTransfer::find(krate.id, conn)?
+            .ok_or_else(|| cargo_err("there is no pending transfer for this crate"))?;
+
+        if !is_recipient(&app, user, &transfer, conn)? {
+            return Err(forbidden());
+        }
+        if transfer.is_expired(&app.config, &krate) {
+            return Err(cargo_err(
+                "the transfer has expired, please ask an owner of the crate to propose it again",
+            ));
+        }
+
+        let recipient = transfer.recipient(conn)?;
+
+        // The owners have to be looked up before they are removed
+        let mut previous_owner_emails = Vec::new();
+        for owner in User::owning(&krate, conn)? {
+            if let Owner::User(owner) = owner {
+                if owner.id != user.id {
+                    previous_owner_emails.extend(owner.verified_email(conn)?);
+                }
+            }
+        }
+
+        transfer.accept(user, conn)?;
+
+        notify_previous_owners(
+            &previous_owner_emails,
+            &krate.name,
+            recipient.login(),
+            &app.emails,
+        );
+
+        ok_true()
+    })
+    .await
+}
+
+/// Handles the `GET /api/v1/me/crate_transfers` route.
+pub async fn list(app: AppState, req: Parts) -> AppResult<Json<Value>> {
+    spawn_blocking(move || {
+        let conn = &mut *app.db_read()?;
+        let auth = AuthCheck::only_cookie().check(&req, conn)?;
+
+        let transfers = CrateTransfer::for_recipient(auth.user(), conn)?
+            .into_iter()
+            .map(|(transfer, krate, proposed_by)| EncodableCrateTransfer {
+                crate_id: krate.id,
+                expires_at: transfer.expires_at(&app.config, &krate),
+                crate_name: krate.name,
+                proposed_by,
+                created_at: transfer.created_at,
+            })
+            .collect::<Vec<_>>();
+
+        Ok(Json(json!({ "crate_transfers": transfers })))
+    })
+    .await
+}
+
+fn ensure_full_rights(
+    app: &AppState,
+    user: &User,
+    krate: &Crate,
+    conn: &mut PgConnection,
+) -> AppResult<()> {
+    let owners = krate.owners(conn)?;
+    match Handle::current().block_on(user.rights(app, &owners))? {
+        Rights::Full => Ok(()),
+        Rights::Publish => Err(cargo_err(
+            "team members don't have permission to transfer crates",
+        )),
+        Rights::None => Err(cargo_err("only owners have permission to transfer crates")),
+    }
+}
+
+/// Whether the user is the recipient of the transfer, or a member of the
+/// recipient team.
+fn is_recipient(
+    app: &AppState,
+    user: &User,
+    transfer: &CrateTransfer,
+    conn: &mut PgConnection,
+) -> AppResult<bool> {
+    match transfer.recipient(conn)? {
+        Owner::User(recipient) => Ok(recipient.id == user.id),
+        Owner::Team(team) => Handle::current().block_on(team.contains_user(app, user)),
+    }
+}
@@ -38,7 +38,9 @@ pub async fn list(
 ) -> AppResult<Json<Value>> {
     spawn_blocking(move || {
         let conn = &mut *app.db_read_prefer_primary()?;
-        let auth = AuthCheck::only_cookie().check(&req, conn)?;
+        let auth = AuthCheck::only_cookie()
+            .with_endpoint_scope(EndpointScope::ManageTokens)
+            .check(&req, conn)?;
         let user = auth.user();
 
         let tokens: Vec<ApiToken> = ApiToken::belonging_to(user)
@@ -153,7 +155,9 @@ pub async fn new(app: AppState, req: BytesRequest) -> AppResult<Json<Value>> {
 pub async fn revoke(app: AppState, Path(id): Path<i32>, req: Parts) -> AppResult<Json<Value>> {
     spawn_blocking(move || {
         let conn = &mut *app.db_write()?;
-        let auth = AuthCheck::default().check(&req, conn)?;
+        let auth = AuthCheck::default()
+            .with_endpoint_scope(EndpointScope::ManageTokens)
+            .check(&req, conn)?;
         let user = auth.user();
         diesel::update(ApiToken::belonging_to(user).find(id))
             .set(api_tokens::revoked.eq(true))
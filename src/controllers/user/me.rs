@@ -1,4 +1,7 @@
 use crate::auth::AuthCheck;
+use crate::middleware::session::RequestSession;
+use crate::models::token::EndpointScope;
+use chrono::{NaiveDateTime, Utc};
 use secrecy::{ExposeSecret, SecretString};
 use std::collections::HashMap;
 
@@ -8,16 +11,23 @@ use crate::controllers::helpers::*;
 
 use crate::controllers::helpers::pagination::{Paginated, PaginationOptions};
 use crate::models::{
-    CrateOwner, Email, Follow, NewEmail, OwnerKind, User, Version, VersionOwnerAction,
+    AccountDeletedEmail, ApiToken, CrateOwner, Email, Follow, NewEmail, OwnerKind, User, Version,
+    VersionAction, VersionOwnerAction,
 };
-use crate::schema::{crate_owners, crates, emails, follows, users, versions};
+use crate::schema::{
+    api_tokens, crate_owners, crates, emails, follows, users, version_owner_actions, versions,
+};
+use crate::util::rfc3339;
 use crate::views::{EncodableMe, EncodablePrivateUser, EncodableVersion, OwnedCrate};
 
 /// Handles the `GET /me` route.
 pub async fn me(app: AppState, req: Parts) -> AppResult<Json<EncodableMe>> {
     spawn_blocking(move || {
         let conn = &mut *app.db_read_prefer_primary()?;
-        let user_id = AuthCheck::only_cookie().check(&req, conn)?.user_id();
+        let user_id = AuthCheck::only_cookie()
+            .with_endpoint_scope(EndpointScope::ReadPrivate)
+            .check(&req, conn)?
+            .user_id();
 
         let (user, verified, email, verification_sent): (User, Option<bool>, Option<String>, bool) =
             users::table
@@ -59,7 +69,9 @@ pub async fn me(app: AppState, req: Parts) -> AppResult<Json<EncodableMe>> {
 pub async fn updates(app: AppState, req: Parts) -> AppResult<Json<Value>> {
     spawn_blocking(move || {
         let conn = &mut app.db_read_prefer_primary()?;
-        let auth = AuthCheck::only_cookie().check(&req, conn)?;
+        let auth = AuthCheck::only_cookie()
+            .with_endpoint_scope(EndpointScope::ReadPrivate)
+            .check(&req, conn)?;
         let user = auth.user();
 
         let followed_crates = Follow::belonging_to(user).select(follows::crate_id);
@@ -97,6 +109,182 @@ pub async fn updates(app: AppState, req: Parts) -> AppResult<Json<Value>> {
     .await
 }
 
+#[derive(Serialize)]
+struct AccountExport {
+    user: EncodablePrivateUser,
+    emails: Vec<ExportedEmail>,
+    api_tokens: Vec<ExportedApiToken>,
+    owned_crates: Vec<OwnedCrate>,
+    actions: Vec<ExportedAction>,
+    #[serde(with = "rfc3339")]
+    exported_at: NaiveDateTime,
+}
+
+#[derive(Serialize)]
+struct ExportedEmail {
+    email: String,
+    verified: bool,
+    #[serde(with = "rfc3339::option")]
+    token_generated_at: Option<NaiveDateTime>,
+}
+
+#[derive(Serialize)]
+struct ExportedApiToken {
+    #[serde(flatten)]
+    token: ApiToken,
+    revoked: bool,
+}
+
+#[derive(Serialize)]
+struct ExportedAction {
+    #[serde(rename = "crate")]
+    krate: String,
+    version: String,
+    action: String,
+    #[serde(with = "rfc3339")]
+    time: NaiveDateTime,
+}
+
+/// Handles the `GET /me/export` route.
+///
+/// Returns all the data that is stored about the user as a JSON file, so
+/// that they can download it before e.g. deleting their account.
+pub async fn export(app: AppState, req: Parts) -> AppResult<Response> {
+    spawn_blocking(move || {
+        let conn = &mut *app.db_read_prefer_primary()?;
+        let auth = AuthCheck::only_cookie().check(&req, conn)?;
+        let user = auth.user();
+
+        let emails = Email::belonging_to(user)
+            .load::<Email>(conn)?
+            .into_iter()
+            .map(|email| ExportedEmail {
+                email: email.email,
+                verified: email.verified,
+                token_generated_at: email.token_generated_at,
+            })
+            .collect::<Vec<_>>();
+
+        let api_tokens = ApiToken::belonging_to(user)
+            .select(ApiToken::as_select())
+            .order(api_tokens::id)
+            .load(conn)?
+            .into_iter()
+            .map(|token| ExportedApiToken {
+                revoked: token.revoked,
+                token,
+            })
+            .collect::<Vec<_>>();
+
+        let owned_crates = CrateOwner::by_owner_kind(OwnerKind::User)
+            .inner_join(crates::table)
+            .filter(crate_owners::owner_id.eq(user.id))
+            .select((crates::id, crates::name, crate_owners::email_notifications))
+            .order(crates::name.asc())
+            .load(conn)?
+            .into_iter()
+            .map(|(id, name, email_notifications)| OwnedCrate {
+                id,
+                name,
+                email_notifications,
+            })
+            .collect::<Vec<_>>();
+
+        let actions = version_owner_actions::table
+            .inner_join(versions::table.inner_join(crates::table))
+            .filter(version_owner_actions::user_id.eq(user.id))
+            .select((
+                crates::name,
+                versions::num,
+                version_owner_actions::action,
+                version_owner_actions::time,
+            ))
+            .order(version_owner_actions::id)
+            .load::<(String, String, VersionAction, NaiveDateTime)>(conn)?
+            .into_iter()
+            .map(|(krate, version, action, time)| ExportedAction {
+                krate,
+                version,
+                action: action.into(),
+                time,
+            })
+            .collect::<Vec<_>>();
+
+        let (email, verified) = emails
+            .first()
+            .map(|email| (Some(email.email.clone()), email.verified))
+            .unwrap_or_default();
+        let verification_sent = emails
+            .first()
+            .is_some_and(|email| email.verified || email.token_generated_at.is_some());
+        let login = user.gh_login.clone();
+        let user = EncodablePrivateUser::from(user.clone(), email, verified, verification_sent);
+
+        let export = AccountExport {
+            user,
+            emails,
+            api_tokens,
+            owned_crates,
+            actions,
+            exported_at: Utc::now().naive_utc(),
+        };
+
+        let content_disposition = format!("attachment; filename=\"crates-io-{login}.json\"");
+        let headers = [(header::CONTENT_DISPOSITION, content_disposition)];
+        Ok((headers, Json(export)).into_response())
+    })
+    .await
+}
+
+/// Handles the `DELETE /me` route.
+///
+/// See [`User::delete_account`] for what happens to the crates of the user.
+pub async fn delete_account(app: AppState, req: BytesRequest) -> AppResult<Json<Value>> {
+    spawn_blocking(move || {
+        /// The user has to confirm the deletion by sending their login.
+        #[derive(Deserialize)]
+        struct DeleteAccount {
+            login: String,
+        }
+
+        let request: DeleteAccount =
+            serde_json::from_slice(req.body()).map_err(|_| bad_request("invalid json request"))?;
+
+        let conn = &mut *app.db_write()?;
+        let auth = AuthCheck::only_cookie().check(&req, conn)?;
+        let user = auth.user();
+
+        if request.login != user.gh_login {
+            return Err(bad_request("the login does not match the account"));
+        }
+
+        let recipient = user.verified_email(conn)?;
+        let deleted_account = user.delete_account(conn)?;
+        req.session().remove("user_id");
+
+        if let Some(recipient) = recipient {
+            let email = AccountDeletedEmail {
+                user_name: &user.gh_login,
+                deleted_account: &deleted_account,
+            };
+            if let Err(error) = app.emails.send(&recipient, email) {
+                error!(
+                    ?error,
+                    ?recipient,
+                    "Failed to send account deletion notification"
+                );
+            }
+        }
+
+        Ok(Json(json!({
+            "ok": true,
+            "left_to_other_owners": deleted_account.left_to_other_owners,
+            "orphaned": deleted_account.orphaned,
+        })))
+    })
+    .await
+}
+
 /// Handles the `PUT /users/:user_id` route.
 pub async fn update_user(
     state: AppState,
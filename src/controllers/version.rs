@@ -1,13 +1,19 @@
+pub mod attestation;
 pub mod deprecated;
+pub mod diff;
 pub mod downloads;
 pub mod metadata;
+pub mod source;
 pub mod yank;
 
 use super::prelude::*;
 
-use crate::models::{Crate, Version};
+use crate::models::{is_version_quarantined, Crate, Version};
+use crate::util::errors::Quarantined;
 
-fn version_and_crate(
+/// Looks up a version and its crate, failing if either of them is
+/// quarantined.
+pub(crate) fn version_and_crate(
     conn: &mut PgConnection,
     crate_name: &str,
     semver: &str,
@@ -15,5 +21,12 @@ fn version_and_crate(
     let krate: Crate = Crate::by_name(crate_name).first(conn)?;
     let version = krate.find_version(conn, semver)?;
 
+    if is_version_quarantined(krate.id, version.id, conn)? {
+        return Err(Box::new(Quarantined {
+            crate_name: krate.name,
+            version: Some(version.num),
+        }));
+    }
+
     Ok((version, krate))
 }
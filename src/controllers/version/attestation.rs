@@ -0,0 +1,40 @@
+//! Endpoint for fetching the signed release attestation of a crate version
+
+use crate::controllers::frontend_prelude::*;
+
+use crate::models::VersionAttestation;
+use crate::util::errors::{internal, not_found};
+use crate::views::EncodableVersionAttestation;
+
+use super::version_and_crate;
+
+/// Handles the `GET /crates/:crate_id/:version/attestation` route.
+///
+/// Returns the signer and checksum of the attestation that was uploaded
+/// together with the `.crate` file, and the attestation document itself.
+pub async fn show(
+    state: AppState,
+    Path((crate_name, version)): Path<(String, String)>,
+) -> AppResult<Json<Value>> {
+    let (crate_name, version, attestation) = spawn_blocking({
+        let state = state.clone();
+        move || {
+            let conn = &mut state.db_read()?;
+            let (version, krate) = version_and_crate(conn, &crate_name, &version)?;
+            let attestation = VersionAttestation::find(version.id, conn)?.ok_or_else(not_found)?;
+            Ok::<_, BoxedAppError>((krate.name, version.num, attestation))
+        }
+    })
+    .await?;
+
+    let bytes = state
+        .storage
+        .download_attestation(&crate_name, &version)
+        .await?;
+
+    let document: Value = serde_json::from_slice(&bytes)
+        .map_err(|error| internal(format!("failed to parse attestation: {error}")))?;
+
+    let attestation = EncodableVersionAttestation::from(attestation, document);
+    Ok(Json(json!({ "attestation": attestation })))
+}
@@ -0,0 +1,470 @@
+//! Endpoint for comparing the contents of two published crate versions
+//!
+//! The diff is calculated from the `.crate` files in the storage backend and
+//! the dependency information in the database. Since published versions are
+//! immutable, the result is cached in the storage backend too.
+
+use crate::controllers::frontend_prelude::*;
+
+use crate::auth::AuthCheck;
+use crate::models::{Dependency, DependencyKind, Version};
+use crate::rate_limiter::LimitedAction;
+use crate::util::errors::internal;
+use crates_io_tarball::{SourceFile, TarballError};
+use hyper::body::Bytes;
+use similar::TextDiff;
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::source::is_binary;
+use super::version_and_crate;
+
+/// Handles the `GET /crates/:crate_id/:from...:to/diff` route.
+///
+/// Returns the files that were added, removed or modified between the two
+/// versions, including a unified diff for text files, and the changes to the
+/// dependencies, features and minimum supported Rust version.
+///
+/// Since calculating a diff requires unpacking two `.crate` files, this
+/// endpoint requires authentication and diffs that aren't cached yet are
+/// rate limited.
+pub async fn diff(
+    state: AppState,
+    Path((crate_name, range)): Path<(String, String)>,
+    req: Parts,
+) -> AppResult<Response> {
+    let Some((from, to)) = range.split_once("...") else {
+        return Err(bad_request(format_args!(
+            "invalid version range `{range}`, expected `<from>...<to>`"
+        )));
+    };
+
+    let (user_id, krate, from, to) = spawn_blocking::<_, _, BoxedAppError>({
+        let state = state.clone();
+        let (from, to) = (from.to_string(), to.to_string());
+        move || {
+            let conn = &mut state.db_write()?;
+            let auth = AuthCheck::default().check(&req, conn)?;
+            let (from, krate) = version_and_crate(conn, &crate_name, &from)?;
+            let (to, _) = version_and_crate(conn, &crate_name, &to)?;
+            Ok((auth.user_id(), krate, from, to))
+        }
+    })
+    .await?;
+
+    let storage = &state.storage;
+    match storage.download_diff(&krate.name, &from.num, &to.num).await {
+        Ok(bytes) => return Ok(json_response(bytes)),
+        Err(object_store::Error::NotFound { .. }) => {}
+        Err(error) => warn!(?error, "Failed to read cached diff"),
+    }
+
+    spawn_blocking::<_, _, BoxedAppError>({
+        let state = state.clone();
+        move || {
+            let conn = &mut state.db_write()?;
+            let rate_limiter = &state.rate_limiter;
+            rate_limiter.check_rate_limit(user_id, LimitedAction::VersionDiff, conn)?;
+            Ok(())
+        }
+    })
+    .await?;
+
+    let (from_tarball, to_tarball) = tokio::try_join!(
+        storage.download_crate_file(&krate.name, &from.num),
+        storage.download_crate_file(&krate.name, &to.num),
+    )?;
+
+    let bytes = spawn_blocking::<_, _, BoxedAppError>({
+        let state = state.clone();
+        let crate_name = krate.name.clone();
+        let (from, to) = (from.clone(), to.clone());
+        move || {
+            let max_file_size = state.config.max_source_file_size;
+            let read_files = |version: &str, tarball: &[u8], max_unpack, max_files| {
+                let pkg_name = format!("{crate_name}-{version}");
+                crates_io_tarball::read_files(
+                    &pkg_name,
+                    tarball,
+                    max_unpack,
+                    max_file_size,
+                    max_files,
+                )
+                .map_err(|error| match error {
+                    TarballError::Malformed(_) | TarballError::TooManyFiles(_) => {
+                        bad_request(format!("cannot compare `{pkg_name}.crate`: {error}"))
+                    }
+                    error => internal(format!("failed to read `{pkg_name}.crate`: {error}")),
+                })
+            };
+
+            // The limits apply to both versions together, so the second
+            // version only gets what is left over by the first one.
+            let max_unpack = state.config.max_diff_unpack_size;
+            let max_files = state.config.max_diff_files;
+            let from_files = read_files(&from.num, &from_tarball, max_unpack, max_files)?;
+
+            let unpacked = from_files.values().map(|file| file.size).sum::<u64>();
+            let max_unpack = max_unpack.saturating_sub(unpacked);
+            let max_files = max_files.saturating_sub(from_files.len());
+            let to_files = read_files(&to.num, &to_tarball, max_unpack, max_files)?;
+
+            let conn = &mut state.db_read()?;
+            let manifest = ManifestDiff::new(&from, &to, conn)?;
+
+            let diff = EncodableVersionDiff {
+                from: &from.num,
+                to: &to.num,
+                files: diff_files(&from_files, &to_files),
+                manifest,
+            };
+
+            Ok(Bytes::from(serde_json::to_vec(&json!({ "diff": diff }))?))
+        }
+    })
+    .await?;
+
+    let result = storage
+        .upload_diff(&krate.name, &from.num, &to.num, bytes.clone())
+        .await;
+
+    if let Err(error) = result {
+        warn!(?error, "Failed to cache diff");
+    }
+
+    Ok(json_response(bytes))
+}
+
+fn json_response(bytes: Bytes) -> Response {
+    ([(header::CONTENT_TYPE, "application/json")], bytes).into_response()
+}
+
+#[derive(Debug, Serialize)]
+struct EncodableVersionDiff<'a> {
+    from: &'a str,
+    to: &'a str,
+    files: Vec<FileDiff>,
+    manifest: ManifestDiff,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum Change {
+    Added,
+    Removed,
+    Modified,
+}
+
+impl Change {
+    fn from_options<T: PartialEq>(from: Option<&T>, to: Option<&T>) -> Option<Self> {
+        match (from, to) {
+            (None, Some(_)) => Some(Change::Added),
+            (Some(_), None) => Some(Change::Removed),
+            (Some(from), Some(to)) if from != to => Some(Change::Modified),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct FileDiff {
+    path: String,
+    change: Change,
+    from_size: Option<u64>,
+    to_size: Option<u64>,
+    binary: bool,
+    /// The unified diff of the file, or `None` for binary files and files
+    /// that are too large to be diffed.
+    diff: Option<String>,
+}
+
+fn diff_files(
+    from: &BTreeMap<String, SourceFile>,
+    to: &BTreeMap<String, SourceFile>,
+) -> Vec<FileDiff> {
+    let paths = from.keys().chain(to.keys()).collect::<BTreeSet<_>>();
+
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let from = from.get(path);
+            let to = to.get(path);
+
+            let from_checksum = from.map(|file| &file.checksum);
+            let to_checksum = to.map(|file| &file.checksum);
+            let change = Change::from_options(from_checksum, to_checksum)?;
+
+            // Missing files are diffed as empty files. `None` means that one
+            // of the files was too large to be read.
+            let empty = Some(&[][..]);
+            let from_contents = from.map_or(empty, |file| file.contents.as_deref());
+            let to_contents = to.map_or(empty, |file| file.contents.as_deref());
+
+            let binary = [from_contents, to_contents]
+                .into_iter()
+                .flatten()
+                .any(is_binary);
+
+            let diff = match (from_contents, to_contents) {
+                (Some(from_contents), Some(to_contents)) if !binary => {
+                    Some(unified_diff(path, from_contents, to_contents))
+                }
+                _ => None,
+            };
+
+            Some(FileDiff {
+                path: path.clone(),
+                change,
+                from_size: from.map(|file| file.size),
+                to_size: to.map(|file| file.size),
+                binary,
+                diff,
+            })
+        })
+        .collect()
+}
+
+fn unified_diff(path: &str, from: &[u8], to: &[u8]) -> String {
+    // `is_binary()` ensures that both files are valid UTF-8
+    let from = String::from_utf8_lossy(from);
+    let to = String::from_utf8_lossy(to);
+
+    TextDiff::from_lines(&from, &to)
+        .unified_diff()
+        .header(&format!("a/{path}"), &format!("b/{path}"))
+        .to_string()
+}
+
+#[derive(Debug, Serialize)]
+struct ManifestDiff {
+    rust_version: RustVersionDiff,
+    dependencies: Vec<DependencyDiff>,
+    features: Vec<FeatureDiff>,
+}
+
+#[derive(Debug, Serialize)]
+struct RustVersionDiff {
+    from: Option<String>,
+    to: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct DependencyDiff {
+    name: String,
+    kind: DependencyKind,
+    target: Option<String>,
+    change: Change,
+    from: Option<EncodableDependencyRequirement>,
+    to: Option<EncodableDependencyRequirement>,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+struct EncodableDependencyRequirement {
+    /// The name of the crate, if the dependency was renamed.
+    package: Option<String>,
+    req: String,
+    optional: bool,
+    default_features: bool,
+    features: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct FeatureDiff {
+    name: String,
+    change: Change,
+    from: Option<Vec<String>>,
+    to: Option<Vec<String>>,
+}
+
+impl ManifestDiff {
+    fn new(from: &Version, to: &Version, conn: &mut PgConnection) -> AppResult<Self> {
+        let rust_version = RustVersionDiff {
+            from: from.rust_version.clone(),
+            to: to.rust_version.clone(),
+        };
+
+        let dependencies = diff_dependencies(from.dependencies(conn)?, to.dependencies(conn)?);
+
+        let from_features = serde_json::from_value(from.features.clone())?;
+        let to_features = serde_json::from_value(to.features.clone())?;
+        let features = diff_features(from_features, to_features);
+
+        Ok(Self {
+            rust_version,
+            dependencies,
+            features,
+        })
+    }
+}
+
+/// Dependencies are identified by their (possibly renamed) name, their kind
+/// and their target, which is the same identity that cargo uses.
+type DependencyKey = (String, i32, Option<String>);
+
+fn dependency_map(
+    dependencies: Vec<(Dependency, String)>,
+) -> BTreeMap<DependencyKey, (DependencyKind, EncodableDependencyRequirement)> {
+    dependencies
+        .into_iter()
+        .map(|(dependency, crate_name)| {
+            let (name, package) = match dependency.explicit_name {
+                Some(explicit_name) => (explicit_name, Some(crate_name)),
+                None => (crate_name, None),
+            };
+
+            let kind = dependency.kind;
+            let key = (name, kind as i32, dependency.target);
+            let requirement = EncodableDependencyRequirement {
+                package,
+                req: dependency.req,
+                optional: dependency.optional,
+                default_features: dependency.default_features,
+                features: dependency.features,
+            };
+
+            (key, (kind, requirement))
+        })
+        .collect()
+}
+
+fn diff_dependencies(
+    from: Vec<(Dependency, String)>,
+    to: Vec<(Dependency, String)>,
+) -> Vec<DependencyDiff> {
+    let mut from = dependency_map(from);
+    let mut to = dependency_map(to);
+
+    let keys = from
+        .keys()
+        .chain(to.keys())
+        .cloned()
+        .collect::<BTreeSet<_>>();
+
+    keys.into_iter()
+        .filter_map(|key| {
+            let from = from.remove(&key);
+            let to = to.remove(&key);
+
+            let change = Change::from_options(from.as_ref(), to.as_ref())?;
+            let kind = from.as_ref().or(to.as_ref()).map(|(kind, _)| *kind)?;
+            let (name, _, target) = key;
+
+            Some(DependencyDiff {
+                name,
+                kind,
+                target,
+                change,
+                from: from.map(|(_, requirement)| requirement),
+                to: to.map(|(_, requirement)| requirement),
+            })
+        })
+        .collect()
+}
+
+fn diff_features(
+    mut from: BTreeMap<String, Vec<String>>,
+    mut to: BTreeMap<String, Vec<String>>,
+) -> Vec<FeatureDiff> {
+    let names = from
+        .keys()
+        .chain(to.keys())
+        .cloned()
+        .collect::<BTreeSet<_>>();
+
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let from = from.remove(&name);
+            let to = to.remove(&name);
+            let change = Change::from_options(from.as_ref(), to.as_ref())?;
+            Some(FeatureDiff {
+                name,
+                change,
+                from,
+                to,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source_file(contents: &str) -> SourceFile {
+        use sha2::{Digest, Sha256};
+
+        SourceFile {
+            size: contents.len() as u64,
+            checksum: Sha256::digest(contents).into(),
+            contents: Some(contents.as_bytes().to_vec()),
+        }
+    }
+
+    #[test]
+    fn file_diffs() {
+        let from = BTreeMap::from([
+            ("README.md".to_string(), source_file("# foo\n")),
+            ("src/lib.rs".to_string(), source_file("fn foo() {}\n")),
+            ("src/old.rs".to_string(), source_file("")),
+        ]);
+        let to = BTreeMap::from([
+            ("README.md".to_string(), source_file("# foo\n")),
+            ("src/lib.rs".to_string(), source_file("fn bar() {}\n")),
+            ("logo.png".to_string(), source_file("\0PNG")),
+        ]);
+
+        let diffs = diff_files(&from, &to);
+        let diffs = diffs
+            .iter()
+            .map(|diff| (diff.path.as_str(), &diff.change, diff.binary))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            diffs,
+            vec![
+                ("logo.png", &Change::Added, true),
+                ("src/lib.rs", &Change::Modified, false),
+                ("src/old.rs", &Change::Removed, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn file_diff_of_large_files() {
+        let mut large_file = source_file("foo");
+        large_file.contents = None;
+
+        let from = BTreeMap::from([("data.txt".to_string(), source_file("bar"))]);
+        let to = BTreeMap::from([("data.txt".to_string(), large_file)]);
+
+        let diffs = diff_files(&from, &to);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].change, Change::Modified);
+        assert!(!diffs[0].binary);
+        assert!(diffs[0].diff.is_none());
+    }
+
+    #[test]
+    fn feature_diffs() {
+        let from = BTreeMap::from([
+            ("default".to_string(), vec!["std".to_string()]),
+            ("std".to_string(), vec![]),
+        ]);
+        let to = BTreeMap::from([
+            ("default".to_string(), vec!["std".to_string()]),
+            ("std".to_string(), vec!["alloc".to_string()]),
+            ("alloc".to_string(), vec![]),
+        ]);
+
+        let diffs = diff_features(from, to);
+        let diffs = diffs
+            .iter()
+            .map(|diff| (diff.name.as_str(), &diff.change))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            diffs,
+            vec![("alloc", &Change::Added), ("std", &Change::Modified)]
+        );
+    }
+}
@@ -6,10 +6,12 @@ use super::version_and_crate;
 use crate::controllers::prelude::*;
 use crate::db::PoolError;
 use crate::middleware::log_request::RequestLogExt;
-use crate::models::VersionDownload;
+use crate::models::{is_version_id_quarantined, VersionDownload};
 use crate::schema::*;
+use crate::util::errors::Quarantined;
 use crate::views::EncodableVersionDownload;
 use chrono::{Duration, NaiveDate, Utc};
+use diesel::dsl::exists;
 use tracing::Instrument;
 
 /// Handles the `GET /crates/:crate_id/:version/download` route.
@@ -32,6 +34,34 @@ pub async fn download(
     if let Some(version_id) = cache_result {
         app.instance_metrics.version_id_cache_hits.inc();
 
+        // Versions can be quarantined after their `version_id` was cached, so
+        // the quarantine is checked on every download. Without a working
+        // database the check is skipped like the rest of the lookup below,
+        // since the files of quarantined versions are moved out of the CDN
+        // anyway.
+        let quarantined = spawn_blocking::<_, _, BoxedAppError>({
+            let app = app.clone();
+            move || {
+                if app.config.force_unconditional_redirects {
+                    return Ok(false);
+                }
+
+                match app.db_read_prefer_primary() {
+                    Ok(mut conn) => Ok(is_version_id_quarantined(version_id, &mut conn)?),
+                    Err(PoolError::UnhealthyPool) => Ok(false),
+                    Err(err) => Err(err.into()),
+                }
+            }
+        })
+        .await?;
+
+        if quarantined {
+            return Err(Box::new(Quarantined {
+                crate_name,
+                version: Some(version),
+            }));
+        }
+
         // The increment does not happen instantly, but it's deferred to be executed in a batch
         // along with other downloads. See crate::downloads_counter for the implementation.
         app.downloads_counter.increment(version_id);
@@ -62,10 +92,19 @@ pub async fn download(
                     // Returns the crate name as stored in the database, or an error if we could
                     // not load the version ID from the database.
                     let metric = &app.instance_metrics.downloads_select_query_execution_time;
-                    let version_id = metric.observe_closure_duration(|| {
+                    let (version_id, quarantined) = metric.observe_closure_duration(|| {
                         get_version_id(&crate_name, &version, &mut conn)
                     })?;
 
+                    // Quarantined versions are not cached, so that they can be downloaded
+                    // again right after the quarantine has been lifted.
+                    if quarantined {
+                        return Err(Box::new(Quarantined {
+                            crate_name,
+                            version: Some(version),
+                        }));
+                    }
+
                     // The increment does not happen instantly, but it's deferred to be executed in a batch
                     // along with other downloads. See crate::downloads_counter for the implementation.
                     app.downloads_counter.increment(version_id);
@@ -118,13 +157,16 @@ pub async fn download(
 }
 
 #[instrument("db.query", skip(conn), fields(message = "SELECT ... FROM versions"))]
-fn get_version_id(krate: &str, version: &str, conn: &mut PgConnection) -> QueryResult<i32> {
+fn get_version_id(krate: &str, version: &str, conn: &mut PgConnection) -> QueryResult<(i32, bool)> {
+    let crate_quarantined = exists(crate_quarantines::table.find(crates::id));
+    let version_quarantined = exists(version_quarantines::table.find(versions::id));
+
     versions::table
         .inner_join(crates::table)
-        .select(versions::id)
+        .select((versions::id, crate_quarantined.or(version_quarantined)))
         .filter(crates::name.eq(&krate))
         .filter(versions::num.eq(&version))
-        .first::<i32>(conn)
+        .first(conn)
 }
 
 /// Handles the `GET /crates/:crate_id/:version/downloads` route.
@@ -6,8 +6,10 @@
 
 use crate::controllers::frontend_prelude::*;
 
-use crate::models::VersionOwnerAction;
-use crate::views::{EncodableDependency, EncodableVersion};
+use crate::models::{VersionLintWarning, VersionMetadata, VersionOwnerAction};
+use crate::views::{
+    EncodableDependency, EncodableVersion, EncodableVersionLintWarning, EncodableVersionMetadata,
+};
 
 use super::version_and_crate;
 
@@ -55,6 +57,10 @@ pub async fn authors() -> Json<Value> {
 ///
 /// The frontend doesn't appear to hit this endpoint, but our tests do, and it seems to be a useful
 /// API route to have.
+///
+/// In addition to the version itself, this returns the package metadata that was extracted
+/// from the published tarball. The `metadata` field is `null` for versions that were
+/// published before this metadata was recorded.
 pub async fn show(
     state: AppState,
     Path((crate_name, version)): Path<(String, String)>,
@@ -68,9 +74,21 @@ pub async fn show(
         let (version, krate) = version_and_crate(conn, &crate_name, &version)?;
         let published_by = version.published_by(conn);
         let actions = VersionOwnerAction::by_version(conn, &version)?;
+        let metadata = VersionMetadata::find(version.id, conn)?;
+        let lint_warnings = VersionLintWarning::for_version(version.id, conn)?;
 
         let version = EncodableVersion::from(version, &krate.name, published_by, actions);
-        Ok(Json(json!({ "version": version })))
+        let metadata = metadata.map(EncodableVersionMetadata::from);
+        let lint_warnings = lint_warnings
+            .into_iter()
+            .map(EncodableVersionLintWarning::from)
+            .collect::<Vec<_>>();
+
+        Ok(Json(json!({
+            "version": version,
+            "metadata": metadata,
+            "lint_warnings": lint_warnings,
+        })))
     })
     .await
 }
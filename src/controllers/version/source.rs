@@ -0,0 +1,234 @@
+//! Endpoints for browsing the source files of published crate versions
+//!
+//! The files are read directly from the `.crate` files in the storage
+//! backend. The list of files in a `.crate` file and the contents of the
+//! served files are cached in memory, since extracting them requires
+//! downloading and unpacking the whole tarball.
+
+use crate::controllers::frontend_prelude::*;
+
+use crate::app::App;
+use crate::util::errors::{internal, not_found};
+use hyper::body::Bytes;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tracing::Instrument;
+
+use super::version_and_crate;
+
+/// Files with a NUL byte within this many bytes from the start are considered
+/// binary files. This is the same heuristic that e.g. `git diff` uses.
+const BINARY_SNIFF_LEN: usize = 8000;
+
+const CONTENT_TYPE_TEXT: &str = "text/plain; charset=utf-8";
+const CONTENT_TYPE_BINARY: &str = "application/octet-stream";
+
+/// Handles the `GET /crates/:crate_id/:version/source` route.
+///
+/// Returns the paths and sizes of all files in the published `.crate` file.
+pub async fn list(
+    state: AppState,
+    Path((crate_name, version)): Path<(String, String)>,
+) -> AppResult<Json<Value>> {
+    let mut crate_file = find_crate_file(&state, crate_name, version).await?;
+    let files = source_index(&state, &mut crate_file).await?;
+
+    let files = files
+        .iter()
+        .map(|(path, size)| json!({ "path": path, "size": size }))
+        .collect::<Vec<_>>();
+
+    Ok(Json(json!({ "files": files })))
+}
+
+/// Handles the `GET /crates/:crate_id/:version/source/*path` route.
+///
+/// Returns the raw contents of a single file in the published `.crate` file.
+/// The `Content-Type` is `text/plain` for UTF-8 encoded text files and
+/// `application/octet-stream` for everything else.
+pub async fn show(
+    state: AppState,
+    Path((crate_name, version, path)): Path<(String, String, String)>,
+) -> AppResult<Response> {
+    let mut crate_file = find_crate_file(&state, crate_name, version).await?;
+    let files = source_index(&state, &mut crate_file).await?;
+
+    let size = *files.get(&path).ok_or_else(not_found)?;
+    let max_size = state.config.max_source_file_size;
+    if size > max_size {
+        return Err(bad_request(format_args!(
+            "file `{path}` is too large to be displayed ({size} bytes, max. {max_size} bytes)"
+        )));
+    }
+
+    // The `.crate` file is only downloaded once, even if neither the list of
+    // files nor the file contents were cached.
+    let contents = source_file(&state, &mut crate_file, path).await?;
+
+    let headers = [
+        (header::CONTENT_TYPE, sniff_content_type(&contents)),
+        (header::X_CONTENT_TYPE_OPTIONS, "nosniff"),
+    ];
+
+    Ok((headers, contents).into_response())
+}
+
+/// A `.crate` file, which is only downloaded when its contents are needed
+/// because they aren't cached.
+struct CrateFile {
+    crate_name: String,
+    version: String,
+    tarball: Option<Bytes>,
+}
+
+impl CrateFile {
+    fn pkg_name(&self) -> String {
+        format!("{}-{}", self.crate_name, self.version)
+    }
+
+    /// Returns the contents of the `.crate` file, downloading it on the first
+    /// call.
+    async fn tarball(&mut self, app: &App) -> AppResult<Bytes> {
+        if let Some(tarball) = &self.tarball {
+            return Ok(tarball.clone());
+        }
+
+        let storage = &app.storage;
+        let tarball = storage
+            .download_crate_file(&self.crate_name, &self.version)
+            .await?;
+
+        self.tarball = Some(tarball.clone());
+        Ok(tarball)
+    }
+}
+
+/// Looks up the version in the database and returns the crate name and version
+/// number that the `.crate` file was stored under.
+async fn find_crate_file(
+    state: &AppState,
+    crate_name: String,
+    version: String,
+) -> AppResult<CrateFile> {
+    let state = state.clone();
+    spawn_blocking(move || {
+        let conn = &mut state.db_read()?;
+        let (version, krate) = version_and_crate(conn, &crate_name, &version)?;
+        Ok(CrateFile {
+            crate_name: krate.name,
+            version: version.num,
+            tarball: None,
+        })
+    })
+    .await
+}
+
+/// Returns the list of files in the `.crate` file, either from the cache or by
+/// downloading and unpacking the `.crate` file.
+async fn source_index(
+    app: &App,
+    crate_file: &mut CrateFile,
+) -> AppResult<Arc<BTreeMap<String, u64>>> {
+    let cache_key = (crate_file.crate_name.clone(), crate_file.version.clone());
+
+    let cache_result = app
+        .source_index_cacher
+        .get(&cache_key)
+        .instrument(info_span!("cache.read", ?cache_key))
+        .await;
+
+    if let Some(files) = cache_result {
+        return Ok(files);
+    }
+
+    let tarball = crate_file.tarball(app).await?;
+
+    let files = spawn_blocking::<_, _, BoxedAppError>({
+        let pkg_name = crate_file.pkg_name();
+        let max_unpack = app.config.max_unpack_size;
+        move || {
+            crates_io_tarball::list_files(&pkg_name, &*tarball, max_unpack)
+                .map_err(|error| internal(format!("failed to read `{pkg_name}.crate`: {error}")))
+        }
+    })
+    .await?;
+
+    let files = Arc::new(files);
+    app.source_index_cacher
+        .insert(cache_key, files.clone())
+        .await;
+
+    Ok(files)
+}
+
+/// Returns the contents of a single file in the `.crate` file, either from the
+/// cache or by downloading and unpacking the `.crate` file.
+async fn source_file(app: &App, crate_file: &mut CrateFile, path: String) -> AppResult<Bytes> {
+    let cache_key = (
+        crate_file.crate_name.clone(),
+        crate_file.version.clone(),
+        path,
+    );
+
+    let cache_result = app
+        .source_file_cacher
+        .get(&cache_key)
+        .instrument(info_span!("cache.read", ?cache_key))
+        .await;
+
+    if let Some(contents) = cache_result {
+        return Ok(contents);
+    }
+
+    let tarball = crate_file.tarball(app).await?;
+
+    let contents = spawn_blocking::<_, _, BoxedAppError>({
+        let pkg_name = crate_file.pkg_name();
+        let path = cache_key.2.clone();
+        let max_unpack = app.config.max_unpack_size;
+        move || {
+            crates_io_tarball::read_file(&pkg_name, &*tarball, &path, max_unpack)
+                .map_err(|error| internal(format!("failed to read `{pkg_name}.crate`: {error}")))?
+                .ok_or_else(not_found)
+        }
+    })
+    .await?;
+
+    let contents = Bytes::from(contents);
+    app.source_file_cacher
+        .insert(cache_key, contents.clone())
+        .await;
+
+    Ok(contents)
+}
+
+fn sniff_content_type(contents: &[u8]) -> &'static str {
+    if is_binary(contents) {
+        CONTENT_TYPE_BINARY
+    } else {
+        CONTENT_TYPE_TEXT
+    }
+}
+
+/// Returns `true` if the file contents are not UTF-8 encoded text.
+pub(super) fn is_binary(contents: &[u8]) -> bool {
+    let head = &contents[..contents.len().min(BINARY_SNIFF_LEN)];
+    head.contains(&0) || std::str::from_utf8(contents).is_err()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn content_type_sniffing() {
+        assert_eq!(sniff_content_type(b""), CONTENT_TYPE_TEXT);
+        assert_eq!(sniff_content_type(b"fn main() {}\n"), CONTENT_TYPE_TEXT);
+        assert_eq!(sniff_content_type("# Über\n".as_bytes()), CONTENT_TYPE_TEXT);
+        assert_eq!(
+            sniff_content_type(b"\x89PNG\r\n\x1a\n"),
+            CONTENT_TYPE_BINARY
+        );
+        assert_eq!(sniff_content_type(b"foo\0bar"), CONTENT_TYPE_BINARY);
+    }
+}
@@ -48,15 +48,16 @@ fn modify_yank(
     // FIXME: Should reject bad requests before authentication, but can't due to
     // lifetime issues with `req`.
 
-    if semver::Version::parse(version).is_err() {
+    let Ok(semver) = semver::Version::parse(version) else {
         return Err(cargo_err(format_args!("invalid semver: {version}")));
-    }
+    };
 
     let conn = &mut *state.db_write()?;
 
     let auth = AuthCheck::default()
         .with_endpoint_scope(EndpointScope::Yank)
         .for_crate(crate_name)
+        .for_version(&semver)
         .check(req, conn)?;
 
     state
@@ -30,6 +30,7 @@ static ALLOC: Jemalloc = Jemalloc;
 
 pub mod admin;
 mod app;
+pub mod attestation;
 pub mod auth;
 pub mod boot;
 pub mod ci;
@@ -43,12 +44,16 @@ pub mod external_urls;
 pub mod fastly;
 pub mod headers;
 mod licenses;
+pub mod lints;
+mod lockfile;
 pub mod metrics;
 pub mod middleware;
 pub mod models;
 pub mod rate_limiter;
 mod real_ip;
+pub mod resolver;
 mod router;
+pub mod scanning;
 pub mod schema;
 pub mod sentry;
 pub mod sql;
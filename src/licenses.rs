@@ -7,6 +7,10 @@ const PARSE_MODE: spdx::ParseMode = spdx::ParseMode {
     allow_postfix_plus_on_gpl: true,
 };
 
+/// The license of crates that only specify a `license-file` instead of a
+/// license expression.
+pub const NON_STANDARD_LICENSE: &str = "non-standard";
+
 pub fn parse_license_expr(s: &str) -> Result<Expression, ParseError> {
     Expression::parse_mode(s, PARSE_MODE)
 }
@@ -0,0 +1,420 @@
+//! Lints that are run against the contents of a published tarball.
+//!
+//! Lints never reject a publish. Their findings are returned to cargo as
+//! publish warnings and stored alongside the published version.
+//!
+//! New lints can be added by implementing the [`Lint`] trait and adding them
+//! to the [`LINTS`] list. Which lints are run can be configured per instance
+//! via the `PUBLISH_LINTS` environment variable.
+
+use crates_io_tarball::{StringOrBool, TarballInfo};
+use std::collections::HashSet;
+
+/// All available lints, in the order in which they are run.
+pub static LINTS: &[&dyn Lint] = &[
+    &MissingRepository,
+    &MissingReadme,
+    &NonStandardLicense,
+    &MissingVcsInfo,
+    &DirtyVcsState,
+    &UnexpectedFiles,
+    &HugeFiles,
+];
+
+/// Directories that are usually not meant to be published.
+static UNEXPECTED_DIRECTORIES: &[&str] = &["target", ".git"];
+
+/// Files larger than this are reported by the `huge_files` lint.
+const HUGE_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
+/// The file names that cargo looks for if `package.readme` is not set.
+static DEFAULT_README_FILES: &[&str] = &["README.md", "README.txt", "README"];
+
+pub trait Lint: Sync {
+    /// The name of the lint, which is used to enable it in the configuration
+    /// and stored alongside its findings.
+    fn name(&self) -> &'static str;
+
+    /// Returns a warning message if the tarball violates the lint.
+    fn check(&self, tarball_info: &TarballInfo) -> Option<String>;
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct LintWarning {
+    pub lint: &'static str,
+    pub message: String,
+}
+
+/// Runs all enabled lints against the tarball and returns their findings.
+pub fn run(tarball_info: &TarballInfo, enabled: &HashSet<String>) -> Vec<LintWarning> {
+    LINTS
+        .iter()
+        .filter(|lint| enabled.contains(lint.name()))
+        .filter_map(|lint| {
+            let message = lint.check(tarball_info)?;
+            Some(LintWarning {
+                lint: lint.name(),
+                message,
+            })
+        })
+        .collect()
+}
+
+/// Returns the names of all available lints.
+pub fn all_lint_names() -> HashSet<String> {
+    LINTS.iter().map(|lint| lint.name().to_string()).collect()
+}
+
+/// Parses a comma separated list of lint names, failing on unknown lints.
+pub fn parse_lint_names(list: &str) -> anyhow::Result<HashSet<String>> {
+    let all = all_lint_names();
+
+    list.split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            if !all.contains(name) {
+                anyhow::bail!("unknown lint `{name}` in PUBLISH_LINTS");
+            }
+            Ok(name.to_string())
+        })
+        .collect()
+}
+
+struct MissingRepository;
+
+impl Lint for MissingRepository {
+    fn name(&self) -> &'static str {
+        "missing_repository"
+    }
+
+    fn check(&self, tarball_info: &TarballInfo) -> Option<String> {
+        let package = tarball_info.manifest.package.as_ref()?;
+        let repository = package
+            .repository
+            .as_ref()
+            .and_then(|r| r.as_ref().as_local());
+        if repository.map_or(true, |repository| repository.is_empty()) {
+            return Some(
+                "the `package.repository` field is not set. Consider linking to the source \
+                repository so that users can review the source code of this crate."
+                    .into(),
+            );
+        }
+
+        None
+    }
+}
+
+struct MissingReadme;
+
+impl Lint for MissingReadme {
+    fn name(&self) -> &'static str {
+        "missing_readme"
+    }
+
+    fn check(&self, tarball_info: &TarballInfo) -> Option<String> {
+        let package = tarball_info.manifest.package.as_ref()?;
+        let readme = package.readme.as_ref().and_then(|r| r.as_ref().as_local());
+
+        match readme {
+            // The readme was explicitly disabled
+            Some(StringOrBool::Bool(false)) => None,
+            Some(StringOrBool::Bool(true)) => check_readme_file(tarball_info, "README.md"),
+            Some(StringOrBool::String(path)) => check_readme_file(tarball_info, path),
+            None => {
+                let has_readme = DEFAULT_README_FILES
+                    .iter()
+                    .any(|file| tarball_info.files.contains_key(*file));
+
+                (!has_readme).then(|| {
+                    "the package does not contain a readme file. Consider adding a `README.md` \
+                    file or setting `package.readme = false` if the crate has no readme."
+                        .into()
+                })
+            }
+        }
+    }
+}
+
+fn check_readme_file(tarball_info: &TarballInfo, path: &str) -> Option<String> {
+    let normalized_path = path.trim_start_matches("./");
+    if tarball_info.files.contains_key(normalized_path) {
+        return None;
+    }
+
+    Some(format!(
+        "the readme file `{path}` that is referenced by `package.readme` is missing from the package."
+    ))
+}
+
+struct NonStandardLicense;
+
+impl Lint for NonStandardLicense {
+    fn name(&self) -> &'static str {
+        "non_standard_license"
+    }
+
+    fn check(&self, tarball_info: &TarballInfo) -> Option<String> {
+        let package = tarball_info.manifest.package.as_ref()?;
+        let license = package.license.as_ref().and_then(|l| l.as_ref().as_local());
+        let license_file = package
+            .license_file
+            .as_ref()
+            .and_then(|l| l.as_ref().as_local());
+
+        if license.is_none() && license_file.is_some() {
+            return Some(
+                "the package only sets `package.license-file`, so its license is recorded as \
+                `non-standard`. Consider using an SPDX license expression in `package.license` \
+                instead."
+                    .into(),
+            );
+        }
+
+        None
+    }
+}
+
+struct MissingVcsInfo;
+
+impl Lint for MissingVcsInfo {
+    fn name(&self) -> &'static str {
+        "missing_vcs_info"
+    }
+
+    fn check(&self, tarball_info: &TarballInfo) -> Option<String> {
+        if tarball_info.vcs_info.is_some() {
+            return None;
+        }
+
+        Some(
+            "the package does not contain a `.cargo_vcs_info.json` file, so it can't be traced \
+            back to a commit in its source repository."
+                .into(),
+        )
+    }
+}
+
+struct DirtyVcsState;
+
+impl Lint for DirtyVcsState {
+    fn name(&self) -> &'static str {
+        "dirty_vcs_state"
+    }
+
+    fn check(&self, tarball_info: &TarballInfo) -> Option<String> {
+        let git = tarball_info.vcs_info.as_ref()?.git.as_ref()?;
+        if !git.dirty {
+            return None;
+        }
+
+        Some(format!(
+            "the package was published with uncommitted changes on top of commit {}, so its \
+            contents might not match the source repository.",
+            git.sha1
+        ))
+    }
+}
+
+struct UnexpectedFiles;
+
+impl Lint for UnexpectedFiles {
+    fn name(&self) -> &'static str {
+        "unexpected_files"
+    }
+
+    fn check(&self, tarball_info: &TarballInfo) -> Option<String> {
+        let (count, size) = tarball_info
+            .files
+            .iter()
+            .filter(|(path, _)| {
+                let first_component = path.split('/').next().unwrap_or_default();
+                UNEXPECTED_DIRECTORIES.contains(&first_component)
+            })
+            .fold((0, 0), |(count, size), (_, file_size)| {
+                (count + 1, size + file_size)
+            });
+
+        if count == 0 {
+            return None;
+        }
+
+        let files = if count == 1 { "file" } else { "files" };
+        Some(format!(
+            "the package contains {count} {files} ({size} bytes) in the `target/` or `.git/` \
+            directories, which are usually not meant to be published. Consider excluding them \
+            with `package.exclude` or `package.include`."
+        ))
+    }
+}
+
+struct HugeFiles;
+
+impl Lint for HugeFiles {
+    fn name(&self) -> &'static str {
+        "huge_files"
+    }
+
+    fn check(&self, tarball_info: &TarballInfo) -> Option<String> {
+        let huge_files = tarball_info
+            .files
+            .iter()
+            .filter(|(_, size)| **size > HUGE_FILE_SIZE)
+            .map(|(path, size)| format!("`{path}` ({size} bytes)"))
+            .collect::<Vec<_>>();
+
+        if huge_files.is_empty() {
+            return None;
+        }
+
+        Some(format!(
+            "the package contains files larger than {} MiB: {}. Consider excluding them with \
+            `package.exclude` or `package.include` if they are not needed to build the crate.",
+            HUGE_FILE_SIZE / 1024 / 1024,
+            huge_files.join(", ")
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crates_io_tarball::{process_tarball, TarballBuilder};
+
+    fn tarball_info(manifest: &str, files: &[&str]) -> TarballInfo {
+        let tarball = files
+            .iter()
+            .fold(TarballBuilder::new(), |builder, file| {
+                builder.add_file(&format!("foo-1.0.0/{file}"), b"")
+            })
+            .add_file("foo-1.0.0/Cargo.toml", manifest.as_bytes())
+            .build();
+
+        process_tarball("foo-1.0.0", &*tarball, u64::MAX).unwrap()
+    }
+
+    fn lint_names(tarball_info: &TarballInfo) -> Vec<&'static str> {
+        run(tarball_info, &all_lint_names())
+            .into_iter()
+            .map(|warning| warning.lint)
+            .collect()
+    }
+
+    const MINIMAL_MANIFEST: &str = "[package]\nname = \"foo\"\nversion = \"1.0.0\"\n";
+
+    const FULL_MANIFEST: &str = r#"
+        [package]
+        name = "foo"
+        version = "1.0.0"
+        license = "MIT"
+        repository = "https://github.com/foo/foo"
+    "#;
+
+    const VCS_INFO: &str = r#"{"git": {"sha1": "0123456789abcdef0123456789abcdef01234567"}}"#;
+
+    #[test]
+    fn clean_package() {
+        let mut info = tarball_info(FULL_MANIFEST, &["README.md", "src/lib.rs"]);
+        info.vcs_info = crates_io_tarball::CargoVcsInfo::from_contents(VCS_INFO).ok();
+        assert_eq!(lint_names(&info), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn minimal_package() {
+        let info = tarball_info(MINIMAL_MANIFEST, &["src/lib.rs"]);
+        assert_eq!(
+            lint_names(&info),
+            vec!["missing_repository", "missing_readme", "missing_vcs_info"]
+        );
+    }
+
+    #[test]
+    fn parse_lint_names() {
+        let names = assert_ok!(super::parse_lint_names("missing_readme, huge_files,"));
+        assert_eq!(
+            names,
+            HashSet::from(["missing_readme".to_string(), "huge_files".to_string()])
+        );
+
+        let error = assert_err!(super::parse_lint_names("missing_readme,missing_licence"));
+        assert_eq!(
+            error.to_string(),
+            "unknown lint `missing_licence` in PUBLISH_LINTS"
+        );
+    }
+
+    #[test]
+    fn disabled_lints() {
+        let info = tarball_info(MINIMAL_MANIFEST, &["src/lib.rs"]);
+        let enabled = HashSet::from(["missing_readme".to_string()]);
+        let warnings = run(&info, &enabled);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].lint, "missing_readme");
+    }
+
+    #[test]
+    fn readme() {
+        let manifest = format!("{MINIMAL_MANIFEST}readme = \"docs/README.md\"\n");
+        let info = tarball_info(&manifest, &["docs/README.md"]);
+        assert_none!(MissingReadme.check(&info));
+
+        let info = tarball_info(&manifest, &["README.md"]);
+        assert_some!(MissingReadme.check(&info));
+
+        let manifest = format!("{MINIMAL_MANIFEST}readme = false\n");
+        let info = tarball_info(&manifest, &[]);
+        assert_none!(MissingReadme.check(&info));
+
+        let info = tarball_info(MINIMAL_MANIFEST, &["README"]);
+        assert_none!(MissingReadme.check(&info));
+    }
+
+    #[test]
+    fn non_standard_license() {
+        let manifest = format!("{MINIMAL_MANIFEST}license-file = \"LICENSE\"\n");
+        let info = tarball_info(&manifest, &["LICENSE"]);
+        assert_some!(NonStandardLicense.check(&info));
+
+        let info = tarball_info(FULL_MANIFEST, &[]);
+        assert_none!(NonStandardLicense.check(&info));
+    }
+
+    #[test]
+    fn dirty_vcs_state() {
+        let mut info = tarball_info(MINIMAL_MANIFEST, &[]);
+        info.vcs_info = crates_io_tarball::CargoVcsInfo::from_contents(VCS_INFO).ok();
+        assert_none!(DirtyVcsState.check(&info));
+
+        let vcs_info =
+            r#"{"git": {"sha1": "0123456789abcdef0123456789abcdef01234567", "dirty": true}}"#;
+        info.vcs_info = crates_io_tarball::CargoVcsInfo::from_contents(vcs_info).ok();
+        assert_some!(DirtyVcsState.check(&info));
+    }
+
+    #[test]
+    fn unexpected_files() {
+        let files = ["src/lib.rs", "src/target/mod.rs", ".gitignore"];
+        let info = tarball_info(MINIMAL_MANIFEST, &files);
+        assert_none!(UnexpectedFiles.check(&info));
+
+        let files = ["src/lib.rs", "target/debug/foo", ".git/HEAD"];
+        let info = tarball_info(MINIMAL_MANIFEST, &files);
+        let message = assert_some!(UnexpectedFiles.check(&info));
+        assert!(message.starts_with("the package contains 2 files (0 bytes)"));
+    }
+
+    #[test]
+    fn huge_files() {
+        let mut info = tarball_info(MINIMAL_MANIFEST, &["src/lib.rs", "data/model.bin"]);
+        info.files.insert("data/model.bin".into(), HUGE_FILE_SIZE);
+        assert_none!(HugeFiles.check(&info));
+
+        info.files
+            .insert("data/model.bin".into(), HUGE_FILE_SIZE + 1);
+        let message = assert_some!(HugeFiles.check(&info));
+        assert!(message.starts_with(
+            "the package contains files larger than 10 MiB: `data/model.bin` (10485761 bytes)."
+        ));
+    }
+}
@@ -0,0 +1,88 @@
+//! Parsing of `Cargo.lock` files, as uploaded to the endpoints that look up
+//! or audit all the dependencies of a project.
+
+/// A package that is locked in a `Cargo.lock` file.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: String,
+    pub source: Option<String>,
+}
+
+impl LockedPackage {
+    /// Whether the package comes from a registry, as opposed to a path or git
+    /// dependency.
+    pub fn is_from_registry(&self) -> bool {
+        self.source
+            .as_deref()
+            .is_some_and(|source| source.starts_with("registry+") || source.starts_with("sparse+"))
+    }
+}
+
+#[derive(Deserialize)]
+struct Lockfile {
+    #[serde(default)]
+    package: Vec<LockedPackage>,
+}
+
+/// Parses a `Cargo.lock` file and returns the packages that come from a
+/// registry.
+pub fn registry_packages(contents: &str) -> Result<Vec<LockedPackage>, toml::de::Error> {
+    let lockfile: Lockfile = toml::from_str(contents)?;
+    let packages = lockfile.package.into_iter();
+    Ok(packages.filter(LockedPackage::is_from_registry).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_lockfile() {
+        let lockfile = r#"
+            # This file is automatically @generated by Cargo.
+            # It is not intended for manual editing.
+            version = 3
+
+            [[package]]
+            name = "foo"
+            version = "0.1.0"
+            dependencies = ["bar", "baz", "qux"]
+
+            [[package]]
+            name = "bar"
+            version = "1.0.0"
+            source = "registry+https://github.com/rust-lang/crates.io-index"
+            checksum = "0000000000000000000000000000000000000000000000000000000000000000"
+
+            [[package]]
+            name = "baz"
+            version = "2.0.0-beta.1"
+            source = "sparse+https://index.crates.io/"
+
+            [[package]]
+            name = "qux"
+            version = "0.3.0"
+            source = "git+https://github.com/foo/qux#0123456789abcdef0123456789abcdef01234567"
+        "#;
+
+        let packages = assert_ok!(registry_packages(lockfile));
+        let packages = packages
+            .iter()
+            .map(|p| (p.name.as_str(), p.version.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(packages, vec![("bar", "1.0.0"), ("baz", "2.0.0-beta.1")]);
+    }
+
+    #[test]
+    fn empty_lockfile() {
+        let packages = assert_ok!(registry_packages("version = 3\n"));
+        assert!(packages.is_empty());
+    }
+
+    #[test]
+    fn invalid_lockfile() {
+        assert_err!(registry_packages("[[package]]\nname = 1\n"));
+        assert_err!(registry_packages("foo"));
+    }
+}
@@ -1,6 +1,13 @@
 pub use self::action::{insert_version_owner_action, VersionAction, VersionOwnerAction};
-pub use self::category::{Category, CrateCategory, NewCategory};
+pub use self::admin_action::{AdminAction, NewAdminAction};
+pub use self::admin_token::{AdminToken, CreatedAdminToken};
+pub use self::adoption_request::{
+    AdoptionRequest, AdoptionRequestStatus, ABANDONED_AFTER_DAYS, WAITING_PERIOD_DAYS,
+};
+pub use self::category::{Category, CrateCategory, NewCategory, ToplevelSeek};
 pub use self::crate_owner_invitation::{CrateOwnerInvitation, NewCrateOwnerInvitationOutcome};
+pub use self::crate_publish_policy::{CratePublishPolicy, NewCratePublishPolicy};
+pub use self::crate_transfer::{notify_previous_owners, CrateTransfer, CrateTransferProposedEmail};
 pub use self::dependency::{Dependency, DependencyKind, ReverseDependency};
 pub use self::download::VersionDownload;
 pub use self::email::{Email, NewEmail};
@@ -8,17 +15,33 @@ pub use self::follow::Follow;
 pub use self::keyword::{CrateKeyword, Keyword};
 pub use self::krate::{Crate, CrateVersions, NewCrate, RecentCrateDownloads};
 pub use self::owner::{CrateOwner, Owner, OwnerKind};
+pub use self::quarantine::{
+    is_crate_quarantined, is_version_id_quarantined, is_version_quarantined,
+    without_quarantined_versions, QuarantineAction, QuarantineActionKind, QuarantineTarget,
+};
 pub use self::rights::Rights;
 pub use self::team::{NewTeam, Team};
 pub use self::token::{ApiToken, CreatedApiToken};
-pub use self::user::{NewUser, User};
-pub use self::version::{NewVersion, TopVersions, Version};
+pub use self::typosquat_finding::{NewTyposquatFinding, TyposquatFinding, TyposquatFindingStatus};
+pub use self::user::{AccountDeletedEmail, DeletedAccount, NewUser, User};
+pub use self::version::{
+    is_compatible_rust_version, parse_rust_version, NewVersion, TopVersions, Version,
+};
+pub use self::version_attestation::{NewVersionAttestation, VersionAttestation};
+pub use self::version_lint_warning::VersionLintWarning;
+pub use self::version_metadata::{NewVersionMetadata, VcsVerificationStatus, VersionMetadata};
+pub use self::version_scan_finding::VersionScanFinding;
 
 pub mod helpers;
 
 mod action;
+mod admin_action;
+mod admin_token;
+mod adoption_request;
 pub mod category;
 mod crate_owner_invitation;
+mod crate_publish_policy;
+mod crate_transfer;
 pub mod dependency;
 mod download;
 mod email;
@@ -26,8 +49,14 @@ mod follow;
 mod keyword;
 pub mod krate;
 mod owner;
+mod quarantine;
 mod rights;
 mod team;
 pub mod token;
+mod typosquat_finding;
 pub mod user;
 pub mod version;
+mod version_attestation;
+mod version_lint_warning;
+mod version_metadata;
+mod version_scan_finding;
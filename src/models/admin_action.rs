@@ -0,0 +1,57 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde_json::Value;
+
+use crate::models::{AdminToken, User};
+use crate::schema::{admin_actions, users};
+use crate::util::rfc3339;
+
+/// An entry of the audit log of the admin API.
+#[derive(Debug, Clone, Queryable, Identifiable, Associations, Selectable, Serialize)]
+#[diesel(
+    table_name = admin_actions,
+    check_for_backend(diesel::pg::Pg),
+    belongs_to(User),
+    belongs_to(AdminToken),
+)]
+pub struct AdminAction {
+    pub id: i32,
+    pub user_id: i32,
+    pub admin_token_id: i32,
+    pub action: String,
+    pub target: String,
+    pub details: Value,
+    #[serde(with = "rfc3339")]
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = admin_actions, check_for_backend(diesel::pg::Pg))]
+pub struct NewAdminAction<'a> {
+    pub user_id: i32,
+    pub admin_token_id: i32,
+    pub action: &'a str,
+    pub target: &'a str,
+    pub details: Value,
+}
+
+impl NewAdminAction<'_> {
+    pub fn insert(&self, conn: &mut PgConnection) -> QueryResult<AdminAction> {
+        diesel::insert_into(admin_actions::table)
+            .values(self)
+            .returning(AdminAction::as_returning())
+            .get_result(conn)
+    }
+}
+
+impl AdminAction {
+    /// Returns the most recent actions, newest first.
+    pub fn recent(limit: i64, conn: &mut PgConnection) -> QueryResult<Vec<(Self, String)>> {
+        admin_actions::table
+            .inner_join(users::table)
+            .select((AdminAction::as_select(), users::gh_login))
+            .order(admin_actions::id.desc())
+            .limit(limit)
+            .load(conn)
+    }
+}
@@ -0,0 +1,86 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+use crate::models::User;
+use crate::schema::admin_tokens;
+use crate::util::errors::{forbidden, AppResult};
+use crate::util::token::{HashedToken, PlainToken};
+
+/// A token for the admin API, which can only be used by users with the admin
+/// role. Admin tokens are separate from API tokens and are not accepted
+/// anywhere else.
+#[derive(Debug, Identifiable, Queryable, Selectable, Associations)]
+#[diesel(belongs_to(User))]
+pub struct AdminToken {
+    pub id: i32,
+    pub user_id: i32,
+    pub name: String,
+    pub created_at: NaiveDateTime,
+    pub last_used_at: Option<NaiveDateTime>,
+    pub expired_at: Option<NaiveDateTime>,
+    pub revoked: bool,
+}
+
+impl AdminToken {
+    /// Generates a new named admin token for a user
+    pub fn insert(
+        conn: &mut PgConnection,
+        user_id: i32,
+        name: &str,
+        expired_at: Option<NaiveDateTime>,
+    ) -> QueryResult<CreatedAdminToken> {
+        let token = PlainToken::generate_admin();
+
+        let model: AdminToken = diesel::insert_into(admin_tokens::table)
+            .values((
+                admin_tokens::user_id.eq(user_id),
+                admin_tokens::name.eq(name),
+                admin_tokens::token.eq(token.hashed()),
+                admin_tokens::expired_at.eq(expired_at),
+            ))
+            .returning(AdminToken::as_returning())
+            .get_result(conn)?;
+
+        Ok(CreatedAdminToken {
+            plaintext: token,
+            model,
+        })
+    }
+
+    pub fn find_by_admin_token(conn: &mut PgConnection, token: &str) -> AppResult<AdminToken> {
+        use diesel::{dsl::now, update};
+
+        let token = HashedToken::parse_admin(token).ok_or_else(forbidden)?;
+
+        let tokens = admin_tokens::table
+            .filter(admin_tokens::revoked.eq(false))
+            .filter(
+                admin_tokens::expired_at
+                    .is_null()
+                    .or(admin_tokens::expired_at.gt(now)),
+            )
+            .filter(admin_tokens::token.eq(&token));
+
+        update(tokens)
+            .set(admin_tokens::last_used_at.eq(now.nullable()))
+            .returning(AdminToken::as_returning())
+            .get_result(conn)
+            .map_err(Into::into)
+    }
+
+    /// Revokes a token, returning `false` if it doesn't exist or is already revoked.
+    pub fn revoke(conn: &mut PgConnection, id: i32) -> QueryResult<bool> {
+        let updated = diesel::update(admin_tokens::table.find(id))
+            .filter(admin_tokens::revoked.eq(false))
+            .set(admin_tokens::revoked.eq(true))
+            .execute(conn)?;
+
+        Ok(updated > 0)
+    }
+}
+
+#[derive(Debug)]
+pub struct CreatedAdminToken {
+    pub model: AdminToken,
+    pub plaintext: PlainToken,
+}
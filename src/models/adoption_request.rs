@@ -0,0 +1,376 @@
+//! Users can request to adopt abandoned crates. The current owners are
+//! notified and can reject the request during a waiting period, after which
+//! the crates.io team decides whether the user becomes an owner of the crate.
+
+use chrono::{Duration, NaiveDateTime, Utc};
+use diesel::dsl::{exists, not};
+use diesel::prelude::*;
+
+use crate::email::{Email, Emails};
+use crate::models::{Crate, CrateOwner, OwnerKind, User};
+use crate::schema::{crate_adoption_requests, crate_owners, crates, users, versions};
+use crate::sql::pg_enum;
+
+/// Crates without a new version for this long can be adopted.
+pub const ABANDONED_AFTER_DAYS: i64 = 365;
+
+/// How long the current owners have to reject a request before it can be
+/// approved.
+pub const WAITING_PERIOD_DAYS: i64 = 14;
+
+pg_enum! {
+    pub enum AdoptionRequestStatus {
+        Pending = 0,
+        Approved = 1,
+        Rejected = 2,
+        Withdrawn = 3,
+    }
+}
+
+impl From<AdoptionRequestStatus> for &'static str {
+    fn from(status: AdoptionRequestStatus) -> Self {
+        match status {
+            AdoptionRequestStatus::Pending => "pending",
+            AdoptionRequestStatus::Approved => "approved",
+            AdoptionRequestStatus::Rejected => "rejected",
+            AdoptionRequestStatus::Withdrawn => "withdrawn",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = crate_adoption_requests, check_for_backend(diesel::pg::Pg))]
+pub struct AdoptionRequest {
+    pub id: i32,
+    pub crate_id: i32,
+    pub user_id: i32,
+    pub reason: String,
+    pub status: AdoptionRequestStatus,
+    pub created_at: NaiveDateTime,
+    /// The end of the waiting period.
+    pub approvable_at: NaiveDateTime,
+    pub decided_by: Option<String>,
+    pub decided_at: Option<NaiveDateTime>,
+    pub decision_reason: Option<String>,
+}
+
+impl AdoptionRequest {
+    /// Whether the crate can be adopted, because it has no user owners anymore,
+    /// or because no new version was published for [`ABANDONED_AFTER_DAYS`].
+    pub fn is_abandoned(krate: &Crate, conn: &mut PgConnection) -> QueryResult<bool> {
+        let abandoned_since = Utc::now().naive_utc() - Duration::days(ABANDONED_AFTER_DAYS);
+
+        let has_user_owners = exists(
+            CrateOwner::by_owner_kind(OwnerKind::User).filter(crate_owners::crate_id.eq(krate.id)),
+        );
+        let has_recent_versions = exists(
+            versions::table
+                .filter(versions::crate_id.eq(krate.id))
+                .filter(versions::created_at.gt(abandoned_since)),
+        );
+
+        diesel::select(not(has_user_owners).or(not(has_recent_versions))).get_result(conn)
+    }
+
+    /// Records the request. It can be approved once the waiting period is
+    /// over.
+    pub fn create(
+        krate: &Crate,
+        user: &User,
+        reason: &str,
+        conn: &mut PgConnection,
+    ) -> QueryResult<Self> {
+        let approvable_at = Utc::now().naive_utc() + Duration::days(WAITING_PERIOD_DAYS);
+
+        diesel::insert_into(crate_adoption_requests::table)
+            .values((
+                crate_adoption_requests::crate_id.eq(krate.id),
+                crate_adoption_requests::user_id.eq(user.id),
+                crate_adoption_requests::reason.eq(reason),
+                crate_adoption_requests::approvable_at.eq(approvable_at),
+            ))
+            .returning(Self::as_returning())
+            .get_result(conn)
+    }
+
+    pub fn find(id: i32, conn: &mut PgConnection) -> QueryResult<Self> {
+        crate_adoption_requests::table
+            .find(id)
+            .select(Self::as_select())
+            .first(conn)
+    }
+
+    /// Like [`AdoptionRequest::find`], but locks the request until the end
+    /// of the transaction.
+    pub fn find_for_update(id: i32, conn: &mut PgConnection) -> QueryResult<Self> {
+        crate_adoption_requests::table
+            .find(id)
+            .select(Self::as_select())
+            .for_update()
+            .first(conn)
+    }
+
+    /// Returns the pending request of the user for the crate, if there is one.
+    pub fn find_pending(
+        crate_id: i32,
+        user_id: i32,
+        conn: &mut PgConnection,
+    ) -> QueryResult<Option<Self>> {
+        crate_adoption_requests::table
+            .filter(crate_adoption_requests::crate_id.eq(crate_id))
+            .filter(crate_adoption_requests::user_id.eq(user_id))
+            .filter(crate_adoption_requests::status.eq(AdoptionRequestStatus::Pending))
+            .select(Self::as_select())
+            .first(conn)
+            .optional()
+    }
+
+    /// Returns all pending requests with the names of the crates and the
+    /// logins of the users, oldest first.
+    pub fn pending(conn: &mut PgConnection) -> QueryResult<Vec<(Self, String, String)>> {
+        crate_adoption_requests::table
+            .inner_join(crates::table)
+            .inner_join(users::table)
+            .filter(crate_adoption_requests::status.eq(AdoptionRequestStatus::Pending))
+            .select((Self::as_select(), crates::name, users::gh_login))
+            .order(crate_adoption_requests::id)
+            .load(conn)
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.status == AdoptionRequestStatus::Pending
+    }
+
+    pub fn is_approvable(&self) -> bool {
+        self.approvable_at <= Utc::now().naive_utc()
+    }
+
+    /// Makes the user an owner of the crate. The current owners are kept.
+    pub fn approve(&self, decided_by: &str, conn: &mut PgConnection) -> QueryResult<Self> {
+        conn.transaction(|conn| {
+            diesel::insert_into(crate_owners::table)
+                .values(&CrateOwner {
+                    crate_id: self.crate_id,
+                    owner_id: self.user_id,
+                    created_by: self.user_id,
+                    owner_kind: OwnerKind::User,
+                    email_notifications: true,
+                })
+                .on_conflict(crate_owners::table.primary_key())
+                .do_update()
+                .set((
+                    crate_owners::deleted.eq(false),
+                    crate_owners::can_publish.eq(true),
+                ))
+                .execute(conn)?;
+
+            self.decide(AdoptionRequestStatus::Approved, decided_by, None, conn)
+        })
+    }
+
+    pub fn reject(
+        &self,
+        decided_by: &str,
+        reason: Option<&str>,
+        conn: &mut PgConnection,
+    ) -> QueryResult<Self> {
+        self.decide(AdoptionRequestStatus::Rejected, decided_by, reason, conn)
+    }
+
+    pub fn withdraw(&self, conn: &mut PgConnection) -> QueryResult<Self> {
+        diesel::update(self)
+            .set(crate_adoption_requests::status.eq(AdoptionRequestStatus::Withdrawn))
+            .returning(Self::as_returning())
+            .get_result(conn)
+    }
+
+    fn decide(
+        &self,
+        status: AdoptionRequestStatus,
+        decided_by: &str,
+        reason: Option<&str>,
+        conn: &mut PgConnection,
+    ) -> QueryResult<Self> {
+        diesel::update(self)
+            .set((
+                crate_adoption_requests::status.eq(status),
+                crate_adoption_requests::decided_by.eq(decided_by),
+                crate_adoption_requests::decided_at.eq(Utc::now().naive_utc()),
+                crate_adoption_requests::decision_reason.eq(reason),
+            ))
+            .returning(Self::as_returning())
+            .get_result(conn)
+    }
+
+    /// Tells the current owners of the crate about the new request. Failures
+    /// are only logged.
+    pub fn notify_owners(
+        &self,
+        krate: &Crate,
+        user: &User,
+        emails: &Emails,
+        conn: &mut PgConnection,
+    ) -> QueryResult<()> {
+        for recipient in krate.owner_emails(conn)? {
+            let email = AdoptionRequestedEmail {
+                domain: &emails.domain,
+                crate_name: &krate.name,
+                user_name: &user.gh_login,
+                reason: &self.reason,
+                approvable_at: self.approvable_at,
+            };
+            if let Err(error) = emails.send(&recipient, email) {
+                error!(
+                    ?error,
+                    ?recipient,
+                    "Failed to send adoption request notification"
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Tells the user who requested the adoption about the decision. Failures
+    /// are only logged.
+    pub fn notify_user(
+        &self,
+        crate_name: &str,
+        emails: &Emails,
+        conn: &mut PgConnection,
+    ) -> QueryResult<()> {
+        let user = User::find(conn, self.user_id)?;
+        let Some(recipient) = user.verified_email(conn)? else {
+            return Ok(());
+        };
+
+        let email = AdoptionDecidedEmail {
+            domain: &emails.domain,
+            crate_name,
+            approved: self.status == AdoptionRequestStatus::Approved,
+            reason: self.decision_reason.as_deref(),
+        };
+        if let Err(error) = emails.send(&recipient, email) {
+            error!(
+                ?error,
+                ?recipient,
+                "Failed to send adoption decision notification"
+            );
+        }
+
+        Ok(())
+    }
+}
+
+struct AdoptionRequestedEmail<'a> {
+    domain: &'a str,
+    crate_name: &'a str,
+    user_name: &'a str,
+    reason: &'a str,
+    approvable_at: NaiveDateTime,
+}
+
+impl Email for AdoptionRequestedEmail<'_> {
+    const SUBJECT: &'static str = "Crate adoption request";
+
+    fn body(&self) -> String {
+        format!(
+            "{user_name} has asked to become an owner of your crate {crate_name}, \
+because it looks abandoned.\n
+Reason: {reason}\n
+If you want to keep maintaining the crate without them, please reject the request on \
+https://{domain}/crates/{crate_name} before {approvable_at} UTC. \
+After that, the crates.io team may approve it.",
+            user_name = self.user_name,
+            crate_name = self.crate_name,
+            reason = self.reason,
+            domain = self.domain,
+            approvable_at = self.approvable_at.format("%Y-%m-%d at %H:%M:%S"),
+        )
+    }
+}
+
+struct AdoptionDecidedEmail<'a> {
+    domain: &'a str,
+    crate_name: &'a str,
+    approved: bool,
+    reason: Option<&'a str>,
+}
+
+impl Email for AdoptionDecidedEmail<'_> {
+    const SUBJECT: &'static str = "Crate adoption request decided";
+
+    fn body(&self) -> String {
+        if self.approved {
+            format!(
+                "Your request to adopt the crate {crate_name} has been approved. \
+You are now an owner of https://{domain}/crates/{crate_name}.",
+                crate_name = self.crate_name,
+                domain = self.domain,
+            )
+        } else {
+            format!(
+                "Your request to adopt the crate {crate_name} has been rejected.\n
+Reason: {reason}",
+                crate_name = self.crate_name,
+                reason = self.reason.unwrap_or("not given"),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::pg_connection;
+    use crate::typosquat::test_util::Faker;
+
+    #[test]
+    fn abandoned_crates() -> anyhow::Result<()> {
+        let mut faker = Faker::new(pg_connection());
+        let owner = faker.user("owner")?;
+        let (krate, version) = faker.crate_and_version("foo", "", &owner, 0)?;
+        let mut conn = faker.into_conn();
+        let conn = &mut conn;
+
+        // A new version was just published
+        assert!(!AdoptionRequest::is_abandoned(&krate, conn)?);
+
+        let old = Utc::now().naive_utc() - Duration::days(ABANDONED_AFTER_DAYS + 1);
+        diesel::update(versions::table.find(version.id))
+            .set(versions::created_at.eq(old))
+            .execute(conn)?;
+        assert!(AdoptionRequest::is_abandoned(&krate, conn)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn approve() -> anyhow::Result<()> {
+        let mut faker = Faker::new(pg_connection());
+        let owner = faker.user("owner")?;
+        let adopter = faker.user("adopter")?;
+        let (krate, _) = faker.crate_and_version("foo", "", &owner, 0)?;
+        let mut conn = faker.into_conn();
+        let conn = &mut conn;
+
+        let request = AdoptionRequest::create(&krate, &adopter, "I use it", conn)?;
+        assert!(request.is_pending());
+        assert!(!request.is_approvable());
+        assert_some!(AdoptionRequest::find_pending(krate.id, adopter.id, conn)?);
+
+        let request = request.approve("admin", conn)?;
+        assert_eq!(request.status, AdoptionRequestStatus::Approved);
+        assert_some_eq!(request.decided_by, "admin");
+        assert_none!(AdoptionRequest::find_pending(krate.id, adopter.id, conn)?);
+
+        let owners = krate
+            .owners(conn)?
+            .into_iter()
+            .map(|owner| owner.login().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(owners, ["owner", "adopter"]);
+
+        Ok(())
+    }
+}
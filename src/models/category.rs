@@ -18,6 +18,17 @@ pub struct Category {
 type WithSlug<'a> = diesel::dsl::Eq<categories::slug, crate::sql::lower::HelperType<&'a str>>;
 type BySlug<'a> = diesel::dsl::Filter<categories::table, WithSlug<'a>>;
 
+/// The position after the last category of a page of top-level categories,
+/// used for seek-based pagination.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToplevelSeek {
+    /// The name of the last category, when sorted alphabetically.
+    Category(String),
+    /// The number of crates and the ID of the last category, when sorted by
+    /// the number of crates.
+    CratesCnt(i32, i32),
+}
+
 #[derive(Associations, Insertable, Identifiable, Debug, Clone, Copy)]
 #[diesel(
     table_name = crates_categories,
@@ -87,18 +98,51 @@ impl Category {
         use diesel::sql_types::Int8;
 
         let sort_sql = match sort {
-            "crates" => "ORDER BY crates_cnt DESC",
+            "crates" => "ORDER BY crates_cnt DESC, id DESC",
             _ => "ORDER BY category ASC",
         };
 
         // Collect all the top-level categories and sum up the crates_cnt of
         // the crates in all subcategories
-        sql_query(format!(include_str!("toplevel.sql"), sort_sql))
+        sql_query(format!(include_str!("toplevel.sql"), "", sort_sql))
             .bind::<Int8, _>(limit)
             .bind::<Int8, _>(offset)
             .load(conn)
     }
 
+    /// Like [`Category::toplevel`], but only returns the categories after the
+    /// given seek position, in the order the position belongs to.
+    pub fn toplevel_after(
+        conn: &mut PgConnection,
+        seek: &ToplevelSeek,
+        limit: i64,
+    ) -> QueryResult<Vec<Category>> {
+        use diesel::sql_types::{Int4, Int8, Text};
+
+        let query = match seek {
+            ToplevelSeek::Category(category) => sql_query(format!(
+                include_str!("toplevel.sql"),
+                "WHERE category > $3", "ORDER BY category ASC",
+            ))
+            .into_boxed()
+            .bind::<Int8, _>(limit)
+            .bind::<Int8, _>(0)
+            .bind::<Text, _>(category),
+            ToplevelSeek::CratesCnt(crates_cnt, id) => sql_query(format!(
+                include_str!("toplevel.sql"),
+                "WHERE crates_cnt < $3 OR (crates_cnt = $3 AND id < $4)",
+                "ORDER BY crates_cnt DESC, id DESC",
+            ))
+            .into_boxed()
+            .bind::<Int8, _>(limit)
+            .bind::<Int8, _>(0)
+            .bind::<Int4, _>(*crates_cnt)
+            .bind::<Int4, _>(*id),
+        };
+
+        query.load(conn)
+    }
+
     pub fn subcategories(&self, conn: &mut PgConnection) -> QueryResult<Vec<Category>> {
         use diesel::sql_types::Text;
 
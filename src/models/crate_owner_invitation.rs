@@ -1,5 +1,8 @@
 use chrono::{NaiveDateTime, Utc};
+use diesel::dsl::sql;
+use diesel::pg::Pg;
 use diesel::prelude::*;
+use diesel::sql_types::{Bool, Integer, Timestamp};
 use secrecy::SecretString;
 
 use crate::config;
@@ -32,7 +35,7 @@ impl CrateOwnerInvitation {
         invited_by_user_id: i32,
         crate_id: i32,
         conn: &mut PgConnection,
-        config: &config::Server,
+        expiration_days: i64,
     ) -> QueryResult<NewCrateOwnerInvitationOutcome> {
         #[derive(Insertable, Clone, Copy, Debug)]
         #[diesel(table_name = crate_owner_invitations, check_for_backend(diesel::pg::Pg))]
@@ -55,7 +58,7 @@ impl CrateOwnerInvitation {
                 .optional()?;
 
             if let Some(existing) = existing {
-                if existing.is_expired(config) {
+                if existing.is_expired(expiration_days) {
                     diesel::delete(&existing).execute(conn)?;
                 }
             }
@@ -96,11 +99,12 @@ impl CrateOwnerInvitation {
     }
 
     pub fn accept(self, conn: &mut PgConnection, config: &config::Server) -> AppResult<()> {
-        if self.is_expired(config) {
-            let crate_name = crates::table
-                .find(self.crate_id)
-                .select(crates::name)
-                .first(conn)?;
+        let (crate_name, crate_expiration_days) = crates::table
+            .find(self.crate_id)
+            .select((crates::name, crates::ownership_invitations_expiration_days))
+            .first(conn)?;
+
+        if self.is_expired(Self::expiration_days(config, crate_expiration_days)) {
             return Err(Box::new(OwnershipInvitationExpired { crate_name }));
         }
 
@@ -133,12 +137,49 @@ impl CrateOwnerInvitation {
         Ok(())
     }
 
-    pub fn is_expired(&self, config: &config::Server) -> bool {
-        self.expires_at(config) <= Utc::now().naive_utc()
+    /// Deletes an invitation on behalf of the crate owners, before the invited user had the
+    /// chance to accept or decline it.
+    pub fn revoke(self, conn: &mut PgConnection) -> QueryResult<()> {
+        diesel::delete(&self).execute(conn)?;
+        Ok(())
+    }
+
+    /// Restarts the expiration period of the invitation, which also revives it if it already
+    /// expired. The token stays the same, so links sent in earlier emails keep working.
+    pub fn renew(self, conn: &mut PgConnection) -> QueryResult<Self> {
+        diesel::update(&self)
+            .set(crate_owner_invitations::created_at.eq(Utc::now().naive_utc()))
+            .get_result(conn)
+    }
+
+    /// Returns the number of days after which invitations expire, given the per-crate
+    /// `ownership_invitations_expiration_days` override.
+    pub fn expiration_days(config: &config::Server, crate_expiration_days: Option<i32>) -> i64 {
+        crate_expiration_days
+            .map(i64::from)
+            .unwrap_or(config.ownership_invitations_expiration_days as i64)
+    }
+
+    /// Returns an SQL filter that only matches invitations that didn't expire yet. The query has
+    /// to be joined with the `crates` table, to take the per-crate expiration into account.
+    pub fn not_expired<QS: 'static>(
+        config: &config::Server,
+    ) -> Box<dyn BoxableExpression<QS, Pg, SqlType = Bool>> {
+        let default_days = config.ownership_invitations_expiration_days as i32;
+        Box::new(
+            sql::<Bool>("crate_owner_invitations.created_at > ")
+                .bind::<Timestamp, _>(Utc::now().naive_utc())
+                .sql(" - make_interval(days => COALESCE(crates.ownership_invitations_expiration_days, ")
+                .bind::<Integer, _>(default_days)
+                .sql("))"),
+        )
+    }
+
+    pub fn is_expired(&self, expiration_days: i64) -> bool {
+        self.expires_at(expiration_days) <= Utc::now().naive_utc()
     }
 
-    pub fn expires_at(&self, config: &config::Server) -> NaiveDateTime {
-        let days = chrono::Duration::days(config.ownership_invitations_expiration_days as i64);
-        self.created_at + days
+    pub fn expires_at(&self, expiration_days: i64) -> NaiveDateTime {
+        self.created_at + chrono::Duration::days(expiration_days)
     }
 }
@@ -0,0 +1,179 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+use crate::models::token::CrateScope;
+use crate::schema::crate_publish_policies;
+use crate::util::errors::{cargo_err, AppResult};
+
+/// Restrictions that the owners of a crate put on the publishing of new
+/// versions, in addition to the owners that are only allowed to yank (see
+/// `crate_owners.can_publish`).
+#[derive(Clone, Debug, PartialEq, Eq, Queryable, Identifiable, Selectable)]
+#[diesel(
+    table_name = crate_publish_policies,
+    check_for_backend(diesel::pg::Pg),
+    primary_key(crate_id),
+)]
+pub struct CratePublishPolicy {
+    pub crate_id: i32,
+    pub require_exact_crate_scope: bool,
+    pub forbid_dirty_vcs: bool,
+    pub require_rust_version: bool,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Insertable, AsChangeset)]
+#[diesel(table_name = crate_publish_policies, check_for_backend(diesel::pg::Pg))]
+pub struct NewCratePublishPolicy {
+    pub crate_id: i32,
+    pub require_exact_crate_scope: bool,
+    pub forbid_dirty_vcs: bool,
+    pub require_rust_version: bool,
+}
+
+impl CratePublishPolicy {
+    pub fn find(crate_id: i32, conn: &mut PgConnection) -> QueryResult<Option<Self>> {
+        crate_publish_policies::table
+            .find(crate_id)
+            .select(CratePublishPolicy::as_select())
+            .first(conn)
+            .optional()
+    }
+
+    /// Checks whether a new version of the crate may be published under this
+    /// policy, given the crate scopes of the API token that was used for
+    /// publishing, the VCS state recorded in `.cargo_vcs_info.json` and the
+    /// declared `rust_version`.
+    pub fn check(
+        &self,
+        crate_name: &str,
+        crate_scopes: Option<&[CrateScope]>,
+        vcs_dirty: Option<bool>,
+        rust_version: Option<&str>,
+    ) -> AppResult<()> {
+        if self.require_exact_crate_scope {
+            let has_exact_scope = crate_scopes
+                .is_some_and(|scopes| scopes.iter().any(|scope| scope.is_exactly(crate_name)));
+
+            if !has_exact_scope {
+                return Err(cargo_err(format_args!(
+                    "the owners of `{crate_name}` require new versions to be published with an \
+                    API token that is scoped to the `{crate_name}` crate, without wildcards"
+                )));
+            }
+        }
+
+        if self.forbid_dirty_vcs {
+            match vcs_dirty {
+                Some(false) => {}
+                Some(true) => {
+                    return Err(cargo_err(format_args!(
+                        "the owners of `{crate_name}` require new versions to be published from \
+                        a clean VCS working directory. Commit your changes and publish without \
+                        `--allow-dirty`."
+                    )));
+                }
+                None => {
+                    return Err(cargo_err(format_args!(
+                        "the owners of `{crate_name}` require new versions to be published from \
+                        a git checkout, but the package does not contain a \
+                        `.cargo_vcs_info.json` file"
+                    )));
+                }
+            }
+        }
+
+        if self.require_rust_version && rust_version.is_none() {
+            return Err(cargo_err(format_args!(
+                "the owners of `{crate_name}` require new versions to declare a `rust-version` \
+                in their `Cargo.toml` file"
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+impl NewCratePublishPolicy {
+    pub fn save(&self, conn: &mut PgConnection) -> QueryResult<CratePublishPolicy> {
+        diesel::insert_into(crate_publish_policies::table)
+            .values(self)
+            .on_conflict(crate_publish_policies::crate_id)
+            .do_update()
+            .set((
+                self,
+                crate_publish_policies::updated_at.eq(diesel::dsl::now),
+            ))
+            .returning(CratePublishPolicy::as_returning())
+            .get_result(conn)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> CratePublishPolicy {
+        CratePublishPolicy {
+            crate_id: 1,
+            require_exact_crate_scope: false,
+            forbid_dirty_vcs: false,
+            require_rust_version: false,
+            updated_at: NaiveDateTime::default(),
+        }
+    }
+
+    fn scopes(patterns: &[&str]) -> Vec<CrateScope> {
+        patterns
+            .iter()
+            .map(|pattern| CrateScope::try_from(*pattern).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn default_policy_allows_everything() {
+        assert_ok!(policy().check("foo", None, None, None));
+        assert_ok!(policy().check("foo", None, Some(true), None));
+    }
+
+    #[test]
+    fn require_exact_crate_scope() {
+        let policy = CratePublishPolicy {
+            require_exact_crate_scope: true,
+            ..policy()
+        };
+
+        assert_ok!(policy.check("foo", Some(&scopes(&["bar", "foo"])), None, None));
+        assert_err!(policy.check("foo", None, None, None));
+        assert_err!(policy.check("foo", Some(&scopes(&["*"])), None, None));
+        assert_err!(policy.check("foo", Some(&scopes(&["fo*"])), None, None));
+        assert_err!(policy.check("foo", Some(&scopes(&["foo-bar"])), None, None));
+    }
+
+    #[test]
+    fn forbid_dirty_vcs() {
+        let policy = CratePublishPolicy {
+            forbid_dirty_vcs: true,
+            ..policy()
+        };
+
+        assert_ok!(policy.check("foo", None, Some(false), None));
+
+        let error = assert_err!(policy.check("foo", None, Some(true), None));
+        assert!(error.to_string().contains("`--allow-dirty`"));
+
+        let error = assert_err!(policy.check("foo", None, None, None));
+        assert!(error.to_string().contains("from a git checkout"));
+    }
+
+    #[test]
+    fn require_rust_version() {
+        let policy = CratePublishPolicy {
+            require_rust_version: true,
+            ..policy()
+        };
+
+        assert_ok!(policy.check("foo", None, None, Some("1.70")));
+        assert_err!(policy.check("foo", None, None, None));
+    }
+}
@@ -0,0 +1,275 @@
+//! Transfers hand a crate over to a new owner. An owner proposes the transfer,
+//! and once the recipient accepts it, all current owners are replaced by the
+//! recipient at once.
+
+use chrono::{Duration, NaiveDateTime, Utc};
+use diesel::dsl::exists;
+use diesel::prelude::*;
+
+use crate::config;
+use crate::email::{Email, Emails};
+use crate::models::{Crate, CrateOwner, CrateOwnerInvitation, Owner, OwnerKind, Team, User};
+use crate::schema::{crate_owner_invitations, crate_owners, crate_transfers, crates, teams, users};
+use crate::util::errors::{cargo_err, AppResult};
+
+/// A pending transfer of a crate to a user or a team.
+#[derive(Clone, Debug, Queryable, Identifiable, Selectable)]
+#[diesel(
+    table_name = crate_transfers,
+    primary_key(crate_id),
+    check_for_backend(diesel::pg::Pg)
+)]
+pub struct CrateTransfer {
+    pub crate_id: i32,
+    pub proposed_by: i32,
+    pub recipient_user_id: Option<i32>,
+    pub recipient_team_id: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+impl CrateTransfer {
+    /// Proposes to hand the crate over to the recipient, which replaces any
+    /// transfer of the crate that was proposed before.
+    pub fn propose(
+        krate: &Crate,
+        proposed_by: &User,
+        recipient: &Owner,
+        conn: &mut PgConnection,
+    ) -> QueryResult<Self> {
+        let (recipient_user_id, recipient_team_id) = match recipient {
+            Owner::User(user) => (Some(user.id), None),
+            Owner::Team(team) => (None, Some(team.id)),
+        };
+
+        let values = (
+            crate_transfers::crate_id.eq(krate.id),
+            crate_transfers::proposed_by.eq(proposed_by.id),
+            crate_transfers::recipient_user_id.eq(recipient_user_id),
+            crate_transfers::recipient_team_id.eq(recipient_team_id),
+            crate_transfers::created_at.eq(Utc::now().naive_utc()),
+        );
+
+        diesel::insert_into(crate_transfers::table)
+            .values(values)
+            .on_conflict(crate_transfers::crate_id)
+            .do_update()
+            .set(values)
+            .returning(Self::as_returning())
+            .get_result(conn)
+    }
+
+    pub fn find(crate_id: i32, conn: &mut PgConnection) -> QueryResult<Option<Self>> {
+        crate_transfers::table
+            .find(crate_id)
+            .select(Self::as_select())
+            .first(conn)
+            .optional()
+    }
+
+    /// Returns the pending transfers to the user, together with the crates
+    /// and the logins of the users who proposed them.
+    pub fn for_recipient(
+        user: &User,
+        conn: &mut PgConnection,
+    ) -> QueryResult<Vec<(Self, Crate, String)>> {
+        crate_transfers::table
+            .inner_join(crates::table)
+            .inner_join(users::table.on(users::id.eq(crate_transfers::proposed_by)))
+            .filter(crate_transfers::recipient_user_id.eq(user.id))
+            .select((Self::as_select(), Crate::as_select(), users::gh_login))
+            .order(crates::name)
+            .load(conn)
+    }
+
+    pub fn recipient(&self, conn: &mut PgConnection) -> QueryResult<Owner> {
+        match (self.recipient_user_id, self.recipient_team_id) {
+            (Some(user_id), _) => User::find(conn, user_id).map(Owner::User),
+            (None, Some(team_id)) => teams::table
+                .find(team_id)
+                .first::<Team>(conn)
+                .map(Owner::Team),
+            (None, None) => Err(diesel::NotFound),
+        }
+    }
+
+    pub fn proposer(&self, conn: &mut PgConnection) -> QueryResult<User> {
+        users::table.find(self.proposed_by).first(conn)
+    }
+
+    /// Transfers expire after the same number of days as ownership
+    /// invitations of the crate.
+    pub fn expires_at(&self, config: &config::Server, krate: &Crate) -> NaiveDateTime {
+        let expiration_days = CrateOwnerInvitation::expiration_days(
+            config,
+            krate.ownership_invitations_expiration_days,
+        );
+        self.created_at + Duration::days(expiration_days)
+    }
+
+    pub fn is_expired(&self, config: &config::Server, krate: &Crate) -> bool {
+        self.expires_at(config, krate) <= Utc::now().naive_utc()
+    }
+
+    /// Replaces all owners of the crate with the recipient and deletes the
+    /// pending ownership invitations of the crate.
+    ///
+    /// If the crate is handed over to a team, the team member who accepted
+    /// the transfer becomes an owner too, since teams can't manage the owners
+    /// of their crates.
+    ///
+    /// The transfer is locked while it is accepted, so that it can't be
+    /// cancelled or replaced at the same time, and it is only accepted if
+    /// the user who proposed it is still an owner of the crate.
+    pub fn accept(&self, accepted_by: &User, conn: &mut PgConnection) -> AppResult<()> {
+        conn.transaction(|conn| {
+            let locked = crate_transfers::table
+                .find(self.crate_id)
+                .filter(crate_transfers::proposed_by.eq(self.proposed_by))
+                .filter(crate_transfers::created_at.eq(self.created_at))
+                .select(Self::as_select())
+                .for_update()
+                .first(conn)
+                .optional()?;
+            if locked.is_none() {
+                return Err(cargo_err("there is no pending transfer for this crate"));
+            }
+
+            let proposer_is_owner = diesel::select(exists(
+                CrateOwner::by_owner_kind(OwnerKind::User)
+                    .filter(crate_owners::crate_id.eq(self.crate_id))
+                    .filter(crate_owners::owner_id.eq(self.proposed_by)),
+            ))
+            .get_result::<bool>(conn)?;
+            if !proposer_is_owner {
+                return Err(cargo_err(
+                    "the user who proposed the transfer is no longer an owner of the crate",
+                ));
+            }
+
+            diesel::update(crate_owners::table.filter(crate_owners::crate_id.eq(self.crate_id)))
+                .set(crate_owners::deleted.eq(true))
+                .execute(conn)?;
+
+            let mut new_owners = vec![(accepted_by.id, OwnerKind::User)];
+            if let Some(team_id) = self.recipient_team_id {
+                new_owners.push((team_id, OwnerKind::Team));
+            }
+
+            for (owner_id, owner_kind) in new_owners {
+                diesel::insert_into(crate_owners::table)
+                    .values(&CrateOwner {
+                        crate_id: self.crate_id,
+                        owner_id,
+                        created_by: self.proposed_by,
+                        owner_kind,
+                        email_notifications: true,
+                    })
+                    .on_conflict(crate_owners::table.primary_key())
+                    .do_update()
+                    .set((
+                        crate_owners::deleted.eq(false),
+                        crate_owners::can_publish.eq(true),
+                    ))
+                    .execute(conn)?;
+            }
+
+            diesel::delete(
+                crate_owner_invitations::table
+                    .filter(crate_owner_invitations::crate_id.eq(self.crate_id)),
+            )
+            .execute(conn)?;
+
+            // Rolls back the transaction if the transfer is already gone.
+            if diesel::delete(self).execute(conn)? == 0 {
+                return Err(cargo_err("there is no pending transfer for this crate"));
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Deletes the transfers that the user proposed for the crate, which is
+    /// used when the user is removed as an owner.
+    pub fn delete_proposed_by(
+        crate_id: i32,
+        user_id: i32,
+        conn: &mut PgConnection,
+    ) -> QueryResult<usize> {
+        diesel::delete(
+            crate_transfers::table
+                .filter(crate_transfers::crate_id.eq(crate_id))
+                .filter(crate_transfers::proposed_by.eq(user_id)),
+        )
+        .execute(conn)
+    }
+
+    /// Deletes the transfer, because an owner cancelled it or the recipient
+    /// declined it.
+    pub fn cancel(&self, conn: &mut PgConnection) -> QueryResult<()> {
+        diesel::delete(self).execute(conn)?;
+        Ok(())
+    }
+}
+
+/// Sent to the recipient of a transfer.
+pub struct CrateTransferProposedEmail<'a> {
+    pub domain: &'a str,
+    pub proposed_by: &'a str,
+    pub crate_name: &'a str,
+    pub expires_at: NaiveDateTime,
+}
+
+impl Email for CrateTransferProposedEmail<'_> {
+    const SUBJECT: &'static str = "Crate transfer";
+
+    fn body(&self) -> String {
+        format!(
+            "{proposed_by} wants to hand the crate {crate_name} over to you. \
+If you accept it, you become the only owner of https://{domain}/crates/{crate_name} \
+and all current owners are removed.\n
+The transfer expires on {expires_at} UTC if you don't accept it.",
+            proposed_by = self.proposed_by,
+            crate_name = self.crate_name,
+            domain = self.domain,
+            expires_at = self.expires_at.format("%Y-%m-%d at %H:%M:%S"),
+        )
+    }
+}
+
+/// Sent to the previous owners of a crate when a transfer was accepted.
+struct CrateTransferredEmail<'a> {
+    crate_name: &'a str,
+    recipient: &'a str,
+}
+
+impl Email for CrateTransferredEmail<'_> {
+    const SUBJECT: &'static str = "Crate transferred";
+
+    fn body(&self) -> String {
+        format!(
+            "The crate {crate_name} has been handed over to {recipient}, \
+who accepted the transfer. You are no longer an owner of the crate.",
+            crate_name = self.crate_name,
+            recipient = self.recipient,
+        )
+    }
+}
+
+/// Sends the emails of an accepted transfer to the given addresses of the
+/// previous owners. Failures are only logged.
+pub fn notify_previous_owners(
+    recipients: &[String],
+    crate_name: &str,
+    recipient: &str,
+    emails: &Emails,
+) {
+    for email_address in recipients {
+        let email = CrateTransferredEmail {
+            crate_name,
+            recipient,
+        };
+        if let Err(error) = emails.send(email_address, email) {
+            error!(?error, recipient = ?email_address, "Failed to send crate transfer notification");
+        }
+    }
+}
@@ -12,8 +12,9 @@ use crate::controllers::helpers::pagination::*;
 use crate::email::Email;
 use crate::models::version::TopVersions;
 use crate::models::{
-    CrateOwner, CrateOwnerInvitation, Dependency, NewCrateOwnerInvitationOutcome, Owner, OwnerKind,
-    ReverseDependency, User, Version,
+    without_quarantined_versions, CrateOwner, CrateOwnerInvitation, CrateTransfer, Dependency,
+    NewCrateOwnerInvitationOutcome, Owner, OwnerKind, ReverseDependency, User, Version,
+    VersionAttestation,
 };
 use crate::util::errors::{cargo_err, AppResult};
 
@@ -47,6 +48,7 @@ pub struct Crate {
     pub repository: Option<String>,
     pub max_upload_size: Option<i32>,
     pub max_features: Option<i16>,
+    pub ownership_invitations_expiration_days: Option<i32>,
 }
 
 /// We literally never want to select `textsearchable_index_col`
@@ -63,6 +65,7 @@ type AllColumns = (
     crates::repository,
     crates::max_upload_size,
     crates::max_features,
+    crates::ownership_invitations_expiration_days,
 );
 
 pub const ALL_COLUMNS: AllColumns = (
@@ -77,6 +80,7 @@ pub const ALL_COLUMNS: AllColumns = (
     crates::repository,
     crates::max_upload_size,
     crates::max_features,
+    crates::ownership_invitations_expiration_days,
 );
 
 pub const MAX_NAME_LENGTH: usize = 64;
@@ -102,6 +106,7 @@ pub struct NewCrate<'a> {
     pub repository: Option<&'a str>,
     pub max_upload_size: Option<i32>,
     pub max_features: Option<i16>,
+    pub ownership_invitations_expiration_days: Option<i32>,
 }
 
 impl<'a> NewCrate<'a> {
@@ -340,15 +345,40 @@ impl Crate {
     }
 
     pub fn owners(&self, conn: &mut PgConnection) -> QueryResult<Vec<Owner>> {
-        let users = CrateOwner::by_owner_kind(OwnerKind::User)
-            .filter(crate_owners::crate_id.eq(self.id))
+        self.load_owners(None, conn)
+    }
+
+    /// Returns the owners that are allowed to publish new versions of the crate, which excludes
+    /// the owners that are only allowed to yank.
+    pub fn publishing_owners(&self, conn: &mut PgConnection) -> QueryResult<Vec<Owner>> {
+        self.load_owners(Some(true), conn)
+    }
+
+    /// Returns the owners that are not allowed to publish new versions of the crate.
+    pub fn yank_only_owners(&self, conn: &mut PgConnection) -> QueryResult<Vec<Owner>> {
+        self.load_owners(Some(false), conn)
+    }
+
+    fn load_owners(
+        &self,
+        can_publish: Option<bool>,
+        conn: &mut PgConnection,
+    ) -> QueryResult<Vec<Owner>> {
+        let owners_query = |kind| {
+            let query = CrateOwner::by_owner_kind(kind).filter(crate_owners::crate_id.eq(self.id));
+            match can_publish {
+                Some(can_publish) => query.filter(crate_owners::can_publish.eq(can_publish)),
+                None => query,
+            }
+        };
+
+        let users = owners_query(OwnerKind::User)
             .inner_join(users::table)
             .select(users::all_columns)
             .load(conn)?
             .into_iter()
             .map(Owner::User);
-        let teams = CrateOwner::by_owner_kind(OwnerKind::Team)
-            .filter(crate_owners::crate_id.eq(self.id))
+        let teams = owners_query(OwnerKind::Team)
             .inner_join(teams::table)
             .select(teams::all_columns)
             .load(conn)?
@@ -358,6 +388,21 @@ impl Crate {
         Ok(users.chain(teams).collect())
     }
 
+    /// Sets whether an owner of the crate is allowed to publish new versions, or only to yank
+    /// them.
+    pub fn set_owner_can_publish(
+        &self,
+        owner: &Owner,
+        can_publish: bool,
+        conn: &mut PgConnection,
+    ) -> QueryResult<()> {
+        let target = crate_owners::table.find((self.id, owner.id(), owner.kind()));
+        diesel::update(target)
+            .set(crate_owners::can_publish.eq(can_publish))
+            .execute(conn)?;
+        Ok(())
+    }
+
     pub fn owner_add(
         &self,
         app: &App,
@@ -372,22 +417,19 @@ impl Crate {
         match owner {
             // Users are invited and must accept before being added
             Owner::User(user) => {
-                let config = &app.config;
-                match CrateOwnerInvitation::create(user.id, req_user.id, self.id, conn, config)? {
+                let expiration_days = CrateOwnerInvitation::expiration_days(
+                    &app.config,
+                    self.ownership_invitations_expiration_days,
+                );
+                match CrateOwnerInvitation::create(
+                    user.id,
+                    req_user.id,
+                    self.id,
+                    conn,
+                    expiration_days,
+                )? {
                     NewCrateOwnerInvitationOutcome::InviteCreated { plaintext_token } => {
-                        if let Ok(Some(recipient)) = user.verified_email(conn) {
-                            // Swallow any error. Whether or not the email is sent, the invitation
-                            // entry will be created in the database and the user will see the
-                            // invitation when they visit https://crates.io/me/pending-invites/.
-                            let email = OwnerInviteEmail {
-                                user_name: &req_user.gh_login,
-                                domain: &app.emails.domain,
-                                crate_name: &self.name,
-                                token: plaintext_token,
-                            };
-
-                            let _ = app.emails.send(&recipient, email);
-                        }
+                        self.send_owner_invite_email(app, conn, req_user, &user, plaintext_token);
 
                         Ok(format!(
                             "user {} has been invited to be an owner of crate {}",
@@ -424,6 +466,41 @@ impl Crate {
         }
     }
 
+    /// Sends the email for an ownership invitation to the invited user, if they have a verified
+    /// email address.
+    pub fn send_owner_invite_email(
+        &self,
+        app: &App,
+        conn: &mut PgConnection,
+        inviter: &User,
+        invitee: &User,
+        token: SecretString,
+    ) {
+        if let Ok(Some(recipient)) = invitee.verified_email(conn) {
+            // Swallow any error. Whether or not the email is sent, the invitation
+            // entry will be created in the database and the user will see the
+            // invitation when they visit https://crates.io/me/pending-invites/.
+            let email = OwnerInviteEmail {
+                user_name: &inviter.gh_login,
+                domain: &app.emails.domain,
+                crate_name: &self.name,
+                token,
+            };
+
+            let _ = app.emails.send(&recipient, email);
+        }
+    }
+
+    /// Returns the verified email addresses of the user owners of the crate.
+    pub fn owner_emails(&self, conn: &mut PgConnection) -> QueryResult<Vec<String>> {
+        CrateOwner::by_owner_kind(OwnerKind::User)
+            .filter(crate_owners::crate_id.eq(self.id))
+            .inner_join(emails::table.on(emails::user_id.eq(crate_owners::owner_id)))
+            .filter(emails::verified.eq(true))
+            .select(emails::email)
+            .load(conn)
+    }
+
     pub fn owner_remove(&self, conn: &mut PgConnection, login: &str) -> AppResult<()> {
         let owner = Owner::find_by_login(conn, login)?;
 
@@ -431,18 +508,30 @@ impl Crate {
         diesel::update(target)
             .set(crate_owners::deleted.eq(true))
             .execute(conn)?;
+
+        // Transfers that a removed owner proposed must not be accepted anymore
+        if let Owner::User(user) = &owner {
+            CrateTransfer::delete_proposed_by(self.id, user.id, conn)?;
+        }
+
         Ok(())
     }
 
-    /// Returns (dependency, dependent crate name, dependent crate downloads)
+    /// Returns the crates that depend on this crate, sorted by their number
+    /// of downloads and their name.
+    ///
+    /// If `after` is set to the downloads and name of a dependent crate, only
+    /// the crates after it are returned, which is used for seek-based
+    /// pagination.
     #[instrument(skip_all, fields(krate.name = %self.name))]
     pub(crate) fn reverse_dependencies(
         &self,
         conn: &mut PgConnection,
         options: PaginationOptions,
+        after: Option<(i32, &str)>,
     ) -> QueryResult<(Vec<ReverseDependency>, i64)> {
         use diesel::sql_query;
-        use diesel::sql_types::{BigInt, Integer};
+        use diesel::sql_types::{BigInt, Integer, Nullable, Text};
 
         let offset = options.offset().unwrap_or_default();
         let rows: Vec<WithCount<ReverseDependency>> =
@@ -450,6 +539,8 @@ impl Crate {
                 .bind::<Integer, _>(self.id)
                 .bind::<BigInt, _>(offset)
                 .bind::<BigInt, _>(options.per_page)
+                .bind::<Nullable<Integer>, _>(after.map(|(downloads, _)| downloads))
+                .bind::<Nullable<Text>, _>(after.map(|(_, name)| name))
                 .load(conn)?;
 
         Ok(rows.records_and_total())
@@ -460,7 +551,8 @@ impl Crate {
         &self,
         conn: &mut PgConnection,
     ) -> QueryResult<Vec<crates_io_index::Crate>> {
-        let mut versions: Vec<Version> = self.all_versions().load(conn)?;
+        let mut versions: Vec<Version> =
+            without_quarantined_versions(self.all_versions()).load(conn)?;
 
         // We sort by `created_at` by default, but since tests run within a
         // single database transaction the versions will all have the same
@@ -474,10 +566,17 @@ impl Crate {
 
         let deps = deps.grouped_by(&versions);
 
+        let attestations: Vec<VersionAttestation> = VersionAttestation::belonging_to(&versions)
+            .select(VersionAttestation::as_select())
+            .load(conn)?;
+
+        let attestations = attestations.grouped_by(&versions);
+
         versions
             .into_iter()
             .zip(deps)
-            .map(|(version, deps)| {
+            .zip(attestations)
+            .map(|((version, deps), attestations)| {
                 let mut deps = deps
                     .into_iter()
                     .map(|(dep, name)| {
@@ -530,6 +629,10 @@ impl Crate {
                     features,
                     links: version.links,
                     rust_version: version.rust_version,
+                    attestation: attestations
+                        .into_iter()
+                        .next()
+                        .map(|attestation| attestation.checksum),
                     features2,
                     v,
                 };
@@ -564,8 +667,9 @@ or go to https://{domain}/me/pending-invites to manage all of your crate ownersh
 }
 
 pub trait CrateVersions {
+    /// Returns the versions that are neither yanked nor quarantined.
     fn versions(&self) -> versions::BoxedQuery<'_, Pg> {
-        self.all_versions().filter(versions::yanked.eq(false))
+        without_quarantined_versions(self.all_versions().filter(versions::yanked.eq(false)))
     }
 
     fn all_versions(&self) -> versions::BoxedQuery<'_, Pg>;
@@ -1,34 +1,41 @@
 -- Apply pagination to the whole thing
-SELECT *, COUNT(*) OVER () as total FROM (
-    -- Multple dependencies can exist, make it distinct
-    SELECT DISTINCT ON (crate_downloads, crate_name)
-    dependencies.*,
-    crates.downloads AS crate_downloads,
-    crates.name AS crate_name
-    FROM dependencies
-    -- We only want the crates whose *max* version is dependent, so we join on a
-    -- subselect that includes the versions with their ordinal position
-    INNER JOIN (
-        SELECT DISTINCT ON (crate_id)
-           *
-        FROM versions
-        WHERE NOT yanked
+SELECT * FROM (
+    SELECT *, COUNT(*) OVER () as total FROM (
+        -- Multple dependencies can exist, make it distinct
+        SELECT DISTINCT ON (crate_downloads, crate_name)
+        dependencies.*,
+        crates.downloads AS crate_downloads,
+        crates.name AS crate_name
+        FROM dependencies
+        -- We only want the crates whose *max* version is dependent, so we join on a
+        -- subselect that includes the versions with their ordinal position
+        INNER JOIN (
+            SELECT DISTINCT ON (crate_id)
+               *
+            FROM versions
+            WHERE NOT yanked
+            ORDER BY
+                crate_id,
+                semver_no_prerelease DESC NULLS LAST,
+                id DESC
+        ) versions
+          ON versions.id = dependencies.version_id
+        INNER JOIN crates
+          ON crates.id = versions.crate_id
+        WHERE dependencies.crate_id = $1
+        -- this ORDER BY is redundant with the outer one but benefits
+        -- the `DISTINCT ON`
         ORDER BY
-            crate_id,
-            semver_no_prerelease DESC NULLS LAST,
-            id DESC
-    ) versions
-      ON versions.id = dependencies.version_id
-    INNER JOIN crates
-      ON crates.id = versions.crate_id
-    WHERE dependencies.crate_id = $1
-    -- this ORDER BY is redundant with the outer one but benefits
-    -- the `DISTINCT ON`
-    ORDER BY
-        crate_downloads DESC,
-        crate_name ASC,
-        dependencies.id ASC
+            crate_downloads DESC,
+            crate_name ASC,
+            dependencies.id ASC
+    ) t
 ) t
+-- Skip the results up to and including the seek position, if there is one.
+-- This happens after counting, so that the total includes all results.
+WHERE $4::int4 IS NULL
+   OR crate_downloads < $4
+   OR (crate_downloads = $4 AND crate_name > $5)
 ORDER BY
     crate_downloads DESC,
     crate_name ASC
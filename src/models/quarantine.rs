@@ -0,0 +1,372 @@
+//! Quarantines hide a crate or a single version from search, the index and downloads while it is
+//! being investigated, without deleting any data. Lifting the quarantine restores everything.
+
+use chrono::NaiveDateTime;
+use diesel::dsl::{exists, not};
+use diesel::pg::Pg;
+use diesel::prelude::*;
+
+use crate::email::{Email, Emails};
+use crate::models::{Crate, Owner, Version};
+use crate::schema::{crate_quarantines, quarantine_actions, version_quarantines, versions};
+use crate::sql::pg_enum;
+
+pg_enum! {
+    pub enum QuarantineActionKind {
+        Quarantine = 0,
+        Lift = 1,
+    }
+}
+
+/// An entry of the audit trail of quarantines.
+#[derive(Clone, Debug, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = quarantine_actions, check_for_backend(diesel::pg::Pg))]
+pub struct QuarantineAction {
+    pub id: i32,
+    pub crate_id: i32,
+    pub version_id: Option<i32>,
+    pub action: QuarantineActionKind,
+    pub reason: Option<String>,
+    pub actor: String,
+    pub created_at: NaiveDateTime,
+}
+
+impl QuarantineAction {
+    /// Returns the audit trail of a crate and all of its versions, oldest first.
+    pub fn for_crate(crate_id: i32, conn: &mut PgConnection) -> QueryResult<Vec<Self>> {
+        quarantine_actions::table
+            .filter(quarantine_actions::crate_id.eq(crate_id))
+            .order(quarantine_actions::id)
+            .select(QuarantineAction::as_select())
+            .load(conn)
+    }
+}
+
+/// A crate or a single version of it that can be quarantined.
+#[derive(Clone, Copy, Debug)]
+pub enum QuarantineTarget<'a> {
+    Crate(&'a Crate),
+    Version(&'a Crate, &'a Version),
+}
+
+impl QuarantineTarget<'_> {
+    pub fn krate(&self) -> &Crate {
+        match self {
+            QuarantineTarget::Crate(krate) | QuarantineTarget::Version(krate, _) => krate,
+        }
+    }
+
+    fn version_id(&self) -> Option<i32> {
+        match self {
+            QuarantineTarget::Crate(_) => None,
+            QuarantineTarget::Version(_, version) => Some(version.id),
+        }
+    }
+
+    /// Returns the reason of the quarantine, if the target is quarantined.
+    pub fn quarantine_reason(&self, conn: &mut PgConnection) -> QueryResult<Option<String>> {
+        match self {
+            QuarantineTarget::Crate(krate) => crate_quarantines::table
+                .find(krate.id)
+                .select(crate_quarantines::reason)
+                .first(conn)
+                .optional(),
+            QuarantineTarget::Version(_, version) => version_quarantines::table
+                .find(version.id)
+                .select(version_quarantines::reason)
+                .first(conn)
+                .optional(),
+        }
+    }
+
+    /// Quarantines the target and records the action in the audit trail.
+    ///
+    /// Returns `false` without recording anything if the target is already quarantined.
+    pub fn quarantine(
+        &self,
+        reason: &str,
+        actor: &str,
+        conn: &mut PgConnection,
+    ) -> QueryResult<bool> {
+        let inserted = match self {
+            QuarantineTarget::Crate(krate) => diesel::insert_into(crate_quarantines::table)
+                .values((
+                    crate_quarantines::crate_id.eq(krate.id),
+                    crate_quarantines::reason.eq(reason),
+                    crate_quarantines::created_by.eq(actor),
+                ))
+                .on_conflict_do_nothing()
+                .execute(conn)?,
+            QuarantineTarget::Version(_, version) => {
+                diesel::insert_into(version_quarantines::table)
+                    .values((
+                        version_quarantines::version_id.eq(version.id),
+                        version_quarantines::reason.eq(reason),
+                        version_quarantines::created_by.eq(actor),
+                    ))
+                    .on_conflict_do_nothing()
+                    .execute(conn)?
+            }
+        };
+
+        if inserted == 0 {
+            return Ok(false);
+        }
+
+        self.record(QuarantineActionKind::Quarantine, Some(reason), actor, conn)?;
+        Ok(true)
+    }
+
+    /// Lifts the quarantine of the target and records the action in the audit trail.
+    ///
+    /// Returns `false` without recording anything if the target is not quarantined.
+    pub fn lift(
+        &self,
+        reason: Option<&str>,
+        actor: &str,
+        conn: &mut PgConnection,
+    ) -> QueryResult<bool> {
+        let deleted = match self {
+            QuarantineTarget::Crate(krate) => {
+                diesel::delete(crate_quarantines::table.find(krate.id)).execute(conn)?
+            }
+            QuarantineTarget::Version(_, version) => {
+                diesel::delete(version_quarantines::table.find(version.id)).execute(conn)?
+            }
+        };
+
+        if deleted == 0 {
+            return Ok(false);
+        }
+
+        self.record(QuarantineActionKind::Lift, reason, actor, conn)?;
+        Ok(true)
+    }
+
+    fn record(
+        &self,
+        action: QuarantineActionKind,
+        reason: Option<&str>,
+        actor: &str,
+        conn: &mut PgConnection,
+    ) -> QueryResult<()> {
+        diesel::insert_into(quarantine_actions::table)
+            .values((
+                quarantine_actions::crate_id.eq(self.krate().id),
+                quarantine_actions::version_id.eq(self.version_id()),
+                quarantine_actions::action.eq(action),
+                quarantine_actions::reason.eq(reason),
+                quarantine_actions::actor.eq(actor),
+            ))
+            .execute(conn)?;
+
+        Ok(())
+    }
+
+    /// Notifies the user owners of the crate with a verified email address that the target was
+    /// quarantined, or that its quarantine was lifted. Failures are only logged.
+    pub fn notify_owners(
+        &self,
+        action: QuarantineActionKind,
+        reason: Option<&str>,
+        emails: &Emails,
+        conn: &mut PgConnection,
+    ) -> QueryResult<()> {
+        let krate = self.krate();
+        let version = match self {
+            QuarantineTarget::Crate(_) => None,
+            QuarantineTarget::Version(_, version) => Some(version.num.as_str()),
+        };
+
+        for owner in krate.owners(conn)? {
+            let Owner::User(user) = owner else {
+                continue;
+            };
+
+            let Some(recipient) = user.verified_email(conn)? else {
+                continue;
+            };
+
+            let result = match action {
+                QuarantineActionKind::Quarantine => emails.send(
+                    &recipient,
+                    QuarantineEmail {
+                        domain: &emails.domain,
+                        crate_name: &krate.name,
+                        version,
+                        reason,
+                    },
+                ),
+                QuarantineActionKind::Lift => emails.send(
+                    &recipient,
+                    QuarantineLiftedEmail {
+                        domain: &emails.domain,
+                        crate_name: &krate.name,
+                        version,
+                    },
+                ),
+            };
+
+            if let Err(error) = result {
+                error!(?error, ?recipient, "Failed to send quarantine notification");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns whether the crate is quarantined.
+pub fn is_crate_quarantined(crate_id: i32, conn: &mut PgConnection) -> QueryResult<bool> {
+    diesel::select(exists(crate_quarantines::table.find(crate_id))).get_result(conn)
+}
+
+/// Returns whether the version, or the crate it belongs to, is quarantined.
+pub fn is_version_quarantined(
+    crate_id: i32,
+    version_id: i32,
+    conn: &mut PgConnection,
+) -> QueryResult<bool> {
+    diesel::select(
+        exists(crate_quarantines::table.find(crate_id))
+            .or(exists(version_quarantines::table.find(version_id))),
+    )
+    .get_result(conn)
+}
+
+/// Returns whether the version with the given ID, or the crate it belongs to,
+/// is quarantined.
+pub fn is_version_id_quarantined(version_id: i32, conn: &mut PgConnection) -> QueryResult<bool> {
+    let crate_quarantined = crate_quarantines::table
+        .inner_join(versions::table.on(versions::crate_id.eq(crate_quarantines::crate_id)))
+        .filter(versions::id.eq(version_id));
+
+    diesel::select(
+        exists(version_quarantines::table.find(version_id)).or(exists(crate_quarantined)),
+    )
+    .get_result(conn)
+}
+
+/// Excludes the quarantined versions from a query of the `versions` table.
+pub fn without_quarantined_versions(
+    query: versions::BoxedQuery<'_, Pg>,
+) -> versions::BoxedQuery<'_, Pg> {
+    query.filter(not(exists(
+        version_quarantines::table.filter(version_quarantines::version_id.eq(versions::id)),
+    )))
+}
+
+struct QuarantineEmail<'a> {
+    domain: &'a str,
+    crate_name: &'a str,
+    version: Option<&'a str>,
+    reason: Option<&'a str>,
+}
+
+impl Email for QuarantineEmail<'_> {
+    const SUBJECT: &'static str = "Crate quarantined";
+
+    fn body(&self) -> String {
+        let target = match self.version {
+            Some(version) => format!("Version {version} of your crate {}", self.crate_name),
+            None => format!("Your crate {}", self.crate_name),
+        };
+
+        format!(
+            "{target} has been quarantined by the crates.io team while it is being investigated. \
+It is hidden from search and the index, and it can't be downloaded until the quarantine is lifted.\n
+Reason: {reason}\n
+If you think this is a mistake, please contact help@crates.io and mention \
+https://{domain}/crates/{crate_name}.",
+            reason = self.reason.unwrap_or("not given"),
+            domain = self.domain,
+            crate_name = self.crate_name,
+        )
+    }
+}
+
+struct QuarantineLiftedEmail<'a> {
+    domain: &'a str,
+    crate_name: &'a str,
+    version: Option<&'a str>,
+}
+
+impl Email for QuarantineLiftedEmail<'_> {
+    const SUBJECT: &'static str = "Crate quarantine lifted";
+
+    fn body(&self) -> String {
+        let target = match self.version {
+            Some(version) => format!("version {version} of your crate {}", self.crate_name),
+            None => format!("your crate {}", self.crate_name),
+        };
+
+        format!(
+            "The quarantine of {target} has been lifted by the crates.io team. \
+It is available again at https://{domain}/crates/{crate_name}.",
+            domain = self.domain,
+            crate_name = self.crate_name,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::pg_connection;
+    use crate::typosquat::test_util::Faker;
+
+    #[test]
+    fn quarantine_and_lift() -> anyhow::Result<()> {
+        let mut faker = Faker::new(pg_connection());
+        let user = faker.user("alice")?;
+        let (krate, version) = faker.crate_and_version("foo", "", &user, 0)?;
+        let mut conn = faker.into_conn();
+        let conn = &mut conn;
+
+        let target = QuarantineTarget::Version(&krate, &version);
+        assert!(!is_version_quarantined(krate.id, version.id, conn)?);
+
+        assert!(target.quarantine("malware", "admin", conn)?);
+        assert!(!target.quarantine("again", "admin", conn)?);
+        assert!(is_version_quarantined(krate.id, version.id, conn)?);
+        assert!(!is_crate_quarantined(krate.id, conn)?);
+        assert_some_eq!(target.quarantine_reason(conn)?, "malware");
+
+        assert!(target.lift(Some("false positive"), "admin", conn)?);
+        assert!(!target.lift(None, "admin", conn)?);
+        assert!(!is_version_quarantined(krate.id, version.id, conn)?);
+
+        let target = QuarantineTarget::Crate(&krate);
+        assert!(target.quarantine("malware", "admin", conn)?);
+        assert!(is_crate_quarantined(krate.id, conn)?);
+        assert!(is_version_quarantined(krate.id, version.id, conn)?);
+
+        let trail = QuarantineAction::for_crate(krate.id, conn)?
+            .into_iter()
+            .map(|action| (action.version_id, action.action, action.reason))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            trail,
+            [
+                (
+                    Some(version.id),
+                    QuarantineActionKind::Quarantine,
+                    Some("malware".into())
+                ),
+                (
+                    Some(version.id),
+                    QuarantineActionKind::Lift,
+                    Some("false positive".into())
+                ),
+                (
+                    None,
+                    QuarantineActionKind::Quarantine,
+                    Some("malware".into())
+                ),
+            ]
+        );
+
+        Ok(())
+    }
+}
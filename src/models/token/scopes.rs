@@ -3,6 +3,7 @@ use diesel::deserialize::{self, FromSql};
 use diesel::pg::Pg;
 use diesel::serialize::{self, IsNull, Output, ToSql};
 use diesel::sql_types::Text;
+use semver::{Version, VersionReq};
 use std::io::Write;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, AsExpression, Serialize)]
@@ -13,6 +14,13 @@ pub enum EndpointScope {
     PublishUpdate,
     Yank,
     ChangeOwners,
+    /// Reading private data of the user, like their email address, their
+    /// notification settings and their pending ownership invitations.
+    ReadPrivate,
+    /// Listing and revoking the API tokens of the user.
+    ManageTokens,
+    /// Following and unfollowing crates.
+    Follow,
 }
 
 impl From<&EndpointScope> for &[u8] {
@@ -22,6 +30,9 @@ impl From<&EndpointScope> for &[u8] {
             EndpointScope::PublishUpdate => b"publish-update",
             EndpointScope::Yank => b"yank",
             EndpointScope::ChangeOwners => b"change-owners",
+            EndpointScope::ReadPrivate => b"read-private",
+            EndpointScope::ManageTokens => b"manage-tokens",
+            EndpointScope::Follow => b"follow",
         }
     }
 }
@@ -42,11 +53,22 @@ impl TryFrom<&[u8]> for EndpointScope {
             b"publish-update" => Ok(EndpointScope::PublishUpdate),
             b"yank" => Ok(EndpointScope::Yank),
             b"change-owners" => Ok(EndpointScope::ChangeOwners),
+            b"read-private" => Ok(EndpointScope::ReadPrivate),
+            b"manage-tokens" => Ok(EndpointScope::ManageTokens),
+            b"follow" => Ok(EndpointScope::Follow),
             _ => Err("Unrecognized enum variant".to_string()),
         }
     }
 }
 
+impl EndpointScope {
+    /// Whether the endpoints with this scope only read data of the user, in
+    /// which case tokens with crate scopes may use them too.
+    pub fn is_read_only(&self) -> bool {
+        matches!(self, EndpointScope::ReadPrivate)
+    }
+}
+
 impl FromSql<Text, Pg> for EndpointScope {
     fn from_sql(bytes: diesel::pg::PgValue<'_>) -> deserialize::Result<Self> {
         let value = <String as FromSql<Text, Pg>>::from_sql(bytes)?;
@@ -54,6 +76,12 @@ impl FromSql<Text, Pg> for EndpointScope {
     }
 }
 
+/// A pattern of crate names that a token is restricted to, like `serde` or
+/// `serde*`.
+///
+/// The pattern can be followed by `@` and a version requirement (like
+/// `serde@^1.0`) or `@prerelease` to further restrict the token to matching
+/// versions. Such tokens can only be used for publishing and yanking.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 #[serde(transparent)]
 pub struct CrateScope {
@@ -99,27 +127,86 @@ impl ToSql<Text, Pg> for CrateScope {
 
 impl CrateScope {
     fn is_valid_pattern(pattern: &str) -> bool {
-        if pattern.is_empty() {
+        let (name_pattern, versions) = split_pattern(pattern);
+        if let Some(versions) = versions {
+            if versions != PRERELEASE && VersionReq::parse(versions).is_err() {
+                return false;
+            }
+        }
+
+        if name_pattern.is_empty() {
             return false;
         }
 
-        if pattern == "*" {
+        if name_pattern == "*" {
             return true;
         }
 
-        let name_without_wildcard = pattern.strip_suffix('*').unwrap_or(pattern);
+        let name_without_wildcard = name_pattern.strip_suffix('*').unwrap_or(name_pattern);
         Crate::validate_crate_name("crate", name_without_wildcard).is_ok()
     }
 
+    /// Whether the scope names the given crate, without using a wildcard.
+    ///
+    /// Scopes with a version restriction only count if they are pinned to a
+    /// single version, either with `=` or with a bare full version.
+    pub fn is_exactly(&self, crate_name: &str) -> bool {
+        let (name_pattern, versions) = split_pattern(&self.pattern);
+        if name_pattern != crate_name {
+            return false;
+        }
+
+        match versions {
+            None => true,
+            Some(versions) => {
+                let version = versions.trim();
+                let version = version.strip_prefix('=').unwrap_or(version);
+                Version::parse(version.trim()).is_ok()
+            }
+        }
+    }
+
+    /// Whether the scope grants access to all versions of the given crate.
     pub fn matches(&self, crate_name: &str) -> bool {
-        if self.pattern == "*" {
-            return true;
+        let (name_pattern, versions) = split_pattern(&self.pattern);
+        versions.is_none() && name_matches(name_pattern, crate_name)
+    }
+
+    /// Whether the scope grants access to the given version of the crate.
+    pub fn matches_version(&self, crate_name: &str, version: &Version) -> bool {
+        let (name_pattern, versions) = split_pattern(&self.pattern);
+        if !name_matches(name_pattern, crate_name) {
+            return false;
         }
 
-        return match self.pattern.strip_suffix('*') {
-            Some(prefix) => crate_name.starts_with(prefix),
-            None => crate_name == self.pattern,
-        };
+        match versions {
+            None => true,
+            Some(PRERELEASE) => !version.pre.is_empty(),
+            Some(versions) => VersionReq::parse(versions).is_ok_and(|req| req.matches(version)),
+        }
+    }
+}
+
+/// The version restriction of crate scopes that only match prerelease versions.
+const PRERELEASE: &str = "prerelease";
+
+/// Splits a crate scope pattern into the crate name pattern and the optional
+/// version restriction.
+fn split_pattern(pattern: &str) -> (&str, Option<&str>) {
+    match pattern.split_once('@') {
+        Some((name_pattern, versions)) => (name_pattern, Some(versions)),
+        None => (pattern, None),
+    }
+}
+
+fn name_matches(name_pattern: &str, crate_name: &str) -> bool {
+    if name_pattern == "*" {
+        return true;
+    }
+
+    match name_pattern.strip_suffix('*') {
+        Some(prefix) => crate_name.starts_with(prefix),
+        None => crate_name == name_pattern,
     }
 }
 
@@ -138,6 +225,9 @@ mod tests {
         assert(EndpointScope::PublishNew, "\"publish-new\"");
         assert(EndpointScope::PublishUpdate, "\"publish-update\"");
         assert(EndpointScope::Yank, "\"yank\"");
+        assert(EndpointScope::ReadPrivate, "\"read-private\"");
+        assert(EndpointScope::ManageTokens, "\"manage-tokens\"");
+        assert(EndpointScope::Follow, "\"follow\"");
     }
 
     #[googletest::test]
@@ -175,6 +265,15 @@ mod tests {
 
         // invalid characters
         expect_that!(CrateScope::try_from("test#"), err(anything()));
+
+        // version restrictions
+        expect_that!(CrateScope::try_from("foo@^1.2"), ok(anything()));
+        expect_that!(CrateScope::try_from("foo@>=1.0, <2.0"), ok(anything()));
+        expect_that!(CrateScope::try_from("foo*@prerelease"), ok(anything()));
+        expect_that!(CrateScope::try_from("*@=1.0.0"), ok(anything()));
+        expect_that!(CrateScope::try_from("foo@"), err(anything()));
+        expect_that!(CrateScope::try_from("foo@latest"), err(anything()));
+        expect_that!(CrateScope::try_from("@^1.0"), err(anything()));
     }
 
     #[googletest::test]
@@ -206,4 +305,71 @@ mod tests {
         expect_that!(scope("foo_*").matches("foo-bar"), eq(false));
         expect_that!(scope("foo_*").matches("foo_bar"), eq(true));
     }
+
+    #[googletest::test]
+    fn crate_scope_version_matching() {
+        let scope = |pattern: &str| CrateScope::try_from(pattern).unwrap();
+        let version = |version: &str| Version::parse(version).unwrap();
+
+        // scopes without version restrictions match all versions
+        expect_that!(
+            scope("foo").matches_version("foo", &version("1.0.0")),
+            eq(true)
+        );
+        expect_that!(
+            scope("foo*").matches_version("foo-bar", &version("1.0.0-beta.1")),
+            eq(true)
+        );
+        expect_that!(
+            scope("foo").matches_version("bar", &version("1.0.0")),
+            eq(false)
+        );
+
+        // version requirements
+        let req = scope("foo@>=1.2, <2.0");
+        expect_that!(req.matches_version("foo", &version("1.2.0")), eq(true));
+        expect_that!(req.matches_version("foo", &version("1.9.3")), eq(true));
+        expect_that!(req.matches_version("foo", &version("2.0.0")), eq(false));
+        expect_that!(req.matches_version("foo", &version("1.1.0")), eq(false));
+        expect_that!(req.matches_version("bar", &version("1.2.0")), eq(false));
+
+        // prereleases
+        let prerelease = scope("foo*@prerelease");
+        expect_that!(
+            prerelease.matches_version("foo", &version("1.0.0-rc.1")),
+            eq(true)
+        );
+        expect_that!(
+            prerelease.matches_version("foo-bar", &version("0.1.0-alpha")),
+            eq(true)
+        );
+        expect_that!(
+            prerelease.matches_version("foo", &version("1.0.0")),
+            eq(false)
+        );
+
+        // version restricted scopes don't match the crate as a whole
+        expect_that!(req.matches("foo"), eq(false));
+        expect_that!(prerelease.matches("foo"), eq(false));
+        expect_that!(req.is_exactly("foo"), eq(false));
+    }
+
+    #[googletest::test]
+    fn crate_scope_is_exactly() {
+        let scope = |pattern: &str| CrateScope::try_from(pattern).unwrap();
+
+        expect_that!(scope("foo").is_exactly("foo"), eq(true));
+        expect_that!(scope("foo@=1.2.3").is_exactly("foo"), eq(true));
+        expect_that!(scope("foo@1.2.3").is_exactly("foo"), eq(true));
+        expect_that!(scope("foo@=1.0.0-rc.1").is_exactly("foo"), eq(true));
+
+        expect_that!(scope("foo").is_exactly("bar"), eq(false));
+        expect_that!(scope("foo*").is_exactly("foo"), eq(false));
+        expect_that!(scope("foo@^1").is_exactly("foo"), eq(false));
+        expect_that!(scope("foo@1").is_exactly("foo"), eq(false));
+        expect_that!(scope("foo@1.2").is_exactly("foo"), eq(false));
+        expect_that!(scope("foo@~1.2.3").is_exactly("foo"), eq(false));
+        expect_that!(scope("foo@>=1.2, <2.0").is_exactly("foo"), eq(false));
+        expect_that!(scope("foo@prerelease").is_exactly("foo"), eq(false));
+    }
 }
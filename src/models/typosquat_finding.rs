@@ -0,0 +1,99 @@
+use chrono::NaiveDateTime;
+use diesel::dsl::{exists, now};
+use diesel::prelude::*;
+
+use crate::schema::typosquat_findings;
+use crate::sql::pg_enum;
+
+pg_enum! {
+    pub enum TyposquatFindingStatus {
+        Pending = 0,
+        // The crate is not typosquatting the other crate.
+        Approved = 1,
+        // The crate is typosquatting the other crate and needs further
+        // action, like being deleted.
+        Rejected = 2,
+    }
+}
+
+/// A potential typosquatting of a popular crate by a new crate, which was
+/// found by the `check_typosquat` background job and awaits review.
+#[derive(Clone, Debug, PartialEq, Eq, Queryable, Identifiable, Selectable)]
+#[diesel(table_name = typosquat_findings, check_for_backend(diesel::pg::Pg))]
+pub struct TyposquatFinding {
+    pub id: i32,
+    pub crate_id: i32,
+    pub squatted_crate: String,
+    pub message: String,
+    pub status: TyposquatFindingStatus,
+    /// Whether the crate is kept out of the index until the finding is
+    /// approved.
+    pub holds_index: bool,
+    pub created_at: NaiveDateTime,
+    pub reviewed_at: Option<NaiveDateTime>,
+    pub reviewed_by: Option<String>,
+    pub review_note: Option<String>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = typosquat_findings, check_for_backend(diesel::pg::Pg))]
+pub struct NewTyposquatFinding<'a> {
+    pub crate_id: i32,
+    pub squatted_crate: &'a str,
+    pub message: &'a str,
+    pub holds_index: bool,
+}
+
+impl TyposquatFinding {
+    pub fn for_crate(crate_id: i32, conn: &mut PgConnection) -> QueryResult<Vec<Self>> {
+        typosquat_findings::table
+            .filter(typosquat_findings::crate_id.eq(crate_id))
+            .order(typosquat_findings::id)
+            .select(Self::as_select())
+            .load(conn)
+    }
+
+    /// Whether the crate has to be kept out of the index, because it has
+    /// findings that hold the index and have not been approved.
+    pub fn holds_index_of(crate_id: i32, conn: &mut PgConnection) -> QueryResult<bool> {
+        diesel::select(exists(
+            typosquat_findings::table
+                .filter(typosquat_findings::crate_id.eq(crate_id))
+                .filter(typosquat_findings::holds_index)
+                .filter(typosquat_findings::status.ne(TyposquatFindingStatus::Approved)),
+        ))
+        .get_result(conn)
+    }
+
+    /// Records the review of the given findings and returns the updated
+    /// findings.
+    pub fn review(
+        ids: &[i32],
+        status: TyposquatFindingStatus,
+        reviewer: &str,
+        note: Option<&str>,
+        conn: &mut PgConnection,
+    ) -> QueryResult<Vec<Self>> {
+        diesel::update(typosquat_findings::table)
+            .filter(typosquat_findings::id.eq_any(ids))
+            .set((
+                typosquat_findings::status.eq(status),
+                typosquat_findings::reviewed_at.eq(now),
+                typosquat_findings::reviewed_by.eq(reviewer),
+                typosquat_findings::review_note.eq(note),
+            ))
+            .returning(Self::as_returning())
+            .get_results(conn)
+    }
+}
+
+impl NewTyposquatFinding<'_> {
+    /// Inserts the findings, skipping the ones that were already recorded
+    /// for the crate, and returns the number of new findings.
+    pub fn insert_all(findings: &[Self], conn: &mut PgConnection) -> QueryResult<usize> {
+        diesel::insert_into(typosquat_findings::table)
+            .values(findings)
+            .on_conflict_do_nothing()
+            .execute(conn)
+    }
+}
@@ -1,15 +1,20 @@
-use chrono::NaiveDateTime;
+use chrono::{NaiveDateTime, Utc};
 use diesel::prelude::*;
 use secrecy::SecretString;
 use std::borrow::Cow;
 
 use crate::app::App;
 use crate::controllers::user::me::UserConfirmEmail;
-use crate::email::Emails;
+use crate::email::{self, Emails};
 use crate::util::errors::AppResult;
 
-use crate::models::{ApiToken, Crate, CrateOwner, Email, NewEmail, Owner, OwnerKind, Rights};
-use crate::schema::{crate_owners, emails, users};
+use crate::models::{
+    AdoptionRequestStatus, ApiToken, Crate, CrateOwner, Email, NewEmail, Owner, OwnerKind, Rights,
+};
+use crate::schema::{
+    admin_tokens, api_tokens, crate_adoption_requests, crate_owner_invitations, crate_owners,
+    crate_transfers, crates, emails, follows, users,
+};
 use crate::sql::lower;
 
 /// The model representing a row in the `users` database table.
@@ -23,6 +28,8 @@ pub struct User {
     pub gh_id: i32,
     pub account_lock_reason: Option<String>,
     pub account_lock_until: Option<NaiveDateTime>,
+    pub is_admin: bool,
+    pub deleted_at: Option<NaiveDateTime>,
 }
 
 /// Represents a new user record insertable to the `users` table
@@ -193,4 +200,260 @@ impl User {
             .first(conn)
             .optional()
     }
+
+    /// Locks the account until the given time, or indefinitely. Locked users
+    /// can neither sign in nor use their API tokens.
+    pub fn lock(
+        &self,
+        reason: &str,
+        until: Option<NaiveDateTime>,
+        conn: &mut PgConnection,
+    ) -> QueryResult<User> {
+        diesel::update(self)
+            .set((
+                users::account_lock_reason.eq(reason),
+                users::account_lock_until.eq(until),
+            ))
+            .get_result(conn)
+    }
+
+    pub fn unlock(&self, conn: &mut PgConnection) -> QueryResult<User> {
+        diesel::update(self)
+            .set((
+                users::account_lock_reason.eq(None::<String>),
+                users::account_lock_until.eq(None::<NaiveDateTime>),
+            ))
+            .get_result(conn)
+    }
+
+    /// Tells the user that their account was locked or unlocked, depending on
+    /// the current state of the account. Nothing is sent if the user has no
+    /// verified email address, and failures are only logged.
+    pub fn notify_account_lock(&self, emails: &Emails, conn: &mut PgConnection) -> QueryResult<()> {
+        let Some(recipient) = self.verified_email(conn)? else {
+            return Ok(());
+        };
+
+        let result = match &self.account_lock_reason {
+            Some(reason) => emails.send(
+                &recipient,
+                AccountLockedEmail {
+                    user_name: &self.gh_login,
+                    reason,
+                    until: self.account_lock_until,
+                },
+            ),
+            None => emails.send(
+                &recipient,
+                AccountUnlockedEmail {
+                    user_name: &self.gh_login,
+                    domain: &emails.domain,
+                },
+            ),
+        };
+
+        if let Err(error) = result {
+            error!(
+                ?error,
+                ?recipient,
+                "Failed to send account lock notification"
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Deletes the account of the user.
+    ///
+    /// The row in the `users` table is kept, since the versions that the user
+    /// published still refer to it, but all personal data is removed and the
+    /// GitHub account is unlinked, so that signing in again creates a new
+    /// account. The API tokens are revoked and the email address, follows and
+    /// pending ownership invitations are deleted.
+    ///
+    /// The ownerships of the user are removed, and no crates are transferred to
+    /// anyone:
+    ///
+    /// - Crates with other user owners are left to these owners, who already
+    ///   have full rights.
+    /// - Crates that the user is the only user owner of are orphaned. They stay
+    ///   published and their team owners keep their rights, but nobody can
+    ///   manage their owners anymore until somebody adopts them.
+    pub fn delete_account(&self, conn: &mut PgConnection) -> QueryResult<DeletedAccount> {
+        conn.transaction(|conn| {
+            let owned_crates: Vec<(i32, String)> = CrateOwner::by_owner_kind(OwnerKind::User)
+                .inner_join(crates::table)
+                .filter(crate_owners::owner_id.eq(self.id))
+                .select((crates::id, crates::name))
+                .order(crates::name)
+                .load(conn)?;
+
+            let crate_ids = owned_crates.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+            let shared_crate_ids: Vec<i32> = CrateOwner::by_owner_kind(OwnerKind::User)
+                .filter(crate_owners::crate_id.eq_any(&crate_ids))
+                .filter(crate_owners::owner_id.ne(self.id))
+                .select(crate_owners::crate_id)
+                .distinct()
+                .load(conn)?;
+
+            let (left_to_other_owners, orphaned): (Vec<_>, Vec<_>) = owned_crates
+                .into_iter()
+                .partition(|(id, _)| shared_crate_ids.contains(id));
+
+            let user_ownerships = crate_owners::table
+                .filter(crate_owners::owner_kind.eq(OwnerKind::User))
+                .filter(crate_owners::owner_id.eq(self.id));
+            diesel::update(user_ownerships)
+                .set(crate_owners::deleted.eq(true))
+                .execute(conn)?;
+
+            diesel::delete(
+                crate_owner_invitations::table.filter(
+                    crate_owner_invitations::invited_user_id
+                        .eq(self.id)
+                        .or(crate_owner_invitations::invited_by_user_id.eq(self.id)),
+                ),
+            )
+            .execute(conn)?;
+
+            diesel::delete(
+                crate_transfers::table.filter(
+                    crate_transfers::recipient_user_id
+                        .eq(self.id)
+                        .or(crate_transfers::proposed_by.eq(self.id)),
+                ),
+            )
+            .execute(conn)?;
+
+            diesel::update(
+                crate_adoption_requests::table
+                    .filter(crate_adoption_requests::user_id.eq(self.id))
+                    .filter(crate_adoption_requests::status.eq(AdoptionRequestStatus::Pending)),
+            )
+            .set(crate_adoption_requests::status.eq(AdoptionRequestStatus::Withdrawn))
+            .execute(conn)?;
+
+            diesel::update(api_tokens::table.filter(api_tokens::user_id.eq(self.id)))
+                .set(api_tokens::revoked.eq(true))
+                .execute(conn)?;
+
+            diesel::update(admin_tokens::table.filter(admin_tokens::user_id.eq(self.id)))
+                .set(admin_tokens::revoked.eq(true))
+                .execute(conn)?;
+
+            diesel::delete(follows::table.filter(follows::user_id.eq(self.id))).execute(conn)?;
+            diesel::delete(emails::table.filter(emails::user_id.eq(self.id))).execute(conn)?;
+
+            diesel::update(self)
+                .set((
+                    users::gh_login.eq(format!("deleted-user-{}", self.id)),
+                    users::gh_id.eq(-1),
+                    users::name.eq(None::<String>),
+                    users::gh_avatar.eq(None::<String>),
+                    users::gh_access_token.eq(""),
+                    users::is_admin.eq(false),
+                    users::deleted_at.eq(Utc::now().naive_utc()),
+                ))
+                .execute(conn)?;
+
+            Ok(DeletedAccount {
+                left_to_other_owners: left_to_other_owners
+                    .into_iter()
+                    .map(|(_, name)| name)
+                    .collect(),
+                orphaned: orphaned.into_iter().map(|(_, name)| name).collect(),
+            })
+        })
+    }
+}
+
+/// What happened to the crates of a deleted account.
+#[derive(Debug, Default, Serialize)]
+pub struct DeletedAccount {
+    /// The crates that other users own as well, which are left to them.
+    pub left_to_other_owners: Vec<String>,
+    /// The crates that have no user owners anymore.
+    pub orphaned: Vec<String>,
+}
+
+struct AccountLockedEmail<'a> {
+    user_name: &'a str,
+    reason: &'a str,
+    until: Option<NaiveDateTime>,
+}
+
+impl email::Email for AccountLockedEmail<'_> {
+    const SUBJECT: &'static str = "Your crates.io account has been locked";
+
+    fn body(&self) -> String {
+        let duration = match self.until {
+            Some(until) => format!("until {}", until.format("%Y-%m-%d at %H:%M:%S UTC")),
+            None => "indefinitely".to_string(),
+        };
+
+        format!(
+            "Hello {user_name}!\n
+Your crates.io account has been locked {duration} by the crates.io team. \
+You can't sign in or use your API tokens while it is locked.\n
+Reason: {reason}\n
+If you think this is a mistake, please contact help@crates.io.",
+            user_name = self.user_name,
+            reason = self.reason,
+        )
+    }
+}
+
+struct AccountUnlockedEmail<'a> {
+    user_name: &'a str,
+    domain: &'a str,
+}
+
+impl email::Email for AccountUnlockedEmail<'_> {
+    const SUBJECT: &'static str = "Your crates.io account has been unlocked";
+
+    fn body(&self) -> String {
+        format!(
+            "Hello {user_name}!\n
+Your crates.io account has been unlocked by the crates.io team. \
+You can sign in at https://{domain} and use your API tokens again.",
+            user_name = self.user_name,
+            domain = self.domain,
+        )
+    }
+}
+
+/// Sent to the user when they deleted their account.
+pub struct AccountDeletedEmail<'a> {
+    pub user_name: &'a str,
+    pub deleted_account: &'a DeletedAccount,
+}
+
+impl email::Email for AccountDeletedEmail<'_> {
+    const SUBJECT: &'static str = "Your crates.io account has been deleted";
+
+    fn body(&self) -> String {
+        let mut body = format!(
+            "Hello {}!\n
+Your crates.io account has been deleted as you requested. \
+The crates you published stay available.",
+            self.user_name
+        );
+
+        let DeletedAccount {
+            left_to_other_owners,
+            orphaned,
+        } = self.deleted_account;
+
+        if !left_to_other_owners.is_empty() {
+            body.push_str("\n\nThese crates are left to their other owners: ");
+            body.push_str(&left_to_other_owners.join(", "));
+        }
+
+        if !orphaned.is_empty() {
+            body.push_str("\n\nThese crates have no owners anymore: ");
+            body.push_str(&orphaned.join(", "));
+        }
+
+        body
+    }
 }
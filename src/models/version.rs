@@ -55,6 +55,9 @@ pub struct TopVersions {
     pub highest_stable: Option<semver::Version>,
     /// The "newest" version in terms of publishing date
     pub newest: Option<semver::Version>,
+    /// The "highest" non-prerelease version that supports a specific Rust
+    /// version, see [`TopVersions::with_highest_compatible()`]
+    pub highest_compatible: Option<semver::Version>,
 }
 
 impl TopVersions {
@@ -93,10 +96,54 @@ impl TopVersions {
             highest,
             highest_stable,
             newest,
+            highest_compatible: None,
+        }
+    }
+
+    /// Determine the highest non-prerelease version that supports the given
+    /// Rust version from a collection of version/`rust_version` pairs.
+    ///
+    /// Versions that don't declare a `rust_version` are considered compatible.
+    pub fn with_highest_compatible<'a, T>(self, pairs: T, rust_version: &semver::Version) -> Self
+    where
+        T: IntoIterator<Item = (&'a str, Option<&'a str>)>,
+    {
+        let highest_compatible = pairs
+            .into_iter()
+            .filter(|(_, msrv)| is_compatible_rust_version(*msrv, rust_version))
+            .filter_map(|(version, _)| semver::Version::parse(version).ok())
+            .filter(|v| v.pre.is_empty())
+            .max();
+
+        Self {
+            highest_compatible,
+            ..self
         }
     }
 }
 
+/// Parses a `rust-version` value like `1.70` or `1.70.0` into a full version.
+pub fn parse_rust_version(value: &str) -> Option<semver::Version> {
+    let mut parts = value.split('.').map(|part| part.parse::<u64>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().unwrap_or(Some(0))?;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    if parts.next().is_some() {
+        return None;
+    }
+
+    Some(semver::Version::new(major, minor, patch))
+}
+
+/// Whether a version with the given `rust_version` can be built with the
+/// given Rust version.
+pub fn is_compatible_rust_version(msrv: Option<&str>, rust_version: &semver::Version) -> bool {
+    match msrv {
+        None => true,
+        Some(msrv) => parse_rust_version(msrv).is_some_and(|msrv| &msrv <= rust_version),
+    }
+}
+
 impl Version {
     /// Returns (dependency, crate dependency name)
     pub fn dependencies(&self, conn: &mut PgConnection) -> QueryResult<Vec<(Dependency, String)>> {
@@ -196,7 +243,7 @@ fn strip_build_metadata(version: &str) -> &str {
 
 #[cfg(test)]
 mod tests {
-    use super::TopVersions;
+    use super::{is_compatible_rust_version, parse_rust_version, TopVersions};
     use chrono::NaiveDateTime;
 
     #[track_caller]
@@ -218,6 +265,7 @@ mod tests {
                 highest: None,
                 highest_stable: None,
                 newest: None,
+                highest_compatible: None,
             }
         );
     }
@@ -231,6 +279,7 @@ mod tests {
                 highest: Some(version("1.0.0")),
                 highest_stable: Some(version("1.0.0")),
                 newest: Some(version("1.0.0")),
+                highest_compatible: None,
             }
         );
     }
@@ -244,6 +293,7 @@ mod tests {
                 highest: Some(version("1.0.0-beta.5")),
                 highest_stable: None,
                 newest: Some(version("1.0.0-beta.5")),
+                highest_compatible: None,
             }
         );
     }
@@ -263,7 +313,50 @@ mod tests {
                 highest: Some(version("2.0.0-alpha.1")),
                 highest_stable: Some(version("1.1.0")),
                 newest: Some(version("1.0.4")),
+                highest_compatible: None,
             }
         );
     }
+
+    #[test]
+    fn top_versions_highest_compatible() {
+        let versions = vec![
+            ("1.0.0", None),
+            ("1.1.0", Some("1.60")),
+            ("1.2.0", Some("1.70.0")),
+            ("2.0.0-alpha.1", Some("1.60")),
+            ("2.0.0", Some("1.74")),
+        ];
+        let top_versions = TopVersions::from_date_version_pairs(vec![]);
+
+        let compatible = |rust_version: &str| {
+            top_versions
+                .clone()
+                .with_highest_compatible(versions.clone(), &version(rust_version))
+                .highest_compatible
+        };
+
+        assert_eq!(compatible("1.50.0"), Some(version("1.0.0")));
+        assert_eq!(compatible("1.65.0"), Some(version("1.1.0")));
+        assert_eq!(compatible("1.70.0"), Some(version("1.2.0")));
+        assert_eq!(compatible("1.75.0"), Some(version("2.0.0")));
+    }
+
+    #[test]
+    fn rust_versions() {
+        assert_eq!(parse_rust_version("1"), Some(version("1.0.0")));
+        assert_eq!(parse_rust_version("1.70"), Some(version("1.70.0")));
+        assert_eq!(parse_rust_version("1.70.1"), Some(version("1.70.1")));
+        assert_eq!(parse_rust_version(""), None);
+        assert_eq!(parse_rust_version("1.70.0.1"), None);
+        assert_eq!(parse_rust_version("^1.70"), None);
+        assert_eq!(parse_rust_version("1.70.0-nightly"), None);
+
+        assert!(is_compatible_rust_version(None, &version("1.0.0")));
+        assert!(is_compatible_rust_version(Some("1.70"), &version("1.70.0")));
+        assert!(!is_compatible_rust_version(
+            Some("1.70"),
+            &version("1.69.0")
+        ));
+    }
 }
@@ -0,0 +1,49 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+use crate::models::Version;
+use crate::schema::version_attestations;
+
+/// A signed release attestation that was uploaded together with the `.crate`
+/// file of a version. The attestation document itself is stored next to the
+/// `.crate` file in the storage backend.
+#[derive(Clone, Debug, Queryable, Identifiable, Associations, Selectable)]
+#[diesel(
+    table_name = version_attestations,
+    check_for_backend(diesel::pg::Pg),
+    primary_key(version_id),
+    belongs_to(Version),
+)]
+pub struct VersionAttestation {
+    pub version_id: i32,
+    pub signer: String,
+    pub checksum: String,
+    pub created_at: NaiveDateTime,
+}
+
+impl VersionAttestation {
+    pub fn find(version_id: i32, conn: &mut PgConnection) -> QueryResult<Option<Self>> {
+        version_attestations::table
+            .find(version_id)
+            .select(VersionAttestation::as_select())
+            .first(conn)
+            .optional()
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = version_attestations, check_for_backend(diesel::pg::Pg))]
+pub struct NewVersionAttestation<'a> {
+    pub version_id: i32,
+    pub signer: &'a str,
+    pub checksum: &'a str,
+}
+
+impl NewVersionAttestation<'_> {
+    pub fn insert(&self, conn: &mut PgConnection) -> QueryResult<VersionAttestation> {
+        diesel::insert_into(version_attestations::table)
+            .values(self)
+            .returning(VersionAttestation::as_returning())
+            .get_result(conn)
+    }
+}
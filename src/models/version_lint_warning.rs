@@ -0,0 +1,51 @@
+use diesel::prelude::*;
+
+use crate::lints::LintWarning;
+use crate::models::Version;
+use crate::schema::version_lint_warnings;
+
+/// A finding of a [lint](crate::lints) that was run against the published
+/// tarball of a version.
+#[derive(Clone, Debug, Queryable, Identifiable, Associations, Selectable)]
+#[diesel(
+    table_name = version_lint_warnings,
+    check_for_backend(diesel::pg::Pg),
+    belongs_to(Version),
+)]
+pub struct VersionLintWarning {
+    pub id: i32,
+    pub version_id: i32,
+    pub lint: String,
+    pub message: String,
+}
+
+impl VersionLintWarning {
+    pub fn insert_all(
+        version_id: i32,
+        warnings: &[LintWarning],
+        conn: &mut PgConnection,
+    ) -> QueryResult<usize> {
+        let values = warnings
+            .iter()
+            .map(|warning| {
+                (
+                    version_lint_warnings::version_id.eq(version_id),
+                    version_lint_warnings::lint.eq(warning.lint),
+                    version_lint_warnings::message.eq(&warning.message),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        diesel::insert_into(version_lint_warnings::table)
+            .values(values)
+            .execute(conn)
+    }
+
+    pub fn for_version(version_id: i32, conn: &mut PgConnection) -> QueryResult<Vec<Self>> {
+        version_lint_warnings::table
+            .filter(version_lint_warnings::version_id.eq(version_id))
+            .order(version_lint_warnings::id)
+            .select(VersionLintWarning::as_select())
+            .load(conn)
+    }
+}
@@ -0,0 +1,240 @@
+use cargo_manifest::{Edition, Product, StringOrBool};
+use chrono::NaiveDateTime;
+use crates_io_tarball::TarballInfo;
+use diesel::prelude::*;
+
+use crate::models::Version;
+use crate::schema::version_metadata;
+
+/// Package metadata of a published version, which was extracted from the
+/// `Cargo.toml` file and the file list of the uploaded tarball.
+#[derive(Clone, Debug, Queryable, Identifiable, Associations, Selectable)]
+#[diesel(
+    table_name = version_metadata,
+    check_for_backend(diesel::pg::Pg),
+    primary_key(version_id),
+    belongs_to(Version),
+)]
+pub struct VersionMetadata {
+    pub version_id: i32,
+    pub edition: String,
+    pub has_build_script: bool,
+    pub lib: Option<serde_json::Value>,
+    pub bins: serde_json::Value,
+    pub badges: serde_json::Value,
+    pub layout: serde_json::Value,
+    pub files: serde_json::Value,
+    pub vcs_sha1: Option<String>,
+    pub vcs_dirty: Option<bool>,
+    pub path_in_vcs: Option<String>,
+    pub vcs_verification_status: Option<String>,
+    pub vcs_verification_message: Option<String>,
+    pub vcs_verified_at: Option<NaiveDateTime>,
+}
+
+impl VersionMetadata {
+    pub fn find(version_id: i32, conn: &mut PgConnection) -> QueryResult<Option<Self>> {
+        version_metadata::table
+            .find(version_id)
+            .select(VersionMetadata::as_select())
+            .first(conn)
+            .optional()
+    }
+
+    /// Records the result of comparing the published tarball with the
+    /// `vcs_sha1` commit of the declared repository.
+    pub fn record_vcs_verification(
+        version_id: i32,
+        status: VcsVerificationStatus,
+        message: Option<&str>,
+        conn: &mut PgConnection,
+    ) -> QueryResult<usize> {
+        diesel::update(version_metadata::table.find(version_id))
+            .set((
+                version_metadata::vcs_verification_status.eq(status.as_str()),
+                version_metadata::vcs_verification_message.eq(message),
+                version_metadata::vcs_verified_at.eq(diesel::dsl::now),
+            ))
+            .execute(conn)
+    }
+}
+
+/// The result of comparing a published tarball with the commit that it was
+/// published from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VcsVerificationStatus {
+    /// All files of the tarball match the files in the commit.
+    Verified,
+    /// Some files of the tarball are missing from the commit or differ from it.
+    Mismatch,
+    /// The commit could not be fetched from the repository.
+    Failed,
+}
+
+impl VcsVerificationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Verified => "verified",
+            Self::Mismatch => "mismatch",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// Package metadata that has not been associated with a version yet.
+///
+/// This is built before the version is inserted into the database, since the
+/// manifest gets partially consumed by the publish endpoint.
+#[derive(Debug)]
+pub struct NewVersionMetadata {
+    edition: String,
+    has_build_script: bool,
+    lib: Option<serde_json::Value>,
+    bins: serde_json::Value,
+    badges: serde_json::Value,
+    layout: serde_json::Value,
+    files: serde_json::Value,
+    vcs_sha1: Option<String>,
+    vcs_dirty: Option<bool>,
+    path_in_vcs: Option<String>,
+}
+
+impl NewVersionMetadata {
+    pub fn new(tarball_info: &TarballInfo) -> serde_json::Result<Self> {
+        let manifest = &tarball_info.manifest;
+        let files = &tarball_info.files;
+        let package = manifest.package.as_ref();
+
+        // Manifests without an explicit edition are compiled with the 2015 edition
+        let edition = package
+            .and_then(|p| p.edition.as_ref())
+            .and_then(|edition| edition.as_ref().as_local())
+            .copied()
+            .unwrap_or_default();
+
+        let edition = match edition {
+            Edition::E2015 => "2015",
+            Edition::E2018 => "2018",
+            Edition::E2021 => "2021",
+        };
+
+        // This matches the build script auto-discovery of cargo: `build = false`
+        // disables the build script, an explicit path enables it, and otherwise
+        // a `build.rs` file in the package root is used if it exists.
+        let has_build_script = match package.and_then(|p| p.build.as_ref()) {
+            Some(StringOrBool::Bool(false)) => false,
+            Some(StringOrBool::String(_)) => true,
+            Some(StringOrBool::Bool(true)) | None => files.contains_key("build.rs"),
+        };
+
+        let lib = manifest.lib.as_ref().map(EncodableTarget::from);
+        let bins = manifest
+            .bin
+            .iter()
+            .flatten()
+            .map(EncodableTarget::from)
+            .collect::<Vec<_>>();
+
+        let layout = package.map(|package| EncodableLayout {
+            autobins: package.autobins,
+            autoexamples: package.autoexamples,
+            autotests: package.autotests,
+            autobenches: package.autobenches,
+        });
+
+        let files = files
+            .iter()
+            .map(|(path, &size)| EncodableFile { path, size })
+            .collect::<Vec<_>>();
+
+        let vcs_info = tarball_info.vcs_info.as_ref();
+        let git = vcs_info.and_then(|vcs_info| vcs_info.git.as_ref());
+
+        Ok(Self {
+            edition: edition.to_string(),
+            has_build_script,
+            lib: lib.map(serde_json::to_value).transpose()?,
+            bins: serde_json::to_value(bins)?,
+            badges: serde_json::to_value(&manifest.badges)?,
+            layout: serde_json::to_value(layout.unwrap_or_default())?,
+            files: serde_json::to_value(files)?,
+            vcs_sha1: git.map(|git| git.sha1.clone()),
+            vcs_dirty: git.map(|git| git.dirty),
+            path_in_vcs: vcs_info.map(|vcs_info| vcs_info.path_in_vcs.clone()),
+        })
+    }
+
+    pub fn save(&self, version_id: i32, conn: &mut PgConnection) -> QueryResult<VersionMetadata> {
+        diesel::insert_into(version_metadata::table)
+            .values((
+                version_metadata::version_id.eq(version_id),
+                version_metadata::edition.eq(&self.edition),
+                version_metadata::has_build_script.eq(self.has_build_script),
+                version_metadata::lib.eq(&self.lib),
+                version_metadata::bins.eq(&self.bins),
+                version_metadata::badges.eq(&self.badges),
+                version_metadata::layout.eq(&self.layout),
+                version_metadata::files.eq(&self.files),
+                version_metadata::vcs_sha1.eq(&self.vcs_sha1),
+                version_metadata::vcs_dirty.eq(self.vcs_dirty),
+                version_metadata::path_in_vcs.eq(&self.path_in_vcs),
+            ))
+            .returning(VersionMetadata::as_returning())
+            .get_result(conn)
+    }
+}
+
+/// A `[lib]` or `[[bin]]` target as it is stored in the database.
+#[derive(Debug, Serialize)]
+struct EncodableTarget<'a> {
+    name: Option<&'a str>,
+    path: Option<&'a str>,
+    proc_macro: bool,
+    crate_type: Option<&'a [String]>,
+    required_features: &'a [String],
+}
+
+impl<'a> From<&'a Product> for EncodableTarget<'a> {
+    fn from(product: &'a Product) -> Self {
+        // `crate-type = ["proc-macro"]` is an alternative to `proc-macro = true`
+        let is_proc_macro_crate_type = product
+            .crate_type
+            .iter()
+            .flatten()
+            .any(|crate_type| crate_type == "proc-macro");
+
+        Self {
+            name: product.name.as_deref(),
+            path: product.path.as_deref(),
+            proc_macro: product.proc_macro || is_proc_macro_crate_type,
+            crate_type: product.crate_type.as_deref(),
+            required_features: &product.required_features,
+        }
+    }
+}
+
+/// The target auto-discovery settings of a package.
+#[derive(Debug, Serialize)]
+struct EncodableLayout {
+    autobins: bool,
+    autoexamples: bool,
+    autotests: bool,
+    autobenches: bool,
+}
+
+impl Default for EncodableLayout {
+    fn default() -> Self {
+        Self {
+            autobins: true,
+            autoexamples: true,
+            autotests: true,
+            autobenches: true,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct EncodableFile<'a> {
+    path: &'a str,
+    size: u64,
+}
@@ -0,0 +1,55 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+use crate::models::Version;
+use crate::scanning::Finding;
+use crate::schema::version_scan_findings;
+
+/// A finding of a [scanning rule](crate::scanning) that was run against the
+/// files of a published version.
+#[derive(Clone, Debug, Queryable, Identifiable, Associations, Selectable)]
+#[diesel(
+    table_name = version_scan_findings,
+    check_for_backend(diesel::pg::Pg),
+    belongs_to(Version),
+)]
+pub struct VersionScanFinding {
+    pub id: i32,
+    pub version_id: i32,
+    pub rule: String,
+    pub path: String,
+    pub message: String,
+    pub created_at: NaiveDateTime,
+}
+
+impl VersionScanFinding {
+    pub fn insert_all(
+        version_id: i32,
+        findings: &[Finding],
+        conn: &mut PgConnection,
+    ) -> QueryResult<usize> {
+        let values = findings
+            .iter()
+            .map(|finding| {
+                (
+                    version_scan_findings::version_id.eq(version_id),
+                    version_scan_findings::rule.eq(finding.rule),
+                    version_scan_findings::path.eq(&finding.path),
+                    version_scan_findings::message.eq(&finding.message),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        diesel::insert_into(version_scan_findings::table)
+            .values(values)
+            .execute(conn)
+    }
+
+    pub fn for_version(version_id: i32, conn: &mut PgConnection) -> QueryResult<Vec<Self>> {
+        version_scan_findings::table
+            .filter(version_scan_findings::version_id.eq(version_id))
+            .order(version_scan_findings::id)
+            .select(VersionScanFinding::as_select())
+            .load(conn)
+    }
+}
@@ -14,15 +14,25 @@ pg_enum! {
         PublishNew = 0,
         PublishUpdate = 1,
         YankUnyank = 2,
+        BulkLookup = 3,
+        ResendInvitation = 4,
+        VersionDiff = 5,
+        Audit = 6,
+        Resolve = 7,
     }
 }
 
 impl LimitedAction {
     pub fn default_rate_seconds(&self) -> u64 {
         match self {
-            LimitedAction::PublishNew => 10 * 60, // 10 minutes
-            LimitedAction::PublishUpdate => 60,   // 1 minute
-            LimitedAction::YankUnyank => 60,      // 1 minute
+            LimitedAction::PublishNew => 10 * 60,       // 10 minutes
+            LimitedAction::PublishUpdate => 60,         // 1 minute
+            LimitedAction::YankUnyank => 60,            // 1 minute
+            LimitedAction::BulkLookup => 10,            // 10 seconds
+            LimitedAction::ResendInvitation => 10 * 60, // 10 minutes
+            LimitedAction::VersionDiff => 10,           // 10 seconds
+            LimitedAction::Audit => 10,                 // 10 seconds
+            LimitedAction::Resolve => 10,               // 10 seconds
         }
     }
 
@@ -31,6 +41,11 @@ impl LimitedAction {
             LimitedAction::PublishNew => 5,
             LimitedAction::PublishUpdate => 30,
             LimitedAction::YankUnyank => 100,
+            LimitedAction::BulkLookup => 30,
+            LimitedAction::ResendInvitation => 5,
+            LimitedAction::VersionDiff => 30,
+            LimitedAction::Audit => 30,
+            LimitedAction::Resolve => 30,
         }
     }
 
@@ -39,6 +54,11 @@ impl LimitedAction {
             LimitedAction::PublishNew => "PUBLISH_NEW",
             LimitedAction::PublishUpdate => "PUBLISH_UPDATE",
             LimitedAction::YankUnyank => "YANK_UNYANK",
+            LimitedAction::BulkLookup => "BULK_LOOKUP",
+            LimitedAction::ResendInvitation => "RESEND_INVITATION",
+            LimitedAction::VersionDiff => "VERSION_DIFF",
+            LimitedAction::Audit => "AUDIT",
+            LimitedAction::Resolve => "RESOLVE",
         }
     }
 
@@ -53,6 +73,19 @@ impl LimitedAction {
             LimitedAction::YankUnyank => {
                 "You have yanked or unyanked too many versions in a short period of time"
             }
+            LimitedAction::BulkLookup => {
+                "You have looked up too many crates in bulk in a short period of time"
+            }
+            LimitedAction::ResendInvitation => {
+                "You have resent too many crate owner invitations in a short period of time"
+            }
+            LimitedAction::VersionDiff => {
+                "You have compared too many crate versions in a short period of time"
+            }
+            LimitedAction::Audit => "You have audited too many lockfiles in a short period of time",
+            LimitedAction::Resolve => {
+                "You have resolved too many dependency graphs in a short period of time"
+            }
         }
     }
 }
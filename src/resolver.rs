@@ -0,0 +1,801 @@
+//! A dependency resolver that works on the versions and dependencies stored in
+//! the database, to preview the dependency graph of a crate without running
+//! cargo against the index.
+//!
+//! The resolver is intentionally simpler than the one in cargo: it always
+//! picks the highest matching version of a dependency, unless a previously
+//! selected version already matches the requirement, and it does not
+//! backtrack. Requirements that cargo could only satisfy by selecting an older
+//! version result in an error instead.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+
+use diesel::prelude::*;
+use semver::{Version, VersionReq};
+
+use crate::models::{is_compatible_rust_version, DependencyKind};
+use crate::schema::{crates, dependencies, versions};
+use crate::util::errors::{bad_request, AppResult};
+
+/// The maximum number of packages in a resolved dependency graph.
+const MAX_RESOLVED_PACKAGES: usize = 1000;
+
+/// An unyanked version of a crate that can be selected by the resolver.
+#[derive(Clone, Debug)]
+pub struct Candidate {
+    pub version_id: i32,
+    pub num: Version,
+    pub license: Option<String>,
+    pub rust_version: Option<String>,
+    pub features: BTreeMap<String, Vec<String>>,
+}
+
+/// A dependency of a [`Candidate`].
+#[derive(Clone, Debug, Queryable)]
+pub struct CandidateDependency {
+    pub crate_name: String,
+    pub explicit_name: Option<String>,
+    pub req: String,
+    pub optional: bool,
+    pub default_features: bool,
+    pub features: Vec<String>,
+    pub target: Option<String>,
+    pub kind: DependencyKind,
+}
+
+impl CandidateDependency {
+    /// The name of the dependency in the `Cargo.toml` file of the dependent,
+    /// which is also used to refer to it in features.
+    fn toml_name(&self) -> &str {
+        self.explicit_name.as_deref().unwrap_or(&self.crate_name)
+    }
+}
+
+/// The source of the versions and dependencies that the resolver works on.
+pub trait Registry {
+    /// Returns all unyanked versions of the crate.
+    fn candidates(&mut self, crate_name: &str) -> AppResult<Vec<Candidate>>;
+
+    /// Returns the dependencies of a version.
+    fn dependencies(&mut self, version_id: i32) -> AppResult<Vec<CandidateDependency>>;
+}
+
+pub struct DatabaseRegistry<'a> {
+    conn: &'a mut PgConnection,
+}
+
+impl<'a> DatabaseRegistry<'a> {
+    pub fn new(conn: &'a mut PgConnection) -> Self {
+        Self { conn }
+    }
+}
+
+#[derive(Queryable)]
+struct CandidateRow {
+    id: i32,
+    num: String,
+    license: Option<String>,
+    rust_version: Option<String>,
+    features: serde_json::Value,
+}
+
+impl Registry for DatabaseRegistry<'_> {
+    fn candidates(&mut self, crate_name: &str) -> AppResult<Vec<Candidate>> {
+        let rows: Vec<CandidateRow> = versions::table
+            .inner_join(crates::table)
+            .filter(crates::name.eq(crate_name))
+            .filter(versions::yanked.eq(false))
+            .select((
+                versions::id,
+                versions::num,
+                versions::license,
+                versions::rust_version,
+                versions::features,
+            ))
+            .load(self.conn)?;
+
+        let candidates = rows
+            .into_iter()
+            .filter_map(|row| {
+                let num = Version::parse(&row.num).ok()?;
+                Some(Candidate {
+                    version_id: row.id,
+                    num,
+                    license: row.license,
+                    rust_version: row.rust_version,
+                    features: serde_json::from_value(row.features).unwrap_or_default(),
+                })
+            })
+            .collect();
+
+        Ok(candidates)
+    }
+
+    fn dependencies(&mut self, version_id: i32) -> AppResult<Vec<CandidateDependency>> {
+        let dependencies = dependencies::table
+            .inner_join(crates::table)
+            .filter(dependencies::version_id.eq(version_id))
+            .select((
+                crates::name,
+                dependencies::explicit_name,
+                dependencies::req,
+                dependencies::optional,
+                dependencies::default_features,
+                dependencies::features,
+                dependencies::target,
+                dependencies::kind,
+            ))
+            .order(dependencies::id)
+            .load(self.conn)?;
+
+        Ok(dependencies)
+    }
+}
+
+/// A root requirement of the dependency graph, like a dependency that is
+/// about to be added to a `Cargo.toml` file.
+#[derive(Clone, Debug)]
+pub struct Requirement {
+    pub name: String,
+    pub req: VersionReq,
+    pub features: Vec<String>,
+    pub default_features: bool,
+}
+
+#[derive(Debug)]
+pub struct ResolvedPackage {
+    pub name: String,
+    pub version: Version,
+    pub license: Option<String>,
+    pub rust_version: Option<String>,
+    /// The features that are activated for this package.
+    pub features: BTreeSet<String>,
+}
+
+/// An edge of the dependency graph.
+#[derive(Debug)]
+pub struct ResolvedDependency {
+    /// The index of the dependent package, or `None` for root requirements.
+    pub from: Option<usize>,
+    /// The index of the selected package.
+    pub to: usize,
+    pub req: String,
+    pub kind: DependencyKind,
+    pub target: Option<String>,
+    pub optional: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct Resolve {
+    pub packages: Vec<ResolvedPackage>,
+    pub dependencies: Vec<ResolvedDependency>,
+}
+
+/// Resolves the dependency graph of the given requirements.
+///
+/// If `rust_version` is given, only versions that support it are selected.
+/// The dev-dependencies of the root packages are included if
+/// `dev_dependencies` is set.
+pub fn resolve(
+    registry: &mut impl Registry,
+    requirements: &[Requirement],
+    rust_version: Option<&Version>,
+    dev_dependencies: bool,
+) -> AppResult<Resolve> {
+    let mut resolver = Resolver {
+        registry,
+        rust_version,
+        candidates: HashMap::new(),
+        packages: Vec::new(),
+        edges: Vec::new(),
+        queue: VecDeque::new(),
+    };
+
+    let mut roots = Vec::with_capacity(requirements.len());
+    for requirement in requirements {
+        let edge = NewEdge {
+            from: None,
+            crate_name: &requirement.name,
+            name: &requirement.name,
+            req: &requirement.req,
+            kind: DependencyKind::Normal,
+            target: None,
+            optional: false,
+        };
+        let features = requirement.features.clone();
+        let package = resolver.activate(edge, features, requirement.default_features)?;
+        roots.push(package);
+    }
+
+    if dev_dependencies {
+        for package in roots {
+            let dependencies = &resolver.packages[package].dependencies;
+            for (index, dependency) in dependencies.iter().enumerate() {
+                if dependency.kind == DependencyKind::Dev && !dependency.optional {
+                    resolver.queue.push_back(Task::Dependency(package, index));
+                }
+            }
+        }
+    }
+
+    resolver.run()?;
+
+    let packages = resolver
+        .packages
+        .into_iter()
+        .map(|package| ResolvedPackage {
+            name: package.name,
+            version: package.candidate.num,
+            license: package.candidate.license,
+            rust_version: package.candidate.rust_version,
+            features: package.features,
+        })
+        .collect();
+
+    let dependencies = resolver
+        .edges
+        .into_iter()
+        .map(|edge| ResolvedDependency {
+            from: edge.from,
+            to: edge.to,
+            req: edge.req,
+            kind: edge.kind,
+            target: edge.target,
+            optional: edge.optional,
+        })
+        .collect();
+
+    Ok(Resolve {
+        packages,
+        dependencies,
+    })
+}
+
+struct Package {
+    name: String,
+    candidate: Candidate,
+    dependencies: Vec<CandidateDependency>,
+    features: BTreeSet<String>,
+    /// The names of the optional dependencies that have been enabled.
+    enabled_dependencies: HashSet<String>,
+    /// The features that have been requested for the dependencies of this
+    /// package, by dependency name.
+    dependency_features: HashMap<String, BTreeSet<String>>,
+}
+
+struct Edge {
+    from: Option<usize>,
+    to: usize,
+    /// The name of the dependency in the dependent's `Cargo.toml` file.
+    name: String,
+    req: String,
+    kind: DependencyKind,
+    target: Option<String>,
+    optional: bool,
+}
+
+struct NewEdge<'a> {
+    from: Option<usize>,
+    crate_name: &'a str,
+    /// The name of the dependency in the dependent's `Cargo.toml` file.
+    name: &'a str,
+    req: &'a VersionReq,
+    kind: DependencyKind,
+    target: Option<&'a str>,
+    optional: bool,
+}
+
+enum Task {
+    /// Activate a feature of a package.
+    Feature(usize, String),
+    /// Activate the dependency with the given index of a package.
+    Dependency(usize, usize),
+}
+
+struct Resolver<'a, R> {
+    registry: &'a mut R,
+    rust_version: Option<&'a Version>,
+    candidates: HashMap<String, Vec<Candidate>>,
+    packages: Vec<Package>,
+    edges: Vec<Edge>,
+    queue: VecDeque<Task>,
+}
+
+impl<R: Registry> Resolver<'_, R> {
+    fn run(&mut self) -> AppResult<()> {
+        while let Some(task) = self.queue.pop_front() {
+            match task {
+                Task::Feature(package, feature) => self.activate_feature(package, feature)?,
+                Task::Dependency(package, index) => {
+                    let dependency = self.packages[package].dependencies[index].clone();
+                    let req = VersionReq::parse(&dependency.req).map_err(|_| {
+                        bad_request(format!(
+                            "invalid version requirement `{}` for `{}` in `{}`",
+                            dependency.req,
+                            dependency.crate_name,
+                            self.package_id(package),
+                        ))
+                    })?;
+
+                    let toml_name = dependency.toml_name();
+                    let mut features = dependency.features.to_vec();
+                    if let Some(requested) =
+                        self.packages[package].dependency_features.get(toml_name)
+                    {
+                        features.extend(requested.iter().cloned());
+                    }
+
+                    let edge = NewEdge {
+                        from: Some(package),
+                        crate_name: &dependency.crate_name,
+                        name: toml_name,
+                        req: &req,
+                        kind: dependency.kind,
+                        target: dependency.target.as_deref(),
+                        optional: dependency.optional,
+                    };
+                    self.activate(edge, features, dependency.default_features)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Selects a version for the dependency, records the edge to it and
+    /// queues its features for activation.
+    fn activate(
+        &mut self,
+        edge: NewEdge<'_>,
+        features: Vec<String>,
+        default_features: bool,
+    ) -> AppResult<usize> {
+        let package = match self.find_activated(edge.crate_name, edge.req) {
+            Some(package) => package,
+            None => self.select(edge.crate_name, edge.req)?,
+        };
+
+        self.edges.push(Edge {
+            from: edge.from,
+            to: package,
+            name: edge.name.to_string(),
+            req: edge.req.to_string(),
+            kind: edge.kind,
+            target: edge.target.map(ToString::to_string),
+            optional: edge.optional,
+        });
+
+        if default_features {
+            self.queue
+                .push_back(Task::Feature(package, "default".to_string()));
+        }
+        for feature in features {
+            self.queue.push_back(Task::Feature(package, feature));
+        }
+
+        Ok(package)
+    }
+
+    fn find_activated(&self, name: &str, req: &VersionReq) -> Option<usize> {
+        self.packages
+            .iter()
+            .position(|package| package.name == name && req.matches(&package.candidate.num))
+    }
+
+    /// Selects the highest version of the crate that matches the requirement
+    /// and adds it to the dependency graph.
+    fn select(&mut self, name: &str, req: &VersionReq) -> AppResult<usize> {
+        if !self.candidates.contains_key(name) {
+            let candidates = self.registry.candidates(name)?;
+            self.candidates.insert(name.to_string(), candidates);
+        }
+
+        let rust_version = self.rust_version;
+        let candidate = self.candidates[name]
+            .iter()
+            .filter(|candidate| req.matches(&candidate.num))
+            .filter(|candidate| {
+                rust_version.map_or(true, |rust_version| {
+                    is_compatible_rust_version(candidate.rust_version.as_deref(), rust_version)
+                })
+            })
+            .max_by(|a, b| a.num.cmp(&b.num))
+            .cloned();
+
+        let Some(candidate) = candidate else {
+            let msrv = match rust_version {
+                Some(rust_version) => format!(" that supports Rust {rust_version}"),
+                None => String::new(),
+            };
+            return Err(bad_request(format!(
+                "failed to select a version of `{name}` matching `{req}`{msrv}"
+            )));
+        };
+
+        let bucket = compatibility_bucket(&candidate.num);
+        let conflict = self.packages.iter().find(|package| {
+            package.name == name && compatibility_bucket(&package.candidate.num) == bucket
+        });
+        if let Some(conflict) = conflict {
+            return Err(bad_request(format!(
+                "failed to select a version of `{name}` matching `{req}`: the semver \
+                compatible version {} was already selected for another dependency",
+                conflict.candidate.num
+            )));
+        }
+
+        if self.packages.len() >= MAX_RESOLVED_PACKAGES {
+            return Err(bad_request(format!(
+                "the dependency graph has more than {MAX_RESOLVED_PACKAGES} packages"
+            )));
+        }
+
+        let dependencies = self.registry.dependencies(candidate.version_id)?;
+        let package = self.packages.len();
+        for (index, dependency) in dependencies.iter().enumerate() {
+            if !dependency.optional && dependency.kind != DependencyKind::Dev {
+                self.queue.push_back(Task::Dependency(package, index));
+            }
+        }
+
+        self.packages.push(Package {
+            name: name.to_string(),
+            candidate,
+            dependencies,
+            features: BTreeSet::new(),
+            enabled_dependencies: HashSet::new(),
+            dependency_features: HashMap::new(),
+        });
+
+        Ok(package)
+    }
+
+    fn activate_feature(&mut self, package: usize, feature: String) -> AppResult<()> {
+        let pkg = &self.packages[package];
+        if pkg.features.contains(&feature) {
+            return Ok(());
+        }
+
+        if let Some(values) = pkg.candidate.features.get(&feature).cloned() {
+            self.packages[package].features.insert(feature);
+            for value in values {
+                self.activate_feature_value(package, &value);
+            }
+        } else if pkg.has_optional_dependency(&feature) {
+            // Optional dependencies are implicit features of the package.
+            self.enable_dependency(package, &feature);
+            self.packages[package].features.insert(feature);
+        } else if feature != "default" {
+            return Err(bad_request(format!(
+                "package `{}` does not have the feature `{feature}`",
+                self.package_id(package)
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn activate_feature_value(&mut self, package: usize, value: &str) {
+        if let Some(dependency) = value.strip_prefix("dep:") {
+            self.enable_dependency(package, dependency);
+        } else if let Some((dependency, feature)) = value.split_once('/') {
+            match dependency.strip_suffix('?') {
+                // Weak dependency features don't enable the dependency itself.
+                Some(dependency) => self.request_dependency_feature(package, dependency, feature),
+                None => {
+                    self.request_dependency_feature(package, dependency, feature);
+                    self.enable_dependency(package, dependency);
+                }
+            }
+        } else {
+            self.queue
+                .push_back(Task::Feature(package, value.to_string()));
+        }
+    }
+
+    fn enable_dependency(&mut self, package: usize, name: &str) {
+        let pkg = &mut self.packages[package];
+        if !pkg.enabled_dependencies.insert(name.to_string()) {
+            return;
+        }
+
+        for (index, dependency) in pkg.dependencies.iter().enumerate() {
+            if dependency.optional
+                && dependency.kind != DependencyKind::Dev
+                && dependency.toml_name() == name
+            {
+                self.queue.push_back(Task::Dependency(package, index));
+            }
+        }
+    }
+
+    /// Records that the given feature of a dependency should be activated,
+    /// which applies to the already activated dependencies and to the ones
+    /// that are activated later on.
+    fn request_dependency_feature(&mut self, package: usize, dependency: &str, feature: &str) {
+        let requested = self.packages[package]
+            .dependency_features
+            .entry(dependency.to_string())
+            .or_default();
+        if !requested.insert(feature.to_string()) {
+            return;
+        }
+
+        for edge in &self.edges {
+            if edge.from == Some(package) && edge.name == dependency {
+                self.queue
+                    .push_back(Task::Feature(edge.to, feature.to_string()));
+            }
+        }
+    }
+
+    fn package_id(&self, package: usize) -> String {
+        let package = &self.packages[package];
+        format!("{}@{}", package.name, package.candidate.num)
+    }
+}
+
+impl Package {
+    fn has_optional_dependency(&self, name: &str) -> bool {
+        self.dependencies
+            .iter()
+            .any(|dependency| dependency.optional && dependency.toml_name() == name)
+    }
+}
+
+/// Versions in the same bucket are semver compatible with each other, so
+/// only one of them can be selected, just like in cargo.
+fn compatibility_bucket(version: &Version) -> (u64, u64, u64) {
+    match version {
+        Version {
+            major: 0,
+            minor: 0,
+            patch,
+            ..
+        } => (0, 0, *patch),
+        Version {
+            major: 0, minor, ..
+        } => (0, *minor, 0),
+        Version { major, .. } => (*major, 0, 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct TestRegistry {
+        candidates: HashMap<String, Vec<Candidate>>,
+        dependencies: HashMap<i32, Vec<CandidateDependency>>,
+    }
+
+    impl TestRegistry {
+        fn add(&mut self, name: &str, num: &str, features: &[(&str, &[&str])]) -> i32 {
+            let version_id = self.dependencies.len() as i32 + 1;
+            let features = features
+                .iter()
+                .map(|(name, values)| {
+                    let values = values.iter().map(ToString::to_string).collect();
+                    (name.to_string(), values)
+                })
+                .collect();
+
+            self.candidates
+                .entry(name.to_string())
+                .or_default()
+                .push(Candidate {
+                    version_id,
+                    num: Version::parse(num).unwrap(),
+                    license: Some("MIT".into()),
+                    rust_version: None,
+                    features,
+                });
+            self.dependencies.insert(version_id, vec![]);
+            version_id
+        }
+
+        fn depend(&mut self, version_id: i32, name: &str, req: &str, optional: bool) {
+            self.dependencies
+                .get_mut(&version_id)
+                .unwrap()
+                .push(CandidateDependency {
+                    crate_name: name.into(),
+                    explicit_name: None,
+                    req: req.into(),
+                    optional,
+                    default_features: true,
+                    features: vec![],
+                    target: None,
+                    kind: DependencyKind::Normal,
+                });
+        }
+    }
+
+    impl Registry for TestRegistry {
+        fn candidates(&mut self, crate_name: &str) -> AppResult<Vec<Candidate>> {
+            Ok(self.candidates.get(crate_name).cloned().unwrap_or_default())
+        }
+
+        fn dependencies(&mut self, version_id: i32) -> AppResult<Vec<CandidateDependency>> {
+            Ok(self.dependencies[&version_id].clone())
+        }
+    }
+
+    fn requirement(name: &str, req: &str, features: &[&str]) -> Requirement {
+        Requirement {
+            name: name.into(),
+            req: VersionReq::parse(req).unwrap(),
+            features: features.iter().map(ToString::to_string).collect(),
+            default_features: true,
+        }
+    }
+
+    fn packages(resolve: &Resolve) -> Vec<String> {
+        let mut packages = resolve
+            .packages
+            .iter()
+            .map(|package| format!("{}@{}", package.name, package.version))
+            .collect::<Vec<_>>();
+        packages.sort();
+        packages
+    }
+
+    fn features<'a>(resolve: &'a Resolve, name: &str) -> Vec<&'a str> {
+        let package = resolve.packages.iter().find(|p| p.name == name).unwrap();
+        package.features.iter().map(String::as_str).collect()
+    }
+
+    #[test]
+    fn selects_highest_matching_versions() {
+        let mut registry = TestRegistry::default();
+        registry.add("foo", "1.0.0", &[]);
+        let foo = registry.add("foo", "1.1.0", &[]);
+        registry.add("foo", "2.0.0", &[]);
+        registry.add("bar", "0.1.0", &[]);
+        registry.add("bar", "0.1.5", &[]);
+        registry.add("bar", "0.2.0", &[]);
+        registry.depend(foo, "bar", "^0.1", false);
+
+        let graph = assert_ok!(resolve(
+            &mut registry,
+            &[requirement("foo", "^1", &[])],
+            None,
+            false
+        ));
+        assert_eq!(packages(&graph), vec!["bar@0.1.5", "foo@1.1.0"]);
+        assert_eq!(graph.dependencies.len(), 2);
+        assert_eq!(graph.dependencies[0].from, None);
+        assert_eq!(graph.dependencies[1].from, Some(0));
+    }
+
+    #[test]
+    fn reuses_activated_versions() {
+        let mut registry = TestRegistry::default();
+        let foo = registry.add("foo", "1.0.0", &[]);
+        registry.add("bar", "1.0.0", &[]);
+        registry.add("bar", "1.2.0", &[]);
+        registry.add("bar", "2.0.0", &[]);
+        registry.depend(foo, "bar", "^1.0", false);
+
+        let requirements = [
+            requirement("bar", "^1.2", &[]),
+            requirement("foo", "^1", &[]),
+            requirement("bar", "^2", &[]),
+        ];
+        let graph = assert_ok!(resolve(&mut registry, &requirements, None, false));
+        assert_eq!(
+            packages(&graph),
+            vec!["bar@1.2.0", "bar@2.0.0", "foo@1.0.0"]
+        );
+    }
+
+    #[test]
+    fn semver_compatible_conflicts() {
+        let mut registry = TestRegistry::default();
+        registry.add("bar", "1.0.0", &[]);
+        registry.add("bar", "1.2.0", &[]);
+
+        let requirements = [
+            requirement("bar", "^1.0", &[]),
+            requirement("bar", "=1.0.0", &[]),
+        ];
+        assert_err!(resolve(&mut registry, &requirements, None, false));
+    }
+
+    #[test]
+    fn respects_rust_version() {
+        let mut registry = TestRegistry::default();
+        registry.add("foo", "1.0.0", &[]);
+        registry.add("foo", "1.1.0", &[]);
+        registry.candidates.get_mut("foo").unwrap()[1].rust_version = Some("1.70".into());
+
+        let requirements = [requirement("foo", "^1", &[])];
+        let rust_version = Version::new(1, 65, 0);
+        let graph = assert_ok!(resolve(
+            &mut registry,
+            &requirements,
+            Some(&rust_version),
+            false
+        ));
+        assert_eq!(packages(&graph), vec!["foo@1.0.0"]);
+
+        let rust_version = Version::new(1, 50, 0);
+        registry.candidates.get_mut("foo").unwrap()[0].rust_version = Some("1.60".into());
+        assert_err!(resolve(
+            &mut registry,
+            &requirements,
+            Some(&rust_version),
+            false
+        ));
+    }
+
+    #[test]
+    fn features_enable_optional_dependencies() {
+        let mut registry = TestRegistry::default();
+        let foo = registry.add(
+            "foo",
+            "1.0.0",
+            &[
+                ("default", &["std"]),
+                ("std", &[]),
+                ("derive", &["dep:foo_derive", "bar?/derive"]),
+                ("full", &["derive", "bar/alloc"]),
+            ],
+        );
+        registry.add("foo_derive", "1.0.0", &[]);
+        registry.add("bar", "1.0.0", &[("derive", &[]), ("alloc", &[])]);
+        registry.add("baz", "1.0.0", &[]);
+        registry.depend(foo, "foo_derive", "^1", true);
+        registry.depend(foo, "bar", "^1", true);
+        registry.depend(foo, "baz", "^1", true);
+
+        let graph = assert_ok!(resolve(
+            &mut registry,
+            &[requirement("foo", "^1", &[])],
+            None,
+            false
+        ));
+        assert_eq!(packages(&graph), vec!["foo@1.0.0"]);
+        assert_eq!(features(&graph, "foo"), vec!["default", "std"]);
+
+        let graph = assert_ok!(resolve(
+            &mut registry,
+            &[requirement("foo", "^1", &["derive"])],
+            None,
+            false
+        ));
+        assert_eq!(packages(&graph), vec!["foo@1.0.0", "foo_derive@1.0.0"]);
+
+        let graph = assert_ok!(resolve(
+            &mut registry,
+            &[requirement("foo", "^1", &["full", "baz"])],
+            None,
+            false
+        ));
+        assert_eq!(
+            packages(&graph),
+            vec!["bar@1.0.0", "baz@1.0.0", "foo@1.0.0", "foo_derive@1.0.0"]
+        );
+        assert_eq!(features(&graph, "bar"), vec!["alloc", "derive"]);
+        assert_eq!(
+            features(&graph, "foo"),
+            vec!["baz", "default", "derive", "full", "std"]
+        );
+
+        assert_err!(resolve(
+            &mut registry,
+            &[requirement("foo", "^1", &["unknown"])],
+            None,
+            false
+        ));
+    }
+
+    #[test]
+    fn compatibility_buckets() {
+        let bucket = |version: &str| compatibility_bucket(&Version::parse(version).unwrap());
+        assert_eq!(bucket("1.2.3"), (1, 0, 0));
+        assert_eq!(bucket("0.2.3"), (0, 2, 0));
+        assert_eq!(bucket("0.0.3"), (0, 0, 3));
+    }
+}
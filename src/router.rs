@@ -15,6 +15,9 @@ pub fn build_axum_router(state: AppState) -> Router<()> {
     let mut router = Router::new()
         // Route used by both `cargo search` and the frontend
         .route("/api/v1/crates", get(krate::search::search))
+        .route("/api/v1/audit/crates", post(krate::audit::audit))
+        .route("/api/v1/lookup/crates", post(krate::lookup::lookup))
+        .route("/api/v1/resolve/crates", post(krate::resolve::resolve))
         // Routes used by `cargo`
         .route(
             "/api/v1/crates/new",
@@ -66,6 +69,22 @@ pub fn build_axum_router(state: AppState) -> Router<()> {
             "/api/v1/crates/:crate_id/:version/authors",
             get(version::metadata::authors),
         )
+        .route(
+            "/api/v1/crates/:crate_id/:version/source",
+            get(version::source::list),
+        )
+        .route(
+            "/api/v1/crates/:crate_id/:version/source/*path",
+            get(version::source::show),
+        )
+        .route(
+            "/api/v1/crates/:crate_id/:version/diff",
+            get(version::diff::diff),
+        )
+        .route(
+            "/api/v1/crates/:crate_id/:version/attestation",
+            get(version::attestation::show),
+        )
         .route(
             "/api/v1/crates/:crate_id/downloads",
             get(krate::downloads::downloads),
@@ -90,6 +109,38 @@ pub fn build_axum_router(state: AppState) -> Router<()> {
             "/api/v1/crates/:crate_id/owner_user",
             get(krate::owners::owner_user),
         )
+        .route(
+            "/api/v1/crates/:crate_id/owner_invitations",
+            get(crate_owner_invitation::crate_list),
+        )
+        .route(
+            "/api/v1/crates/:crate_id/owner_invitations/:login",
+            delete(crate_owner_invitation::revoke),
+        )
+        .route(
+            "/api/v1/crates/:crate_id/owner_invitations/:login/resend",
+            post(crate_owner_invitation::resend),
+        )
+        .route(
+            "/api/v1/crates/:crate_id/transfer",
+            put(krate::transfer::propose).delete(krate::transfer::cancel),
+        )
+        .route(
+            "/api/v1/crates/:crate_id/transfer/accept",
+            put(krate::transfer::accept),
+        )
+        .route(
+            "/api/v1/crates/:crate_id/adoption_requests",
+            post(krate::adoption::create),
+        )
+        .route(
+            "/api/v1/crates/:crate_id/adoption_requests/:id",
+            delete(krate::adoption::delete),
+        )
+        .route(
+            "/api/v1/crates/:crate_id/publish_policy",
+            get(krate::publish_policy::show).put(krate::publish_policy::update),
+        )
         .route(
             "/api/v1/crates/:crate_id/reverse_dependencies",
             get(krate::metadata::reverse_dependencies),
@@ -105,7 +156,11 @@ pub fn build_axum_router(state: AppState) -> Router<()> {
         )
         .route("/api/v1/users/:user_id/stats", get(user::other::stats))
         .route("/api/v1/teams/:team_id", get(team::show_team))
-        .route("/api/v1/me", get(user::me::me))
+        .route(
+            "/api/v1/me",
+            get(user::me::me).delete(user::me::delete_account),
+        )
+        .route("/api/v1/me/export", get(user::me::export))
         .route("/api/v1/me/updates", get(user::me::updates))
         .route("/api/v1/me/tokens", get(token::list).put(token::new))
         .route("/api/v1/me/tokens/:id", delete(token::revoke))
@@ -122,6 +177,7 @@ pub fn build_axum_router(state: AppState) -> Router<()> {
             "/api/v1/me/crate_owner_invitations/accept/:token",
             put(crate_owner_invitation::handle_invite_with_token),
         )
+        .route("/api/v1/me/crate_transfers", get(krate::transfer::list))
         .route(
             "/api/v1/me/email_notifications",
             put(user::me::update_email_notifications),
@@ -153,6 +209,50 @@ pub fn build_axum_router(state: AppState) -> Router<()> {
             "/api/private/crate_owner_invitations",
             get(crate_owner_invitation::private_list),
         )
+        // Operator actions for users with the admin role
+        .route("/api/private/admin/actions", get(admin::list_actions))
+        .route(
+            "/api/private/admin/crates/:crate_id",
+            delete(admin::delete_crate),
+        )
+        .route(
+            "/api/private/admin/crates/:crate_id/quarantine",
+            put(admin::quarantine_crate).delete(admin::lift_crate_quarantine),
+        )
+        .route(
+            "/api/private/admin/crates/:crate_id/:version",
+            delete(admin::delete_version),
+        )
+        .route(
+            "/api/private/admin/crates/:crate_id/:version/yank",
+            put(admin::yank_version),
+        )
+        .route(
+            "/api/private/admin/crates/:crate_id/:version/quarantine",
+            put(admin::quarantine_version).delete(admin::lift_version_quarantine),
+        )
+        .route(
+            "/api/private/admin/transfer_crates",
+            post(admin::transfer_crates),
+        )
+        .route(
+            "/api/private/admin/users/:login/lock",
+            put(admin::lock_account).delete(admin::unlock_account),
+        )
+        .route(
+            "/api/private/admin/adoption_requests",
+            get(admin::list_adoption_requests),
+        )
+        .route(
+            "/api/private/admin/adoption_requests/:id/approve",
+            put(admin::approve_adoption_request),
+        )
+        .route(
+            "/api/private/admin/adoption_requests/:id/reject",
+            put(admin::reject_adoption_request),
+        )
+        .route("/api/private/admin/jobs", post(admin::enqueue_job))
+        .route("/api/private/admin/verify_token", post(admin::verify_token))
         // Alerts from GitHub scanning for exposed API tokens
         .route(
             "/api/github/secret-scanning/verify",
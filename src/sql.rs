@@ -1,4 +1,5 @@
 use diesel::sql_types::{Date, Double, Integer, Interval, SingleValue, Text, Timestamp};
+use diesel_full_text_search::{RegConfig, TsQuery};
 
 sql_function!(#[aggregate] fn array_agg<T: SingleValue>(x: T) -> Array<T>);
 sql_function!(fn canon_crate_name(x: Text) -> Text);
@@ -13,6 +14,10 @@ sql_function!(fn floor(x: Double) -> Integer);
 sql_function!(fn greatest<T: SingleValue>(x: T, y: T) -> T);
 sql_function!(fn least<T: SingleValue>(x: T, y: T) -> T);
 sql_function!(fn split_part(string: Text, delimiter: Text, n: Integer) -> Text);
+sql_function! {
+    #[sql_name = "plainto_tsquery"]
+    fn plainto_tsquery_with_search_config(config: RegConfig, query: Text) -> TsQuery;
+}
 
 macro_rules! pg_enum {
     (
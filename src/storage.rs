@@ -19,11 +19,16 @@ use tokio::io::AsyncWriteExt;
 
 const PREFIX_CRATES: &str = "crates";
 const PREFIX_READMES: &str = "readmes";
+const PREFIX_DIFFS: &str = "diffs";
+/// Files of quarantined versions are moved below this prefix, which must not
+/// be served by the CDN.
+const PREFIX_QUARANTINE: &str = "quarantine";
 const DEFAULT_REGION: &str = "us-west-1";
 const CONTENT_TYPE_CRATE: &str = "application/gzip";
 const CONTENT_TYPE_DB_DUMP: &str = "application/gzip";
 const CONTENT_TYPE_INDEX: &str = "text/plain";
 const CONTENT_TYPE_README: &str = "text/html";
+const CONTENT_TYPE_DIFF: &str = "application/json";
 const CACHE_CONTROL_IMMUTABLE: &str = "public,max-age=31536000,immutable";
 const CACHE_CONTROL_INDEX: &str = "public,max-age=600";
 const CACHE_CONTROL_README: &str = "public,max-age=604800";
@@ -114,6 +119,7 @@ pub struct Storage {
     store: Box<dyn ObjectStore>,
     crate_upload_store: Box<dyn ObjectStore>,
     readme_upload_store: Box<dyn ObjectStore>,
+    diff_upload_store: Box<dyn ObjectStore>,
     db_dump_upload_store: Box<dyn ObjectStore>,
 
     index_store: Box<dyn ObjectStore>,
@@ -139,6 +145,11 @@ impl Storage {
                 let options = client_options(CONTENT_TYPE_README, CACHE_CONTROL_README);
                 let readme_upload_store = build_s3(default, options);
 
+                // Diffs are deleted when one of their versions is quarantined,
+                // so they must not be cached as immutable files.
+                let options = ClientOptions::default().with_default_content_type(CONTENT_TYPE_DIFF);
+                let diff_upload_store = build_s3(default, options);
+
                 let options =
                     ClientOptions::default().with_default_content_type(CONTENT_TYPE_DB_DUMP);
                 let db_dump_upload_store = build_s3(default, options);
@@ -157,6 +168,7 @@ impl Storage {
                     store: Box::new(store),
                     crate_upload_store: Box::new(crate_upload_store),
                     readme_upload_store: Box::new(readme_upload_store),
+                    diff_upload_store: Box::new(diff_upload_store),
                     db_dump_upload_store: Box::new(db_dump_upload_store),
                     cdn_prefix,
                     index_store: Box::new(index_store),
@@ -188,6 +200,7 @@ impl Storage {
                     store: Box::new(store.clone()),
                     crate_upload_store: Box::new(store.clone()),
                     readme_upload_store: Box::new(store.clone()),
+                    diff_upload_store: Box::new(store.clone()),
                     db_dump_upload_store: Box::new(store),
                     cdn_prefix,
                     index_store: Box::new(index_store.clone()),
@@ -203,6 +216,7 @@ impl Storage {
                     store: Box::new(store.clone()),
                     crate_upload_store: Box::new(store.clone()),
                     readme_upload_store: Box::new(store.clone()),
+                    diff_upload_store: Box::new(store.clone()),
                     db_dump_upload_store: Box::new(store.clone()),
                     cdn_prefix,
                     index_store: Box::new(PrefixStore::new(store.clone(), "index")),
@@ -238,18 +252,37 @@ impl Storage {
         self.delete_all_with_prefix(&prefix).await
     }
 
+    #[instrument(skip(self))]
+    pub async fn delete_all_diffs(&self, name: &str) -> Result<()> {
+        let prefix = format!("{PREFIX_DIFFS}/{name}").into();
+        self.delete_all_with_prefix(&prefix).await
+    }
+
     #[instrument(skip(self))]
     pub async fn delete_crate_file(&self, name: &str, version: &str) -> Result<()> {
         let path = crate_file_path(name, version);
         self.store.delete(&path).await
     }
 
+    #[instrument(skip(self))]
+    pub async fn delete_attestation(&self, name: &str, version: &str) -> Result<()> {
+        let path = attestation_path(name, version);
+        self.store.delete(&path).await
+    }
+
     #[instrument(skip(self))]
     pub async fn delete_readme(&self, name: &str, version: &str) -> Result<()> {
         let path = readme_path(name, version);
         self.store.delete(&path).await
     }
 
+    /// Downloads the `.crate` file of a published version.
+    #[instrument(skip(self))]
+    pub async fn download_crate_file(&self, name: &str, version: &str) -> Result<Bytes> {
+        let path = crate_file_path(name, version);
+        self.store.get(&path).await?.bytes().await
+    }
+
     #[instrument(skip(self, bytes))]
     pub async fn upload_crate_file(&self, name: &str, version: &str, bytes: Bytes) -> Result<()> {
         let path = crate_file_path(name, version);
@@ -257,6 +290,22 @@ impl Storage {
         Ok(())
     }
 
+    /// Uploads the signed release attestation of a version, which is stored
+    /// next to its `.crate` file.
+    #[instrument(skip(self, bytes))]
+    pub async fn upload_attestation(&self, name: &str, version: &str, bytes: Bytes) -> Result<()> {
+        let path = attestation_path(name, version);
+        self.crate_upload_store.put(&path, bytes).await?;
+        Ok(())
+    }
+
+    /// Downloads the signed release attestation of a version.
+    #[instrument(skip(self))]
+    pub async fn download_attestation(&self, name: &str, version: &str) -> Result<Bytes> {
+        let path = attestation_path(name, version);
+        self.store.get(&path).await?.bytes().await
+    }
+
     #[instrument(skip(self, bytes))]
     pub async fn upload_readme(&self, name: &str, version: &str, bytes: Bytes) -> Result<()> {
         let path = readme_path(name, version);
@@ -264,6 +313,67 @@ impl Storage {
         Ok(())
     }
 
+    /// Moves the `.crate` file, the attestation and the README of a
+    /// quarantined version from the paths that are served by the CDN to the
+    /// `quarantine/` prefix.
+    ///
+    /// Returns the paths that were moved, which need to be invalidated on the
+    /// CDN. Files that don't exist or were already moved are skipped.
+    #[instrument(skip(self))]
+    pub async fn quarantine_version_files(&self, name: &str, version: &str) -> Result<Vec<Path>> {
+        let mut moved = Vec::new();
+        for path in version_file_paths(name, version) {
+            if self
+                .rename_if_exists(&path, &quarantine_path(&path))
+                .await?
+            {
+                moved.push(path);
+            }
+        }
+
+        Ok(moved)
+    }
+
+    /// Moves the files of a version back from the `quarantine/` prefix after
+    /// its quarantine has been lifted. This is the reverse of
+    /// [`Storage::quarantine_version_files()`].
+    #[instrument(skip(self))]
+    pub async fn restore_version_files(&self, name: &str, version: &str) -> Result<Vec<Path>> {
+        let mut moved = Vec::new();
+        for path in version_file_paths(name, version) {
+            if self
+                .rename_if_exists(&quarantine_path(&path), &path)
+                .await?
+            {
+                moved.push(path);
+            }
+        }
+
+        Ok(moved)
+    }
+
+    async fn rename_if_exists(&self, from: &Path, to: &Path) -> Result<bool> {
+        match self.store.rename(from, to).await {
+            Ok(()) => Ok(true),
+            Err(object_store::Error::NotFound { .. }) => Ok(false),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Downloads a previously cached diff between two versions of a crate.
+    #[instrument(skip(self))]
+    pub async fn download_diff(&self, name: &str, from: &str, to: &str) -> Result<Bytes> {
+        let path = diff_path(name, from, to);
+        self.store.get(&path).await?.bytes().await
+    }
+
+    #[instrument(skip(self, bytes))]
+    pub async fn upload_diff(&self, name: &str, from: &str, to: &str, bytes: Bytes) -> Result<()> {
+        let path = diff_path(name, from, to);
+        self.diff_upload_store.put(&path, bytes).await?;
+        Ok(())
+    }
+
     #[instrument(skip(self, content))]
     pub async fn sync_index(&self, name: &str, content: Option<String>) -> Result<()> {
         let path = crates_io_index::Repository::relative_index_file_for_url(name).into();
@@ -343,10 +453,31 @@ fn crate_file_path(name: &str, version: &str) -> Path {
     format!("{PREFIX_CRATES}/{name}/{name}-{version}.crate").into()
 }
 
+fn attestation_path(name: &str, version: &str) -> Path {
+    format!("{PREFIX_CRATES}/{name}/{name}-{version}.crate.attestation").into()
+}
+
 fn readme_path(name: &str, version: &str) -> Path {
     format!("{PREFIX_READMES}/{name}/{name}-{version}.html").into()
 }
 
+/// The files of a version that are served by the CDN.
+fn version_file_paths(name: &str, version: &str) -> [Path; 3] {
+    [
+        crate_file_path(name, version),
+        attestation_path(name, version),
+        readme_path(name, version),
+    ]
+}
+
+fn quarantine_path(path: &Path) -> Path {
+    format!("{PREFIX_QUARANTINE}/{path}").into()
+}
+
+fn diff_path(name: &str, from: &str, to: &str) -> Path {
+    format!("{PREFIX_DIFFS}/{name}/{name}-{from}...{to}.json").into()
+}
+
 fn apply_cdn_prefix(cdn_prefix: &Option<String>, path: &Path) -> String {
     match cdn_prefix {
         Some(cdn_prefix) if !cdn_prefix.starts_with("https://") => {
@@ -370,6 +501,7 @@ mod tests {
             "crates/bar/bar-2.0.0.crate",
             "crates/foo/foo-1.0.0.crate",
             "crates/foo/foo-1.2.3.crate",
+            "diffs/foo/foo-1.0.0...1.2.3.json",
             "readmes/bar/bar-2.0.0.html",
             "readmes/foo/foo-1.0.0.html",
             "readmes/foo/foo-1.2.3.html",
@@ -456,6 +588,7 @@ mod tests {
 
         let expected_files = vec![
             "crates/bar/bar-2.0.0.crate",
+            "diffs/foo/foo-1.0.0...1.2.3.json",
             "readmes/bar/bar-2.0.0.html",
             "readmes/foo/foo-1.0.0.html",
             "readmes/foo/foo-1.2.3.html",
@@ -473,11 +606,29 @@ mod tests {
             "crates/bar/bar-2.0.0.crate",
             "crates/foo/foo-1.0.0.crate",
             "crates/foo/foo-1.2.3.crate",
+            "diffs/foo/foo-1.0.0...1.2.3.json",
             "readmes/bar/bar-2.0.0.html",
         ];
         assert_eq!(stored_files(&storage.store).await, expected_files);
     }
 
+    #[tokio::test]
+    async fn delete_all_diffs() {
+        let storage = prepare().await;
+
+        storage.delete_all_diffs("foo").await.unwrap();
+
+        let expected_files = vec![
+            "crates/bar/bar-2.0.0.crate",
+            "crates/foo/foo-1.0.0.crate",
+            "crates/foo/foo-1.2.3.crate",
+            "readmes/bar/bar-2.0.0.html",
+            "readmes/foo/foo-1.0.0.html",
+            "readmes/foo/foo-1.2.3.html",
+        ];
+        assert_eq!(stored_files(&storage.store).await, expected_files);
+    }
+
     #[tokio::test]
     async fn delete_crate_file() {
         let storage = prepare().await;
@@ -487,6 +638,7 @@ mod tests {
         let expected_files = vec![
             "crates/bar/bar-2.0.0.crate",
             "crates/foo/foo-1.0.0.crate",
+            "diffs/foo/foo-1.0.0...1.2.3.json",
             "readmes/bar/bar-2.0.0.html",
             "readmes/foo/foo-1.0.0.html",
             "readmes/foo/foo-1.2.3.html",
@@ -504,6 +656,7 @@ mod tests {
             "crates/bar/bar-2.0.0.crate",
             "crates/foo/foo-1.0.0.crate",
             "crates/foo/foo-1.2.3.crate",
+            "diffs/foo/foo-1.0.0...1.2.3.json",
             "readmes/bar/bar-2.0.0.html",
             "readmes/foo/foo-1.0.0.html",
         ];
@@ -532,6 +685,63 @@ mod tests {
         assert_eq!(stored_files(&s.store).await, expected_files);
     }
 
+    #[tokio::test]
+    async fn download_crate_file() {
+        let s = Storage::from_config(&StorageConfig::in_memory());
+
+        let bytes = Bytes::from_static(b"crate contents");
+        s.upload_crate_file("foo", "1.2.3", bytes.clone())
+            .await
+            .unwrap();
+
+        assert_eq!(s.download_crate_file("foo", "1.2.3").await.unwrap(), bytes);
+        assert!(s.download_crate_file("foo", "2.0.0").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn upload_attestation() {
+        let s = Storage::from_config(&StorageConfig::in_memory());
+
+        let bytes = Bytes::from_static(b"{}");
+        s.upload_crate_file("foo", "1.2.3", Bytes::new())
+            .await
+            .unwrap();
+        s.upload_attestation("foo", "1.2.3", bytes.clone())
+            .await
+            .unwrap();
+
+        let expected_files = vec![
+            "crates/foo/foo-1.2.3.crate",
+            "crates/foo/foo-1.2.3.crate.attestation",
+        ];
+        assert_eq!(stored_files(&s.store).await, expected_files);
+
+        let downloaded = s.download_attestation("foo", "1.2.3").await;
+        assert_eq!(downloaded.unwrap(), bytes);
+
+        s.delete_attestation("foo", "1.2.3").await.unwrap();
+        assert_eq!(
+            stored_files(&s.store).await,
+            vec!["crates/foo/foo-1.2.3.crate"]
+        );
+    }
+
+    #[tokio::test]
+    async fn upload_diff() {
+        let s = Storage::from_config(&StorageConfig::in_memory());
+
+        let bytes = Bytes::from_static(b"{}");
+        s.upload_diff("foo", "1.0.0", "2.0.0+foo", bytes.clone())
+            .await
+            .unwrap();
+
+        let expected_files = vec!["diffs/foo/foo-1.0.0...2.0.0+foo.json"];
+        assert_eq!(stored_files(&s.store).await, expected_files);
+
+        let downloaded = s.download_diff("foo", "1.0.0", "2.0.0+foo").await;
+        assert_eq!(downloaded.unwrap(), bytes);
+    }
+
     #[tokio::test]
     async fn upload_readme() {
         let s = Storage::from_config(&StorageConfig::in_memory());
@@ -583,4 +793,44 @@ mod tests {
         let expected_files = vec![target];
         assert_eq!(stored_files(&s.store).await, expected_files);
     }
+
+    #[tokio::test]
+    async fn quarantine_version_files() {
+        let s = Storage::from_config(&StorageConfig::in_memory());
+
+        s.upload_crate_file("foo", "1.2.3", Bytes::new())
+            .await
+            .unwrap();
+        s.upload_readme("foo", "1.2.3", Bytes::new()).await.unwrap();
+        s.upload_crate_file("foo", "2.0.0", Bytes::new())
+            .await
+            .unwrap();
+
+        let moved = s.quarantine_version_files("foo", "1.2.3").await.unwrap();
+        let moved = moved.iter().map(Path::as_ref).collect::<Vec<_>>();
+        assert_eq!(
+            moved,
+            ["crates/foo/foo-1.2.3.crate", "readmes/foo/foo-1.2.3.html"]
+        );
+
+        let expected_files = vec![
+            "crates/foo/foo-2.0.0.crate",
+            "quarantine/crates/foo/foo-1.2.3.crate",
+            "quarantine/readmes/foo/foo-1.2.3.html",
+        ];
+        assert_eq!(stored_files(&s.store).await, expected_files);
+
+        let moved = s.quarantine_version_files("foo", "1.2.3").await.unwrap();
+        assert!(moved.is_empty());
+
+        let moved = s.restore_version_files("foo", "1.2.3").await.unwrap();
+        assert_eq!(moved.len(), 2);
+
+        let expected_files = vec![
+            "crates/foo/foo-1.2.3.crate",
+            "crates/foo/foo-2.0.0.crate",
+            "readmes/foo/foo-1.2.3.html",
+        ];
+        assert_eq!(stored_files(&s.store).await, expected_files);
+    }
 }
@@ -59,6 +59,8 @@ struct CrateMeta {
     total: i32,
     next_page: Option<String>,
     prev_page: Option<String>,
+    #[serde(default)]
+    suggestions: Vec<String>,
 }
 #[derive(Deserialize)]
 pub struct CrateResponse {
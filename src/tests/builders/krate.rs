@@ -116,6 +116,12 @@ impl<'a> CrateBuilder<'a> {
         self
     }
 
+    /// Sets the crate's `ownership_invitations_expiration_days` override value.
+    pub fn ownership_invitations_expiration_days(mut self, days: i32) -> Self {
+        self.krate.ownership_invitations_expiration_days = Some(days);
+        self
+    }
+
     pub fn build(mut self, connection: &mut PgConnection) -> AppResult<Crate> {
         use diesel::{insert_into, select, update};
 
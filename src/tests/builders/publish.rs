@@ -8,6 +8,9 @@ use crates_io_tarball::TarballBuilder;
 
 use super::DependencyBuilder;
 
+/// Builds an attestation document for the given tarball contents.
+type AttestationFn = Box<dyn FnOnce(&[u8]) -> Vec<u8>>;
+
 /// A builder for constructing a crate for the purposes of testing publishing. If you only need
 /// a crate to exist and don't need to test behavior caused by the publish request, inserting
 /// a crate into the database directly by using CrateBuilder will be faster.
@@ -25,6 +28,7 @@ pub struct PublishBuilder {
     readme: Option<String>,
     version: semver::Version,
     features: BTreeMap<String, Vec<String>>,
+    attestation: Option<AttestationFn>,
 }
 
 enum Manifest {
@@ -51,6 +55,7 @@ impl PublishBuilder {
             readme: None,
             version: semver::Version::parse(version).unwrap(),
             features: BTreeMap::new(),
+            attestation: None,
         }
     }
 
@@ -138,6 +143,13 @@ impl PublishBuilder {
         self
     }
 
+    /// Upload an attestation together with the tarball. The closure receives
+    /// the tarball contents and returns the attestation document.
+    pub fn attestation(mut self, attestation: impl FnOnce(&[u8]) -> Vec<u8> + 'static) -> Self {
+        self.attestation = Some(Box::new(attestation));
+        self
+    }
+
     pub fn build(self) -> (String, Vec<u8>) {
         let metadata = u::PublishMetadata {
             name: self.krate_name.clone(),
@@ -206,9 +218,20 @@ impl PublishBuilder {
     }
 
     /// Consume this builder to make the Put request body
-    pub fn body(self) -> Bytes {
+    pub fn body(mut self) -> Bytes {
+        let attestation = self.attestation.take();
         let (json, tarball) = self.build();
-        PublishBuilder::create_publish_body(&json, &tarball)
+        let body = PublishBuilder::create_publish_body(&json, &tarball);
+
+        let Some(attestation) = attestation else {
+            return body;
+        };
+
+        let attestation = attestation(&tarball);
+        let mut body = BytesMut::from(&body[..]);
+        body.put_u32_le(attestation.len() as u32);
+        body.put_slice(&attestation);
+        body.freeze()
     }
 
     pub fn create_publish_body(json: &str, tarball: &[u8]) -> Bytes {
@@ -1,4 +1,5 @@
 mod following;
 mod publish;
+mod quarantine;
 mod versions;
 mod yanking;
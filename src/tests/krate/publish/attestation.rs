@@ -0,0 +1,154 @@
+use crate::builders::PublishBuilder;
+use crate::util::{RequestHelper, TestApp};
+use base64::{engine::general_purpose, Engine};
+use crates_io::attestation::TrustRoot;
+use http::StatusCode;
+use insta::assert_json_snapshot;
+use p256::ecdsa::signature::Signer;
+use p256::ecdsa::{Signature, SigningKey};
+
+const SIGNER: &str = "https://github.com/foo/foo/.github/workflows/release.yml";
+
+fn signing_key() -> SigningKey {
+    SigningKey::from_slice(&[1; 32]).unwrap()
+}
+
+fn sign(signer: &'static str) -> impl FnOnce(&[u8]) -> Vec<u8> {
+    move |tarball| {
+        let signature: Signature = signing_key().sign(tarball);
+        let signature = general_purpose::STANDARD.encode(signature.to_der());
+        let attestation = serde_json::json!({
+            "version": 1,
+            "signer": signer,
+            "signature": signature,
+        });
+        serde_json::to_vec(&attestation).unwrap()
+    }
+}
+
+fn trust_root() -> TrustRoot {
+    TrustRoot::new().with_signer(SIGNER, *signing_key().verifying_key())
+}
+
+#[test]
+fn publish_with_attestation() {
+    let (app, anon, _, token) = TestApp::full()
+        .with_config(|config| config.attestation_trust_root = trust_root())
+        .with_token();
+
+    let crate_to_publish = PublishBuilder::new("foo", "1.0.0").attestation(sign(SIGNER));
+    token.publish_crate(crate_to_publish).good();
+
+    let expected_files = vec![
+        "crates/foo/foo-1.0.0.crate",
+        "crates/foo/foo-1.0.0.crate.attestation",
+        "index/3/f/foo",
+    ];
+    assert_eq!(app.stored_files(), expected_files);
+
+    let response = anon.get::<()>("/api/v1/crates/foo/1.0.0/attestation");
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.into_json();
+    assert_json_snapshot!(json, {
+        ".attestation.checksum" => "[checksum]",
+        ".attestation.created_at" => "[datetime]",
+        ".attestation.document.signature" => "[signature]",
+    });
+
+    let checksum = json["attestation"]["checksum"].as_str().unwrap();
+    let crates = app.crates_from_index_head("foo");
+    assert_eq!(crates.len(), 1);
+    assert_eq!(crates[0].attestation.as_deref(), Some(checksum));
+}
+
+#[test]
+fn publish_without_attestation() {
+    let (app, anon, _, token) = TestApp::full()
+        .with_config(|config| config.attestation_trust_root = trust_root())
+        .with_token();
+
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .good();
+
+    let crates = app.crates_from_index_head("foo");
+    assert_eq!(crates[0].attestation, None);
+
+    let response = anon.get::<()>("/api/v1/crates/foo/1.0.0/attestation");
+    response.assert_not_found();
+}
+
+#[test]
+fn untrusted_signer() {
+    let (app, _, _, token) = TestApp::full()
+        .with_config(|config| config.attestation_trust_root = trust_root())
+        .with_token();
+
+    let crate_to_publish = PublishBuilder::new("foo", "1.0.0").attestation(sign("someone else"));
+    let response = token.publish_crate(crate_to_publish);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.into_json());
+    assert!(app.stored_files().is_empty());
+}
+
+#[test]
+fn invalid_signature() {
+    let (app, _, _, token) = TestApp::full()
+        .with_config(|config| config.attestation_trust_root = trust_root())
+        .with_token();
+
+    let crate_to_publish =
+        PublishBuilder::new("foo", "1.0.0").attestation(|_tarball| sign(SIGNER)(b"something else"));
+    let response = token.publish_crate(crate_to_publish);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.into_json());
+    assert!(app.stored_files().is_empty());
+}
+
+#[test]
+fn invalid_format() {
+    let (app, _, _, token) = TestApp::full()
+        .with_config(|config| config.attestation_trust_root = trust_root())
+        .with_token();
+
+    let crate_to_publish =
+        PublishBuilder::new("foo", "1.0.0").attestation(|_tarball| b"not json".to_vec());
+    let response = token.publish_crate(crate_to_publish);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.into_json());
+    assert!(app.stored_files().is_empty());
+}
+
+#[test]
+fn signer_of_another_account() {
+    const OTHER_SIGNER: &str = "https://github.com/bar/bar/.github/workflows/release.yml";
+
+    let (app, _, _, token) = TestApp::full()
+        .with_config(|config| {
+            config.attestation_trust_root =
+                trust_root().with_signer(OTHER_SIGNER, *signing_key().verifying_key())
+        })
+        .with_token();
+
+    let crate_to_publish = PublishBuilder::new("foo", "1.0.0").attestation(sign(OTHER_SIGNER));
+    let response = token.publish_crate(crate_to_publish);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.into_json());
+    assert!(app.stored_files().is_empty());
+}
+
+#[test]
+fn trailing_bytes() {
+    let (app, _, _, token) = TestApp::full()
+        .with_config(|config| config.attestation_trust_root = trust_root())
+        .with_token();
+
+    let crate_to_publish = PublishBuilder::new("foo", "1.0.0").attestation(sign(SIGNER));
+    let mut body = crate_to_publish.body().to_vec();
+    body.extend_from_slice(b"trailing");
+
+    let response = token.put::<()>("/api/v1/crates/new", body);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.into_json());
+    assert!(app.stored_files().is_empty());
+}
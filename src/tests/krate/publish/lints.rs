@@ -0,0 +1,65 @@
+use crate::builders::PublishBuilder;
+use crate::util::{RequestHelper, TestApp};
+use crates_io::lints;
+use diesel::prelude::*;
+use http::StatusCode;
+use insta::assert_json_snapshot;
+
+#[test]
+fn lint_warnings() {
+    let (_app, anon, _, token) = TestApp::full()
+        .with_config(|config| {
+            config.enabled_publish_lints = lints::all_lint_names();
+        })
+        .with_token();
+
+    let crate_to_publish =
+        PublishBuilder::new("foo", "1.0.0").add_file("foo-1.0.0/target/debug/foo", "binary");
+    let response = token.publish_crate(crate_to_publish);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.into_json()["warnings"]);
+
+    let json = anon.get::<()>("/api/v1/crates/foo/1.0.0").into_json();
+    let lints = json["lint_warnings"].as_array().unwrap();
+    assert!(lints.iter().all(|w| w["message"].is_string()));
+
+    let lints = lints
+        .iter()
+        .map(|w| w["lint"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        lints,
+        vec![
+            "missing_repository",
+            "missing_readme",
+            "missing_vcs_info",
+            "unexpected_files"
+        ]
+    );
+}
+
+#[test]
+fn disabled_lints() {
+    let (app, _, _, token) = TestApp::full()
+        .with_config(|config| {
+            config.enabled_publish_lints = ["missing_repository".to_string()].into();
+        })
+        .with_token();
+
+    let crate_to_publish = PublishBuilder::new("foo", "1.0.0");
+    let response = token.publish_crate(crate_to_publish);
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let json = response.into_json();
+    let other = json["warnings"]["other"].as_array().unwrap();
+    assert_eq!(other.len(), 1);
+    assert!(other[0].as_str().unwrap().contains("`package.repository`"));
+
+    let count: i64 = app.db(|conn| {
+        crates_io::schema::version_lint_warnings::table
+            .count()
+            .get_result(conn)
+            .unwrap()
+    });
+    assert_eq!(count, 1);
+}
@@ -130,3 +130,66 @@ fn invalid_rust_version() {
     assert_eq!(response.status(), StatusCode::OK);
     assert_json_snapshot!(response.into_json());
 }
+
+#[test]
+fn package_metadata() {
+    let (_app, _anon, _cookie, token) = TestApp::full().with_token();
+
+    let response = token.publish_crate(
+        PublishBuilder::new("foo", "1.0.0")
+            .custom_manifest(
+                r#"[package]
+                name = "foo"
+                version = "1.0.0"
+                edition = "2021"
+                description = "description"
+                license = "MIT"
+                autobins = false
+
+                [lib]
+                proc-macro = true
+
+                [[bin]]
+                name = "foo-cli"
+                path = "src/main.rs"
+                required-features = ["cli"]
+
+                [badges]
+                maintenance = { status = "actively-developed" }"#,
+            )
+            .add_file("foo-1.0.0/build.rs", "fn main() {}")
+            .add_file("foo-1.0.0/src/lib.rs", "")
+            .add_file("foo-1.0.0/src/main.rs", "fn main() {}\n"),
+    );
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = token.get::<()>("/api/v1/crates/foo/1.0.0");
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.into_json();
+    assert_json_snapshot!(json["metadata"]);
+}
+
+#[test]
+fn package_metadata_with_disabled_build_script() {
+    let (_app, _anon, _cookie, token) = TestApp::full().with_token();
+
+    let response = token.publish_crate(
+        PublishBuilder::new("foo", "1.0.0")
+            .custom_manifest(
+                r#"[package]
+                name = "foo"
+                version = "1.0.0"
+                description = "description"
+                license = "MIT"
+                build = false"#,
+            )
+            .add_file("foo-1.0.0/build.rs", "fn main() {}"),
+    );
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = token.get::<()>("/api/v1/crates/foo/1.0.0");
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.into_json();
+    assert_eq!(json["metadata"]["edition"], "2015");
+    assert_eq!(json["metadata"]["has_build_script"], false);
+}
@@ -1,3 +1,4 @@
+mod attestation;
 mod audit_action;
 mod auth;
 mod basics;
@@ -9,11 +10,15 @@ mod features;
 mod git;
 mod inheritance;
 mod keywords;
+mod lints;
 mod manifest;
 mod max_size;
+mod policy;
 mod rate_limit;
 mod readme;
+mod scan;
 mod similar_names;
 mod tarball;
 mod timestamps;
 mod validation;
+mod vcs;
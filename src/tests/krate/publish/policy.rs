@@ -0,0 +1,246 @@
+use crate::builders::{CrateBuilder, PublishBuilder};
+use crate::util::{MockCookieUser, RequestHelper, Response, TestApp};
+use crates_io::models::token::CrateScope;
+use crates_io::models::{CrateOwner, OwnerKind};
+use crates_io::schema::crate_owners;
+use diesel::RunQueryDsl;
+use http::StatusCode;
+use serde_json::Value;
+
+fn set_policy(user: &impl RequestHelper, krate: &str, policy: Value) -> Response<Value> {
+    let body = json!({ "publish_policy": policy });
+    user.put(
+        &format!("/api/v1/crates/{krate}/publish_policy"),
+        body.to_string(),
+    )
+}
+
+fn add_owner(app: &TestApp, crate_id: i32, owner: &MockCookieUser) {
+    app.db(|conn| {
+        diesel::insert_into(crate_owners::table)
+            .values(CrateOwner {
+                crate_id,
+                owner_id: owner.as_model().id,
+                created_by: owner.as_model().id,
+                owner_kind: OwnerKind::User,
+                email_notifications: true,
+            })
+            .execute(conn)
+            .unwrap();
+    });
+}
+
+fn error_detail<T>(response: Response<T>) -> Value {
+    assert_eq!(response.status(), StatusCode::OK);
+    response.into_json()["errors"][0]["detail"].clone()
+}
+
+#[test]
+fn show_and_update_policy() {
+    let (app, anon, user) = TestApp::full().with_user();
+    let other = app.db_new_user("other");
+    let krate = app.db(|conn| CrateBuilder::new("foo", user.as_model().id).expect_build(conn));
+    add_owner(&app, krate.id, &other);
+
+    let json = anon
+        .get::<()>("/api/v1/crates/foo/publish_policy")
+        .into_json();
+    assert_eq!(
+        json,
+        json!({
+            "publish_policy": {
+                "require_exact_crate_scope": false,
+                "forbid_dirty_vcs": false,
+                "require_rust_version": false,
+                "yank_only_owners": [],
+            },
+        })
+    );
+
+    let policy = json!({
+        "require_exact_crate_scope": true,
+        "require_rust_version": true,
+        "yank_only_owners": ["OTHER"],
+    });
+    let json = set_policy(&user, "foo", policy).good();
+    let expected = json!({
+        "publish_policy": {
+            "require_exact_crate_scope": true,
+            "forbid_dirty_vcs": false,
+            "require_rust_version": true,
+            "yank_only_owners": ["other"],
+        },
+    });
+    assert_eq!(json, expected);
+
+    let json = anon
+        .get::<()>("/api/v1/crates/foo/publish_policy")
+        .into_json();
+    assert_eq!(json, expected);
+}
+
+#[test]
+fn update_policy_errors() {
+    let (app, anon, user) = TestApp::full().with_user();
+    let other = app.db_new_user("other");
+    app.db(|conn| CrateBuilder::new("foo", user.as_model().id).expect_build(conn));
+
+    let policy = json!({ "forbid_dirty_vcs": true });
+    let response = set_policy(&anon, "foo", policy.clone());
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = set_policy(&other, "foo", policy);
+    assert_eq!(
+        error_detail(response),
+        "only owners have permission to change the publish policy"
+    );
+
+    let policy = json!({ "yank_only_owners": ["other"] });
+    let response = set_policy(&user, "foo", policy);
+    assert_eq!(
+        error_detail(response),
+        "`other` is not an owner of this crate"
+    );
+
+    let login = user.as_model().gh_login.clone();
+    let policy = json!({ "yank_only_owners": [login] });
+    let response = set_policy(&user, "foo", policy);
+    assert_eq!(
+        error_detail(response),
+        "at least one owner must be allowed to publish new versions"
+    );
+}
+
+#[test]
+fn require_exact_crate_scope() {
+    let (app, _, user, token) = TestApp::full().with_token();
+    app.db(|conn| CrateBuilder::new("foo", user.as_model().id).expect_build(conn));
+    set_policy(&user, "foo", json!({ "require_exact_crate_scope": true })).good();
+
+    let response = token.publish_crate(PublishBuilder::new("foo", "1.1.0"));
+    assert_eq!(
+        error_detail(response),
+        "the owners of `foo` require new versions to be published with an API token that is \
+        scoped to the `foo` crate, without wildcards"
+    );
+
+    let scopes = Some(vec![CrateScope::try_from("fo*").unwrap()]);
+    let wildcard_token = user.db_new_scoped_token("wildcard", scopes, None, None);
+    let response = wildcard_token.publish_crate(PublishBuilder::new("foo", "1.1.0"));
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.into_json()["errors"].is_array());
+
+    let scopes = Some(vec![CrateScope::try_from("foo@^1").unwrap()]);
+    let caret_token = user.db_new_scoped_token("caret", scopes, None, None);
+    let response = caret_token.publish_crate(PublishBuilder::new("foo", "1.1.0"));
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.into_json()["errors"].is_array());
+
+    let scopes = Some(vec![CrateScope::try_from("foo@=1.1.0").unwrap()]);
+    let pinned_token = user.db_new_scoped_token("pinned", scopes, None, None);
+    pinned_token
+        .publish_crate(PublishBuilder::new("foo", "1.1.0"))
+        .good();
+
+    let scopes = Some(vec![CrateScope::try_from("foo").unwrap()]);
+    let exact_token = user.db_new_scoped_token("exact", scopes, None, None);
+    exact_token
+        .publish_crate(PublishBuilder::new("foo", "1.2.0"))
+        .good();
+}
+
+#[test]
+fn rights_are_checked_before_policy() {
+    let (app, _, user) = TestApp::full().with_user();
+    app.db(|conn| CrateBuilder::new("foo", user.as_model().id).expect_build(conn));
+    set_policy(&user, "foo", json!({ "require_exact_crate_scope": true })).good();
+
+    let other = app.db_new_user("other");
+    let other_token = other.db_new_token("other");
+    let response = other_token.publish_crate(PublishBuilder::new("foo", "1.1.0"));
+    assert_eq!(
+        error_detail(response),
+        "this crate exists but you don't seem to be an owner. If you believe this is a mistake, \
+        perhaps you need to accept an invitation to be an owner before publishing."
+    );
+}
+
+#[test]
+fn forbid_dirty_vcs() {
+    let (app, _, user, token) = TestApp::full().with_token();
+    app.db(|conn| CrateBuilder::new("foo", user.as_model().id).expect_build(conn));
+    set_policy(&user, "foo", json!({ "forbid_dirty_vcs": true })).good();
+
+    let vcs_info = |dirty: bool| {
+        format!(
+            r#"{{"git": {{"sha1": "0123456789abcdef0123456789abcdef01234567", "dirty": {dirty}}}, "path_in_vcs": ""}}"#
+        )
+    };
+
+    let response = token.publish_crate(PublishBuilder::new("foo", "1.1.0"));
+    assert_eq!(
+        error_detail(response),
+        "the owners of `foo` require new versions to be published from a git checkout, but the \
+        package does not contain a `.cargo_vcs_info.json` file"
+    );
+
+    let expected = "the owners of `foo` require new versions to be published from a clean VCS \
+        working directory. Commit your changes and publish without `--allow-dirty`.";
+
+    let response = token.publish_crate(
+        PublishBuilder::new("foo", "1.1.0")
+            .add_file("foo-1.1.0/.cargo_vcs_info.json", vcs_info(true)),
+    );
+    assert_eq!(error_detail(response), expected);
+
+    token
+        .publish_crate(
+            PublishBuilder::new("foo", "1.1.0")
+                .add_file("foo-1.1.0/.cargo_vcs_info.json", vcs_info(false)),
+        )
+        .good();
+}
+
+#[test]
+fn require_rust_version() {
+    let (app, _, user, token) = TestApp::full().with_token();
+    app.db(|conn| CrateBuilder::new("foo", user.as_model().id).expect_build(conn));
+    set_policy(&user, "foo", json!({ "require_rust_version": true })).good();
+
+    let response = token.publish_crate(PublishBuilder::new("foo", "1.1.0"));
+    assert_eq!(
+        error_detail(response),
+        "the owners of `foo` require new versions to declare a `rust-version` in their \
+        `Cargo.toml` file"
+    );
+
+    let manifest = "[package]\nname = \"foo\"\nversion = \"1.1.0\"\ndescription = \"description\"\nlicense = \"MIT\"\nrust-version = \"1.70\"\n";
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.1.0").custom_manifest(manifest))
+        .good();
+}
+
+#[test]
+fn yank_only_owners() {
+    let (app, _, user, token) = TestApp::full().with_token();
+    let other = app.db_new_user("other");
+    let other_token = other.db_new_token("other");
+    let krate = app.db(|conn| CrateBuilder::new("foo", user.as_model().id).expect_build(conn));
+    add_owner(&app, krate.id, &other);
+    set_policy(&user, "foo", json!({ "yank_only_owners": ["other"] })).good();
+
+    let response = other_token.publish_crate(PublishBuilder::new("foo", "1.1.0"));
+    assert_eq!(
+        error_detail(response),
+        "the owners of `foo` only allow some of them to publish new versions, and you are not \
+        one of them"
+    );
+
+    // The other owner can still yank versions.
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.1.0"))
+        .good();
+    let response = other_token.delete::<()>("/api/v1/crates/foo/1.1.0/yank");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.into_json(), json!({ "ok": true }));
+}
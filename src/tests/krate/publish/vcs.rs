@@ -0,0 +1,76 @@
+use crate::builders::PublishBuilder;
+use crate::util::{RequestHelper, TestApp};
+use http::StatusCode;
+use insta::assert_json_snapshot;
+
+const VCS_INFO: &str = r#"{
+  "git": {
+    "sha1": "0123456789abcdef0123456789abcdef01234567",
+    "dirty": true
+  },
+  "path_in_vcs": "crates/foo"
+}"#;
+
+#[test]
+fn vcs_info() {
+    let (_app, _anon, _cookie, token) = TestApp::full().with_token();
+
+    let response = token.publish_crate(
+        PublishBuilder::new("foo", "1.0.0").add_file("foo-1.0.0/.cargo_vcs_info.json", VCS_INFO),
+    );
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = token.get::<()>("/api/v1/crates/foo/1.0.0");
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.into_json();
+    assert_json_snapshot!(json["metadata"]["vcs"]);
+}
+
+#[test]
+fn without_vcs_info() {
+    let (_app, _anon, _cookie, token) = TestApp::full().with_token();
+
+    let response = token.publish_crate(PublishBuilder::new("foo", "1.0.0"));
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = token.get::<()>("/api/v1/crates/foo/1.0.0");
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.into_json();
+    assert_eq!(json["metadata"]["vcs"], serde_json::Value::Null);
+}
+
+#[test]
+fn failed_verification() {
+    let (_app, _anon, _cookie, token) = TestApp::full()
+        .with_config(|config| config.verify_vcs_commits = true)
+        .with_token();
+
+    // Commits are only fetched from well-known forges, so the verification fails without
+    // connecting to this repository.
+    let manifest = "\
+        [package]\n\
+        name = \"foo\"\n\
+        version = \"1.0.0\"\n\
+        description = \"description\"\n\
+        license = \"MIT\"\n\
+        repository = \"http://127.0.0.1:1/foo.git\"\n";
+
+    let response = token.publish_crate(
+        PublishBuilder::new("foo", "1.0.0")
+            .custom_manifest(manifest)
+            .add_file("foo-1.0.0/.cargo_vcs_info.json", VCS_INFO),
+    );
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = token.get::<()>("/api/v1/crates/foo/1.0.0");
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.into_json();
+    assert_json_snapshot!(json["metadata"]["vcs"]["verification"], {
+        ".verified_at" => "[datetime]",
+        ".message" => insta::dynamic_redaction(|value, _| {
+            let message = value.as_str().unwrap();
+            assert!(message.starts_with("commits can only be verified for https repositories"));
+            "[message]"
+        }),
+    });
+}
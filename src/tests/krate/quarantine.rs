@@ -0,0 +1,242 @@
+use crate::builders::PublishBuilder;
+use crate::util::{RequestHelper, TestApp};
+use crates_io::models::{Crate, QuarantineActionKind, QuarantineTarget, Version};
+use crates_io::schema::versions;
+use crates_io::worker::jobs;
+use crates_io_worker::BackgroundJob;
+use diesel::prelude::*;
+use http::StatusCode;
+
+/// Quarantines a crate or version, or lifts its quarantine, like the
+/// `crates-admin quarantine` command does.
+fn change(app: &TestApp, action: QuarantineActionKind, version: Option<&str>) {
+    app.db(|conn| {
+        let krate: Crate = Crate::by_name("foo").first(conn).unwrap();
+        let version: Option<Version> = version.map(|version| {
+            Version::belonging_to(&krate)
+                .filter(versions::num.eq(version))
+                .first(conn)
+                .unwrap()
+        });
+
+        let target = match &version {
+            Some(version) => QuarantineTarget::Version(&krate, version),
+            None => QuarantineTarget::Crate(&krate),
+        };
+
+        let changed = match action {
+            QuarantineActionKind::Quarantine => target.quarantine("malware", "admin", conn),
+            QuarantineActionKind::Lift => target.lift(None, "admin", conn),
+        };
+        assert!(changed.unwrap());
+
+        jobs::enqueue_sync_to_index(&krate.name, conn).unwrap();
+        jobs::SyncQuarantinedFiles::new(&krate.name)
+            .enqueue(conn)
+            .unwrap();
+
+        let emails = &app.as_inner().emails;
+        target
+            .notify_owners(action, Some("malware"), emails, conn)
+            .unwrap();
+    });
+
+    app.run_pending_background_jobs();
+}
+
+fn download_status(anon: &impl RequestHelper, version: &str) -> StatusCode {
+    anon.get::<()>(&format!("/api/v1/crates/foo/{version}/download"))
+        .status()
+}
+
+fn index_versions(app: &TestApp) -> Vec<String> {
+    app.upstream_index()
+        .crates_from_index_head("foo")
+        .map(|crates| crates.into_iter().map(|krate| krate.vers).collect())
+        .unwrap_or_default()
+}
+
+fn search_total(anon: &impl RequestHelper) -> i32 {
+    anon.search("q=foo").meta.total
+}
+
+/// Returns the subjects of the emails that were sent to the owner.
+fn owner_emails(app: &TestApp) -> Vec<String> {
+    app.as_inner()
+        .emails
+        .mails_in_memory()
+        .unwrap()
+        .into_iter()
+        .filter(|(envelope, _)| {
+            envelope
+                .to()
+                .iter()
+                .any(|address| address.to_string() == "something@example.com")
+        })
+        .filter_map(|(_, message)| {
+            let subject = message.lines().find(|line| line.starts_with("Subject: "))?;
+            Some(subject.trim_start_matches("Subject: ").to_string())
+        })
+        .filter(|subject| subject.contains("quarantine"))
+        .collect()
+}
+
+fn status(anon: &impl RequestHelper, url: &str) -> StatusCode {
+    anon.get::<()>(url).status()
+}
+
+/// The endpoints that serve the files or the metadata of a single version.
+fn version_urls(version: &str) -> Vec<String> {
+    [
+        "",
+        "/download",
+        "/readme",
+        "/source",
+        "/source/src/lib.rs",
+        "/attestation",
+        "/dependencies",
+    ]
+    .iter()
+    .map(|suffix| format!("/api/v1/crates/foo/{version}{suffix}"))
+    .chain([format!("/api/v1/crates/foo/1.0.0...{version}/diff")])
+    .collect()
+}
+
+fn crate_file_exists(app: &TestApp, version: &str) -> bool {
+    let storage = &app.as_inner().storage;
+    app.runtime()
+        .block_on(storage.download_crate_file("foo", version))
+        .is_ok()
+}
+
+#[test]
+fn quarantine_version() {
+    let (app, anon, _, token) = TestApp::full().with_token();
+
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .good();
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.1.0"))
+        .good();
+    assert_eq!(index_versions(&app), ["1.0.0", "1.1.0"]);
+
+    change(&app, QuarantineActionKind::Quarantine, Some("1.1.0"));
+    assert_eq!(index_versions(&app), ["1.0.0"]);
+    assert_eq!(download_status(&anon, "1.1.0"), StatusCode::FORBIDDEN);
+    assert_eq!(download_status(&anon, "1.0.0"), StatusCode::FOUND);
+    assert_eq!(search_total(&anon), 1);
+
+    change(&app, QuarantineActionKind::Lift, Some("1.1.0"));
+    assert_eq!(index_versions(&app), ["1.0.0", "1.1.0"]);
+    assert_eq!(download_status(&anon, "1.1.0"), StatusCode::FOUND);
+
+    assert_eq!(
+        owner_emails(&app),
+        ["Crate quarantined", "Crate quarantine lifted"]
+    );
+}
+
+#[test]
+fn quarantine_crate() {
+    let (app, anon, _, token) = TestApp::full().with_token();
+
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .good();
+    assert_eq!(search_total(&anon), 1);
+
+    change(&app, QuarantineActionKind::Quarantine, None);
+    assert!(index_versions(&app).is_empty());
+    assert_eq!(download_status(&anon, "1.0.0"), StatusCode::FORBIDDEN);
+    assert_eq!(search_total(&anon), 0);
+
+    // The data is kept for the investigation, but it isn't served anymore
+    assert_eq!(status(&anon, "/api/v1/crates/foo"), StatusCode::FORBIDDEN);
+    assert_eq!(
+        status(&anon, "/api/v1/crates/foo/versions"),
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        status(&anon, "/api/v1/crates/foo/1.0.0"),
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        app.db(|conn| Crate::by_name("foo")
+            .count()
+            .get_result::<i64>(conn)
+            .unwrap()),
+        1
+    );
+
+    change(&app, QuarantineActionKind::Lift, None);
+    assert_eq!(index_versions(&app), ["1.0.0"]);
+    assert_eq!(download_status(&anon, "1.0.0"), StatusCode::FOUND);
+    assert_eq!(search_total(&anon), 1);
+}
+
+#[test]
+fn quarantine_downloaded_version() {
+    let (app, anon, _, token) = TestApp::full().with_token();
+
+    for version in ["1.0.0", "1.1.0"] {
+        let crate_to_publish = PublishBuilder::new("foo", version)
+            .readme("# foo")
+            .add_file(format!("foo-{version}/src/lib.rs"), "pub fn foo() {}\n");
+        token.publish_crate(crate_to_publish).good();
+    }
+
+    // Cache the `version_id` of the download endpoint
+    assert_eq!(download_status(&anon, "1.1.0"), StatusCode::FOUND);
+
+    change(&app, QuarantineActionKind::Quarantine, Some("1.1.0"));
+    for url in version_urls("1.1.0") {
+        assert_eq!(status(&anon, &url), StatusCode::FORBIDDEN, "{url}");
+    }
+    assert!(!crate_file_exists(&app, "1.1.0"));
+    assert!(crate_file_exists(&app, "1.0.0"));
+
+    // The other version and the crate are still available
+    assert_eq!(download_status(&anon, "1.0.0"), StatusCode::FOUND);
+    let json = anon.show_crate("foo");
+    assert_eq!(json.krate.max_version, "1.0.0");
+    let versions = json.versions.unwrap();
+    assert_eq!(versions.len(), 1);
+    assert_eq!(versions[0].num, "1.0.0");
+
+    change(&app, QuarantineActionKind::Lift, Some("1.1.0"));
+    assert!(crate_file_exists(&app, "1.1.0"));
+    assert_eq!(download_status(&anon, "1.1.0"), StatusCode::FOUND);
+    assert_eq!(
+        status(&anon, "/api/v1/crates/foo/1.1.0/source/src/lib.rs"),
+        StatusCode::OK
+    );
+}
+
+#[test]
+fn crates_with_only_quarantined_versions_are_hidden() {
+    let (app, anon, _, token) = TestApp::full().with_token();
+
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .good();
+    assert_eq!(search_total(&anon), 1);
+
+    change(&app, QuarantineActionKind::Quarantine, Some("1.0.0"));
+    assert_eq!(search_total(&anon), 0);
+    assert_eq!(anon.search("q=foo&include_yanked=yes").meta.total, 0);
+}
+
+#[test]
+fn quarantined_crates_are_not_suggested() {
+    let (app, anon, _, token) = TestApp::full().with_token();
+
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .good();
+    let suggestions = || anon.search("q=fooo").meta.suggestions;
+    assert_eq!(suggestions(), ["foo"]);
+
+    change(&app, QuarantineActionKind::Quarantine, None);
+    assert!(suggestions().is_empty());
+}
@@ -3,6 +3,8 @@ use crate::util::{RequestHelper, TestApp};
 use crates_io::schema::versions;
 use crates_io::views::EncodableVersion;
 use diesel::{prelude::*, update};
+use http::StatusCode;
+use serde_json::Value;
 
 #[derive(Deserialize)]
 struct VersionsList {
@@ -42,3 +44,179 @@ fn versions() {
         user.gh_login
     );
 }
+
+#[derive(Deserialize)]
+struct PaginatedVersionsList {
+    versions: Vec<EncodableVersion>,
+    meta: VersionsMeta,
+}
+
+#[derive(Deserialize)]
+struct VersionsMeta {
+    total: i64,
+    next_page: Option<String>,
+}
+
+fn page_through_versions(anon: &impl RequestHelper, query: &str) -> Vec<String> {
+    let mut url = Some(format!("?{query}"));
+    let mut results = Vec::new();
+    while let Some(current_url) = url.take() {
+        let json: PaginatedVersionsList = anon
+            .get_with_query(
+                "/api/v1/crates/foo_versions/versions",
+                current_url.trim_start_matches('?'),
+            )
+            .good();
+        assert_eq!(json.meta.total, 4);
+        assert!(json.versions.len() <= 3);
+
+        results.extend(json.versions.into_iter().map(|v| v.num));
+        url = json.meta.next_page;
+    }
+    results
+}
+
+#[test]
+fn versions_seek_based_pagination() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let user = user.as_model();
+    app.db(|conn| {
+        CrateBuilder::new("foo_versions", user.id)
+            .version("0.5.1")
+            .version("1.0.0")
+            .version("0.10.0")
+            .version("0.5.0")
+            .expect_build(conn);
+    });
+
+    // Without `per_page` all versions are returned
+    let json: PaginatedVersionsList = anon.get("/api/v1/crates/foo_versions/versions").good();
+    assert_eq!(json.versions.len(), 4);
+    assert_eq!(json.meta.total, 4);
+    assert_none!(json.meta.next_page);
+
+    assert_eq!(
+        page_through_versions(&anon, "per_page=3"),
+        vec!["1.0.0", "0.10.0", "0.5.1", "0.5.0"]
+    );
+
+    // All versions are created in the same transaction, so they are sorted by their ID
+    assert_eq!(
+        page_through_versions(&anon, "sort=date&per_page=3"),
+        vec!["0.5.0", "0.10.0", "1.0.0", "0.5.1"]
+    );
+
+    let response = anon.get_with_query::<()>("/api/v1/crates/foo_versions/versions", "page=2");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+fn filtered_versions(anon: &impl RequestHelper, query: &str) -> Vec<String> {
+    let json: VersionsList = anon
+        .get_with_query("/api/v1/crates/foo_versions/versions", query)
+        .good();
+    json.versions.into_iter().map(|v| v.num).collect()
+}
+
+#[test]
+fn versions_filters() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let user = user.as_model();
+    app.db(|conn| {
+        CrateBuilder::new("foo_versions", user.id)
+            .version(VersionBuilder::new("0.9.0"))
+            .version(VersionBuilder::new("1.0.0").rust_version("1.60"))
+            .version(
+                VersionBuilder::new("1.1.0")
+                    .rust_version("1.65")
+                    .yanked(true),
+            )
+            .version(VersionBuilder::new("1.2.0").rust_version("1.70"))
+            .version(VersionBuilder::new("2.0.0-beta.1").rust_version("1.74"))
+            .expect_build(conn);
+    });
+
+    assert_eq!(
+        filtered_versions(&anon, "req=%5E1.0"),
+        vec!["1.2.0", "1.1.0", "1.0.0"]
+    );
+    assert_eq!(
+        filtered_versions(&anon, "req=%3E%3D1.1&include_yanked=no"),
+        vec!["1.2.0"]
+    );
+    assert_eq!(
+        filtered_versions(&anon, "req=%3E%3D2.0.0-beta.1"),
+        vec!["2.0.0-beta.1"]
+    );
+    assert_eq!(
+        filtered_versions(&anon, "include_prerelease=no&include_yanked=no"),
+        vec!["1.2.0", "1.0.0", "0.9.0"]
+    );
+    assert_eq!(
+        filtered_versions(&anon, "rust_version=1.65"),
+        vec!["1.1.0", "1.0.0", "0.9.0"]
+    );
+    assert_eq!(
+        filtered_versions(&anon, "rust_version=1.65&sort=date&include_yanked=no"),
+        vec!["1.0.0", "0.9.0"]
+    );
+
+    // The total only counts the matching versions
+    let json: PaginatedVersionsList = anon
+        .get_with_query(
+            "/api/v1/crates/foo_versions/versions",
+            "include_prerelease=no&per_page=2",
+        )
+        .good();
+    assert_eq!(json.meta.total, 4);
+    assert_eq!(json.versions.len(), 2);
+    assert_some!(json.meta.next_page);
+
+    let response =
+        anon.get_with_query::<()>("/api/v1/crates/foo_versions/versions", "req=not-a-req");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = anon.get_with_query::<()>(
+        "/api/v1/crates/foo_versions/versions",
+        "rust_version=latest",
+    );
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn versions_top_versions() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let user = user.as_model();
+    app.db(|conn| {
+        CrateBuilder::new("foo_versions", user.id)
+            .version(VersionBuilder::new("1.0.0").rust_version("1.60"))
+            .version(
+                VersionBuilder::new("1.1.0")
+                    .rust_version("1.65")
+                    .yanked(true),
+            )
+            .version(VersionBuilder::new("1.2.0").rust_version("1.70"))
+            .version(VersionBuilder::new("2.0.0-beta.1").rust_version("1.74"))
+            .expect_build(conn);
+    });
+
+    let json = anon
+        .get_with_query::<Value>("/api/v1/crates/foo_versions/versions", "rust_version=1.69")
+        .good();
+    assert_eq!(
+        json["meta"]["top_versions"],
+        json!({
+            "highest": "2.0.0-beta.1",
+            "highest_stable": "1.2.0",
+            "highest_compatible": "1.0.0",
+            "newest": "2.0.0-beta.1",
+        })
+    );
+
+    let json = anon
+        .get::<Value>("/api/v1/crates/foo_versions/versions")
+        .good();
+    assert_eq!(
+        json["meta"]["top_versions"]["highest_compatible"],
+        json!(null)
+    );
+}
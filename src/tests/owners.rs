@@ -16,8 +16,10 @@ use crates_io::{
 
 use chrono::{Duration, Utc};
 use crates_io::models::token::{CrateScope, EndpointScope};
+use crates_io::rate_limiter::LimitedAction;
 use diesel::prelude::*;
 use http::StatusCode;
+use serde_json::Value;
 
 #[derive(Deserialize)]
 struct TeamResponse {
@@ -623,6 +625,35 @@ fn invitations_list_v1() {
     );
 }
 
+#[test]
+fn invitations_list_v1_paginated() {
+    let (app, _, owner, token) = TestApp::init().with_token();
+    let owner = owner.as_model();
+    let user = app.db_new_user("invited_user");
+
+    app.db(|conn| {
+        CrateBuilder::new("crate_1", owner.id).expect_build(conn);
+        CrateBuilder::new("crate_2", owner.id).expect_build(conn);
+    });
+    token.add_user_owner("crate_1", "invited_user");
+    token.add_user_owner("crate_2", "invited_user");
+
+    let json: Value = user
+        .get_with_query("/api/v1/me/crate_owner_invitations", "per_page=1")
+        .good();
+    assert_eq!(json["crate_owner_invitations"][0]["crate_name"], "crate_1");
+    let next_page = json["meta"]["next_page"].as_str().unwrap();
+
+    let json: Value = user
+        .get_with_query(
+            "/api/v1/me/crate_owner_invitations",
+            next_page.trim_start_matches('?'),
+        )
+        .good();
+    assert_eq!(json["crate_owner_invitations"][0]["crate_name"], "crate_2");
+    assert_eq!(json["meta"]["next_page"], Value::Null);
+}
+
 #[test]
 fn invitations_list_does_not_include_expired_invites_v1() {
     let (app, _, owner, token) = TestApp::init().with_token();
@@ -1233,3 +1264,201 @@ fn invitation_list_other_crates() {
         owner.get_with_query::<()>("/api/private/crate_owner_invitations", "crate_name=crate_2");
     assert_eq!(resp.status(), StatusCode::FORBIDDEN);
 }
+
+#[test]
+fn invitation_list_by_inviter() {
+    let (app, _, owner, token) = TestApp::init().with_token();
+    let other_user = app.db_new_user("other");
+    let krate = app.db(|conn| CrateBuilder::new("crate_1", owner.as_model().id).expect_build(conn));
+    let user = app.db_new_user("invited_user");
+    token.add_user_owner("crate_1", "invited_user");
+
+    let invitations = get_invitations(&owner, &format!("inviter_id={}", owner.as_model().id));
+    assert_eq!(
+        invitations,
+        CrateOwnerInvitationsResponse {
+            invitations: vec![EncodableCrateOwnerInvitation {
+                crate_id: krate.id,
+                crate_name: krate.name,
+                invitee_id: user.as_model().id,
+                inviter_id: owner.as_model().id,
+                // The timestamps depend on when the test is run.
+                created_at: invitations.invitations[0].created_at,
+                expires_at: invitations.invitations[0].expires_at,
+            }],
+            users: vec![
+                owner.as_model().clone().into(),
+                user.as_model().clone().into(),
+            ],
+            meta: CrateOwnerInvitationsMeta { next_page: None },
+        }
+    );
+
+    // Retrieving the invitations sent by other users doesn't work.
+    let resp = owner.get_with_query::<()>(
+        "/api/private/crate_owner_invitations",
+        &format!("inviter_id={}", other_user.as_model().id),
+    );
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
+
+//
+// Tests for the `/api/v1/crates/:crate_id/owner_invitations` endpoints
+//
+
+#[test]
+fn crate_invitation_list() {
+    let (app, _, owner, token) = TestApp::init().with_token();
+    let other_user = app.db_new_user("other");
+    let user = app.db_new_user("invited_user");
+    let krate = app.db(|conn| CrateBuilder::new("crate_1", owner.as_model().id).expect_build(conn));
+    token.add_user_owner("crate_1", "invited_user");
+
+    let invitations: CrateOwnerInvitationsResponse =
+        token.get("/api/v1/crates/crate_1/owner_invitations").good();
+    assert_eq!(invitations.invitations.len(), 1);
+    assert_eq!(invitations.invitations[0].crate_id, krate.id);
+    assert_eq!(invitations.invitations[0].invitee_id, user.as_model().id);
+
+    // Only owners can list the invitations of a crate.
+    let resp = other_user.get::<()>("/api/v1/crates/crate_1/owner_invitations");
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
+
+#[test]
+fn revoke_invitation() {
+    let (app, _, owner, token) = TestApp::init().with_token();
+    let other_user = app.db_new_user("other");
+    let user = app.db_new_user("invited_user");
+    let krate = app.db(|conn| CrateBuilder::new("crate_1", owner.as_model().id).expect_build(conn));
+    token.add_user_owner("crate_1", "invited_user");
+
+    // Only owners can revoke invitations.
+    let resp = other_user.delete::<()>("/api/v1/crates/crate_1/owner_invitations/invited_user");
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        resp.into_json(),
+        json!({ "errors": [{ "detail": "only owners have permission to manage owner invitations" }] })
+    );
+
+    let resp = token.delete::<()>("/api/v1/crates/crate_1/owner_invitations/invited_user");
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        resp.into_json(),
+        json!({
+            "ok": true,
+            "msg": "the invitation of user invited_user to be an owner of crate crate_1 has been revoked",
+        })
+    );
+
+    assert_eq!(user.list_invitations().crate_owner_invitations.len(), 0);
+    let resp = user.try_accept_ownership_invitation::<()>(&krate.name, krate.id);
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    // The invitation can't be revoked twice.
+    let resp = token.delete::<()>("/api/v1/crates/crate_1/owner_invitations/invited_user");
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        resp.into_json(),
+        json!({ "errors": [{ "detail": "user invited_user has no pending invitation to be an owner of crate crate_1" }] })
+    );
+}
+
+#[test]
+fn resend_invitation() {
+    let (app, anon, owner, token) = TestApp::init().with_token();
+    let user = app.db_new_user("invited_user");
+    let krate = app.db(|conn| CrateBuilder::new("crate_1", owner.as_model().id).expect_build(conn));
+    token.add_user_owner("crate_1", "invited_user");
+    expire_invitation(&app, krate.id);
+    assert_eq!(user.list_invitations().crate_owner_invitations.len(), 0);
+
+    let request =
+        token.post_request("/api/v1/crates/crate_1/owner_invitations/invited_user/resend");
+    let resp = token.run::<()>(request);
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        resp.into_json(),
+        json!({
+            "ok": true,
+            "msg": "the invitation of user invited_user to be an owner of crate crate_1 has been resent",
+        })
+    );
+
+    // The invitation was renewed and the email was sent a second time.
+    assert_eq!(user.list_invitations().crate_owner_invitations.len(), 1);
+    let emails = app.as_inner().emails.mails_in_memory().unwrap();
+    let invites = emails
+        .iter()
+        .filter(|(_, m)| m.contains("Subject: Crate ownership invitation"))
+        .count();
+    assert_eq!(invites, 2);
+
+    let invite_token = extract_token_from_invite_email(&app.as_inner().emails);
+    anon.accept_ownership_invitation_by_token(&invite_token);
+    assert_eq!(anon.show_crate_owners("crate_1").users.len(), 2);
+}
+
+#[test]
+fn resend_invitation_ratelimit_hit() {
+    let (app, _, owner, token) = TestApp::init()
+        .with_rate_limit(
+            LimitedAction::ResendInvitation,
+            std::time::Duration::from_secs(60 * 60),
+            1,
+        )
+        .with_token();
+    app.db_new_user("invited_user");
+    app.db(|conn| CrateBuilder::new("crate_1", owner.as_model().id).expect_build(conn));
+    token.add_user_owner("crate_1", "invited_user");
+
+    let url = "/api/v1/crates/crate_1/owner_invitations/invited_user/resend";
+    let resp = token.run::<()>(token.post_request(url));
+    assert_eq!(resp.status(), StatusCode::OK);
+    token
+        .run::<()>(token.post_request(url))
+        .assert_rate_limited(LimitedAction::ResendInvitation);
+
+    // Only the original invitation and the first resend were sent.
+    let emails = app.as_inner().emails.mails_in_memory().unwrap();
+    let invites = emails
+        .iter()
+        .filter(|(_, m)| m.contains("Subject: Crate ownership invitation"))
+        .count();
+    assert_eq!(invites, 2);
+}
+
+#[test]
+fn per_crate_invitation_expiration() {
+    use crates_io::schema::crate_owner_invitations;
+
+    let (app, _, owner, token) = TestApp::init().with_token();
+    let user = app.db_new_user("invited_user");
+    let (crate1, crate2) = app.db(|conn| {
+        (
+            CrateBuilder::new("crate_1", owner.as_model().id)
+                .ownership_invitations_expiration_days(2)
+                .expect_build(conn),
+            CrateBuilder::new("crate_2", owner.as_model().id).expect_build(conn),
+        )
+    });
+    token.add_user_owner("crate_1", "invited_user");
+    token.add_user_owner("crate_2", "invited_user");
+
+    // Move both invitations three days into the past, which only expires the one for `crate_1`.
+    app.db(|conn| {
+        let created_at = (Utc::now() - Duration::days(3)).naive_utc();
+        diesel::update(crate_owner_invitations::table)
+            .set(crate_owner_invitations::created_at.eq(created_at))
+            .execute(conn)
+            .unwrap();
+    });
+
+    let invitations = user.list_invitations().crate_owner_invitations;
+    assert_eq!(invitations.len(), 1);
+    assert_eq!(invitations[0].crate_id, crate2.id);
+
+    let resp = user.try_accept_ownership_invitation::<()>(&crate1.name, crate1.id);
+    assert_eq!(resp.status(), StatusCode::GONE);
+    user.accept_ownership_invitation(&crate2.name, crate2.id);
+}
@@ -0,0 +1,160 @@
+use super::{audit_log, audit_log_is_empty};
+use crate::builders::{CrateBuilder, VersionBuilder};
+use crate::util::{RequestHelper, TestApp};
+use crate::OkBool;
+use chrono::{Duration, Utc};
+use crates_io::schema::crate_adoption_requests;
+use diesel::prelude::*;
+use http::StatusCode;
+use serde_json::Value;
+
+const URL: &str = "/api/private/admin/adoption_requests";
+
+/// Sets up an abandoned crate `foo` with a pending adoption request from
+/// `adopter`, and returns the ID of the request.
+fn request_adoption(app: &TestApp, owner_id: i32) -> i64 {
+    let two_years_ago = Utc::now().naive_utc() - Duration::days(2 * 365);
+    app.db(|conn| {
+        CrateBuilder::new("foo", owner_id)
+            .version(VersionBuilder::new("1.0.0").created_at(two_years_ago))
+            .expect_build(conn);
+    });
+
+    let adopter = app.db_new_user("adopter");
+    let body = r#"{ "reason": "I maintain a fork" }"#;
+    let json: Value = adopter
+        .post("/api/v1/crates/foo/adoption_requests", body)
+        .good();
+    json["adoption_request"]["id"].as_i64().unwrap()
+}
+
+fn end_waiting_period(app: &TestApp) {
+    app.db(|conn| {
+        diesel::update(crate_adoption_requests::table)
+            .set(crate_adoption_requests::approvable_at.eq(Utc::now().naive_utc()))
+            .execute(conn)
+            .unwrap();
+    });
+}
+
+fn owner_logins(user: &impl RequestHelper) -> Vec<String> {
+    let json: Value = user.get("/api/v1/crates/foo/owners").good();
+    json["users"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|owner| owner["login"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn approve_adoption_request() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let admin = user.db_new_admin_token("admin");
+    let id = request_adoption(&app, user.as_model().id);
+
+    let json: Value = admin.get(URL).good();
+    let requests = json["adoption_requests"].as_array().unwrap();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0]["crate_name"], "foo");
+    assert_eq!(requests[0]["user"], "adopter");
+
+    // The owners still have time to reject the request
+    let response = admin.put::<()>(&format!("{URL}/{id}/approve"), "");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert!(audit_log_is_empty(&app));
+
+    end_waiting_period(&app);
+    admin
+        .put::<OkBool>(&format!("{URL}/{id}/approve"), "")
+        .good();
+    assert_eq!(owner_logins(&anon), ["foo", "adopter"]);
+
+    let response = admin.put::<()>(&format!("{URL}/{id}/approve"), "");
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "the adoption request has already been decided" }] })
+    );
+
+    let json: Value = admin.get(URL).good();
+    assert_eq!(json["adoption_requests"], json!([]));
+
+    let mails = app.as_inner().emails.mails_in_memory().unwrap();
+    let (_, last) = mails.last().unwrap();
+    assert!(last.contains("Subject: Crate adoption request decided"));
+    assert!(last.contains("has been approved"));
+
+    assert_eq!(
+        audit_log(&admin),
+        [("approve_adoption".into(), "foo".into())]
+    );
+}
+
+#[test]
+fn reject_adoption_request() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let admin = user.db_new_admin_token("admin");
+    let id = request_adoption(&app, user.as_model().id);
+
+    let response = admin.put::<()>(&format!("{URL}/{id}/reject"), "");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // Requests can be rejected during the waiting period
+    let body = r#"{ "reason": "the owner is still around" }"#;
+    admin
+        .put::<OkBool>(&format!("{URL}/{id}/reject"), body)
+        .good();
+    assert_eq!(owner_logins(&anon), ["foo"]);
+
+    let mails = app.as_inner().emails.mails_in_memory().unwrap();
+    let (_, last) = mails.last().unwrap();
+    assert!(last.contains("has been rejected"));
+    assert!(last.contains("the owner is still around"));
+
+    assert_eq!(
+        audit_log(&admin),
+        [("reject_adoption".into(), "foo".into())]
+    );
+}
+
+#[test]
+fn unknown_adoption_requests() {
+    let (app, _, user) = TestApp::init().with_user();
+    let admin = user.db_new_admin_token("admin");
+
+    admin
+        .put::<()>(&format!("{URL}/1/approve"), "")
+        .assert_not_found();
+
+    // Regular users can't decide about requests
+    user.put::<()>(&format!("{URL}/1/approve"), "")
+        .assert_forbidden();
+
+    assert!(audit_log_is_empty(&app));
+}
+
+#[test]
+fn crates_with_new_versions_are_not_adopted() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let admin = user.db_new_admin_token("admin");
+    let id = request_adoption(&app, user.as_model().id);
+
+    // The owner publishes a new version during the waiting period
+    app.db(|conn| {
+        use crates_io::models::Crate;
+
+        let krate: Crate = Crate::by_name("foo").first(conn).unwrap();
+        VersionBuilder::new("1.1.0").expect_build(krate.id, user.as_model().id, conn);
+    });
+    end_waiting_period(&app);
+
+    let response = admin.put::<()>(&format!("{URL}/{id}/approve"), "");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "the crate is not abandoned anymore and can't be adopted" }] })
+    );
+
+    assert_eq!(owner_logins(&anon), ["foo"]);
+    assert!(audit_log_is_empty(&app));
+}
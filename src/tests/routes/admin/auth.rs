@@ -0,0 +1,97 @@
+use super::{audit_log, audit_log_is_empty};
+use crate::util::{RequestHelper, TestApp};
+use crate::OkBool;
+use crates_io::models::AdminToken;
+use crates_io::schema::{admin_actions, users};
+use diesel::prelude::*;
+use http::StatusCode;
+use serde_json::Value;
+
+const URL: &str = "/api/private/admin/jobs";
+const BODY: &[u8] = br#"{ "job": "squash_index" }"#;
+
+#[test]
+fn anonymous_users_are_rejected() {
+    let (app, anon) = TestApp::init().empty();
+
+    let response = anon.post::<()>(URL, BODY);
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert!(audit_log_is_empty(&app));
+}
+
+#[test]
+fn api_tokens_and_cookies_are_rejected() {
+    let (app, _, user, token) = TestApp::init().with_token();
+
+    // Even if the user is an admin, only admin tokens are accepted.
+    let _admin = user.db_new_admin_token("admin");
+
+    let response = user.post::<()>(URL, BODY);
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = token.post::<()>(URL, BODY);
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    assert!(audit_log_is_empty(&app));
+}
+
+#[test]
+fn admin_tokens_are_rejected_by_other_endpoints() {
+    let (_, _, user) = TestApp::init().with_user();
+    let admin = user.db_new_admin_token("admin");
+
+    // Admin tokens don't look like API tokens, so they are rejected as invalid
+    let response = admin.get::<()>("/api/v1/me/tokens");
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[test]
+fn revoking_the_admin_role_disables_the_tokens() {
+    let (app, _, user) = TestApp::full().with_user();
+    let admin = user.db_new_admin_token("admin");
+
+    admin.post::<OkBool>(URL, BODY).good();
+    assert_eq!(
+        audit_log(&admin),
+        [("enqueue_job".into(), "squash_index".into())]
+    );
+
+    app.db(|conn| {
+        diesel::update(users::table.find(user.as_model().id))
+            .set(users::is_admin.eq(false))
+            .execute(conn)
+            .unwrap();
+    });
+
+    let response = admin.post::<()>(URL, BODY);
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // The rejected request is recorded in the audit log
+    let (target, details) = app.db(|conn| {
+        admin_actions::table
+            .filter(admin_actions::action.eq("rejected"))
+            .filter(admin_actions::admin_token_id.eq(admin.as_model().id))
+            .select((admin_actions::target, admin_actions::details))
+            .first::<(String, Value)>(conn)
+            .unwrap()
+    });
+    assert_eq!(target, URL);
+    assert_eq!(
+        details,
+        json!({ "method": "POST", "reason": "the user is not an admin" })
+    );
+
+    app.run_pending_background_jobs();
+}
+
+#[test]
+fn revoked_tokens_are_rejected() {
+    let (app, _, user) = TestApp::init().with_user();
+    let admin = user.db_new_admin_token("admin");
+
+    app.db(|conn| assert!(AdminToken::revoke(conn, admin.as_model().id).unwrap()));
+
+    let response = admin.post::<()>(URL, BODY);
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert!(audit_log_is_empty(&app));
+}
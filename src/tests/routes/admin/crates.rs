@@ -0,0 +1,125 @@
+use super::audit_log;
+use crate::builders::PublishBuilder;
+use crate::util::{RequestHelper, TestApp};
+use crate::OkBool;
+use http::StatusCode;
+use serde_json::json;
+
+#[test]
+fn delete_crate() {
+    let (app, anon, user, token) = TestApp::full().with_token();
+    let admin = user.db_new_admin_token("admin");
+
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .good();
+    assert!(!app.stored_files().is_empty());
+
+    admin
+        .delete::<OkBool>("/api/private/admin/crates/foo")
+        .good();
+    app.run_pending_background_jobs();
+
+    let response = anon.get::<()>("/api/v1/crates/foo");
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert!(app.upstream_index().crates_from_index_head("foo").is_err());
+    assert!(app.stored_files().iter().all(|path| !path.contains("foo")));
+
+    let response = admin.delete::<()>("/api/private/admin/crates/foo");
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    assert_eq!(audit_log(&admin), [("delete_crate".into(), "foo".into())]);
+}
+
+#[test]
+fn delete_and_yank_versions() {
+    let (app, anon, user, token) = TestApp::full().with_token();
+    let admin = user.db_new_admin_token("admin");
+
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .good();
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.1.0"))
+        .good();
+
+    admin
+        .delete::<OkBool>("/api/private/admin/crates/foo/1.1.0")
+        .good();
+    admin
+        .put::<OkBool>("/api/private/admin/crates/foo/1.0.0/yank", "")
+        .good();
+    // Yanking a yanked version is not recorded again
+    admin
+        .put::<OkBool>("/api/private/admin/crates/foo/1.0.0/yank", "")
+        .good();
+    app.run_pending_background_jobs();
+
+    let response = anon.get::<()>("/api/v1/crates/foo/1.1.0");
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "crate `foo` does not have a version `1.1.0`" }] })
+    );
+    assert!(anon.show_version("foo", "1.0.0").version.yanked);
+
+    let index = app.crates_from_index_head("foo");
+    assert_eq!(index.len(), 1);
+    assert_some_eq!(index[0].yanked, true);
+
+    assert_eq!(
+        audit_log(&admin),
+        [
+            ("delete_version".into(), "foo@1.1.0".into()),
+            ("yank_version".into(), "foo@1.0.0".into()),
+        ]
+    );
+}
+
+#[test]
+fn quarantine() {
+    let (app, anon, user, token) = TestApp::full().with_token();
+    let admin = user.db_new_admin_token("admin");
+
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .good();
+
+    // Quarantines need a reason that is sent to the owners
+    let response = admin.put::<()>("/api/private/admin/crates/foo/1.0.0/quarantine", "");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = r#"{ "reason": "malware" }"#;
+    admin
+        .put::<OkBool>("/api/private/admin/crates/foo/1.0.0/quarantine", body)
+        .good();
+    admin
+        .put::<OkBool>("/api/private/admin/crates/foo/quarantine", body)
+        .good();
+    app.run_pending_background_jobs();
+
+    let response = anon.get::<()>("/api/v1/crates/foo/1.0.0/download");
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert!(app.upstream_index().crates_from_index_head("foo").is_err());
+
+    admin
+        .delete::<OkBool>("/api/private/admin/crates/foo/quarantine")
+        .good();
+    admin
+        .delete::<OkBool>("/api/private/admin/crates/foo/1.0.0/quarantine")
+        .good();
+    app.run_pending_background_jobs();
+
+    let response = anon.get::<()>("/api/v1/crates/foo/1.0.0/download");
+    assert_eq!(response.status(), StatusCode::FOUND);
+    assert_eq!(app.crates_from_index_head("foo").len(), 1);
+
+    assert_eq!(
+        audit_log(&admin),
+        [
+            ("quarantine".into(), "foo@1.0.0".into()),
+            ("quarantine".into(), "foo".into()),
+            ("lift_quarantine".into(), "foo".into()),
+            ("lift_quarantine".into(), "foo@1.0.0".into()),
+        ]
+    );
+}
@@ -0,0 +1,57 @@
+use super::audit_log;
+use crate::builders::CrateBuilder;
+use crate::util::{RequestHelper, TestApp};
+use crate::OkBool;
+use crates_io::schema::background_jobs;
+use diesel::prelude::*;
+use http::StatusCode;
+use serde_json::Value;
+
+const URL: &str = "/api/private/admin/jobs";
+
+fn job_types(app: &TestApp) -> Vec<String> {
+    app.db(|conn| {
+        background_jobs::table
+            .select(background_jobs::job_type)
+            .order(background_jobs::id)
+            .load(conn)
+            .unwrap()
+    })
+}
+
+#[test]
+fn enqueue_jobs() {
+    let (app, _, user) = TestApp::full().with_user();
+    let admin = user.db_new_admin_token("admin");
+
+    app.db(|conn| {
+        CrateBuilder::new("foo", user.as_model().id).expect_build(conn);
+    });
+
+    let json: Value = admin.post(URL, r#"{ "job": "update_downloads" }"#).good();
+    assert_eq!(json["enqueued"], true);
+
+    // The job is only enqueued once
+    let json: Value = admin.post(URL, r#"{ "job": "update_downloads" }"#).good();
+    assert_eq!(json["enqueued"], false);
+
+    admin
+        .post::<OkBool>(URL, r#"{ "job": "check_typosquat", "name": "foo" }"#)
+        .good();
+
+    let response = admin.post::<()>(URL, r#"{ "job": "check_typosquat", "name": "bar" }"#);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = admin.post::<()>(URL, r#"{ "job": "dump_db" }"#);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    assert_eq!(job_types(&app), ["update_downloads", "check_typosquat"]);
+    app.run_pending_background_jobs();
+    assert_eq!(
+        audit_log(&admin),
+        [
+            ("enqueue_job".into(), "update_downloads".into()),
+            ("enqueue_job".into(), "check_typosquat".into()),
+        ]
+    );
+}
@@ -0,0 +1,42 @@
+use crate::util::{RequestHelper, TestApp};
+use serde_json::Value;
+
+mod adoption_requests;
+mod auth;
+mod crates;
+mod jobs;
+mod transfer_crates;
+mod users;
+mod verify_token;
+
+/// Returns the `action` and `target` of the entries of the audit log, oldest
+/// first.
+fn audit_log(admin: &impl RequestHelper) -> Vec<(String, String)> {
+    let json: Value = admin.get("/api/private/admin/actions").good();
+    let mut actions = json["actions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|action| {
+            let name = action["action"].as_str().unwrap().to_string();
+            let target = action["target"].as_str().unwrap().to_string();
+            (name, target)
+        })
+        .collect::<Vec<_>>();
+
+    actions.reverse();
+    actions
+}
+
+fn audit_log_is_empty(app: &TestApp) -> bool {
+    use crates_io::schema::admin_actions;
+    use diesel::prelude::*;
+
+    app.db(|conn| {
+        admin_actions::table
+            .count()
+            .get_result::<i64>(conn)
+            .unwrap()
+            == 0
+    })
+}
@@ -0,0 +1,115 @@
+use super::audit_log;
+use crate::builders::CrateBuilder;
+use crate::util::{RequestHelper, TestApp};
+use crates_io::models::{CrateOwner, OwnerKind};
+use crates_io::schema::crate_owners;
+use diesel::prelude::*;
+use http::StatusCode;
+use serde_json::Value;
+
+const URL: &str = "/api/private/admin/transfer_crates";
+
+#[test]
+fn transfer_crates() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let admin = user.db_new_admin_token("admin");
+    let from = app.db_new_user("from");
+    let to = app.db_new_user("to");
+
+    app.db(|conn| {
+        CrateBuilder::new("foo", from.as_model().id).expect_build(conn);
+        CrateBuilder::new("bar", from.as_model().id).expect_build(conn);
+    });
+
+    let body = r#"{ "from": "from", "to": "to", "confirm_different_account": true }"#;
+    let json: Value = admin.post(URL, body).good();
+    assert_eq!(json["transferred"], 2);
+
+    assert_eq!(anon.search_by_user_id(from.as_model().id).crates.len(), 0);
+    assert_eq!(anon.search_by_user_id(to.as_model().id).crates.len(), 2);
+
+    let response = admin.post::<()>(URL, r#"{ "from": "from", "to": "nobody" }"#);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    assert_eq!(
+        audit_log(&admin),
+        [("transfer_crates".into(), "from".into())]
+    );
+}
+
+#[test]
+fn different_accounts_have_to_be_confirmed() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let admin = user.db_new_admin_token("admin");
+    let from = app.db_new_user("from");
+    app.db_new_user("to");
+
+    app.db(|conn| CrateBuilder::new("foo", from.as_model().id).expect_build(conn));
+
+    let response = admin.post::<()>(URL, r#"{ "from": "from", "to": "to" }"#);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "the users have different GitHub IDs and may not be the same person, set `confirm_different_account` to `true` to transfer the crates anyway" }] })
+    );
+
+    let response = admin.post::<()>(URL, r#"{ "from": "from", "to": "from" }"#);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "cannot transfer crates to the same user" }] })
+    );
+
+    assert_eq!(anon.search_by_user_id(from.as_model().id).crates.len(), 1);
+
+    // Rejected requests are recorded in the audit log too
+    let target = "/api/private/admin/transfer_crates".to_string();
+    assert_eq!(
+        audit_log(&admin),
+        [
+            ("rejected".into(), target.clone()),
+            ("rejected".into(), target)
+        ]
+    );
+}
+
+#[test]
+fn crates_of_both_users_are_merged() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let admin = user.db_new_admin_token("admin");
+    let from = app.db_new_user("from");
+    let to = app.db_new_user("to");
+
+    app.db(|conn| {
+        let both = CrateBuilder::new("both", from.as_model().id).expect_build(conn);
+        let former = CrateBuilder::new("former", from.as_model().id).expect_build(conn);
+        CrateBuilder::new("from_only", from.as_model().id).expect_build(conn);
+
+        // `to` already owns one of the crates, and used to own another one
+        for (krate, deleted) in [(both, false), (former, true)] {
+            diesel::insert_into(crate_owners::table)
+                .values(&CrateOwner {
+                    crate_id: krate.id,
+                    owner_id: to.as_model().id,
+                    created_by: to.as_model().id,
+                    owner_kind: OwnerKind::User,
+                    email_notifications: true,
+                })
+                .execute(conn)
+                .unwrap();
+
+            diesel::update(crate_owners::table)
+                .filter(crate_owners::crate_id.eq(krate.id))
+                .filter(crate_owners::owner_id.eq(to.as_model().id))
+                .set(crate_owners::deleted.eq(deleted))
+                .execute(conn)
+                .unwrap();
+        }
+    });
+
+    let body = r#"{ "from": "from", "to": "to", "confirm_different_account": true }"#;
+    let json: Value = admin.post(URL, body).good();
+    assert_eq!(json["transferred"], 2);
+
+    assert_eq!(anon.search_by_user_id(from.as_model().id).crates.len(), 0);
+    assert_eq!(anon.search_by_user_id(to.as_model().id).crates.len(), 3);
+}
@@ -0,0 +1,64 @@
+use super::audit_log;
+use crate::util::{RequestHelper, TestApp};
+use crate::OkBool;
+use http::StatusCode;
+
+const URL: &str = "/api/private/admin/users/other/lock";
+
+/// Returns the subjects of the emails about account locks.
+fn lock_emails(app: &TestApp) -> Vec<String> {
+    app.as_inner()
+        .emails
+        .mails_in_memory()
+        .unwrap()
+        .into_iter()
+        .filter_map(|(_, message)| {
+            let subject = message.lines().find(|line| line.starts_with("Subject: "))?;
+            Some(subject.trim_start_matches("Subject: ").to_string())
+        })
+        .filter(|subject| subject.contains("locked"))
+        .collect()
+}
+
+#[test]
+fn lock_and_unlock_accounts() {
+    let (app, _, user) = TestApp::init().with_user();
+    let admin = user.db_new_admin_token("admin");
+    let other = app.db_new_user("other");
+
+    let response = admin.put::<()>(URL, "");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = r#"{ "reason": "spam", "until": "2099-01-01T00:00:00" }"#;
+    admin.put::<OkBool>(URL, body).good();
+
+    let response = other.get::<()>("/api/v1/me");
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "This account is locked until 2099-01-01 at 00:00:00 UTC. Reason: spam" }] })
+    );
+
+    admin.delete::<OkBool>(URL).good();
+    // Unlocking an unlocked account is not recorded again
+    admin.delete::<OkBool>(URL).good();
+    other.get::<serde_json::Value>("/api/v1/me").good();
+
+    let response = admin.put::<()>("/api/private/admin/users/nobody/lock", body);
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    assert_eq!(
+        lock_emails(&app),
+        [
+            "Your crates.io account has been locked",
+            "Your crates.io account has been unlocked"
+        ]
+    );
+    assert_eq!(
+        audit_log(&admin),
+        [
+            ("lock_account".into(), "other".into()),
+            ("unlock_account".into(), "other".into()),
+        ]
+    );
+}
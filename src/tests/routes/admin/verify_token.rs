@@ -0,0 +1,24 @@
+use super::audit_log;
+use crate::util::{RequestHelper, TestApp};
+use http::StatusCode;
+use secrecy::ExposeSecret;
+use serde_json::{json, Value};
+
+const URL: &str = "/api/private/admin/verify_token";
+
+#[test]
+fn verify_token() {
+    let (app, _, user) = TestApp::init().with_user();
+    let admin = user.db_new_admin_token("admin");
+    let other = app.db_new_user("other");
+    let token = other.db_new_token("bar");
+
+    let body = json!({ "api_token": token.plaintext().expose_secret() }).to_string();
+    let json: Value = admin.post(URL, body).good();
+    assert_eq!(json["user"]["login"], "other");
+
+    let response = admin.post::<()>(URL, r#"{ "api_token": "cioinvalid" }"#);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    assert_eq!(audit_log(&admin), [("verify_token".into(), "other".into())]);
+}
@@ -27,3 +27,40 @@ fn index() {
         ".categories[].created_at" => "[datetime]",
     });
 }
+
+#[test]
+fn seek_based_pagination() {
+    let (app, anon) = TestApp::init().empty();
+
+    app.db(|conn| {
+        for slug in ["foo", "bar", "baz", "baz::qux"] {
+            new_category(slug, slug, "Crates")
+                .create_or_update(conn)
+                .unwrap();
+        }
+    });
+
+    for (sort, expected) in [
+        ("alpha", vec!["bar", "baz", "foo"]),
+        ("crates", vec!["baz", "bar", "foo"]),
+    ] {
+        let mut url = Some(format!("?sort={sort}&per_page=2"));
+        let mut results = Vec::new();
+        while let Some(current_url) = url.take() {
+            let json: Value = anon
+                .get_with_query("/api/v1/categories", current_url.trim_start_matches('?'))
+                .good();
+            assert_eq!(json["meta"]["total"], 3);
+
+            let categories = json["categories"].as_array().unwrap();
+            results.extend(
+                categories
+                    .iter()
+                    .map(|c| c["slug"].as_str().unwrap().to_string()),
+            );
+            url = json["meta"]["next_page"].as_str().map(String::from);
+        }
+
+        assert_eq!(results, expected, "sort={sort}");
+    }
+}
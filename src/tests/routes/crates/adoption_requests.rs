@@ -0,0 +1,159 @@
+use crate::builders::{CrateBuilder, VersionBuilder};
+use crate::util::{RequestHelper, TestApp};
+use crate::OkBool;
+use chrono::{Duration, Utc};
+use crates_io::schema::crate_adoption_requests;
+use diesel::prelude::*;
+use http::StatusCode;
+use serde_json::Value;
+
+const URL: &str = "/api/v1/crates/foo/adoption_requests";
+
+/// Publishes the crate `foo` with a version from two years ago.
+fn abandoned_crate(app: &TestApp, owner_id: i32) {
+    let two_years_ago = Utc::now().naive_utc() - Duration::days(2 * 365);
+    app.db(|conn| {
+        CrateBuilder::new("foo", owner_id)
+            .version(VersionBuilder::new("1.0.0").created_at(two_years_ago))
+            .expect_build(conn);
+    });
+}
+
+fn statuses(app: &TestApp) -> Vec<String> {
+    use crates_io::models::AdoptionRequestStatus;
+
+    app.db(|conn| {
+        crate_adoption_requests::table
+            .select(crate_adoption_requests::status)
+            .order(crate_adoption_requests::id)
+            .load::<AdoptionRequestStatus>(conn)
+            .unwrap()
+            .into_iter()
+            .map(|status| <&str>::from(status).to_string())
+            .collect()
+    })
+}
+
+#[test]
+fn request_adoption() {
+    let (app, _, owner) = TestApp::init().with_user();
+    let adopter = app.db_new_user("adopter");
+    abandoned_crate(&app, owner.as_model().id);
+
+    let body = r#"{ "reason": "I maintain a fork" }"#;
+    let json: Value = adopter.post(URL, body).good();
+    let request = &json["adoption_request"];
+    assert_eq!(request["crate_name"], "foo");
+    assert_eq!(request["user"], "adopter");
+    assert_eq!(request["reason"], "I maintain a fork");
+    assert_eq!(request["status"], "pending");
+
+    let response = adopter.post::<()>(URL, body);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "you already have a pending request to adopt this crate" }] })
+    );
+
+    // The owners are notified about the request
+    let mails = app.as_inner().emails.mails_in_memory().unwrap();
+    assert_eq!(mails.len(), 1);
+    assert!(mails[0].1.contains("Subject: Crate adoption request"));
+    assert!(mails[0].1.contains("adopter has asked to become an owner"));
+}
+
+#[test]
+fn invalid_adoption_requests() {
+    let (app, _, owner) = TestApp::init().with_user();
+    let adopter = app.db_new_user("adopter");
+    app.db(|conn| {
+        CrateBuilder::new("foo", owner.as_model().id)
+            .version("1.0.0")
+            .expect_build(conn);
+    });
+
+    let response = adopter.post::<()>(URL, r#"{ "reason": "I want it" }"#);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "only crates without owners or without a new version in the last year can be adopted" }] })
+    );
+
+    let response = adopter.post::<()>(URL, r#"{ "reason": " " }"#);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = owner.post::<()>(URL, r#"{ "reason": "I want it" }"#);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "you are already an owner of this crate" }] })
+    );
+
+    let response = adopter.post::<()>(
+        "/api/v1/crates/bar/adoption_requests",
+        r#"{ "reason": "I want it" }"#,
+    );
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let token = adopter.db_new_token("token");
+    let response = token.post::<()>(URL, r#"{ "reason": "I want it" }"#);
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    assert!(statuses(&app).is_empty());
+}
+
+#[test]
+fn withdraw_and_reject_adoption_requests() {
+    let (app, _, owner) = TestApp::init().with_user();
+    let adopter = app.db_new_user("adopter");
+    let other = app.db_new_user("other");
+    abandoned_crate(&app, owner.as_model().id);
+
+    let body = r#"{ "reason": "I maintain a fork" }"#;
+    let json: Value = adopter.post(URL, body).good();
+    let id = json["adoption_request"]["id"].as_i64().unwrap();
+
+    // Unrelated users can't reject the request
+    let response = other.delete::<()>(&format!("{URL}/{id}"));
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    adopter.delete::<OkBool>(&format!("{URL}/{id}")).good();
+
+    let json: Value = adopter.post(URL, body).good();
+    let id = json["adoption_request"]["id"].as_i64().unwrap();
+    owner.delete::<OkBool>(&format!("{URL}/{id}")).good();
+
+    let response = owner.delete::<()>(&format!("{URL}/{id}"));
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "the adoption request has already been decided" }] })
+    );
+
+    assert_eq!(statuses(&app), ["withdrawn", "rejected"]);
+
+    // The user is told that the owner rejected the request
+    let mails = app.as_inner().emails.mails_in_memory().unwrap();
+    let (_, last) = mails.last().unwrap();
+    assert!(last.contains("Subject: Crate adoption request decided"));
+    assert!(last.contains("has been rejected"));
+}
+
+#[test]
+fn crates_without_owners_can_be_adopted() {
+    let (app, _, owner) = TestApp::init().with_user();
+    let adopter = app.db_new_user("adopter");
+    app.db(|conn| {
+        CrateBuilder::new("foo", owner.as_model().id)
+            .version("1.0.0")
+            .expect_build(conn);
+    });
+
+    // A crate with a recent version can be adopted once its only owner
+    // deleted their account
+    owner
+        .delete_with_body::<Value>("/api/v1/me", r#"{ "login": "foo" }"#)
+        .good();
+
+    let body = r#"{ "reason": "I use it" }"#;
+    let json: Value = adopter.post(URL, body).good();
+    assert_eq!(json["adoption_request"]["status"], "pending");
+}
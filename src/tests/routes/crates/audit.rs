@@ -0,0 +1,180 @@
+use crate::builders::{CrateBuilder, VersionBuilder};
+use crate::util::{RequestHelper, Response, TestApp};
+use crates_io::rate_limiter::LimitedAction;
+use http::StatusCode;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+fn audit(user: &impl RequestHelper, lockfile: &str) -> Response<Value> {
+    let mut request = user.post_request("/api/v1/audit/crates");
+    *request.body_mut() = lockfile.to_string().into();
+    user.run(request)
+}
+
+const LOCKFILE: &str = r#"
+version = 3
+
+[[package]]
+name = "my-project"
+version = "0.1.0"
+dependencies = ["foo", "bar", "baz", "missing"]
+
+[[package]]
+name = "foo"
+version = "1.0.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "bar"
+version = "0.1.0"
+source = "sparse+https://index.crates.io/"
+
+[[package]]
+name = "baz"
+version = "0.2.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "missing"
+version = "1.0.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+"#;
+
+#[test]
+fn audit_lockfile() {
+    let (app, _, user) = TestApp::init().with_user();
+    let user_id = user.as_model().id;
+
+    app.db(|conn| {
+        CrateBuilder::new("foo", user_id)
+            .version(
+                VersionBuilder::new("1.0.0")
+                    .license(Some("MIT OR Apache-2.0"))
+                    .rust_version("1.60"),
+            )
+            .version(VersionBuilder::new("1.1.0").yanked(true))
+            .version("1.2.0")
+            .version("2.0.0")
+            .expect_build(conn);
+        CrateBuilder::new("bar", user_id)
+            .version(VersionBuilder::new("0.1.0").yanked(true))
+            .version("0.1.1")
+            .version("0.2.0")
+            .expect_build(conn);
+        CrateBuilder::new("baz", user_id)
+            .version(VersionBuilder::new("0.2.0").license(Some("Apache 2.0")))
+            .expect_build(conn);
+    });
+
+    let json = audit(&user, LOCKFILE).good();
+    assert_eq!(
+        json,
+        json!({
+            "packages": [{
+                "name": "foo",
+                "version": "1.0.0",
+                "found": true,
+                "yanked": false,
+                "license": "MIT OR Apache-2.0",
+                "license_error": null,
+                "rust_version": "1.60",
+                "newer_compatible_versions": ["1.2.0"],
+            }, {
+                "name": "bar",
+                "version": "0.1.0",
+                "found": true,
+                "yanked": true,
+                "license": null,
+                "license_error": null,
+                "rust_version": null,
+                "newer_compatible_versions": ["0.1.1"],
+            }, {
+                "name": "baz",
+                "version": "0.2.0",
+                "found": true,
+                "yanked": false,
+                "license": "Apache 2.0",
+                "license_error": json["packages"][2]["license_error"],
+                "rust_version": null,
+                "newer_compatible_versions": [],
+            }, {
+                "name": "missing",
+                "version": "1.0.0",
+                "found": false,
+                "yanked": false,
+                "license": null,
+                "license_error": null,
+                "rust_version": null,
+                "newer_compatible_versions": [],
+            }],
+            "summary": {
+                "missing": 1,
+                "yanked": 1,
+                "invalid_licenses": 1,
+                "outdated": 2,
+            },
+        })
+    );
+    assert!(json["packages"][2]["license_error"].is_string());
+}
+
+#[test]
+fn invalid_lockfile() {
+    let (_, _, user) = TestApp::init().with_user();
+
+    let response = audit(&user, "[[package]]");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn requires_authentication() {
+    let (_, anon) = TestApp::init().empty();
+
+    let response = audit(&anon, LOCKFILE);
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[test]
+fn custom_license_file() {
+    let (app, _, user) = TestApp::init().with_user();
+    let user_id = user.as_model().id;
+
+    // Crates with a `license-file` are stored with a "non-standard" license
+    app.db(|conn| {
+        CrateBuilder::new("foo", user_id)
+            .version(VersionBuilder::new("1.0.0").license(Some("non-standard")))
+            .expect_build(conn);
+    });
+
+    let json = audit(&user, LOCKFILE).good();
+    assert_eq!(json["packages"][0]["license"], "custom license file");
+    assert_eq!(json["packages"][0]["license_error"], Value::Null);
+    assert_eq!(json["summary"]["invalid_licenses"], 0);
+}
+
+#[test]
+fn has_its_own_rate_limit() {
+    let (_, _, user) = TestApp::init()
+        .with_rate_limit(LimitedAction::BulkLookup, Duration::from_secs(60), 1)
+        .with_rate_limit(LimitedAction::Audit, Duration::from_secs(60), 1)
+        .with_user();
+
+    audit(&user, LOCKFILE).good();
+    audit(&user, LOCKFILE).assert_rate_limited(LimitedAction::Audit);
+
+    // Bulk lookups are not affected by audits
+    let body = json!({ "crates": [{ "name": "foo", "version": "1.0.0" }] });
+    let mut request = user.post_request("/api/v1/lookup/crates");
+    *request.body_mut() = serde_json::to_vec(&body).unwrap().into();
+    assert_eq!(user.run::<Value>(request).status(), StatusCode::OK);
+}
+
+#[test]
+fn does_not_shadow_crate_named_audit() {
+    let (app, anon, user) = TestApp::init().with_user();
+    app.db(|conn| CrateBuilder::new("audit", user.as_model().id).expect_build(conn));
+
+    let response = anon.get::<()>("/api/v1/crates/audit");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.into_json()["crate"]["name"], "audit");
+}
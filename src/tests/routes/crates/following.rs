@@ -1,5 +1,6 @@
 use crate::builders::CrateBuilder;
 use crate::util::{RequestHelper, TestApp};
+use crates_io::models::token::EndpointScope;
 
 #[test]
 fn diesel_not_found_results_in_404() {
@@ -25,3 +26,34 @@ fn disallow_api_token_auth_for_get_crate_following_status() {
         .get(&format!("/api/v1/crates/{a_crate}/following"))
         .assert_forbidden();
 }
+
+#[test]
+fn follow_with_follow_scope() {
+    let (app, _, user) = TestApp::init().with_user();
+    let a_crate = "a_crate";
+
+    app.db(|conn| {
+        CrateBuilder::new(a_crate, user.as_model().id).expect_build(conn);
+    });
+
+    let follow_path = format!("/api/v1/crates/{a_crate}/follow");
+    let following_path = format!("/api/v1/crates/{a_crate}/following");
+
+    let token = user.db_new_scoped_token("yank", None, Some(vec![EndpointScope::Yank]), None);
+    token
+        .put::<()>(&follow_path, b"" as &[u8])
+        .assert_forbidden();
+
+    let token = user.db_new_scoped_token("follow", None, Some(vec![EndpointScope::Follow]), None);
+    token
+        .put::<serde_json::Value>(&follow_path, b"" as &[u8])
+        .good();
+
+    let json = token.get::<serde_json::Value>(&following_path).good();
+    assert_eq!(json["following"], true);
+
+    token.delete::<serde_json::Value>(&follow_path).good();
+
+    let json = token.get::<serde_json::Value>(&following_path).good();
+    assert_eq!(json["following"], false);
+}
@@ -474,6 +474,91 @@ fn loose_search_order() {
     assert_eq!(search_temp.crates.len(), 3);
 }
 
+#[test]
+fn relevance_includes_popularity_and_yank_state() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let user = user.as_model();
+
+    app.db(|conn| {
+        let popular = CrateBuilder::new("serde_json", user.id)
+            .description("A JSON serialization file format")
+            .recent_downloads(10_000_000)
+            .expect_build(conn);
+
+        for name in ["dependent_1", "dependent_2", "dependent_3"] {
+            CrateBuilder::new(name, user.id)
+                .version(VersionBuilder::new("1.0.0").dependency(&popular, None))
+                .expect_build(conn);
+        }
+
+        // Repeating the search terms results in a higher text rank
+        CrateBuilder::new("json_stuffer", user.id)
+            .description("json json json json json")
+            .readme("json json json json json")
+            .keyword("json")
+            .expect_build(conn);
+
+        CrateBuilder::new("json_yanked", user.id)
+            .description("json json json json json")
+            .readme("json json json json json")
+            .keyword("json")
+            .version(VersionBuilder::new("1.0.0").yanked(true))
+            .expect_build(conn);
+
+        sql_query("SELECT refresh_reverse_dependency_counts()")
+            .execute(conn)
+            .unwrap();
+    });
+
+    let expected = ["serde_json", "json_stuffer", "json_yanked"];
+
+    let json = anon.search("q=json");
+    assert_eq!(json.meta.total, 3);
+    let names = json.crates.iter().map(|c| &c.name).collect::<Vec<_>>();
+    assert_eq!(names, expected);
+
+    // The scores are stable across the pages of seek-based pagination
+    let mut url = Some("?q=json&per_page=1".to_string());
+    let mut names = Vec::new();
+    while let Some(current_url) = url.take() {
+        let response = anon.search(current_url.trim_start_matches('?'));
+        names.extend(response.crates.into_iter().map(|c| c.name));
+        url = response.meta.next_page;
+    }
+    assert_eq!(names, expected);
+}
+
+#[test]
+fn name_suggestions() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let user = user.as_model();
+
+    app.db(|conn| {
+        CrateBuilder::new("serde", user.id)
+            .downloads(1000)
+            .expect_build(conn);
+        CrateBuilder::new("serde_json", user.id).expect_build(conn);
+        CrateBuilder::new("sered", user.id).expect_build(conn);
+        CrateBuilder::new("tokio", user.id).expect_build(conn);
+        CrateBuilder::new("tokio-util", user.id).expect_build(conn);
+    });
+
+    let suggestions = |q: &str| anon.search(&format!("q={q}")).meta.suggestions;
+
+    assert_eq!(suggestions("serd"), ["serde", "sered"]);
+    assert_eq!(suggestions("SERDE-JSN"), ["serde_json"]);
+    assert_eq!(suggestions("serdejson"), ["serde_json"]);
+    assert_eq!(suggestions("tokio-utl"), ["tokio-util"]);
+    assert_eq!(suggestions("tokio_utli"), ["tokio-util"]);
+
+    // No suggestions for existing crates and queries that aren't names
+    assert_that!(suggestions("serde"), empty());
+    assert_that!(suggestions("serde_JSON"), empty());
+    assert_that!(suggestions("async%20runtime"), empty());
+    assert_that!(suggestions("zzzzzz"), empty());
+    assert_that!(anon.search("").meta.suggestions, empty());
+}
+
 #[test]
 fn index_include_yanked() {
     let (app, anon, user) = TestApp::init().with_user();
@@ -697,17 +782,16 @@ fn pagination_links_included_if_applicable() {
         CrateBuilder::new("pagination_links_3", user.id).expect_build(conn);
     });
 
-    // This uses a filter (`letter=p`) to disable seek-based pagination, as seek-based pagination
-    // does not return page numbers. If the test fails after expanding the scope of seek-based
-    // pagination replace the filter with something else still using pages.
+    // This uses an explicit page (`page=1`) to disable seek-based pagination, as seek-based
+    // pagination does not return page numbers.
 
-    let page1 = anon.search("letter=p&per_page=1");
+    let page1 = anon.search("letter=p&page=1&per_page=1");
     let page2 = anon.search("letter=p&page=2&per_page=1");
     let page3 = anon.search("letter=p&page=3&per_page=1");
     let page4 = anon.search("letter=p&page=4&per_page=1");
 
     assert_eq!(
-        Some("?letter=p&per_page=1&page=2".to_string()),
+        Some("?letter=p&page=2&per_page=1".to_string()),
         page1.meta.next_page
     );
     assert_eq!(None, page1.meta.prev_page);
@@ -774,6 +858,81 @@ fn seek_based_pagination() {
     );
 }
 
+#[test]
+fn seek_based_pagination_with_filters_and_sorts() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let user = user.as_model();
+
+    app.db(|conn| {
+        CrateBuilder::new("pagination_links", user.id)
+            .keyword("paginate")
+            .downloads(10)
+            .expect_build(conn);
+        CrateBuilder::new("pagination_links_1", user.id)
+            .keyword("paginate")
+            .downloads(20)
+            .recent_downloads(5)
+            .expect_build(conn);
+        CrateBuilder::new("pagination_links_2", user.id)
+            .keyword("paginate")
+            .downloads(20)
+            .recent_downloads(5)
+            .expect_build(conn);
+        CrateBuilder::new("pagination_links_3", user.id)
+            .downloads(5)
+            .recent_downloads(10)
+            .version(VersionBuilder::new("1.0.0").yanked(true))
+            .expect_build(conn);
+        CrateBuilder::new("other", user.id)
+            .downloads(30)
+            .expect_build(conn);
+    });
+
+    let queries = [
+        ("letter=p", 4),
+        ("keyword=paginate", 3),
+        ("all_keywords=paginate", 3),
+        (&*format!("user_id={}", user.id), 5),
+        ("include_yanked=no", 4),
+        ("sort=downloads", 5),
+        ("sort=recent-downloads", 5),
+        ("sort=recent-updates", 5),
+        ("sort=new", 5),
+        ("q=pagination_links", 4),
+        ("q=pagination_links&sort=alpha", 4),
+        ("q=pagination_links&sort=downloads", 4),
+        ("letter=p&sort=recent-downloads", 4),
+    ];
+
+    for (query, total) in queries {
+        // Loading all results at once uses offset-based pagination
+        let expected = anon.search(&format!("{query}&page=1&per_page=100"));
+        let expected = expected
+            .crates
+            .into_iter()
+            .map(|c| c.name)
+            .collect::<Vec<_>>();
+        assert_eq!(expected.len(), total, "{query}");
+
+        let mut url = Some(format!("?{query}&per_page=1"));
+        let mut results = Vec::new();
+        while let Some(current_url) = url.take() {
+            let resp = anon.search(current_url.trim_start_matches('?'));
+            assert_eq!(resp.meta.total, total as i32, "{query}");
+
+            if let Some(new_url) = resp.meta.next_page {
+                assert!(new_url.contains("seek="), "{query}");
+                assert_that!(resp.crates, len(eq(1)));
+                url = Some(new_url);
+            }
+
+            results.extend(resp.crates.into_iter().map(|c| c.name));
+        }
+
+        assert_eq!(results, expected, "{query}");
+    }
+}
+
 #[test]
 fn test_pages_work_even_with_seek_based_pagination() {
     let (app, anon, user) = TestApp::init().with_user();
@@ -856,3 +1015,111 @@ fn crates_by_user_id_not_including_deleted_owners() {
     let response = anon.search_by_user_id(user.id);
     assert_eq!(response.crates.len(), 0);
 }
+
+#[test]
+fn structured_search_queries() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let user = user.as_model();
+    let other_user = app.db_new_user("bar");
+    let other_user = other_user.as_model();
+
+    app.db(|conn| {
+        new_category("Web programming", "web-programming", "Web crates")
+            .create_or_update(conn)
+            .unwrap();
+
+        CrateBuilder::new("async_web", user.id)
+            .keyword("async")
+            .category("web-programming")
+            .version(
+                VersionBuilder::new("1.0.0")
+                    .license(Some("MIT OR Apache-2.0"))
+                    .rust_version("1.65"),
+            )
+            .updated_at(date("2024-06-01"))
+            .expect_build(conn);
+
+        CrateBuilder::new("async_old", user.id)
+            .keyword("async")
+            .version(VersionBuilder::new("0.1.0").license(Some("MIT")))
+            .version(
+                VersionBuilder::new("0.2.0")
+                    .license(Some("Apache-2.0"))
+                    .rust_version("1.75.0"),
+            )
+            .updated_at(date("2022-01-01"))
+            .expect_build(conn);
+
+        CrateBuilder::new("sync_web", other_user.id)
+            .keyword("sync")
+            .category("web-programming")
+            .version(
+                VersionBuilder::new("1.0.0")
+                    .license(Some("MIT-0"))
+                    .rust_version("1.60"),
+            )
+            .updated_at(date("2024-01-01") + chrono::Duration::hours(12))
+            .expect_build(conn);
+    });
+
+    let search = |query: &str| {
+        let json = anon.search(&format!("q={query}"));
+        assert_eq!(json.meta.total as usize, json.crates.len());
+        let mut names = json.crates.into_iter().map(|c| c.name).collect::<Vec<_>>();
+        names.sort();
+        names
+    };
+
+    assert_eq!(search("keyword:async"), ["async_old", "async_web"]);
+    assert_eq!(
+        search("keyword:ASYNC%20category:web-programming"),
+        ["async_web"]
+    );
+    assert_eq!(
+        search("category:web-programming"),
+        ["async_web", "sync_web"]
+    );
+    assert_eq!(search("license:MIT"), ["async_web"]);
+    assert_eq!(search("license:mit-0"), ["sync_web"]);
+    assert_eq!(search("license:Apache-2.0"), ["async_old", "async_web"]);
+    assert_eq!(search("owner:bar"), ["sync_web"]);
+    assert_eq!(
+        search("owner:FOO%20keyword:async"),
+        ["async_old", "async_web"]
+    );
+    assert_eq!(search("msrv:%3C%3D1.70"), ["async_web", "sync_web"]);
+    assert_eq!(search("msrv:%3E1.70"), ["async_old"]);
+    assert_eq!(search("msrv:1.65.0"), ["async_web"]);
+    assert_eq!(search("updated:%3E2024-02-01"), ["async_web"]);
+    assert_eq!(
+        search("updated:%3C%3D2024-01-01"),
+        ["async_old", "sync_web"]
+    );
+    assert_eq!(search("updated:2024-01-01"), ["sync_web"]);
+    assert_eq!(search("updated:%3E2024-01-01"), ["async_web"]);
+    assert_eq!(search("web%20keyword:async"), ["async_web"]);
+    assert_eq!(search("keyword:async%20owner:bar"), Vec::<String>::new());
+
+    // Qualifiers can be combined with the other filters and sorts
+    let json = anon.search("q=keyword:async&sort=recent-updates&per_page=1");
+    assert_eq!(json.meta.total, 2);
+    assert_eq!(json.crates[0].name, "async_web");
+
+    let response = anon.get_with_query::<()>("/api/v1/crates", "q=msrv:latest");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let response = anon.get_with_query::<()>("/api/v1/crates", "q=updated:yesterday");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let response = anon.get_with_query::<()>("/api/v1/crates", "q=keyword:");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // Words ending with a colon are free text
+    let response = anon.get_with_query::<()>("/api/v1/crates", "q=async_web:");
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+fn date(date: &str) -> chrono::NaiveDateTime {
+    chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap()
+}
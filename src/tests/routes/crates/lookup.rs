@@ -0,0 +1,222 @@
+use crate::builders::{CrateBuilder, VersionBuilder};
+use crate::util::{RequestHelper, Response, TestApp};
+use crates_io::rate_limiter::LimitedAction;
+use http::StatusCode;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+fn lookup(user: &impl RequestHelper, body: Value) -> Response<Value> {
+    let mut request = user.post_request("/api/v1/lookup/crates");
+    *request.body_mut() = serde_json::to_vec(&body).unwrap().into();
+    user.run(request)
+}
+
+/// Returns the version numbers of the versions in the result with the given
+/// index.
+fn result_versions(json: &Value, index: usize) -> Vec<&str> {
+    let versions = json["versions"].as_array().unwrap();
+    json["results"][index]["versions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|id| {
+            let version = versions.iter().find(|v| &v["id"] == id).unwrap();
+            version["num"].as_str().unwrap()
+        })
+        .collect()
+}
+
+fn create_crates(app: &TestApp, user_id: i32) {
+    app.db(|conn| {
+        CrateBuilder::new("foo", user_id)
+            .version(VersionBuilder::new("1.0.0").license(Some("MIT")))
+            .version(VersionBuilder::new("1.1.0").yanked(true))
+            .version(VersionBuilder::new("2.0.0").license(Some("Apache-2.0")))
+            .expect_build(conn);
+        CrateBuilder::new("bar", user_id)
+            .version("0.1.0")
+            .expect_build(conn);
+    });
+}
+
+#[test]
+fn lookup_crates() {
+    let (app, _, user) = TestApp::init().with_user();
+    create_crates(&app, user.as_model().id);
+
+    let body = json!({
+        "crates": [
+            { "name": "foo", "version": "^1" },
+            { "name": "foo", "version": "2.0.0" },
+            { "name": "bar", "version": ">=1.0.0" },
+            { "name": "missing", "version": "1.0.0" },
+        ],
+    });
+    let json = lookup(&user, body).good();
+
+    let results = json["results"].as_array().unwrap();
+    assert_eq!(results.len(), 4);
+    assert_eq!(results[0]["crate"], "foo");
+    assert_eq!(result_versions(&json, 0), vec!["1.1.0", "1.0.0"]);
+    assert_eq!(results[1]["crate"], "foo");
+    assert_eq!(result_versions(&json, 1), vec!["2.0.0"]);
+    assert_eq!(results[2]["crate"], "bar");
+    assert_eq!(result_versions(&json, 2), Vec::<&str>::new());
+    assert_eq!(results[3]["crate"], Value::Null);
+    assert_eq!(result_versions(&json, 3), Vec::<&str>::new());
+
+    // Crates and versions are only included once
+    let crates = json["crates"].as_array().unwrap();
+    assert_eq!(crates.len(), 2);
+    let krate = crates.iter().find(|c| c["name"] == "foo").unwrap();
+    assert_eq!(krate["max_version"], "2.0.0");
+
+    let versions = json["versions"].as_array().unwrap();
+    assert_eq!(versions.len(), 3);
+    let yanked = versions.iter().find(|v| v["num"] == "1.1.0").unwrap();
+    assert_eq!(yanked["yanked"], true);
+    let latest = versions.iter().find(|v| v["num"] == "2.0.0").unwrap();
+    assert_eq!(latest["yanked"], false);
+    assert_eq!(latest["license"], "Apache-2.0");
+}
+
+#[test]
+fn lookup_canonical_names() {
+    let (app, _, user) = TestApp::init().with_user();
+    app.db(|conn| {
+        CrateBuilder::new("foo-bar", user.as_model().id)
+            .version("1.0.0")
+            .expect_build(conn);
+    });
+
+    let body = json!({
+        "crates": [
+            { "name": "foo-bar", "version": "1.0.0" },
+            { "name": "Foo_Bar", "version": "1.0.0" },
+        ],
+    });
+    let json = lookup(&user, body).good();
+
+    let results = json["results"].as_array().unwrap();
+    assert_eq!(results[1]["name"], "Foo_Bar");
+    for (index, result) in results.iter().enumerate() {
+        assert_eq!(result["crate"], "foo-bar");
+        assert_eq!(result_versions(&json, index), vec!["1.0.0"]);
+    }
+    assert_eq!(json["crates"].as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn bare_versions_are_exact() {
+    let (app, _, user) = TestApp::init().with_user();
+    create_crates(&app, user.as_model().id);
+
+    let body = json!({
+        "crates": [
+            { "name": "foo", "version": "1.0.0" },
+            { "name": "foo", "version": "^1.0.0" },
+            { "name": "foo", "version": "=1.0.0" },
+        ],
+    });
+    let json = lookup(&user, body).good();
+
+    assert_eq!(result_versions(&json, 0), vec!["1.0.0"]);
+    assert_eq!(result_versions(&json, 1), vec!["1.1.0", "1.0.0"]);
+    assert_eq!(result_versions(&json, 2), vec!["1.0.0"]);
+}
+
+#[test]
+fn lookup_lockfile() {
+    let (app, _, user) = TestApp::init().with_user();
+    create_crates(&app, user.as_model().id);
+
+    let lockfile = r#"
+        version = 3
+
+        [[package]]
+        name = "my-project"
+        version = "0.1.0"
+        dependencies = ["bar", "foo"]
+
+        [[package]]
+        name = "foo"
+        version = "1.0.0"
+        source = "registry+https://github.com/rust-lang/crates.io-index"
+
+        [[package]]
+        name = "bar"
+        version = "0.1.0"
+        source = "sparse+https://index.crates.io/"
+    "#;
+
+    let body = json!({ "crates": [{ "name": "foo", "version": "2.0.0" }], "lockfile": lockfile });
+    let json = lookup(&user, body).good();
+
+    let results = json["results"].as_array().unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0]["name"], "foo");
+    assert_eq!(result_versions(&json, 0), vec!["2.0.0"]);
+    assert_eq!(results[1]["name"], "foo");
+    assert_eq!(results[1]["version"], "1.0.0");
+    assert_eq!(result_versions(&json, 1), vec!["1.0.0"]);
+    assert_eq!(results[2]["name"], "bar");
+    assert_eq!(result_versions(&json, 2), vec!["0.1.0"]);
+
+    let versions = json["versions"].as_array().unwrap();
+    let version = versions.iter().find(|v| v["num"] == "1.0.0").unwrap();
+    assert_eq!(version["license"], "MIT");
+}
+
+#[test]
+fn invalid_requests() {
+    let (_, _, user) = TestApp::init().with_user();
+
+    let body = json!({ "crates": [{ "name": "foo", "version": "not a version" }] });
+    let response = lookup(&user, body);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "invalid version requirement `not a version` for crate `foo`" }] })
+    );
+
+    let response = lookup(&user, json!({ "lockfile": "[[package]]" }));
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let entries = vec![json!({ "name": "foo", "version": "1.0.0" }); 1001];
+    let response = lookup(&user, json!({ "crates": entries }));
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "cannot look up more than 1000 crates at once" }] })
+    );
+}
+
+#[test]
+fn requires_authentication() {
+    let (_, anon) = TestApp::init().empty();
+
+    let body = json!({ "crates": [{ "name": "foo", "version": "1.0.0" }] });
+    let response = lookup(&anon, body);
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[test]
+fn rate_limited() {
+    let (_, _, user) = TestApp::init()
+        .with_rate_limit(LimitedAction::BulkLookup, Duration::from_secs(60), 1)
+        .with_user();
+
+    let body = json!({ "crates": [{ "name": "foo", "version": "1.0.0" }] });
+    lookup(&user, body.clone()).good();
+    lookup(&user, body).assert_rate_limited(LimitedAction::BulkLookup);
+}
+
+#[test]
+fn does_not_shadow_crate_named_lookup() {
+    let (app, anon, user) = TestApp::init().with_user();
+    app.db(|conn| CrateBuilder::new("lookup", user.as_model().id).expect_build(conn));
+
+    let response = anon.get::<()>("/api/v1/crates/lookup");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.into_json()["crate"]["name"], "lookup");
+}
@@ -1,8 +1,14 @@
+mod adoption_requests;
+mod audit;
 pub mod downloads;
 mod following;
 mod list;
+mod lookup;
 mod new;
 pub mod owners;
 mod read;
+mod relevance;
+mod resolve;
 mod reverse_dependencies;
+mod transfer;
 pub mod versions;
@@ -0,0 +1,206 @@
+//! Offline evaluation of the relevance ranking of search results
+//!
+//! The crates of the fixture corpus in `relevance_corpus.json` are inserted
+//! into the test database and ranked by the search endpoint, and the resulting
+//! order is recorded in a snapshot, so that changes to the weights of
+//! `SearchRelevanceConfig` show up as reviewable rank changes.
+
+use crate::builders::{CrateBuilder, VersionBuilder};
+use crate::util::{MockAnonymousUser, RequestHelper, TestApp};
+use chrono::{Duration, Utc};
+use crates_io::config::SearchRelevanceConfig;
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::{BigInt, Integer};
+use insta::assert_snapshot;
+use std::fmt::Write;
+
+#[derive(Deserialize)]
+struct Corpus {
+    queries: Vec<Query>,
+}
+
+#[derive(Deserialize)]
+struct Query {
+    query: String,
+    /// The crates that should be at the top of the results, in order.
+    expected: Vec<String>,
+    candidates: Vec<Candidate>,
+}
+
+#[derive(Deserialize)]
+struct Candidate {
+    name: String,
+    description: String,
+    keywords: Vec<String>,
+    recent_downloads: i32,
+    reverse_dependencies: i64,
+    /// `None` if all versions of the crate are yanked.
+    days_since_release: Option<i64>,
+}
+
+fn corpus() -> Corpus {
+    serde_json::from_str(include_str!("relevance_corpus.json")).unwrap()
+}
+
+/// Creates a test app with all crates of the corpus.
+fn corpus_app(config: SearchRelevanceConfig) -> (TestApp, MockAnonymousUser) {
+    let (app, anon, user) = TestApp::init()
+        .with_config(|server| server.search_relevance = config)
+        .with_user();
+    let user_id = user.as_model().id;
+
+    let corpus = corpus();
+    let candidates = corpus.queries.iter().flat_map(|query| &query.candidates);
+    let max_dependents = candidates
+        .clone()
+        .map(|candidate| candidate.reverse_dependencies)
+        .max()
+        .unwrap_or_default();
+
+    app.db(|conn| {
+        // The dependents are created in bulk, since the popular crates of the
+        // corpus have tens of thousands of them.
+        sql_query(
+            "INSERT INTO crates (name) \
+            SELECT 'dependent-' || n FROM generate_series(1, $1) n",
+        )
+        .bind::<BigInt, _>(max_dependents)
+        .execute(conn)
+        .unwrap();
+
+        sql_query(
+            "INSERT INTO versions (crate_id, num, checksum) \
+            SELECT id, '1.0.0', repeat('0', 64) FROM crates WHERE name LIKE 'dependent-%'",
+        )
+        .execute(conn)
+        .unwrap();
+
+        for candidate in candidates {
+            let created_at = Utc::now().naive_utc()
+                - Duration::days(candidate.days_since_release.unwrap_or_default());
+            let version = VersionBuilder::new("1.0.0")
+                .created_at(created_at)
+                .yanked(candidate.days_since_release.is_none());
+
+            let mut builder = CrateBuilder::new(&candidate.name, user_id)
+                .description(&candidate.description)
+                .recent_downloads(candidate.recent_downloads)
+                .version(version);
+            for keyword in &candidate.keywords {
+                builder = builder.keyword(keyword);
+            }
+            let krate = builder.expect_build(conn);
+
+            sql_query(
+                "INSERT INTO dependencies \
+                (version_id, crate_id, req, optional, default_features, features) \
+                SELECT versions.id, $1, '^1.0', false, true, '{}' FROM versions \
+                INNER JOIN crates ON crates.id = versions.crate_id \
+                WHERE crates.name LIKE 'dependent-%' \
+                ORDER BY versions.id \
+                LIMIT $2",
+            )
+            .bind::<Integer, _>(krate.id)
+            .bind::<BigInt, _>(candidate.reverse_dependencies)
+            .execute(conn)
+            .unwrap();
+        }
+
+        sql_query("SELECT refresh_reverse_dependency_counts()")
+            .execute(conn)
+            .unwrap();
+    });
+
+    (app, anon)
+}
+
+/// Returns the names of the search results of the query, in order.
+fn rank(anon: &MockAnonymousUser, query: &Query) -> Vec<String> {
+    let q = query.query.replace(' ', "%20");
+    let response = anon.search(&format!("q={q}&per_page=100"));
+    response
+        .crates
+        .into_iter()
+        .map(|krate| krate.name)
+        .collect()
+}
+
+/// The mean reciprocal rank of the expected top result of each query.
+fn mean_reciprocal_rank(anon: &MockAnonymousUser, corpus: &Corpus) -> f64 {
+    let sum = corpus
+        .queries
+        .iter()
+        .map(|query| {
+            let ranked = rank(anon, query);
+            let position = ranked
+                .iter()
+                .position(|name| *name == query.expected[0])
+                .unwrap();
+            1. / (position + 1) as f64
+        })
+        .sum::<f64>();
+
+    sum / corpus.queries.len() as f64
+}
+
+fn text_only() -> SearchRelevanceConfig {
+    SearchRelevanceConfig {
+        text_weight: 1.,
+        recent_downloads_weight: 0.,
+        reverse_dependencies_weight: 0.,
+        recency_weight: 0.,
+        recency_half_life_days: 365.,
+        yanked_penalty: 0.,
+    }
+}
+
+/// Checks the top and the bottom of the rankings of the corpus with the
+/// default weights, and records the full rankings, so that changes to the
+/// weights show up as rank changes in the snapshot.
+#[test]
+fn corpus_rankings() {
+    let (_app, anon) = corpus_app(SearchRelevanceConfig::default());
+
+    let mut report = String::new();
+    for query in corpus().queries {
+        let ranked = rank(&anon, &query);
+
+        assert_eq!(
+            ranked[..query.expected.len()],
+            query.expected,
+            "unexpected ranking for `{}`",
+            query.query
+        );
+
+        // Crates without any unyanked versions are ranked last
+        let yanked = query
+            .candidates
+            .iter()
+            .filter(|candidate| candidate.days_since_release.is_none());
+        for candidate in yanked {
+            assert_eq!(ranked.last(), Some(&candidate.name));
+        }
+
+        writeln!(report, "{}", query.query).unwrap();
+        for (position, name) in ranked.iter().enumerate() {
+            writeln!(report, "  {}. {name}", position + 1).unwrap();
+        }
+    }
+
+    assert_snapshot!(report);
+}
+
+#[test]
+fn improves_on_text_rank() {
+    let corpus = corpus();
+
+    let (_app, anon) = corpus_app(SearchRelevanceConfig::default());
+    let default = mean_reciprocal_rank(&anon, &corpus);
+
+    let (_app, anon) = corpus_app(text_only());
+    let text_only = mean_reciprocal_rank(&anon, &corpus);
+
+    assert_eq!(default, 1.);
+    assert!(text_only < default, "{text_only} >= {default}");
+}
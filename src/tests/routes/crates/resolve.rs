@@ -0,0 +1,154 @@
+use crate::builders::{CrateBuilder, VersionBuilder};
+use crate::util::{RequestHelper, Response, TestApp};
+use crates_io::rate_limiter::LimitedAction;
+use http::StatusCode;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+fn resolve(user: &impl RequestHelper, body: Value) -> Response<Value> {
+    let mut request = user.post_request("/api/v1/resolve/crates");
+    *request.body_mut() = body.to_string().into();
+    user.run(request)
+}
+
+fn error_detail<T>(response: Response<T>) -> String {
+    let json = response.into_json();
+    json["errors"][0]["detail"].as_str().unwrap().to_string()
+}
+
+#[test]
+fn resolve_dependency_graph() {
+    let (app, _, user) = TestApp::init().with_user();
+    let user_id = user.as_model().id;
+
+    app.db(|conn| {
+        let bar = CrateBuilder::new("bar", user_id)
+            .version(VersionBuilder::new("1.0.0").license(Some("MIT")))
+            .version(
+                VersionBuilder::new("1.1.0")
+                    .license(Some("Apache-2.0"))
+                    .rust_version("1.80"),
+            )
+            .version(VersionBuilder::new("2.0.0").yanked(true))
+            .expect_build(conn);
+
+        CrateBuilder::new("foo", user_id)
+            .version(
+                VersionBuilder::new("1.0.0")
+                    .license(Some("MIT OR Apache-2.0"))
+                    .dependency(&bar, Some("cfg(unix)")),
+            )
+            .expect_build(conn);
+    });
+
+    let response = resolve(
+        &user,
+        json!({ "dependencies": [{ "name": "foo", "req": "^1" }] }),
+    );
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.into_json();
+
+    let packages = json["packages"].as_array().unwrap();
+    assert_eq!(packages.len(), 2);
+    assert_eq!(packages[0]["id"], "foo@1.0.0");
+    assert_eq!(packages[1]["id"], "bar@1.1.0");
+    assert_eq!(packages[1]["rust_version"], "1.80");
+    assert_eq!(
+        json["dependencies"],
+        json!([
+            { "from": null, "to": "foo@1.0.0", "req": "^1", "kind": "normal", "target": null, "optional": false },
+            { "from": "foo@1.0.0", "to": "bar@1.1.0", "req": ">=0", "kind": "normal", "target": "cfg(unix)", "optional": false },
+        ])
+    );
+    assert_eq!(json["licenses"], json!(["Apache-2.0", "MIT OR Apache-2.0"]));
+
+    // Versions that don't support the given Rust version are skipped
+    let body = json!({ "dependencies": [{ "name": "foo" }], "rust_version": "1.70" });
+    let json = resolve(&user, body).good();
+    assert_eq!(json["packages"][1]["id"], "bar@1.0.0");
+    assert_eq!(json["licenses"], json!(["MIT", "MIT OR Apache-2.0"]));
+}
+
+#[test]
+fn resolve_errors() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let user_id = user.as_model().id;
+
+    app.db(|conn| {
+        CrateBuilder::new("foo", user_id)
+            .version(VersionBuilder::new("1.0.0").rust_version("1.80"))
+            .expect_build(conn);
+    });
+
+    let body = json!({ "dependencies": [{ "name": "foo" }] });
+    let response = resolve(&anon, body);
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = resolve(&user, json!({ "dependencies": [] }));
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = json!({ "dependencies": [{ "name": "foo", "req": "not-a-req" }] });
+    let response = resolve(&user, body);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        error_detail(response),
+        "invalid version requirement `not-a-req` for `foo`"
+    );
+
+    let body = json!({ "dependencies": [{ "name": "foo", "req": "^2" }] });
+    let response = resolve(&user, body);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        error_detail(response),
+        "failed to select a version of `foo` matching `^2`"
+    );
+
+    let body = json!({ "dependencies": [{ "name": "foo" }], "rust_version": "1.70" });
+    let response = resolve(&user, body);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        error_detail(response),
+        "failed to select a version of `foo` matching `*` that supports Rust 1.70.0"
+    );
+
+    let body = json!({ "dependencies": [{ "name": "foo", "features": ["unknown"] }] });
+    let response = resolve(&user, body);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        error_detail(response),
+        "package `foo@1.0.0` does not have the feature `unknown`"
+    );
+}
+
+#[test]
+fn rate_limited() {
+    let (app, _, user) = TestApp::init()
+        .with_rate_limit(LimitedAction::BulkLookup, Duration::from_secs(60), 1)
+        .with_rate_limit(LimitedAction::Resolve, Duration::from_secs(60), 1)
+        .with_user();
+    app.db(|conn| {
+        CrateBuilder::new("foo", user.as_model().id)
+            .version("1.0.0")
+            .expect_build(conn);
+    });
+
+    let body = json!({ "dependencies": [{ "name": "foo", "req": "^1" }] });
+    resolve(&user, body.clone()).good();
+    resolve(&user, body).assert_rate_limited(LimitedAction::Resolve);
+
+    // Bulk lookups have their own rate limit
+    let body = json!({ "crates": [{ "name": "foo", "version": "1.0.0" }] });
+    let mut request = user.post_request("/api/v1/lookup/crates");
+    *request.body_mut() = body.to_string().into();
+    assert_eq!(user.run::<Value>(request).status(), StatusCode::OK);
+}
+
+#[test]
+fn does_not_shadow_crate_named_resolve() {
+    let (app, anon, user) = TestApp::init().with_user();
+    app.db(|conn| CrateBuilder::new("resolve", user.as_model().id).expect_build(conn));
+
+    let response = anon.get::<()>("/api/v1/crates/resolve");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.into_json()["crate"]["name"], "resolve");
+}
@@ -215,3 +215,50 @@ fn reverse_dependencies_query_supports_u64_version_number_parts() {
     assert_eq!(deps.versions[0].krate, "c2");
     assert_eq!(deps.versions[0].num, large_but_valid_version_number);
 }
+
+#[test]
+fn reverse_dependencies_seek_based_pagination() {
+    let (app, anon, user) = TestApp::init().with_user();
+    let user = user.as_model();
+
+    app.db(|conn| {
+        let c1 = CrateBuilder::new("c1", user.id).expect_build(conn);
+        for (name, downloads) in [("c2", 10), ("c3", 20), ("c4", 10)] {
+            CrateBuilder::new(name, user.id)
+                .downloads(downloads)
+                .version(VersionBuilder::new("1.0.0").dependency(&c1, None))
+                .expect_build(conn);
+        }
+    });
+
+    let mut url = Some("?per_page=2".to_string());
+    let mut results = Vec::new();
+    while let Some(current_url) = url.take() {
+        let deps: RevDeps = anon
+            .get_with_query(
+                "/api/v1/crates/c1/reverse_dependencies",
+                current_url.trim_start_matches('?'),
+            )
+            .good();
+        assert_eq!(deps.meta.total, 3);
+
+        for dep in deps.dependencies {
+            let version = deps.versions.iter().find(|v| v.id == dep.version_id);
+            results.push(version.unwrap().krate.clone());
+        }
+        url = deps.meta.next_page;
+    }
+
+    assert_eq!(results, vec!["c3", "c2", "c4"]);
+
+    // Explicit pages still use offset-based pagination
+    let deps: RevDeps = anon
+        .get_with_query(
+            "/api/v1/crates/c1/reverse_dependencies",
+            "page=2&per_page=2",
+        )
+        .good();
+    assert_eq!(deps.meta.total, 3);
+    assert_eq!(deps.versions.len(), 1);
+    assert_eq!(deps.versions[0].krate, "c4");
+}
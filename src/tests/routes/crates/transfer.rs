@@ -0,0 +1,226 @@
+use crate::builders::CrateBuilder;
+use crate::util::{RequestHelper, TestApp};
+use crate::OkBool;
+use chrono::{Duration, Utc};
+use crates_io::models::{Crate, CrateOwner, OwnerKind, User};
+use crates_io::schema::{crate_owners, crate_transfers};
+use diesel::prelude::*;
+use http::StatusCode;
+use serde_json::Value;
+
+const URL: &str = "/api/v1/crates/foo/transfer";
+
+/// Returns the subjects of the emails about crate transfers.
+fn transfer_emails(app: &TestApp) -> Vec<String> {
+    app.as_inner()
+        .emails
+        .mails_in_memory()
+        .unwrap()
+        .into_iter()
+        .filter_map(|(_, message)| {
+            let subject = message.lines().find(|line| line.starts_with("Subject: "))?;
+            Some(subject.trim_start_matches("Subject: ").to_string())
+        })
+        .filter(|subject| subject.contains("transfer"))
+        .collect()
+}
+
+fn add_owner(krate: &Crate, user: &User, conn: &mut PgConnection) {
+    diesel::insert_into(crate_owners::table)
+        .values(&CrateOwner {
+            crate_id: krate.id,
+            owner_id: user.id,
+            created_by: user.id,
+            owner_kind: OwnerKind::User,
+            email_notifications: true,
+        })
+        .execute(conn)
+        .unwrap();
+}
+
+fn owner_logins(user: &impl RequestHelper) -> Vec<String> {
+    let json: Value = user.get("/api/v1/crates/foo/owners").good();
+    json["users"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|owner| owner["login"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn transfer_crate_to_user() {
+    let (app, anon, owner) = TestApp::init().with_user();
+    let co_owner = app.db_new_user("co-owner");
+    let recipient = app.db_new_user("recipient");
+    app.db(|conn| {
+        let krate = CrateBuilder::new("foo", owner.as_model().id).expect_build(conn);
+        add_owner(&krate, co_owner.as_model(), conn);
+    });
+
+    let body = r#"{ "to": "recipient" }"#;
+    let json: Value = owner.put(URL, body).good();
+    assert_eq!(
+        json["msg"],
+        "recipient has been asked to accept the transfer of crate foo"
+    );
+
+    let json: Value = recipient.get("/api/v1/me/crate_transfers").good();
+    let transfers = json["crate_transfers"].as_array().unwrap();
+    assert_eq!(transfers.len(), 1);
+    assert_eq!(transfers[0]["crate_name"], "foo");
+    assert_eq!(transfers[0]["proposed_by"], "foo");
+
+    // Only the recipient can accept the transfer
+    let response = co_owner.put::<()>(&format!("{URL}/accept"), "");
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    recipient.put::<OkBool>(&format!("{URL}/accept"), "").good();
+    assert_eq!(owner_logins(&anon), ["recipient"]);
+
+    let json: Value = recipient.get("/api/v1/me/crate_transfers").good();
+    assert_eq!(json["crate_transfers"], json!([]));
+
+    // The transfer is gone after it was accepted
+    let response = recipient.put::<()>(&format!("{URL}/accept"), "");
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "there is no pending transfer for this crate" }] })
+    );
+
+    assert_eq!(
+        transfer_emails(&app),
+        ["Crate transfer", "Crate transferred", "Crate transferred"]
+    );
+}
+
+#[test]
+fn only_owners_can_propose_transfers() {
+    let (app, _, owner) = TestApp::init().with_user();
+    let other = app.db_new_user("other");
+    app.db(|conn| CrateBuilder::new("foo", owner.as_model().id).expect_build(conn));
+
+    let response = other.put::<()>(URL, r#"{ "to": "other" }"#);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "only owners have permission to transfer crates" }] })
+    );
+
+    let response = owner.put::<()>(URL, r#"{ "to": "foo" }"#);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "cannot transfer a crate to yourself" }] })
+    );
+
+    let response = owner.put::<()>(URL, r#"{ "to": "nobody" }"#);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "could not find user with login `nobody`" }] })
+    );
+
+    let count: i64 = app.db(|conn| crate_transfers::table.count().get_result(conn).unwrap());
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn cancel_and_decline_transfers() {
+    let (app, anon, owner) = TestApp::init().with_user();
+    let recipient = app.db_new_user("recipient");
+    let other = app.db_new_user("other");
+    app.db(|conn| CrateBuilder::new("foo", owner.as_model().id).expect_build(conn));
+
+    let body = r#"{ "to": "recipient" }"#;
+    owner.put::<Value>(URL, body).good();
+
+    // Unrelated users can't cancel the transfer
+    let response = other.delete::<()>(URL);
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "only owners have permission to transfer crates" }] })
+    );
+
+    owner.delete::<OkBool>(URL).good();
+    let response = recipient.put::<()>(&format!("{URL}/accept"), "");
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "there is no pending transfer for this crate" }] })
+    );
+
+    owner.put::<Value>(URL, body).good();
+    recipient.delete::<OkBool>(URL).good();
+
+    let json: Value = recipient.get("/api/v1/me/crate_transfers").good();
+    assert_eq!(json["crate_transfers"], json!([]));
+    assert_eq!(owner_logins(&anon), ["foo"]);
+}
+
+#[test]
+fn expired_transfers_cannot_be_accepted() {
+    let (app, anon, owner) = TestApp::init().with_user();
+    let recipient = app.db_new_user("recipient");
+    app.db(|conn| CrateBuilder::new("foo", owner.as_model().id).expect_build(conn));
+
+    owner.put::<Value>(URL, r#"{ "to": "recipient" }"#).good();
+
+    app.db(|conn| {
+        diesel::update(crate_transfers::table)
+            .set(crate_transfers::created_at.eq(Utc::now().naive_utc() - Duration::days(365)))
+            .execute(conn)
+            .unwrap();
+    });
+
+    let response = recipient.put::<()>(&format!("{URL}/accept"), "");
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "the transfer has expired, please ask an owner of the crate to propose it again" }] })
+    );
+    assert_eq!(owner_logins(&anon), ["foo"]);
+}
+
+#[test]
+fn anonymous_users_cannot_list_transfers() {
+    let (_, anon) = TestApp::init().empty();
+
+    let response = anon.get::<()>("/api/v1/me/crate_transfers");
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[test]
+fn transfers_of_removed_owners_cannot_be_accepted() {
+    let (app, anon, owner) = TestApp::init().with_user();
+    let co_owner = app.db_new_user("co-owner");
+    let recipient = app.db_new_user("recipient");
+    app.db(|conn| {
+        let krate = CrateBuilder::new("foo", owner.as_model().id).expect_build(conn);
+        add_owner(&krate, co_owner.as_model(), conn);
+    });
+
+    // Removing the owner deletes the transfers that they proposed
+    co_owner
+        .put::<Value>(URL, r#"{ "to": "recipient" }"#)
+        .good();
+    owner
+        .delete_with_body::<Value>("/api/v1/crates/foo/owners", r#"{ "owners": ["co-owner"] }"#)
+        .good();
+
+    let json: Value = recipient.get("/api/v1/me/crate_transfers").good();
+    assert_eq!(json["crate_transfers"], json!([]));
+
+    // Transfers whose proposer lost their ownership in another way are
+    // rejected when they are accepted
+    owner.put::<Value>(URL, r#"{ "to": "recipient" }"#).good();
+    app.db(|conn| {
+        let owner_id = owner.as_model().id;
+        diesel::update(crate_owners::table.filter(crate_owners::owner_id.eq(owner_id)))
+            .set(crate_owners::deleted.eq(true))
+            .execute(conn)
+            .unwrap();
+    });
+
+    let response = recipient.put::<()>(&format!("{URL}/accept"), "");
+    assert_eq!(
+        response.into_json(),
+        json!({ "errors": [{ "detail": "the user who proposed the transfer is no longer an owner of the crate" }] })
+    );
+    assert_eq!(owner_logins(&anon), Vec::<String>::new());
+}
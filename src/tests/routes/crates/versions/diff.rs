@@ -0,0 +1,147 @@
+use crate::builders::{CrateBuilder, DependencyBuilder, PublishBuilder};
+use crate::util::{MockCookieUser, RequestHelper, TestApp};
+use crates_io::rate_limiter::LimitedAction;
+use http::StatusCode;
+use insta::assert_json_snapshot;
+use std::time::Duration;
+
+fn publish_versions(app: &TestApp, user: &MockCookieUser, token: &impl RequestHelper) {
+    app.db(|conn| {
+        // Insert the dependencies directly into the database so that foo can depend on them
+        CrateBuilder::new("bar", user.as_model().id).expect_build(conn);
+        CrateBuilder::new("baz", user.as_model().id).expect_build(conn);
+    });
+
+    let crate_to_publish = PublishBuilder::new("foo", "1.0.0")
+        .dependency(DependencyBuilder::new("bar").version_req("^1.0.0"))
+        .feature("default", &["std"])
+        .feature("std", &[])
+        .add_file("foo-1.0.0/src/lib.rs", "pub fn foo() {}\n")
+        .add_file("foo-1.0.0/src/old.rs", "pub fn old() {}\n");
+
+    token.publish_crate(crate_to_publish).good();
+
+    let manifest = "\
+        [package]\n\
+        name = \"foo\"\n\
+        version = \"1.1.0\"\n\
+        description = \"description\"\n\
+        license = \"MIT\"\n\
+        rust-version = \"1.70\"\n\
+        \n\
+        [dependencies]\n\
+        bar = \"^1.0.1\"\n\
+        baz = \"^1.0.0\"\n\
+        \n\
+        [features]\n\
+        default = [\"std\"]\n\
+        std = [\"alloc\"]\n\
+        alloc = []\n";
+
+    let crate_to_publish = PublishBuilder::new("foo", "1.1.0")
+        .custom_manifest(manifest)
+        .add_file(
+            "foo-1.1.0/src/lib.rs",
+            "pub fn foo() {}\n\npub fn bar() {}\n",
+        )
+        .add_file("foo-1.1.0/logo.png", &b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"[..]);
+
+    token.publish_crate(crate_to_publish).good();
+}
+
+#[test]
+fn diff() {
+    let (app, _, user, token) = TestApp::full().with_token();
+    publish_versions(&app, &user, &token);
+
+    let response = user.get::<()>("/api/v1/crates/foo/1.0.0...1.1.0/diff");
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.into_json();
+    assert_json_snapshot!(json);
+
+    let diff_path = "diffs/foo/foo-1.0.0...1.1.0.json".to_string();
+    assert!(app.stored_files().contains(&diff_path));
+
+    // The second request is served from the cache
+    let response = user.get::<()>("/api/v1/crates/foo/1.0.0...1.1.0/diff");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.into_json(), json);
+}
+
+#[test]
+fn invalid_range() {
+    let (app, _, user, token) = TestApp::full().with_token();
+    publish_versions(&app, &user, &token);
+
+    let response = user.get::<()>("/api/v1/crates/foo/1.0.0..1.1.0/diff");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_json_snapshot!(response.into_json());
+}
+
+#[test]
+fn unknown_version() {
+    let (app, _, user, token) = TestApp::full().with_token();
+    publish_versions(&app, &user, &token);
+
+    let response = user.get::<()>("/api/v1/crates/foo/1.0.0...2.0.0/diff");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.into_json());
+}
+
+#[test]
+fn anonymous_users_are_rejected() {
+    let (app, anon, user, token) = TestApp::full().with_token();
+    publish_versions(&app, &user, &token);
+
+    let response = anon.get::<()>("/api/v1/crates/foo/1.0.0...1.1.0/diff");
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert!(!app
+        .stored_files()
+        .iter()
+        .any(|path| path.starts_with("diffs/")));
+}
+
+#[test]
+fn uncached_diffs_are_rate_limited() {
+    let (app, _, user, token) = TestApp::full()
+        .with_rate_limit(LimitedAction::VersionDiff, Duration::from_secs(60 * 60), 1)
+        .with_token();
+    publish_versions(&app, &user, &token);
+    let crate_to_publish = PublishBuilder::new("foo", "1.2.0");
+    token.publish_crate(crate_to_publish).good();
+
+    let response = user.get::<()>("/api/v1/crates/foo/1.0.0...1.1.0/diff");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Cached diffs are not rate limited
+    let response = user.get::<()>("/api/v1/crates/foo/1.0.0...1.1.0/diff");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    user.get::<()>("/api/v1/crates/foo/1.1.0...1.2.0/diff")
+        .assert_rate_limited(LimitedAction::VersionDiff);
+}
+
+#[test]
+fn too_many_files() {
+    let (app, _, user, token) = TestApp::full()
+        .with_config(|config| config.max_diff_files = 10)
+        .with_token();
+
+    // Both versions have 6 files, which is too many files in total
+    for version in ["1.0.0", "1.1.0"] {
+        let mut crate_to_publish = PublishBuilder::new("foo", version);
+        for i in 0..5 {
+            let path = format!("foo-{version}/src/file{i}.rs");
+            crate_to_publish = crate_to_publish.add_file(path, "");
+        }
+        token.publish_crate(crate_to_publish).good();
+    }
+
+    let response = user.get::<()>("/api/v1/crates/foo/1.0.0...1.1.0/diff");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_json_snapshot!(response.into_json());
+    assert!(!app
+        .stored_files()
+        .iter()
+        .any(|path| path.starts_with("diffs/")));
+}
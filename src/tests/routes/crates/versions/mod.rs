@@ -1,5 +1,7 @@
 mod authors;
 pub mod dependencies;
+mod diff;
 pub mod download;
 mod read;
+mod source;
 pub mod yank_unyank;
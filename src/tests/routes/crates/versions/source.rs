@@ -0,0 +1,108 @@
+use crate::builders::PublishBuilder;
+use crate::util::{RequestHelper, TestApp};
+use http::{header, StatusCode};
+use insta::assert_json_snapshot;
+
+fn publish_foo(token: &impl RequestHelper) {
+    let crate_to_publish = PublishBuilder::new("foo", "1.0.0")
+        .add_file("foo-1.0.0/src/lib.rs", "pub fn foo() {}\n")
+        .add_file("foo-1.0.0/logo.png", &b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"[..])
+        .add_file("foo-1.0.0/data/large.txt", "x".repeat(2000));
+
+    token.publish_crate(crate_to_publish).good();
+}
+
+#[test]
+fn list_files() {
+    let (_app, anon, _cookie, token) = TestApp::full().with_token();
+    publish_foo(&token);
+
+    let response = anon.get::<()>("/api/v1/crates/foo/1.0.0/source");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_json_snapshot!(response.into_json());
+}
+
+#[test]
+fn show_text_file() {
+    let (_app, anon, _cookie, token) = TestApp::full().with_token();
+    publish_foo(&token);
+
+    let response = anon.get::<()>("/api/v1/crates/foo/1.0.0/source/src/lib.rs");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()[header::CONTENT_TYPE],
+        "text/plain; charset=utf-8"
+    );
+    assert_eq!(
+        response.headers()[header::X_CONTENT_TYPE_OPTIONS],
+        "nosniff"
+    );
+    assert_eq!(response.into_text(), "pub fn foo() {}\n");
+}
+
+#[test]
+fn show_binary_file() {
+    let (_app, anon, _cookie, token) = TestApp::full().with_token();
+    publish_foo(&token);
+
+    let response = anon.get::<()>("/api/v1/crates/foo/1.0.0/source/logo.png");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()[header::CONTENT_TYPE],
+        "application/octet-stream"
+    );
+}
+
+#[test]
+fn show_missing_file() {
+    let (_app, anon, _cookie, token) = TestApp::full().with_token();
+    publish_foo(&token);
+
+    anon.get::<()>("/api/v1/crates/foo/1.0.0/source/src/main.rs")
+        .assert_not_found();
+}
+
+#[test]
+fn show_file_too_large() {
+    let (_app, anon, _cookie, token) = TestApp::full().with_token();
+    publish_foo(&token);
+
+    let response = anon.get::<()>("/api/v1/crates/foo/1.0.0/source/data/large.txt");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_json_snapshot!(response.into_json());
+}
+
+#[test]
+fn show_cached_file() {
+    let (app, anon, _cookie, token) = TestApp::full().with_token();
+    publish_foo(&token);
+
+    let url = "/api/v1/crates/foo/1.0.0/source/src/lib.rs";
+    assert_eq!(anon.get::<()>(url).status(), StatusCode::OK);
+
+    // The file is served from the cache after the `.crate` file was removed.
+    let storage = &app.as_inner().storage;
+    let result = app
+        .runtime()
+        .block_on(storage.delete_crate_file("foo", "1.0.0"));
+    assert!(result.is_ok());
+
+    let response = anon.get::<()>(url);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.into_text(), "pub fn foo() {}\n");
+}
+
+#[test]
+fn missing_crate_file() {
+    let (app, anon, _cookie, token) = TestApp::full().with_token();
+    publish_foo(&token);
+
+    let storage = &app.as_inner().storage;
+    let result = app
+        .runtime()
+        .block_on(storage.delete_crate_file("foo", "1.0.0"));
+    assert!(result.is_ok());
+
+    anon.get::<()>("/api/v1/crates/foo/1.0.0/source")
+        .assert_not_found();
+}
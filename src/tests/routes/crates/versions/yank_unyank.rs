@@ -324,4 +324,46 @@ mod auth {
             json!({ "errors": [{ "detail": "must be logged in to perform that action" }] })
         );
     }
+
+    #[test]
+    fn token_user_with_correct_version_restricted_crate_scope() {
+        let (_, _, client) = prepare();
+        let client = client.db_new_scoped_token(
+            "test-token",
+            Some(vec![
+                CrateScope::try_from(format!("{CRATE_NAME}@^1")).unwrap()
+            ]),
+            None,
+            None,
+        );
+
+        let response = client.yank(CRATE_NAME, CRATE_VERSION);
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.into_json(), json!({ "ok": true }));
+
+        let response = client.unyank(CRATE_NAME, CRATE_VERSION);
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.into_json(), json!({ "ok": true }));
+    }
+
+    #[test]
+    fn token_user_with_incorrect_version_restricted_crate_scope() {
+        let (_, _, client) = prepare();
+        let client = client.db_new_scoped_token(
+            "test-token",
+            Some(vec![
+                CrateScope::try_from(format!("{CRATE_NAME}@prerelease")).unwrap(),
+                CrateScope::try_from(format!("{CRATE_NAME}@^2")).unwrap(),
+            ]),
+            None,
+            None,
+        );
+
+        let response = client.yank(CRATE_NAME, CRATE_VERSION);
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert_eq!(
+            response.into_json(),
+            json!({ "errors": [{ "detail": "must be logged in to perform that action" }] })
+        );
+    }
 }
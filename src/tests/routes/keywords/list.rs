@@ -11,6 +11,7 @@ struct KeywordList {
 #[derive(Deserialize)]
 struct KeywordMeta {
     total: i32,
+    next_page: Option<String>,
 }
 
 #[test]
@@ -30,3 +31,31 @@ fn index() {
     assert_eq!(json.meta.total, 1);
     assert_eq!(json.keywords[0].keyword.as_str(), "foo");
 }
+
+#[test]
+fn seek_based_pagination() {
+    let (app, anon) = TestApp::init().empty();
+
+    app.db(|conn| {
+        Keyword::find_or_create_all(conn, &["foo", "bar", "baz"]).unwrap();
+    });
+
+    for (sort, expected) in [
+        ("alpha", vec!["bar", "baz", "foo"]),
+        ("crates", vec!["baz", "bar", "foo"]),
+    ] {
+        let mut url = Some(format!("?sort={sort}&per_page=2"));
+        let mut results = Vec::new();
+        while let Some(current_url) = url.take() {
+            let json: KeywordList = anon
+                .get_with_query("/api/v1/keywords", current_url.trim_start_matches('?'))
+                .good();
+            assert_eq!(json.meta.total, 3);
+
+            results.extend(json.keywords.into_iter().map(|kw| kw.keyword));
+            url = json.meta.next_page;
+        }
+
+        assert_eq!(results, expected, "sort={sort}");
+    }
+}
@@ -0,0 +1,76 @@
+use crate::builders::CrateBuilder;
+use crate::util::{RequestHelper, TestApp};
+use crates_io::models::{CrateOwner, OwnerKind};
+use crates_io::schema::crate_owners;
+use diesel::prelude::*;
+use http::StatusCode;
+use serde_json::Value;
+
+const URL: &str = "/api/v1/me";
+
+#[test]
+fn delete_account() {
+    let (app, anon, user, token) = TestApp::init().with_token();
+    let other = app.db_new_user("other");
+    let user_id = user.as_model().id;
+
+    app.db(|conn| {
+        let shared = CrateBuilder::new("shared", user_id).expect_build(conn);
+        CrateBuilder::new("solo", user_id).expect_build(conn);
+
+        diesel::insert_into(crate_owners::table)
+            .values(CrateOwner {
+                crate_id: shared.id,
+                owner_id: other.as_model().id,
+                created_by: user_id,
+                owner_kind: OwnerKind::User,
+                email_notifications: true,
+            })
+            .execute(conn)
+            .unwrap();
+    });
+
+    // The deletion has to be confirmed with the login
+    let response = user.delete_with_body::<()>(URL, r#"{ "login": "other" }"#);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // API tokens can't delete the account
+    token
+        .delete_with_body::<()>(URL, r#"{ "login": "foo" }"#)
+        .assert_forbidden();
+
+    let json: Value = user.delete_with_body(URL, r#"{ "login": "foo" }"#).good();
+    assert_eq!(json["left_to_other_owners"], json!(["shared"]));
+    assert_eq!(json["orphaned"], json!(["solo"]));
+
+    // The session and the API tokens don't work anymore
+    user.get::<()>(URL).assert_forbidden();
+    token
+        .get::<()>("/api/v1/crates/solo/following")
+        .assert_forbidden();
+
+    anon.get::<()>("/api/v1/users/foo").assert_not_found();
+
+    let owners = anon.show_crate_owners("shared").users;
+    let logins = owners.iter().map(|owner| &owner.login).collect::<Vec<_>>();
+    assert_eq!(logins, ["other"]);
+    assert!(anon.show_crate_owners("solo").users.is_empty());
+
+    // The personal data is removed
+    let deleted = app.db(|conn| crates_io::models::User::find(conn, user_id).unwrap());
+    assert_eq!(deleted.gh_login, format!("deleted-user-{user_id}"));
+    assert_eq!(deleted.gh_id, -1);
+    assert_none!(&deleted.name);
+    assert_some!(deleted.deleted_at);
+    app.db(|conn| assert_none!(deleted.email(conn).unwrap()));
+
+    let subjects = app
+        .as_inner()
+        .emails
+        .mails_in_memory()
+        .unwrap()
+        .into_iter()
+        .filter(|(_, message)| message.contains("Subject: Your crates.io account has been deleted"))
+        .count();
+    assert_eq!(subjects, 1);
+}
@@ -0,0 +1,35 @@
+use crate::builders::PublishBuilder;
+use crate::util::{RequestHelper, TestApp};
+use http::header;
+use serde_json::Value;
+
+const URL: &str = "/api/v1/me/export";
+
+#[test]
+fn export() {
+    let (_, anon, user, token) = TestApp::full().with_token();
+    token
+        .publish_crate(PublishBuilder::new("foo", "1.0.0"))
+        .good();
+
+    anon.get::<()>(URL).assert_forbidden();
+    token.get::<()>(URL).assert_forbidden();
+
+    let response = user.get::<Value>(URL);
+    assert_eq!(
+        response.headers()[header::CONTENT_DISPOSITION],
+        "attachment; filename=\"crates-io-foo.json\""
+    );
+
+    let json = response.good();
+    assert_eq!(json["user"]["login"], "foo");
+    assert_eq!(json["emails"][0]["email"], "something@example.com");
+    assert_eq!(json["emails"][0]["verified"], true);
+    assert_eq!(json["api_tokens"][0]["name"], "bar");
+    assert_eq!(json["api_tokens"][0]["revoked"], false);
+    assert!(json["api_tokens"][0].get("token").is_none());
+    assert_eq!(json["owned_crates"][0]["name"], "foo");
+    assert_eq!(json["actions"][0]["crate"], "foo");
+    assert_eq!(json["actions"][0]["version"], "1.0.0");
+    assert_eq!(json["actions"][0]["action"], "publish");
+}
@@ -1,5 +1,6 @@
 use crate::builders::CrateBuilder;
 use crate::util::{RequestHelper, TestApp};
+use crates_io::models::token::{CrateScope, EndpointScope};
 use crates_io::views::{EncodablePrivateUser, OwnedCrate};
 
 impl crate::util::MockCookieUser {
@@ -48,3 +49,51 @@ fn test_user_owned_crates_doesnt_include_deleted_ownership() {
     let json = user.show_me();
     assert_eq!(json.owned_crates.len(), 0);
 }
+
+#[test]
+fn me_with_read_private_scope() {
+    let (_, _, user, token) = TestApp::init().with_token();
+
+    // legacy tokens without endpoint scopes are still rejected
+    token.get::<()>("/api/v1/me").assert_forbidden();
+
+    let token = user.db_new_scoped_token("yank", None, Some(vec![EndpointScope::Yank]), None);
+    token.get::<()>("/api/v1/me").assert_forbidden();
+
+    let token = user.db_new_scoped_token(
+        "read-private",
+        None,
+        Some(vec![EndpointScope::ReadPrivate]),
+        None,
+    );
+    let json: UserShowPrivateResponse = token.get("/api/v1/me").good();
+    assert_eq!(json.user.login, user.as_model().gh_login);
+
+    token.get::<serde_json::Value>("/api/v1/me/updates").good();
+    token
+        .get::<serde_json::Value>("/api/v1/me/crate_owner_invitations")
+        .good();
+    token.delete::<()>("/api/v1/me/tokens/1").assert_forbidden();
+}
+
+#[test]
+fn me_with_crate_scoped_token() {
+    let (_, _, user) = TestApp::init().with_user();
+
+    let token = user.db_new_scoped_token(
+        "crate-scoped",
+        Some(vec![CrateScope::try_from("foo").unwrap()]),
+        Some(vec![
+            EndpointScope::ReadPrivate,
+            EndpointScope::ManageTokens,
+        ]),
+        None,
+    );
+    let json: UserShowPrivateResponse = token.get("/api/v1/me").good();
+    assert_eq!(json.user.login, user.as_model().gh_login);
+
+    token
+        .get::<serde_json::Value>("/api/v1/me/crate_owner_invitations")
+        .good();
+    token.get::<()>("/api/v1/me/tokens").assert_forbidden();
+}
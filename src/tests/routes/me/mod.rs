@@ -1,4 +1,6 @@
+mod delete;
 mod email_notifications;
+mod export;
 pub mod get;
 pub mod tokens;
 mod updates;
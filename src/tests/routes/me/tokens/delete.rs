@@ -1,4 +1,5 @@
 use crate::util::{RequestHelper, TestApp};
+use crates_io::models::token::EndpointScope;
 use crates_io::models::ApiToken;
 use crates_io::schema::api_tokens;
 use diesel::prelude::*;
@@ -70,3 +71,32 @@ fn revoke_token_success() {
         assert_eq!(count, Ok(0));
     });
 }
+
+#[test]
+fn revoke_token_with_manage_tokens_scope() {
+    let (app, _, user, token) = TestApp::init().with_token();
+    let token_id = token.as_model().id;
+
+    let yank_token = user.db_new_scoped_token("yank", None, Some(vec![EndpointScope::Yank]), None);
+    yank_token
+        .delete::<()>(&format!("/api/v1/me/tokens/{token_id}"))
+        .assert_forbidden();
+
+    let manage_token = user.db_new_scoped_token(
+        "manage",
+        None,
+        Some(vec![EndpointScope::ManageTokens]),
+        None,
+    );
+    let _json: RevokedResponse = manage_token
+        .delete(&format!("/api/v1/me/tokens/{token_id}"))
+        .good();
+
+    app.db(|conn| {
+        let revoked: bool = assert_ok!(api_tokens::table
+            .find(token_id)
+            .select(api_tokens::revoked)
+            .get_result(conn));
+        assert!(revoked);
+    });
+}
@@ -17,6 +17,26 @@ fn list_with_api_token_is_forbidden() {
     token.get("/api/v1/me/tokens").assert_forbidden();
 }
 
+#[test]
+fn list_with_manage_tokens_scope() {
+    let (_, _, user) = TestApp::init().with_user();
+    let token = user.db_new_scoped_token(
+        "manage",
+        None,
+        Some(vec![EndpointScope::ManageTokens]),
+        None,
+    );
+    let response = token.get::<()>("/api/v1/me/tokens");
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = response.into_json();
+    let response_tokens = json["api_tokens"].as_array().unwrap();
+    assert_eq!(response_tokens.len(), 1);
+    assert_eq!(response_tokens[0]["name"], "manage");
+
+    let token = user.db_new_scoped_token("yank", None, Some(vec![EndpointScope::Yank]), None);
+    token.get::<()>("/api/v1/me/tokens").assert_forbidden();
+}
+
 #[test]
 fn list_empty() {
     let (_, _, user) = TestApp::init().with_user();
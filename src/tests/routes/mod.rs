@@ -11,6 +11,7 @@
 //! - testing output serialization of a route
 //! - testing query parameter combinations of a route
 
+pub mod admin;
 pub mod categories;
 pub mod category_slugs;
 pub mod crates;
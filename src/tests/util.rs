@@ -24,7 +24,7 @@ use crate::{
     OwnersResponse, VersionResponse,
 };
 use crates_io::middleware::session;
-use crates_io::models::{ApiToken, CreatedApiToken, User};
+use crates_io::models::{AdminToken, ApiToken, CreatedAdminToken, CreatedApiToken, User};
 
 use http::{Method, Request};
 
@@ -136,6 +136,14 @@ pub trait RequestHelper {
         self.run(request)
     }
 
+    /// Issue a POST request
+    #[track_caller]
+    fn post<T>(&self, path: &str, body: impl Into<Bytes>) -> Response<T> {
+        let mut request = self.post_request(path);
+        *request.body_mut() = body.into();
+        self.run(request)
+    }
+
     /// Issue a PUT request
     #[track_caller]
     fn put<T>(&self, path: &str, body: impl Into<Bytes>) -> Response<T> {
@@ -306,6 +314,28 @@ impl MockCookieUser {
             token,
         }
     }
+
+    /// Gives the user the admin role, creates an admin token and wraps it in
+    /// a helper struct
+    ///
+    /// This method updates the database directly
+    pub fn db_new_admin_token(&self, name: &str) -> MockAdminUser {
+        use crates_io::schema::users;
+        use diesel::prelude::*;
+
+        let token = self.app.db(|conn| {
+            diesel::update(users::table.find(self.user.id))
+                .set(users::is_admin.eq(true))
+                .execute(conn)
+                .unwrap();
+
+            AdminToken::insert(conn, self.user.id, name, None).unwrap()
+        });
+        MockAdminUser {
+            app: self.app.clone(),
+            token,
+        }
+    }
 }
 
 /// A type that can generate token authenticated requests
@@ -326,6 +356,31 @@ impl RequestHelper for MockTokenUser {
     }
 }
 
+/// A type that can generate requests to the admin API
+pub struct MockAdminUser {
+    app: TestApp,
+    token: CreatedAdminToken,
+}
+
+impl RequestHelper for MockAdminUser {
+    fn request_builder(&self, method: Method, path: &str) -> MockRequest {
+        let mut request = req(method, path);
+        request.header(header::AUTHORIZATION, self.token.plaintext.expose_secret());
+        request
+    }
+
+    fn app(&self) -> &TestApp {
+        &self.app
+    }
+}
+
+impl MockAdminUser {
+    /// Returns a reference to the database `AdminToken` model
+    pub fn as_model(&self) -> &AdminToken {
+        &self.token.model
+    }
+}
+
 impl MockTokenUser {
     /// Returns a reference to the database `ApiToken` model
     pub fn as_model(&self) -> &ApiToken {
@@ -6,6 +6,7 @@ use crates_io::config::{self, BalanceCapacityConfig, Base, DatabasePools, DbPool
 use crates_io::middleware::cargo_compat::StatusCodeConfig;
 use crates_io::models::token::{CrateScope, EndpointScope};
 use crates_io::rate_limiter::{LimitedAction, RateLimiterConfig};
+use crates_io::scanning;
 use crates_io::storage::StorageConfig;
 use crates_io::worker::{Environment, RunnerExt};
 use crates_io::{App, Emails, Env};
@@ -81,6 +82,7 @@ impl TestApp {
             index: None,
             build_job_runner: false,
             use_chaos_proxy: false,
+            scanning: Default::default(),
         }
     }
 
@@ -205,6 +207,7 @@ pub struct TestAppBuilder {
     index: Option<UpstreamIndex>,
     build_job_runner: bool,
     use_chaos_proxy: bool,
+    scanning: scanning::Config,
 }
 
 impl TestAppBuilder {
@@ -265,6 +268,7 @@ impl TestAppBuilder {
                 .storage(app.storage.clone())
                 .connection_pool(app.primary_database.clone())
                 .emails(app.emails.clone())
+                .scanning(self.scanning)
                 .build()
                 .unwrap();
 
@@ -329,6 +333,12 @@ impl TestAppBuilder {
         self
     }
 
+    /// Configures the scans of new versions, which only run if `scan_new_versions` is enabled.
+    pub fn with_scanning(mut self, f: impl FnOnce(&mut scanning::Config)) -> Self {
+        f(&mut self.scanning);
+        self
+    }
+
     pub fn with_rate_limit(self, action: LimitedAction, rate: Duration, burst: i32) -> Self {
         self.with_config(|config| {
             config
@@ -407,6 +417,9 @@ fn simple_config() -> config::Server {
         gh_client_secret: ClientSecret::new(dotenvy::var("GH_CLIENT_SECRET").unwrap_or_default()),
         max_upload_size: 128 * 1024, // 128 kB should be enough for most testing purposes
         max_unpack_size: 128 * 1024, // 128 kB should be enough for most testing purposes
+        max_source_file_size: 1024,
+        max_diff_unpack_size: 128 * 1024,
+        max_diff_files: 100,
         max_features: 10,
         rate_limiter: Default::default(),
         new_version_rate_limit: Some(10),
@@ -424,10 +437,20 @@ fn simple_config() -> config::Server {
         instance_metrics_log_every_seconds: None,
         force_unconditional_redirects: false,
         blocked_routes: HashSet::new(),
+        enabled_publish_lints: HashSet::new(),
+        verify_vcs_commits: false,
+        scan_new_versions: false,
+        attestation_trust_root: Default::default(),
         version_id_cache_size: 10000,
         version_id_cache_ttl: Duration::from_secs(5 * 60),
+        source_index_cache_size: 100,
+        source_index_cache_ttl: Duration::from_secs(5 * 60),
+        source_file_cache_size: 1024 * 1024,
         cdn_user_agent: "Amazon CloudFront".to_string(),
         balance_capacity,
+        search_relevance: Default::default(),
+        typosquat: crates_io::typosquat::Config::embedded().clone(),
+        typosquat_top_crates_ttl: Duration::from_secs(5 * 60),
 
         // The middleware has its own unit tests to verify its functionality.
         // Here, we can test what would happen if we toggled the status code
@@ -7,26 +7,27 @@ use typomania::{
     Harness,
 };
 
-use super::{checks::Suffixes, config, database::TopCrates};
+use super::{checks::Suffixes, config::Config, database::TopCrates};
 
 static NOTIFICATION_EMAILS_ENV: &str = "TYPOSQUAT_NOTIFICATION_EMAILS";
 
 /// A cache containing everything we need to run typosquatting checks.
 ///
-/// Specifically, this includes a corpus of popular crates attached to a typomania harness, and a
-/// list of e-mail addresses that we'll send notifications to if potential typosquatting is
-/// discovered.
+/// Specifically, this includes a corpus of popular crates attached to a typomania harness, a list
+/// of e-mail addresses that we'll send notifications to if potential typosquatting is discovered,
+/// and whether crates with findings are kept out of the index until they have been reviewed.
 pub struct Cache {
     emails: Vec<String>,
-    harness: Option<Harness<TopCrates>>,
+    harness: Harness<TopCrates>,
+    hold_flagged_crates: bool,
 }
 
 impl Cache {
     /// Instantiates a new [`Cache`] from the environment.
     ///
     /// This reads the `NOTIFICATION_EMAILS_ENV` environment variable to get the list of e-mail
-    /// addresses to send notifications to, then invokes [`Cache::new`] to read popular crates from
-    /// the database.
+    /// addresses to send notifications to and the configuration file from [`Config::from_env`],
+    /// then invokes [`Cache::new`] to read popular crates from the database.
     #[instrument(skip_all, err)]
     pub fn from_env(conn: &mut PgConnection) -> Result<Self, Error> {
         let emails: Vec<String> = crates_io_env_vars::var(NOTIFICATION_EMAILS_ENV)
@@ -41,48 +42,52 @@ impl Cache {
             .collect();
 
         if emails.is_empty() {
-            // If we're not notifying anyone, then there's really not much to do here.
+            // The findings are still recorded in the database for review.
             warn!("$TYPOSQUAT_NOTIFICATION_EMAILS is not set; no typosquatting notifications will be sent");
-            Ok(Self {
-                emails,
-                harness: None,
-            })
-        } else {
-            // Otherwise, let's go get the top crates and build a corpus.
-            Self::new(emails, conn)
         }
+
+        let config = Config::from_env().map_err(|e| Error::Config(Arc::new(e)))?;
+        Self::new(emails, &config, conn)
     }
 
     /// Instantiates a cache by querying popular crates and building them into a typomania harness.
     ///
-    /// This relies on configuration in the `super::config` module.
-    pub fn new(emails: Vec<String>, conn: &mut PgConnection) -> Result<Self, Error> {
-        let top = TopCrates::new(conn, config::TOP_CRATES)?;
+    /// This relies on the given [`Config`].
+    pub fn new(
+        emails: Vec<String>,
+        config: &Config,
+        conn: &mut PgConnection,
+    ) -> Result<Self, Error> {
+        let top = TopCrates::new(conn, config.top_crates)?;
 
         Ok(Self {
             emails,
-            harness: Some(
-                Harness::builder()
-                    .with_check(Bitflips::new(
-                        config::CRATE_NAME_ALPHABET,
-                        top.crates.keys().map(String::as_str),
-                    ))
-                    .with_check(Omitted::new(config::CRATE_NAME_ALPHABET))
-                    .with_check(SwappedWords::new("-_"))
-                    .with_check(Typos::new(config::TYPOS.iter().map(|(c, typos)| {
-                        (*c, typos.iter().map(|ss| ss.to_string()).collect())
-                    })))
-                    .with_check(Suffixes::new(
-                        config::SUFFIX_SEPARATORS.iter(),
-                        config::SUFFIXES.iter(),
-                    ))
-                    .build(top),
-            ),
+            harness: Harness::builder()
+                .with_check(Bitflips::new(
+                    &config.crate_name_alphabet,
+                    top.crates.keys().map(String::as_str),
+                ))
+                .with_check(Omitted::new(&config.crate_name_alphabet))
+                .with_check(SwappedWords::new("-_"))
+                .with_check(Typos::new(
+                    config.typos.iter().map(|(c, typos)| (*c, typos.clone())),
+                ))
+                .with_check(Suffixes::new(
+                    config.suffix_separators.iter(),
+                    config.suffixes.iter(),
+                ))
+                .build(top),
+            hold_flagged_crates: config.hold_flagged_crates,
         })
     }
 
-    pub fn get_harness(&self) -> Option<&Harness<TopCrates>> {
-        self.harness.as_ref()
+    pub fn get_harness(&self) -> &Harness<TopCrates> {
+        &self.harness
+    }
+
+    /// Whether crates with findings are kept out of the index until they have been reviewed.
+    pub fn hold_flagged_crates(&self) -> bool {
+        self.hold_flagged_crates
     }
 
     pub fn iter_emails(&self) -> impl Iterator<Item = &str> {
@@ -103,6 +108,9 @@ pub enum Error {
         source: Arc<anyhow::Error>,
     },
 
+    #[error("error reading typosquatting configuration: {0:?}")]
+    Config(#[source] Arc<anyhow::Error>),
+
     #[error("error getting top crates: {0:?}")]
     TopCrates(#[source] Arc<diesel::result::Error>),
 }
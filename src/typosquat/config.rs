@@ -1,63 +1,115 @@
-//! Things that should really be read from a configuration file, but are just hardcoded while we
-//! experiment with this.
-
-/// Valid characters in crate names.
-pub(super) static CRATE_NAME_ALPHABET: &str =
-    "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz1234567890-_";
-
-/// Commonly used separators when building crate names.
-pub(super) static SUFFIX_SEPARATORS: &[&str] = &["-", "_"];
-
-/// Commonly used suffixes when building crate names.
-pub(super) static SUFFIXES: &[&str] = &["api", "cli", "core", "lib", "rs", "rust", "sys"];
-
-/// The number of crates to consider in the "top crates" corpus.
-pub(super) static TOP_CRATES: i64 = 3000;
-
-/// This is based on a pre-existing list we've used with crates.io for "easily confused
-/// characters". This is a mixture of visual substitutions and typos on QWERTY, QWERTZ, and AZERTY
-/// keyboards.
-pub(super) static TYPOS: &[(char, &[&str])] = &[
-    ('1', &["2", "q", "i", "l"]),
-    ('2', &["1", "q", "w", "3"]),
-    ('3', &["2", "w", "e", "4"]),
-    ('4', &["3", "e", "r", "5"]),
-    ('5', &["4", "r", "t", "6", "s"]),
-    ('6', &["5", "t", "y", "7"]),
-    ('7', &["6", "y", "u", "8"]),
-    ('8', &["7", "u", "i", "9"]),
-    ('9', &["8", "i", "o", "0"]),
-    ('0', &["9", "o", "p", "-"]),
-    ('-', &["_", "0", "p", ".", ""]),
-    ('_', &["-", "0", "p", ".", ""]),
-    ('q', &["1", "2", "w", "a", "s", "z"]),
-    ('w', &["2", "3", "e", "s", "a", "q", "vv", "x"]),
-    ('e', &["3", "4", "r", "d", "s", "w", "z"]),
-    ('r', &["4", "5", "t", "f", "d", "e"]),
-    ('t', &["5", "6", "y", "g", "f", "r"]),
-    ('y', &["6", "7", "u", "h", "t", "i", "a", "s", "x"]),
-    ('u', &["7", "8", "i", "j", "y", "v"]),
-    ('i', &["1", "8", "9", "o", "l", "k", "j", "u", "y"]),
-    ('o', &["9", "0", "p", "l", "i"]),
-    ('p', &["0", "-", "o"]),
-    ('a', &["q", "w", "s", "z", "1", "2"]),
-    ('s', &["w", "d", "x", "z", "a", "5", "q"]),
-    ('d', &["e", "r", "f", "c", "x", "s"]),
-    ('f', &["r", "g", "v", "c", "d"]),
-    ('g', &["t", "h", "b", "v", "f"]),
-    ('h', &["y", "j", "n", "b", "g"]),
-    ('j', &["u", "i", "k", "m", "n", "h"]),
-    ('k', &["i", "o", "l", "m", "j"]),
-    ('l', &["i", "o", "p", "k", "1"]),
-    (
-        'z',
-        &["a", "s", "x", "6", "7", "u", "h", "t", "i", "e", "2", "3"],
-    ),
-    ('x', &["z", "s", "d", "c", "w"]),
-    ('c', &["x", "d", "f", "v"]),
-    ('v', &["c", "f", "g", "b", "u"]),
-    ('b', &["v", "g", "h", "n"]),
-    ('n', &["b", "h", "j", "m"]),
-    ('m', &["n", "j", "k", "rn"]),
-    ('.', &["-", "_", ""]),
-];
+//! The configuration of the typosquatting checks, which is read from a TOML file.
+//!
+//! The default configuration in `config.toml` is embedded in the binary, and can be replaced by
+//! pointing the `TYPOSQUAT_CONFIG` environment variable to a different file.
+
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use anyhow::Context;
+
+static CONFIG_PATH_ENV: &str = "TYPOSQUAT_CONFIG";
+
+static DEFAULT_CONFIG: &str = include_str!("config.toml");
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The number of crates to consider in the "top crates" corpus.
+    pub top_crates: i64,
+    /// Valid characters in crate names.
+    pub crate_name_alphabet: String,
+    /// Commonly used separators when building crate names.
+    pub suffix_separators: Vec<String>,
+    /// Commonly used suffixes when building crate names.
+    pub suffixes: Vec<String>,
+    /// Whether new crates with potential typosquatting findings are kept out of the index until
+    /// all findings have been approved by a reviewer.
+    pub hold_flagged_crates: bool,
+    /// What happens when a new crate is published with a name that looks like the name of a
+    /// popular crate.
+    pub publish_check: Severity,
+    /// Strings that look alike, which are replaced with their key before names are compared in
+    /// the publish check.
+    pub confusables: BTreeMap<String, Vec<String>>,
+    /// Easily confused characters, and what they are confused with. An empty string means that the
+    /// character is commonly omitted.
+    pub typos: BTreeMap<char, Vec<String>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// The check is not run.
+    Off,
+    /// The crate is published with a warning.
+    Warn,
+    /// The publish is rejected.
+    Reject,
+}
+
+impl Config {
+    /// Reads the configuration from the file in the `TYPOSQUAT_CONFIG` environment variable, or
+    /// returns the default configuration if it is not set.
+    pub fn from_env() -> anyhow::Result<Self> {
+        match crates_io_env_vars::var(CONFIG_PATH_ENV)? {
+            Some(path) => {
+                let config = std::fs::read_to_string(&path)
+                    .with_context(|| format!("failed to read {CONFIG_PATH_ENV} file {path}"))?;
+                Self::parse(&config)
+                    .with_context(|| format!("invalid {CONFIG_PATH_ENV} file {path}"))
+            }
+            None => Ok(Self::embedded().clone()),
+        }
+    }
+
+    pub fn parse(config: &str) -> anyhow::Result<Self> {
+        let config: Config = toml::from_str(config)?;
+        if config.top_crates <= 0 {
+            anyhow::bail!("top_crates must be a positive number");
+        }
+        Ok(config)
+    }
+
+    /// The embedded default configuration.
+    pub fn embedded() -> &'static Self {
+        static DEFAULT: OnceLock<Config> = OnceLock::new();
+        DEFAULT.get_or_init(|| Self::parse(DEFAULT_CONFIG).expect("invalid default config.toml"))
+    }
+
+    /// Whether `typo` is listed as an easy mistake when typing `c`.
+    pub fn is_typo(&self, c: char, typo: &str) -> bool {
+        self.typos
+            .get(&c)
+            .is_some_and(|typos| typos.iter().any(|t| t == typo))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_config() {
+        let config = Config::embedded();
+        assert_eq!(config.top_crates, 3000);
+        assert!(!config.hold_flagged_crates);
+        assert_eq!(config.publish_check, Severity::Warn);
+        assert_eq!(config.confusables["m"], ["rn"]);
+        assert!(config.is_typo('m', "rn"));
+        assert!(config.is_typo('-', ""));
+        assert!(!config.is_typo('m', "a"));
+    }
+
+    #[test]
+    fn invalid_config() {
+        assert_err!(Config::parse(""));
+        assert_err!(Config::parse(
+            &DEFAULT_CONFIG.replace("top_crates = 3000", "top_crates = 0")
+        ));
+        assert_err!(Config::parse(&format!("unknown = 1\n{DEFAULT_CONFIG}")));
+        assert_err!(Config::parse(
+            &DEFAULT_CONFIG.replace(r#"publish_check = "warn""#, r#"publish_check = "error""#)
+        ));
+    }
+}
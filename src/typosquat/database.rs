@@ -54,6 +54,7 @@ impl TopCrates {
                     krate.name,
                     Crate {
                         owners: HashSet::new(),
+                        teams: Vec::new(),
                     },
                 ),
             );
@@ -77,6 +78,9 @@ impl TopCrates {
             let (crate_id, owner_id, owner_kind) = result?;
             crates.entry(crate_id).and_modify(|(_name, krate)| {
                 krate.owners.insert(Owner::new(owner_id, owner_kind));
+                if owner_kind == models::OwnerKind::Team as i32 {
+                    krate.teams.push(owner_id);
+                }
             });
         }
 
@@ -98,6 +102,8 @@ impl Corpus for TopCrates {
 
 pub struct Crate {
     owners: HashSet<Owner>,
+    /// The IDs of the teams among the owners.
+    teams: Vec<i32>,
 }
 
 impl Crate {
@@ -107,9 +113,32 @@ impl Crate {
         use diesel::prelude::*;
 
         let krate = models::Crate::by_exact_name(name).first(conn)?;
-        let owners = krate.owners(conn)?.into_iter().map(Owner::from).collect();
+        let owners = krate.owners(conn)?;
+        let teams = owners
+            .iter()
+            .filter_map(|owner| match owner {
+                models::Owner::Team(team) => Some(team.id),
+                models::Owner::User(_) => None,
+            })
+            .collect();
+        let owners = owners.into_iter().map(Owner::from).collect();
+
+        Ok(Self { owners, teams })
+    }
+
+    /// A crate that is only owned by the given user, like a crate that is being published for
+    /// the first time.
+    pub fn owned_by_user(user_id: i32) -> Self {
+        let owner = Owner::new(user_id, crate::models::OwnerKind::User as i32);
+        Self {
+            owners: HashSet::from([owner]),
+            teams: Vec::new(),
+        }
+    }
 
-        Ok(Self { owners })
+    /// The IDs of the teams that own the crate.
+    pub fn teams(&self) -> &[i32] {
+        &self.teams
     }
 }
 
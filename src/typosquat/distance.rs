@@ -0,0 +1,112 @@
+//! A typo-aware edit distance between crate names.
+
+use super::config::Config;
+
+/// The cost of substituting a character with one that it is easily confused
+/// with, or of adding or removing a separator.
+const TYPO_COST: f64 = 0.5;
+
+/// Returns the edit distance between two crate names, where substitutions of
+/// easily confused characters and insertions or deletions of the characters
+/// that are commonly omitted (like `-`) count as half an edit, according to
+/// the `typos` of the embedded [`Config`]. Transpositions of adjacent
+/// characters count as a single edit.
+///
+/// The names are compared like `canon_crate_name()` does in the database, so
+/// they are case insensitive and `-` and `_` are equivalent.
+pub fn typo_distance(a: &str, b: &str) -> f64 {
+    let a = canonicalize(a);
+    let b = canonicalize(b);
+
+    // `distances[i][j]` is the distance between the first `i` characters of
+    // `a` and the first `j` characters of `b`.
+    let mut distances = vec![vec![0.; b.len() + 1]; a.len() + 1];
+    for i in 1..=a.len() {
+        distances[i][0] = distances[i - 1][0] + indel_cost(a[i - 1]);
+    }
+    for j in 1..=b.len() {
+        distances[0][j] = distances[0][j - 1] + indel_cost(b[j - 1]);
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let mut distance = f64::min(
+                distances[i - 1][j] + indel_cost(a[i - 1]),
+                distances[i][j - 1] + indel_cost(b[j - 1]),
+            )
+            .min(distances[i - 1][j - 1] + substitution_cost(a[i - 1], b[j - 1]));
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1.);
+            }
+
+            distances[i][j] = distance;
+        }
+    }
+
+    distances[a.len()][b.len()]
+}
+
+fn canonicalize(name: &str) -> Vec<char> {
+    name.chars()
+        .map(|c| match c {
+            '_' => '-',
+            c => c.to_ascii_lowercase(),
+        })
+        .collect()
+}
+
+fn is_typo(c: char, typo: &str) -> bool {
+    Config::embedded().is_typo(c, typo)
+}
+
+fn substitution_cost(a: char, b: char) -> f64 {
+    if a == b {
+        return 0.;
+    }
+
+    let mut buffer = [0; 4];
+    if is_typo(a, b.encode_utf8(&mut buffer)) || is_typo(b, a.encode_utf8(&mut buffer)) {
+        TYPO_COST
+    } else {
+        1.
+    }
+}
+
+fn indel_cost(c: char) -> f64 {
+    if is_typo(c, "") {
+        TYPO_COST
+    } else {
+        1.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_names() {
+        assert_eq!(typo_distance("serde", "serde"), 0.);
+        assert_eq!(typo_distance("Serde_JSON", "serde-json"), 0.);
+    }
+
+    #[test]
+    fn plain_edits() {
+        assert_eq!(typo_distance("serd", "serde"), 1.);
+        assert_eq!(typo_distance("tokio-utl", "tokio-util"), 1.);
+        assert_eq!(typo_distance("tokio", "tokoi"), 1.);
+        assert_eq!(typo_distance("rand", "rang"), 1.);
+        assert_eq!(typo_distance("", "abc"), 3.);
+    }
+
+    #[test]
+    fn typos_are_cheaper() {
+        // `r` and `e` are next to each other on the keyboard
+        assert_eq!(typo_distance("sedre", "serde"), 1.);
+        assert_eq!(typo_distance("swrde", "serde"), 0.5);
+        assert_eq!(typo_distance("l0g", "log"), 0.5);
+        assert_eq!(typo_distance("serdejson", "serde_json"), 0.5);
+        assert_eq!(typo_distance("serdejsn", "serde_json"), 1.5);
+    }
+}
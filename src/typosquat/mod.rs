@@ -2,9 +2,14 @@ mod cache;
 mod checks;
 mod config;
 mod database;
+mod distance;
+mod publish;
 
 #[cfg(test)]
 pub(super) mod test_util;
 
 pub use cache::{Cache, Error as CacheError};
+pub use config::{Config, Severity};
 pub use database::Crate;
+pub use distance::typo_distance;
+pub use publish::{check_new_crate, TopCratesCache};
@@ -0,0 +1,295 @@
+//! A check of the names of new crates against the popular crates, which runs while the crates are
+//! published.
+//!
+//! Unlike the typomania checks of the `check_typosquat` background job, which look for all kinds of
+//! typos after the fact, this check only looks for names that are easily mistaken for the name of
+//! a popular crate: names that only differ by separators, by a common suffix, or by characters
+//! that look alike. That makes it precise enough to warn about, or even reject, the publish.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use diesel::{PgConnection, QueryResult};
+use typomania::Package;
+
+use super::config::Config;
+use super::database::{Crate, TopCrates};
+
+/// The popular crates that new crates are compared with, which are reloaded from the database
+/// once they are older than the TTL.
+pub struct TopCratesCache {
+    ttl: Duration,
+    top: Mutex<Option<(Instant, Arc<TopCrates>)>>,
+}
+
+impl TopCratesCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            top: Mutex::new(None),
+        }
+    }
+
+    pub fn get(&self, config: &Config, conn: &mut PgConnection) -> QueryResult<Arc<TopCrates>> {
+        // The lock is held while the crates are loaded, so that concurrent publishes don't all
+        // load them at the same time.
+        let mut cached = self.top.lock().unwrap_or_else(|error| error.into_inner());
+        if let Some((loaded_at, top)) = &*cached {
+            if loaded_at.elapsed() < self.ttl {
+                return Ok(top.clone());
+            }
+        }
+
+        let top = Arc::new(TopCrates::new(conn, config.top_crates)?);
+        *cached = Some((Instant::now(), top.clone()));
+        Ok(top)
+    }
+}
+
+/// Returns a message for every popular crate whose name looks like `name`, sorted by the name of
+/// the popular crate.
+///
+/// Popular crates that share an owner with the publishing user are skipped, so that owners can
+/// publish related crates like `foo-cli` for their `foo` crate. That includes the crates of the
+/// teams that the user is a member of, which is checked with `is_team_member` for the team IDs.
+pub fn check_new_crate<E>(
+    name: &str,
+    user_id: i32,
+    top: &TopCrates,
+    config: &Config,
+    mut is_team_member: impl FnMut(i32) -> Result<bool, E>,
+) -> Result<Vec<String>, E> {
+    let publisher = Crate::owned_by_user(user_id);
+    let names = Names::new(config);
+
+    let mut squats = Vec::new();
+    for (popular_name, popular) in &top.crates {
+        if popular.shared_authors(publisher.authors()) {
+            continue;
+        }
+
+        let Some(reason) = names.compare(name, popular_name) else {
+            continue;
+        };
+
+        // Team membership is only checked for matching names, since it is looked up on GitHub.
+        let mut is_owned_by_team = false;
+        for team_id in popular.teams() {
+            if is_team_member(*team_id)? {
+                is_owned_by_team = true;
+                break;
+            }
+        }
+
+        if !is_owned_by_team {
+            squats.push((popular_name, reason));
+        }
+    }
+
+    squats.sort();
+
+    Ok(squats
+        .into_iter()
+        .map(|(popular_name, reason)| {
+            format!("`{name}` {reason} of the popular crate `{popular_name}`")
+        })
+        .collect())
+}
+
+/// Compares crate names according to the [`Config`].
+struct Names {
+    /// The separator and suffix combinations, like `-rs`.
+    suffixes: Vec<String>,
+    /// The strings that look alike and what they are replaced with, longest first.
+    confusables: Vec<(String, String)>,
+}
+
+impl Names {
+    fn new(config: &Config) -> Self {
+        let suffixes = config
+            .suffix_separators
+            .iter()
+            .flat_map(|separator| {
+                config
+                    .suffixes
+                    .iter()
+                    .map(move |suffix| format!("{separator}{suffix}"))
+            })
+            .collect();
+
+        let mut confusables = config
+            .confusables
+            .iter()
+            .flat_map(|(replacement, lookalikes)| {
+                lookalikes
+                    .iter()
+                    .map(move |lookalike| (lookalike.clone(), replacement.clone()))
+            })
+            .collect::<Vec<_>>();
+
+        confusables.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+
+        Self {
+            suffixes,
+            confusables,
+        }
+    }
+
+    /// Returns why the name of a new crate looks like the name of a popular crate, if it does.
+    fn compare(&self, name: &str, popular: &str) -> Option<String> {
+        let name = name.to_lowercase();
+        let popular = popular.to_lowercase();
+
+        if without_separators(&name) == without_separators(&popular) {
+            return Some("only differs by `-` and `_` from the name".into());
+        }
+
+        let skeleton = self.skeleton(&name);
+        let popular_skeleton = self.skeleton(&popular);
+        if skeleton == popular_skeleton {
+            return Some("uses characters that look like the ones in the name".into());
+        }
+
+        for suffix in &self.suffixes {
+            let stem = name.strip_suffix(suffix.as_str());
+            if stem.is_some_and(|stem| !stem.is_empty() && self.skeleton(stem) == popular_skeleton)
+            {
+                return Some(format!("adds the `{suffix}` suffix to the name"));
+            }
+
+            let popular_stem = popular.strip_suffix(suffix.as_str());
+            if popular_stem.is_some_and(|stem| !stem.is_empty() && self.skeleton(stem) == skeleton)
+            {
+                return Some(format!("removes the `{suffix}` suffix from the name"));
+            }
+        }
+
+        None
+    }
+
+    /// Removes the separators from a lowercase name and replaces the strings that look alike, so
+    /// that names that are easily mistaken for each other have the same skeleton.
+    fn skeleton(&self, name: &str) -> String {
+        let name = without_separators(name);
+        let mut rest = name.as_str();
+        let mut skeleton = String::with_capacity(name.len());
+
+        'outer: while !rest.is_empty() {
+            for (lookalike, replacement) in &self.confusables {
+                if let Some(remainder) = rest.strip_prefix(lookalike.as_str()) {
+                    skeleton.push_str(replacement);
+                    rest = remainder;
+                    continue 'outer;
+                }
+            }
+
+            let mut chars = rest.chars();
+            skeleton.extend(chars.next());
+            rest = chars.as_str();
+        }
+
+        skeleton
+    }
+}
+
+fn without_separators(name: &str) -> String {
+    name.chars().filter(|c| *c != '-' && *c != '_').collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_util::pg_connection, typosquat::test_util::Faker};
+
+    #[test]
+    fn compare_names() {
+        let names = Names::new(Config::embedded());
+
+        let compare = |name, popular| names.compare(name, popular);
+
+        assert_some_eq!(
+            compare("serdejson", "serde_json"),
+            "only differs by `-` and `_` from the name"
+        );
+        assert_some_eq!(
+            compare("Tokio_Uti_L", "tokio-util"),
+            "only differs by `-` and `_` from the name"
+        );
+        assert_some_eq!(
+            compare("1og", "log"),
+            "uses characters that look like the ones in the name"
+        );
+        assert_some_eq!(
+            compare("tirne", "time"),
+            "uses characters that look like the ones in the name"
+        );
+        assert_some_eq!(
+            compare("reqwest-rs", "reqwest"),
+            "adds the `-rs` suffix to the name"
+        );
+        assert_some_eq!(
+            compare("rand_cli", "rand"),
+            "adds the `_cli` suffix to the name"
+        );
+        assert_some_eq!(
+            compare("openssl", "openssl-sys"),
+            "removes the `-sys` suffix from the name"
+        );
+
+        assert_none!(compare("serde", "serde-json"));
+        assert_none!(compare("tokio", "tokoi"));
+    }
+
+    fn no_teams(_team_id: i32) -> anyhow::Result<bool> {
+        Ok(false)
+    }
+
+    #[test]
+    fn owners_are_exempt() -> anyhow::Result<()> {
+        let mut faker = Faker::new(pg_connection());
+
+        let alice = faker.user("alice")?;
+        let bob = faker.user("bob")?;
+        faker.crate_and_version("my-crate", "It's awesome", &alice, 100)?;
+        faker.crate_and_version("other", "Unrelated", &alice, 100)?;
+        let mut conn = faker.into_conn();
+
+        let config = Config::embedded();
+        let top = TopCrates::new(&mut conn, config.top_crates)?;
+
+        let squats = check_new_crate("mycrate-rs", bob.id, &top, config, no_teams)?;
+        assert_eq!(
+            squats,
+            ["`mycrate-rs` adds the `-rs` suffix to the name of the popular crate `my-crate`"]
+        );
+
+        assert!(check_new_crate("mycrate-rs", alice.id, &top, config, no_teams)?.is_empty());
+        assert!(check_new_crate("something-else", bob.id, &top, config, no_teams)?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn team_members_are_exempt() -> anyhow::Result<()> {
+        let mut faker = Faker::new(pg_connection());
+
+        let alice = faker.user("alice")?;
+        let bob = faker.user("bob")?;
+        let (krate, _) = faker.crate_and_version("my-crate", "It's awesome", &alice, 100)?;
+        let team = faker.team("org", "team")?;
+        faker.add_crate_to_team(&alice, &krate, &team)?;
+        let mut conn = faker.into_conn();
+
+        let config = Config::embedded();
+        let top = TopCrates::new(&mut conn, config.top_crates)?;
+
+        let squats = check_new_crate("mycrate-rs", bob.id, &top, config, no_teams)?;
+        assert_eq!(squats.len(), 1);
+
+        // Bob is a member of the team that owns the popular crate
+        let is_member = |team_id| anyhow::Ok(team_id == team.id());
+        assert!(check_new_crate("mycrate-rs", bob.id, &top, config, is_member)?.is_empty());
+
+        Ok(())
+    }
+}
@@ -33,8 +33,8 @@ use crate::email::EmailError;
 use crates_io_github::GitHubError;
 pub use json::TOKEN_FORMAT_ERROR;
 pub(crate) use json::{
-    InsecurelyGeneratedTokenRevoked, MetricsDisabled, OwnershipInvitationExpired, ReadOnlyMode,
-    RouteBlocked, TooManyRequests,
+    InsecurelyGeneratedTokenRevoked, MetricsDisabled, OwnershipInvitationExpired, Quarantined,
+    ReadOnlyMode, RouteBlocked, TooManyRequests,
 };
 
 pub type BoxedAppError = Box<dyn AppError>;
@@ -235,6 +235,15 @@ impl From<PoolError> for BoxedAppError {
     }
 }
 
+impl From<object_store::Error> for BoxedAppError {
+    fn from(err: object_store::Error) -> BoxedAppError {
+        match err {
+            object_store::Error::NotFound { .. } => not_found(),
+            _ => Box::new(err),
+        }
+    }
+}
+
 impl From<prometheus::Error> for BoxedAppError {
     fn from(err: prometheus::Error) -> BoxedAppError {
         Box::new(err)
@@ -252,6 +252,36 @@ impl fmt::Display for OwnershipInvitationExpired {
     }
 }
 
+#[derive(Debug)]
+pub(crate) struct Quarantined {
+    pub(crate) crate_name: String,
+    /// `None` if the whole crate is quarantined.
+    pub(crate) version: Option<String>,
+}
+
+impl AppError for Quarantined {
+    fn response(&self) -> Response {
+        json_error(&self.to_string(), StatusCode::FORBIDDEN)
+    }
+}
+
+impl fmt::Display for Quarantined {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.version {
+            Some(version) => write!(
+                f,
+                "Version {version} of the {} crate is quarantined while it is being investigated.",
+                self.crate_name
+            ),
+            None => write!(
+                f,
+                "The {} crate is quarantined while it is being investigated.",
+                self.crate_name
+            ),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct MetricsDisabled;
 
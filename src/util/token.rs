@@ -9,14 +9,27 @@ const TOKEN_LENGTH: usize = 32;
 /// revoke all the tokens, disrupting production users.
 const TOKEN_PREFIX: &str = "cio";
 
+/// The prefix of the tokens of the admin API. It must not start with the
+/// prefix of the API tokens, so that the two kinds can't be mistaken for each
+/// other.
+const ADMIN_TOKEN_PREFIX: &str = "adm";
+
 #[derive(FromSqlRow, AsExpression)]
 #[diesel(sql_type = Bytea)]
 pub struct HashedToken(SecretVec<u8>);
 
 impl HashedToken {
     pub(crate) fn parse(plaintext: &str) -> Option<Self> {
+        Self::parse_with_prefix(plaintext, TOKEN_PREFIX)
+    }
+
+    pub(crate) fn parse_admin(plaintext: &str) -> Option<Self> {
+        Self::parse_with_prefix(plaintext, ADMIN_TOKEN_PREFIX)
+    }
+
+    fn parse_with_prefix(plaintext: &str, prefix: &str) -> Option<Self> {
         // This will both reject tokens without a prefix and tokens of the wrong kind.
-        if !plaintext.starts_with(TOKEN_PREFIX) {
+        if !plaintext.starts_with(prefix) {
             return None;
         }
 
@@ -53,9 +66,17 @@ pub struct PlainToken(SecretString);
 
 impl PlainToken {
     pub(crate) fn generate() -> Self {
+        Self::generate_with_prefix(TOKEN_PREFIX)
+    }
+
+    pub(crate) fn generate_admin() -> Self {
+        Self::generate_with_prefix(ADMIN_TOKEN_PREFIX)
+    }
+
+    fn generate_with_prefix(prefix: &str) -> Self {
         let plaintext = format!(
             "{}{}",
-            TOKEN_PREFIX,
+            prefix,
             generate_secure_alphanumeric_string(TOKEN_LENGTH)
         )
         .into();
@@ -104,6 +125,17 @@ mod tests {
         assert_eq!(parsed.0.expose_secret(), token.hashed().0.expose_secret());
     }
 
+    #[test]
+    fn test_admin_tokens_are_a_separate_kind() {
+        let token = PlainToken::generate_admin();
+        assert_that!(token.expose_secret(), starts_with(ADMIN_TOKEN_PREFIX));
+        assert!(HashedToken::parse(token.expose_secret()).is_none());
+        assert!(HashedToken::parse_admin(token.expose_secret()).is_some());
+
+        let token = PlainToken::generate();
+        assert!(HashedToken::parse_admin(token.expose_secret()).is_none());
+    }
+
     #[test]
     fn test_parse_no_kind() {
         assert!(HashedToken::parse("nokind").is_none());
@@ -3,9 +3,9 @@ use secrecy::ExposeSecret;
 
 use crate::external_urls::remove_blocked_urls;
 use crate::models::{
-    ApiToken, Category, Crate, CrateOwnerInvitation, CreatedApiToken, Dependency, DependencyKind,
-    Keyword, Owner, ReverseDependency, Team, TopVersions, User, Version, VersionDownload,
-    VersionOwnerAction,
+    AdoptionRequest, ApiToken, Category, Crate, CrateOwnerInvitation, CreatedApiToken, Dependency,
+    DependencyKind, Keyword, Owner, ReverseDependency, Team, TopVersions, User, Version,
+    VersionAttestation, VersionDownload, VersionLintWarning, VersionMetadata, VersionOwnerAction,
 };
 use crate::util::rfc3339;
 use crates_io_github as github;
@@ -109,6 +109,49 @@ pub struct InvitationResponse {
     pub accepted: bool,
 }
 
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct EncodableCrateTransfer {
+    pub crate_id: i32,
+    pub crate_name: String,
+    /// The login of the owner who proposed the transfer.
+    pub proposed_by: String,
+    #[serde(with = "rfc3339")]
+    pub created_at: NaiveDateTime,
+    #[serde(with = "rfc3339")]
+    pub expires_at: NaiveDateTime,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+pub struct EncodableAdoptionRequest {
+    pub id: i32,
+    pub crate_name: String,
+    /// The login of the user who wants to adopt the crate.
+    pub user: String,
+    pub reason: String,
+    pub status: String,
+    #[serde(with = "rfc3339")]
+    pub created_at: NaiveDateTime,
+    /// The end of the waiting period, in which the owners can reject the request.
+    #[serde(with = "rfc3339")]
+    pub approvable_at: NaiveDateTime,
+}
+
+impl EncodableAdoptionRequest {
+    pub fn from(request: AdoptionRequest, crate_name: String, user: String) -> Self {
+        let status: &'static str = request.status.into();
+
+        EncodableAdoptionRequest {
+            id: request.id,
+            crate_name,
+            user,
+            reason: request.reason,
+            status: status.to_string(),
+            created_at: request.created_at,
+            approvable_at: request.approvable_at,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct EncodableDependency {
     pub id: i32,
@@ -621,6 +664,128 @@ impl EncodableVersion {
     }
 }
 
+/// Package metadata of a version, as extracted from its published tarball.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EncodableVersionMetadata {
+    pub edition: String,
+    pub has_build_script: bool,
+    pub lib: Option<serde_json::Value>,
+    pub bins: serde_json::Value,
+    pub badges: serde_json::Value,
+    pub layout: serde_json::Value,
+    pub files: serde_json::Value,
+    /// The contents of the `.cargo_vcs_info.json` file, or `None` if the
+    /// tarball does not contain one.
+    pub vcs: Option<EncodableVcsInfo>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EncodableVcsInfo {
+    pub path_in_vcs: String,
+    pub sha1: Option<String>,
+    pub dirty: Option<bool>,
+    pub verification: Option<EncodableVcsVerification>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EncodableVcsVerification {
+    pub status: String,
+    pub message: Option<String>,
+    #[serde(with = "rfc3339")]
+    pub verified_at: NaiveDateTime,
+}
+
+impl From<VersionMetadata> for EncodableVersionMetadata {
+    fn from(metadata: VersionMetadata) -> Self {
+        let VersionMetadata {
+            edition,
+            has_build_script,
+            lib,
+            bins,
+            badges,
+            layout,
+            files,
+            vcs_sha1,
+            vcs_dirty,
+            path_in_vcs,
+            vcs_verification_status,
+            vcs_verification_message,
+            vcs_verified_at,
+            ..
+        } = metadata;
+
+        let verification =
+            vcs_verification_status
+                .zip(vcs_verified_at)
+                .map(|(status, verified_at)| EncodableVcsVerification {
+                    status,
+                    message: vcs_verification_message,
+                    verified_at,
+                });
+
+        let vcs = path_in_vcs.map(|path_in_vcs| EncodableVcsInfo {
+            path_in_vcs,
+            sha1: vcs_sha1,
+            dirty: vcs_dirty,
+            verification,
+        });
+
+        Self {
+            edition,
+            has_build_script,
+            lib,
+            bins,
+            badges,
+            layout,
+            files,
+            vcs,
+        }
+    }
+}
+
+/// A lint warning that was reported when the version was published.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EncodableVersionLintWarning {
+    pub lint: String,
+    pub message: String,
+}
+
+impl From<VersionLintWarning> for EncodableVersionLintWarning {
+    fn from(warning: VersionLintWarning) -> Self {
+        let VersionLintWarning { lint, message, .. } = warning;
+        Self { lint, message }
+    }
+}
+
+/// A signed release attestation of a version.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EncodableVersionAttestation {
+    pub signer: String,
+    pub checksum: String,
+    #[serde(with = "rfc3339")]
+    pub created_at: NaiveDateTime,
+    /// The attestation document that was uploaded with the `.crate` file.
+    pub document: serde_json::Value,
+}
+
+impl EncodableVersionAttestation {
+    pub fn from(attestation: VersionAttestation, document: serde_json::Value) -> Self {
+        let VersionAttestation {
+            signer,
+            checksum,
+            created_at,
+            ..
+        } = attestation;
+
+        Self {
+            signer,
+            checksum,
+            created_at,
+            document,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct EncodableVersionLinks {
     pub dependencies: String,
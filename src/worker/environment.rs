@@ -1,6 +1,7 @@
 use crate::cloudfront::CloudFront;
 use crate::db::DieselPool;
 use crate::fastly::Fastly;
+use crate::scanning;
 use crate::storage::Storage;
 use crate::typosquat;
 use crate::Emails;
@@ -25,6 +26,9 @@ pub struct Environment {
     pub storage: Arc<Storage>,
     pub connection_pool: DieselPool,
     pub emails: Emails,
+    /// The configuration of the scans of new versions for malicious code.
+    #[builder(default)]
+    pub scanning: scanning::Config,
 
     /// A lazily initialised cache of the most popular crates ready to use in typosquatting checks.
     #[builder(default, setter(skip))]
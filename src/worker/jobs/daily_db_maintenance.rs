@@ -2,7 +2,7 @@ use crate::tasks::spawn_blocking;
 use crate::worker::Environment;
 use async_trait::async_trait;
 use crates_io_worker::BackgroundJob;
-use diesel::{sql_query, RunQueryDsl};
+use diesel::{select, sql_function, sql_query, RunQueryDsl};
 use std::sync::Arc;
 
 #[derive(Serialize, Deserialize)]
@@ -23,6 +23,10 @@ impl BackgroundJob for DailyDbMaintenance {
     /// We only need to keep 90 days of entries in `version_downloads`. Once we have a mechanism to
     /// archive daily download counts and drop historical data, we can drop this task and rely on
     /// auto-vacuum again.
+    ///
+    /// The number of reverse dependencies that is used for the relevance
+    /// ranking of search results is also refreshed here, since it changes
+    /// slowly and is expensive to calculate.
     async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
         spawn_blocking(move || {
             let mut conn = env.connection_pool.get()?;
@@ -30,6 +34,10 @@ impl BackgroundJob for DailyDbMaintenance {
             info!("Running VACUUM on version_downloads table");
             sql_query("VACUUM version_downloads;").execute(&mut *conn)?;
             info!("Finished running VACUUM on version_downloads table");
+
+            sql_function!(fn refresh_reverse_dependency_counts());
+            select(refresh_reverse_dependency_counts()).execute(&mut *conn)?;
+            info!("Finished running refresh_reverse_dependency_counts");
             Ok(())
         })
         .await
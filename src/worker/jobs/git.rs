@@ -135,6 +135,20 @@ pub fn get_index_data(name: &str, conn: &mut PgConnection) -> anyhow::Result<Opt
         return Ok(None);
     };
 
+    // Crates with unreviewed typosquatting findings can be kept out of the
+    // index until a reviewer has approved them.
+    if models::TyposquatFinding::holds_index_of(krate.id, conn)? {
+        info!("Crate is held from the index until its typosquatting findings are approved");
+        return Ok(None);
+    }
+
+    // Quarantined crates are removed from the index until the quarantine is
+    // lifted. Quarantined versions are left out by `index_metadata()`.
+    if models::is_crate_quarantined(krate.id, conn)? {
+        info!("Crate is removed from the index while it is quarantined");
+        return Ok(None);
+    }
+
     debug!("Gathering remaining index data");
     let crates = krate
         .index_metadata(conn)
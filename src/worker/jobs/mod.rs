@@ -8,16 +8,22 @@ use std::fmt::Display;
 mod daily_db_maintenance;
 pub mod dump_db;
 mod git;
+mod quarantine;
 mod readmes;
+mod scan;
 mod typosquat;
 mod update_downloads;
+mod verify_vcs;
 
 pub use self::daily_db_maintenance::DailyDbMaintenance;
 pub use self::dump_db::DumpDb;
 pub use self::git::{NormalizeIndex, SquashIndex, SyncToGitIndex, SyncToSparseIndex};
+pub use self::quarantine::SyncQuarantinedFiles;
 pub use self::readmes::RenderAndUploadReadme;
+pub use self::scan::ScanVersion;
 pub use self::typosquat::CheckTyposquat;
 pub use self::update_downloads::UpdateDownloads;
+pub use self::verify_vcs::VerifyVcsCommit;
 
 /// Enqueue both index sync jobs (git and sparse) for a crate, unless they
 /// already exist in the background job queue.
@@ -0,0 +1,94 @@
+use crate::models::{Crate, CrateVersions};
+use crate::schema::{crate_quarantines, version_quarantines, versions};
+use crate::tasks::spawn_blocking;
+use crate::worker::Environment;
+use anyhow::Context;
+use async_trait::async_trait;
+use crates_io_worker::BackgroundJob;
+use diesel::dsl::exists;
+use diesel::prelude::*;
+use std::sync::Arc;
+
+/// Moves the files of the quarantined versions of a crate out of the CDN, and
+/// moves them back after the quarantine has been lifted.
+#[derive(Serialize, Deserialize)]
+pub struct SyncQuarantinedFiles {
+    krate: String,
+}
+
+impl SyncQuarantinedFiles {
+    pub fn new(krate: impl Into<String>) -> Self {
+        let krate = krate.into();
+        Self { krate }
+    }
+}
+
+#[async_trait]
+impl BackgroundJob for SyncQuarantinedFiles {
+    const JOB_NAME: &'static str = "sync_quarantined_files";
+    const PRIORITY: i16 = 100;
+
+    type Context = Arc<Environment>;
+
+    #[instrument(skip_all, fields(krate.name = ?self.krate))]
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        info!("Syncing the files of quarantined versions");
+
+        let crate_name = self.krate.clone();
+        let pool = env.connection_pool.clone();
+        let versions = spawn_blocking(move || {
+            let conn = &mut pool.get()?;
+            let Some(krate): Option<Crate> =
+                Crate::by_exact_name(&crate_name).first(conn).optional()?
+            else {
+                return Ok(Vec::new());
+            };
+
+            let crate_quarantined = exists(crate_quarantines::table.find(krate.id));
+            let version_quarantined = exists(version_quarantines::table.find(versions::id));
+
+            krate
+                .all_versions()
+                .select((versions::num, crate_quarantined.or(version_quarantined)))
+                .load::<(String, bool)>(conn)
+                .context("Failed to load versions")
+        })
+        .await?;
+
+        let storage = &env.storage;
+        let mut moved = Vec::new();
+        for (version, quarantined) in &versions {
+            let paths = if *quarantined {
+                storage.quarantine_version_files(&self.krate, version).await
+            } else {
+                storage.restore_version_files(&self.krate, version).await
+            };
+            moved.extend(paths.context("Failed to move version files")?);
+        }
+
+        // Cached diffs contain the code of the quarantined versions, but they
+        // can be calculated again after the quarantine has been lifted.
+        if versions.iter().any(|(_, quarantined)| *quarantined) {
+            let future = storage.delete_all_diffs(&self.krate);
+            future.await.context("Failed to delete cached diffs")?;
+        }
+
+        for path in moved {
+            let path = path.to_string();
+
+            if let Some(cloudfront) = env.cloudfront() {
+                info!(%path, "Invalidating file on CloudFront");
+                let future = cloudfront.invalidate(&path);
+                future.await.context("Failed to invalidate CloudFront")?;
+            }
+
+            if let Some(fastly) = env.fastly() {
+                info!(%path, "Invalidating file on Fastly");
+                let future = fastly.invalidate(&path);
+                future.await.context("Failed to invalidate Fastly")?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -16,6 +16,8 @@ pub struct RenderAndUploadReadme {
     readme_path: String,
     base_url: Option<String>,
     pkg_path_in_vcs: Option<String>,
+    #[serde(default)]
+    vcs_sha1: Option<String>,
 }
 
 impl RenderAndUploadReadme {
@@ -25,6 +27,7 @@ impl RenderAndUploadReadme {
         readme_path: String,
         base_url: Option<String>,
         pkg_path_in_vcs: Option<String>,
+        vcs_sha1: Option<String>,
     ) -> Self {
         Self {
             version_id,
@@ -32,6 +35,7 @@ impl RenderAndUploadReadme {
             readme_path,
             base_url,
             pkg_path_in_vcs,
+            vcs_sha1,
         }
     }
 }
@@ -57,6 +61,7 @@ impl BackgroundJob for RenderAndUploadReadme {
                 &job.readme_path,
                 job.base_url.as_deref(),
                 job.pkg_path_in_vcs.as_ref(),
+                job.vcs_sha1.as_deref(),
             );
             if rendered.is_empty() {
                 return Ok(());
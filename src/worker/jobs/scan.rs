@@ -0,0 +1,161 @@
+//! Scan the files of a newly published version for signs of malicious code.
+
+use crate::admin::on_call;
+use crate::email::Email;
+use crate::models::VersionScanFinding;
+use crate::scanning::{self, Finding};
+use crate::tasks::spawn_blocking;
+use crate::worker::Environment;
+use async_trait::async_trait;
+use crates_io_worker::BackgroundJob;
+use std::sync::Arc;
+
+/// The contents of larger files are not read, so only the rules that look at
+/// file names and checksums apply to them.
+const MAX_SCANNED_FILE_SIZE: u64 = 5 * 1024 * 1024;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ScanVersion {
+    version_id: i32,
+    crate_name: String,
+    version: String,
+    max_unpack_size: u64,
+}
+
+impl ScanVersion {
+    pub fn new(version_id: i32, crate_name: String, version: String, max_unpack_size: u64) -> Self {
+        Self {
+            version_id,
+            crate_name,
+            version,
+            max_unpack_size,
+        }
+    }
+}
+
+#[async_trait]
+impl BackgroundJob for ScanVersion {
+    const JOB_NAME: &'static str = "scan_version";
+    const PRIORITY: i16 = -10;
+
+    type Context = Arc<Environment>;
+
+    #[instrument(skip_all, fields(krate.name = %self.crate_name, krate.version = %self.version))]
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        info!("Scanning version for signs of malicious code");
+
+        let tarball = env
+            .storage
+            .download_crate_file(&self.crate_name, &self.version)
+            .await?;
+
+        let job = self.clone();
+        spawn_blocking(move || {
+            let pkg_name = format!("{}-{}", job.crate_name, job.version);
+            let files = crates_io_tarball::read_files(
+                &pkg_name,
+                &*tarball,
+                job.max_unpack_size,
+                MAX_SCANNED_FILE_SIZE,
+                usize::MAX,
+            )?;
+
+            let findings = scanning::run(&files, &env.scanning);
+            if findings.is_empty() {
+                return Ok(());
+            }
+
+            info!(?findings, "Found potential signs of malicious code");
+
+            let conn = &mut env.connection_pool.get()?;
+            VersionScanFinding::insert_all(job.version_id, &findings, conn)?;
+
+            job.alert(&env, &findings);
+
+            Ok(())
+        })
+        .await
+    }
+}
+
+impl ScanVersion {
+    /// Notifies the configured addresses and, if enabled, pages the on-call
+    /// team. Failures are only logged, since the findings have already been
+    /// recorded.
+    fn alert(&self, env: &Environment, findings: &[Finding]) {
+        let email = MaliciousCodeEmail {
+            domain: &env.emails.domain,
+            crate_name: &self.crate_name,
+            version: &self.version,
+            findings,
+        };
+
+        for recipient in &env.scanning.notification_emails {
+            if let Err(error) = env.emails.send(recipient, email.clone()) {
+                error!(
+                    ?error,
+                    ?recipient,
+                    "Failed to send scan finding notification"
+                );
+            }
+        }
+
+        if env.scanning.page_on_call {
+            let event = on_call::Event::Trigger {
+                incident_key: Some(format!("scan_version_{}", self.version_id)),
+                description: format!(
+                    "Scanning {} v{} found {} potential signs of malicious code",
+                    self.crate_name,
+                    self.version,
+                    findings.len()
+                ),
+            };
+
+            if let Err(error) = event.send() {
+                error!(?error, "Failed to page on-call team about scan findings");
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MaliciousCodeEmail<'a> {
+    domain: &'a str,
+    crate_name: &'a str,
+    version: &'a str,
+    findings: &'a [Finding],
+}
+
+impl Email for MaliciousCodeEmail<'_> {
+    const SUBJECT: &'static str = "Possible malicious code in new version";
+
+    fn body(&self) -> String {
+        let findings = self
+            .findings
+            .iter()
+            .map(|finding| {
+                format!(
+                    "- {rule} in `{path}`: {message}\n",
+                    rule = finding.rule,
+                    path = finding.path,
+                    message = finding.message,
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("");
+
+        format!(
+            "Version {version} of crate {crate_name} may contain malicious code.\n
+Visit https://{domain}/crates/{crate_name}/{version} to see the offending version.\n
+\n
+Findings of the scan:\n
+\n
+{findings}\n
+If the findings are confirmed, the version can be quarantined and yanked with \
+`crates-admin scan quarantine {crate_name} {version}`.",
+            domain = self.domain,
+            crate_name = self.crate_name,
+            version = self.version,
+        )
+    }
+}
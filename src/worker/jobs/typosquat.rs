@@ -2,11 +2,14 @@ use async_trait::async_trait;
 use std::sync::Arc;
 
 use crates_io_worker::BackgroundJob;
-use diesel::PgConnection;
+use diesel::prelude::*;
 use typomania::Package;
 
 use crate::email::Email;
+use crate::models::NewTyposquatFinding;
+use crate::schema::crates;
 use crate::tasks::spawn_blocking;
+use crate::worker::jobs;
 use crate::{
     typosquat::{Cache, Crate},
     worker::Environment,
@@ -15,6 +18,9 @@ use crate::{
 
 /// A job to check the name of a newly published crate against the most popular crates to see if
 /// the new crate might be typosquatting an existing, popular crate.
+///
+/// Potential typosquatting is recorded in the `typosquat_findings` table for review, and the
+/// configured addresses are notified by e-mail.
 #[derive(Serialize, Deserialize, Debug)]
 pub struct CheckTyposquat {
     name: String,
@@ -51,31 +57,62 @@ fn check(
     conn: &mut PgConnection,
     name: &str,
 ) -> anyhow::Result<()> {
-    if let Some(harness) = cache.get_harness() {
-        info!(name, "Checking new crate for potential typosquatting");
-
-        let krate: Box<dyn Package> = Box::new(Crate::from_name(conn, name)?);
-        let squats = harness.check_package(name, krate)?;
-        if !squats.is_empty() {
-            // Well, well, well. For now, the only action we'll take is to e-mail people who
-            // hopefully care to check into things more closely.
-            info!(?squats, "Found potential typosquatting");
-
-            let email = PossibleTyposquatEmail {
-                domain: &emails.domain,
-                crate_name: name,
-                squats: &squats,
-            };
-
-            for recipient in cache.iter_emails() {
-                if let Err(error) = emails.send(recipient, email.clone()) {
-                    error!(
-                        ?error,
-                        ?recipient,
-                        "Failed to send possible typosquat notification"
-                    );
-                }
-            }
+    info!(name, "Checking new crate for potential typosquatting");
+
+    let krate: Box<dyn Package> = Box::new(Crate::from_name(conn, name)?);
+    let squats = cache.get_harness().check_package(name, krate)?;
+    if squats.is_empty() {
+        return Ok(());
+    }
+
+    // Well, well, well. The findings are recorded for review, and the crate is optionally kept
+    // out of the index until a reviewer has approved it.
+    info!(?squats, "Found potential typosquatting");
+
+    let crate_id = crates::table
+        .filter(crates::name.eq(name))
+        .select(crates::id)
+        .first(conn)?;
+
+    let hold = cache.hold_flagged_crates();
+    let messages = squats.iter().map(ToString::to_string).collect::<Vec<_>>();
+    let findings = squats
+        .iter()
+        .zip(&messages)
+        .map(|(squat, message)| NewTyposquatFinding {
+            crate_id,
+            squatted_crate: squat.package(),
+            message,
+            holds_index: hold,
+        })
+        .collect::<Vec<_>>();
+
+    let inserted = NewTyposquatFinding::insert_all(&findings, conn)?;
+    if inserted == 0 {
+        // The crate has been checked before, so everybody already knows about it.
+        return Ok(());
+    }
+
+    if hold {
+        // The index may already have been updated by the publish, so it needs to be synced
+        // again to remove the crate.
+        jobs::enqueue_sync_to_index(name, conn)?;
+    }
+
+    let email = PossibleTyposquatEmail {
+        domain: &emails.domain,
+        crate_name: name,
+        squats: &squats,
+        held: hold,
+    };
+
+    for recipient in cache.iter_emails() {
+        if let Err(error) = emails.send(recipient, email.clone()) {
+            error!(
+                ?error,
+                ?recipient,
+                "Failed to send possible typosquat notification"
+            );
         }
     }
 
@@ -87,6 +124,7 @@ struct PossibleTyposquatEmail<'a> {
     domain: &'a str,
     crate_name: &'a str,
     squats: &'a [typomania::checks::Squat],
+    held: bool,
 }
 
 impl Email for PossibleTyposquatEmail<'_> {
@@ -104,11 +142,18 @@ impl Email for PossibleTyposquatEmail<'_> {
             .collect::<Vec<_>>()
             .join("");
 
+        let held = if self.held {
+            "The crate has been removed from the index until the findings are approved with \
+            `crates-admin typosquat approve`.\n\n"
+        } else {
+            ""
+        };
+
         format!(
             "New crate {crate_name} may be typosquatting one or more other crates.\n
 Visit https://{domain}/crates/{crate_name} to see the offending crate.\n
 \n
-Specific squat checks that triggered:\n
+{held}Specific squat checks that triggered:\n
 \n
 {squats}",
             domain = self.domain,
@@ -119,6 +164,9 @@ Specific squat checks that triggered:\n
 
 #[cfg(test)]
 mod tests {
+    use crate::models::{TyposquatFinding, TyposquatFindingStatus};
+    use crate::typosquat::Config;
+    use crate::worker::jobs::git::get_index_data;
     use crate::{test_util::pg_connection, typosquat::test_util::Faker};
     use lettre::Address;
 
@@ -134,7 +182,11 @@ mod tests {
         faker.crate_and_version("my-crate", "It's awesome", &user, 100)?;
 
         // Prime the cache so it only includes the crate we just created.
-        let cache = Cache::new(vec!["admin@example.com".to_string()], faker.borrow_conn())?;
+        let cache = Cache::new(
+            vec!["admin@example.com".to_string()],
+            Config::embedded(),
+            faker.borrow_conn(),
+        )?;
 
         // Now we'll create new crates: one problematic, one not so.
         let other_user = faker.user("b")?;
@@ -165,6 +217,62 @@ mod tests {
         let sent = sent_mail.into_iter().next().unwrap();
         assert_eq!(&sent.0.to(), &["admin@example.com".parse::<Address>()?]);
 
+        // The findings are recorded for review, but don't hold the crate by default.
+        let findings = TyposquatFinding::for_crate(demon.id, &mut conn)?;
+        assert!(!findings.is_empty());
+        assert!(findings
+            .iter()
+            .all(|f| f.status == TyposquatFindingStatus::Pending && !f.holds_index));
+        assert!(TyposquatFinding::for_crate(angel.id, &mut conn)?.is_empty());
+
+        // Checking the crate again doesn't notify anybody twice.
+        check(&emails, &cache, &mut conn, &demon.name)?;
+        assert_eq!(emails.mails_in_memory().unwrap().len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn hold_flagged_crates() -> anyhow::Result<()> {
+        let emails = Emails::new_in_memory();
+        let mut faker = Faker::new(pg_connection());
+
+        let user = faker.user("a")?;
+        faker.crate_and_version("my-crate", "It's awesome", &user, 100)?;
+
+        let config = Config {
+            hold_flagged_crates: true,
+            ..Config::embedded().clone()
+        };
+        let cache = Cache::new(vec![], &config, faker.borrow_conn())?;
+
+        let other_user = faker.user("b")?;
+        let (demon, _version) = faker.crate_and_version(
+            "mycrate",
+            "I'm even more innocent, obviously",
+            &other_user,
+            0,
+        )?;
+        let mut conn = faker.into_conn();
+
+        assert!(get_index_data(&demon.name, &mut conn)?.is_some());
+
+        check(&emails, &cache, &mut conn, &demon.name)?;
+        assert!(get_index_data(&demon.name, &mut conn)?.is_none());
+
+        let ids = TyposquatFinding::for_crate(demon.id, &mut conn)?
+            .into_iter()
+            .map(|f| f.id)
+            .collect::<Vec<_>>();
+        TyposquatFinding::review(
+            &ids,
+            TyposquatFindingStatus::Approved,
+            "admin",
+            None,
+            &mut conn,
+        )?;
+        assert!(get_index_data(&demon.name, &mut conn)?.is_some());
+
         Ok(())
     }
 }
@@ -0,0 +1,505 @@
+//! Verify that the contents of a published tarball match the git commit that
+//! is recorded in its `.cargo_vcs_info.json` file.
+
+use crate::models::{VcsVerificationStatus, VersionMetadata};
+use crate::tasks::spawn_blocking;
+use crate::worker::Environment;
+use anyhow::Context;
+use async_trait::async_trait;
+use crates_io_tarball::SourceFile;
+use crates_io_worker::BackgroundJob;
+use git2::{Commit, ObjectType, Oid, Repository, Tree};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use url::Url;
+
+/// The maximum number of mismatched files that are listed in the
+/// verification message.
+const MAX_LISTED_MISMATCHES: usize = 10;
+
+/// The hosts that commits are fetched from. The repository URL is chosen by
+/// the publisher, so arbitrary hosts would let them make the worker connect
+/// to internal services.
+const ALLOWED_HOSTS: &[&str] = &[
+    "github.com",
+    "gitlab.com",
+    "codeberg.org",
+    "bitbucket.org",
+    "git.sr.ht",
+];
+
+/// How long connecting to the repository and fetching the commit may take.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The maximum number of bytes that are downloaded from the repository.
+const MAX_FETCH_SIZE: u64 = 100 * 1024 * 1024;
+
+/// How often the fetch is checked for the timeout and the size limit.
+const FETCH_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct VerifyVcsCommit {
+    version_id: i32,
+    crate_name: String,
+    version: String,
+    repository: String,
+    path_in_vcs: String,
+    sha1: String,
+    max_unpack_size: u64,
+}
+
+impl VerifyVcsCommit {
+    pub fn new(
+        version_id: i32,
+        crate_name: String,
+        version: String,
+        repository: String,
+        path_in_vcs: String,
+        sha1: String,
+        max_unpack_size: u64,
+    ) -> Self {
+        Self {
+            version_id,
+            crate_name,
+            version,
+            repository,
+            path_in_vcs,
+            sha1,
+            max_unpack_size,
+        }
+    }
+}
+
+#[async_trait]
+impl BackgroundJob for VerifyVcsCommit {
+    const JOB_NAME: &'static str = "verify_vcs_commit";
+    const PRIORITY: i16 = -10;
+
+    type Context = Arc<Environment>;
+
+    #[instrument(skip_all, fields(krate.name = %self.crate_name, krate.version = %self.version))]
+    async fn run(&self, env: Self::Context) -> anyhow::Result<()> {
+        info!(repository = %self.repository, sha1 = %self.sha1, "Verifying VCS commit");
+
+        let tarball = env
+            .storage
+            .download_crate_file(&self.crate_name, &self.version)
+            .await?;
+
+        let job = self.clone();
+        spawn_blocking(move || {
+            let pkg_name = format!("{}-{}", job.crate_name, job.version);
+
+            // Only the checksums are needed for the comparison, so none of
+            // the file contents are kept in memory.
+            let max_unpack = job.max_unpack_size;
+            let files =
+                crates_io_tarball::read_files(&pkg_name, &*tarball, max_unpack, 0, usize::MAX)?;
+
+            let result = check_repository_url(&job.repository)
+                .and_then(|()| verify(&job.repository, &job.sha1, &job.path_in_vcs, &files));
+            let (status, message) = match result {
+                Ok(mismatches) if mismatches.is_empty() => (VcsVerificationStatus::Verified, None),
+                Ok(mismatches) => {
+                    info!(?mismatches, "Tarball does not match VCS commit");
+                    let message = mismatch_message(&job.sha1, &mismatches);
+                    (VcsVerificationStatus::Mismatch, Some(message))
+                }
+                Err(error) => {
+                    warn!(%error, "Failed to verify VCS commit");
+                    (VcsVerificationStatus::Failed, Some(format!("{error:#}")))
+                }
+            };
+
+            let conn = &mut env.connection_pool.get()?;
+            VersionMetadata::record_vcs_verification(
+                job.version_id,
+                status,
+                message.as_deref(),
+                conn,
+            )?;
+
+            Ok(())
+        })
+        .await
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Mismatch {
+    /// The file does not exist in the commit.
+    Missing(String),
+    /// The file exists in the commit, but with different contents.
+    Modified(String),
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mismatch::Missing(path) => write!(f, "`{path}` (missing)"),
+            Mismatch::Modified(path) => write!(f, "`{path}` (modified)"),
+        }
+    }
+}
+
+fn mismatch_message(sha1: &str, mismatches: &[Mismatch]) -> String {
+    let mut listed = mismatches
+        .iter()
+        .take(MAX_LISTED_MISMATCHES)
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+
+    if mismatches.len() > MAX_LISTED_MISMATCHES {
+        listed.push(format!(
+            "and {} more",
+            mismatches.len() - MAX_LISTED_MISMATCHES
+        ));
+    }
+
+    let files = if mismatches.len() == 1 {
+        "file does"
+    } else {
+        "files do"
+    };
+
+    format!(
+        "{} {files} not match commit {sha1}: {}",
+        mismatches.len(),
+        listed.join(", ")
+    )
+}
+
+/// Fetches the commit from the repository and compares it with the files of
+/// the tarball, returning the list of files that don't match.
+fn verify(
+    repository: &str,
+    sha1: &str,
+    path_in_vcs: &str,
+    files: &BTreeMap<String, SourceFile>,
+) -> anyhow::Result<Vec<Mismatch>> {
+    let oid = Oid::from_str(sha1).with_context(|| format!("invalid commit hash `{sha1}`"))?;
+
+    let tempdir = tempfile::tempdir()?;
+    let repo = Repository::init_bare(tempdir.path())?;
+    let commit = fetch_commit(&repo, repository, oid)
+        .with_context(|| format!("failed to fetch commit {sha1} from {repository}"))?;
+    let tree = commit.tree()?;
+
+    let pkg_root = Path::new(path_in_vcs);
+
+    let mut mismatches = Vec::new();
+    for (path, file) in files {
+        let repo_path = match path.as_str() {
+            // These files are generated by cargo when packaging the crate.
+            ".cargo_vcs_info.json" | "Cargo.toml" | "Cargo.lock" => continue,
+            // This is the original, unnormalized manifest.
+            "Cargo.toml.orig" => "Cargo.toml",
+            path => path,
+        };
+
+        // Files like the readme or license can be located outside of the
+        // package directory, in which case cargo copies them into the
+        // package root.
+        let mut checksum = blob_checksum(&repo, &tree, &pkg_root.join(repo_path))?;
+        if checksum.is_none() && !path_in_vcs.is_empty() && !repo_path.contains('/') {
+            checksum = blob_checksum(&repo, &tree, Path::new(repo_path))?;
+        }
+
+        match checksum {
+            None => mismatches.push(Mismatch::Missing(path.clone())),
+            Some(checksum) if checksum != file.checksum => {
+                mismatches.push(Mismatch::Modified(path.clone()))
+            }
+            Some(_) => {}
+        }
+    }
+
+    Ok(mismatches)
+}
+
+/// Checks that the repository is hosted on one of the [`ALLOWED_HOSTS`].
+fn check_repository_url(repository: &str) -> anyhow::Result<()> {
+    let url = Url::parse(repository).with_context(|| format!("invalid URL `{repository}`"))?;
+
+    let is_allowed = url.scheme() == "https"
+        && url.username().is_empty()
+        && url.password().is_none()
+        && url.port().is_none()
+        && url
+            .host_str()
+            .is_some_and(|host| ALLOWED_HOSTS.contains(&host));
+
+    if !is_allowed {
+        anyhow::bail!(
+            "commits can only be verified for https repositories on {}",
+            ALLOWED_HOSTS.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+/// Fetches only the given commit, without its history.
+///
+/// The fetch runs as a `git` subprocess, since git2 can't limit the time it
+/// waits for the server to connect or respond. The process is killed once it
+/// takes longer than [`FETCH_TIMEOUT`] or the repository grows larger than
+/// [`MAX_FETCH_SIZE`] bytes, and it is always waited for, so that it never
+/// outlives the repository directory.
+fn fetch_commit<'a>(repo: &'a Repository, url: &str, oid: Oid) -> anyhow::Result<Commit<'a>> {
+    let deadline = Instant::now() + FETCH_TIMEOUT;
+
+    let mut child = Command::new("git")
+        .arg("--git-dir")
+        .arg(repo.path())
+        .args(["fetch", "--quiet", "--no-tags", "--depth=1", url])
+        .arg(oid.to_string())
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("failed to run `git fetch`")?;
+
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+
+        let too_large = dir_size(repo.path()) > MAX_FETCH_SIZE;
+        if too_large || Instant::now() >= deadline {
+            // Killing fails if the process exited in the meantime, which is
+            // fine since it is waited for either way.
+            let _ = child.kill();
+            child.wait()?;
+
+            if too_large {
+                anyhow::bail!("repository is larger than {MAX_FETCH_SIZE} bytes");
+            }
+            anyhow::bail!("timed out after {} seconds", FETCH_TIMEOUT.as_secs());
+        }
+
+        thread::sleep(FETCH_POLL_INTERVAL);
+    };
+
+    if !status.success() {
+        let mut stderr = String::new();
+        if let Some(mut pipe) = child.stderr.take() {
+            pipe.read_to_string(&mut stderr)?;
+        }
+        anyhow::bail!("`git fetch` failed ({status}): {}", stderr.trim());
+    }
+
+    Ok(repo.find_commit(oid)?)
+}
+
+/// Returns the total size of the files in a directory, including its
+/// subdirectories. Files that disappear while they are counted are skipped.
+fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| match entry.metadata() {
+            Ok(metadata) if metadata.is_dir() => dir_size(&entry.path()),
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// Returns the SHA256 checksum of the file at `path` in the tree, or `None`
+/// if there is no file at that path.
+fn blob_checksum(
+    repo: &Repository,
+    tree: &Tree<'_>,
+    path: &Path,
+) -> Result<Option<[u8; 32]>, git2::Error> {
+    let entry = match tree.get_path(path) {
+        Ok(entry) => entry,
+        Err(error) if error.code() == git2::ErrorCode::NotFound => return Ok(None),
+        Err(error) => return Err(error),
+    };
+
+    if entry.kind() != Some(ObjectType::Blob) {
+        return Ok(None);
+    }
+
+    let blob = repo.find_blob(entry.id())?;
+    Ok(Some(Sha256::digest(blob.content()).into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crates_io_tarball::TarballBuilder;
+    use git2::Signature;
+
+    /// Creates a bare repository with a single commit containing the given
+    /// files, and returns the repository and the hash of the commit.
+    fn repository(files: &[(&str, &str)]) -> (tempfile::TempDir, String) {
+        let tempdir = tempfile::tempdir().unwrap();
+        let repo = Repository::init_bare(tempdir.path()).unwrap();
+
+        let mut index = repo.index().unwrap();
+        for (path, contents) in files {
+            let entry = git2::IndexEntry {
+                ctime: git2::IndexTime::new(0, 0),
+                mtime: git2::IndexTime::new(0, 0),
+                dev: 0,
+                ino: 0,
+                mode: 0o100644,
+                uid: 0,
+                gid: 0,
+                file_size: contents.len() as u32,
+                id: Oid::zero(),
+                flags: 0,
+                flags_extended: 0,
+                path: path.as_bytes().to_vec(),
+            };
+            index.add_frombuffer(&entry, contents.as_bytes()).unwrap();
+        }
+
+        let tree_id = index.write_tree_to(&repo).unwrap();
+        let tree = repo.find_tree(tree_id).unwrap();
+        let signature = Signature::now("foo", "foo@example.com").unwrap();
+        let commit = repo
+            .commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+
+        (tempdir, commit.to_string())
+    }
+
+    fn files(files: &[(&str, &str)]) -> BTreeMap<String, SourceFile> {
+        let tarball = files
+            .iter()
+            .fold(TarballBuilder::new(), |builder, (path, contents)| {
+                builder.add_file(&format!("foo-1.0.0/{path}"), contents.as_bytes())
+            })
+            .build();
+
+        crates_io_tarball::read_files("foo-1.0.0", &*tarball, u64::MAX, 0, usize::MAX).unwrap()
+    }
+
+    fn url(tempdir: &tempfile::TempDir) -> String {
+        tempdir.path().to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn matching_commit() {
+        let (repo, sha1) = repository(&[
+            ("Cargo.toml", "[package]\nname = \"foo\"\n"),
+            ("src/lib.rs", "pub fn foo() {}\n"),
+        ]);
+
+        let files = files(&[
+            ("Cargo.toml", "# normalized\n"),
+            ("Cargo.toml.orig", "[package]\nname = \"foo\"\n"),
+            (".cargo_vcs_info.json", "{}"),
+            ("src/lib.rs", "pub fn foo() {}\n"),
+        ]);
+
+        let mismatches = assert_ok!(verify(&url(&repo), &sha1, "", &files));
+        assert_eq!(mismatches, vec![]);
+    }
+
+    #[test]
+    fn path_in_vcs() {
+        let (repo, sha1) = repository(&[
+            ("README.md", "# foo\n"),
+            ("foo/Cargo.toml", "[package]\nname = \"foo\"\n"),
+            ("foo/src/lib.rs", "pub fn foo() {}\n"),
+        ]);
+
+        let files = files(&[
+            ("Cargo.toml.orig", "[package]\nname = \"foo\"\n"),
+            ("README.md", "# foo\n"),
+            ("src/lib.rs", "pub fn foo() {}\n"),
+        ]);
+
+        let mismatches = assert_ok!(verify(&url(&repo), &sha1, "foo", &files));
+        assert_eq!(mismatches, vec![]);
+    }
+
+    #[test]
+    fn mismatched_files() {
+        let (repo, sha1) = repository(&[
+            ("Cargo.toml", "[package]\nname = \"foo\"\n"),
+            ("src/lib.rs", "pub fn foo() {}\n"),
+        ]);
+
+        let files = files(&[
+            ("Cargo.toml.orig", "[package]\nname = \"foo\"\n"),
+            ("src/lib.rs", "pub fn bar() {}\n"),
+            ("src/main.rs", "fn main() {}\n"),
+        ]);
+
+        let mismatches = assert_ok!(verify(&url(&repo), &sha1, "", &files));
+        assert_eq!(
+            mismatches,
+            vec![
+                Mismatch::Modified("src/lib.rs".into()),
+                Mismatch::Missing("src/main.rs".into()),
+            ]
+        );
+
+        assert_eq!(
+            mismatch_message(&sha1, &mismatches),
+            format!(
+                "2 files do not match commit {sha1}: `src/lib.rs` (modified), `src/main.rs` (missing)"
+            )
+        );
+    }
+
+    #[test]
+    fn unknown_commit() {
+        let (repo, _sha1) = repository(&[("src/lib.rs", "pub fn foo() {}\n")]);
+        let files = files(&[("src/lib.rs", "pub fn foo() {}\n")]);
+
+        let sha1 = "0123456789abcdef0123456789abcdef01234567";
+        assert_err!(verify(&url(&repo), sha1, "", &files));
+        assert_err!(verify(&url(&repo), "foo", "", &files));
+    }
+
+    #[test]
+    fn fetched_repository_size() {
+        let (repo, sha1) = repository(&[("src/lib.rs", "pub fn foo() {}\n")]);
+
+        let tempdir = tempfile::tempdir().unwrap();
+        let target = Repository::init_bare(tempdir.path()).unwrap();
+        let size = dir_size(tempdir.path());
+        assert!(size > 0);
+
+        let oid = Oid::from_str(&sha1).unwrap();
+        assert_ok!(fetch_commit(&target, &url(&repo), oid));
+        assert!(dir_size(tempdir.path()) > size);
+    }
+
+    #[test]
+    fn repository_hosts() {
+        assert_ok!(check_repository_url(
+            "https://github.com/rust-lang/crates.io"
+        ));
+        assert_ok!(check_repository_url("https://gitlab.com/foo/bar.git"));
+
+        assert_err!(check_repository_url(
+            "http://github.com/rust-lang/crates.io"
+        ));
+        assert_err!(check_repository_url("https://github.com:8080/foo/bar"));
+        assert_err!(check_repository_url("https://user@github.com/foo/bar"));
+        assert_err!(check_repository_url("https://github.com.evil.com/foo/bar"));
+        assert_err!(check_repository_url("https://169.254.169.254/latest"));
+        assert_err!(check_repository_url("https://localhost/foo"));
+        assert_err!(check_repository_url("file:///etc"));
+        assert_err!(check_repository_url("not a url"));
+    }
+}
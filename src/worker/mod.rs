@@ -24,9 +24,12 @@ impl RunnerExt for Runner<Arc<Environment>> {
             .register_job_type::<jobs::DumpDb>()
             .register_job_type::<jobs::NormalizeIndex>()
             .register_job_type::<jobs::RenderAndUploadReadme>()
+            .register_job_type::<jobs::ScanVersion>()
             .register_job_type::<jobs::SquashIndex>()
+            .register_job_type::<jobs::SyncQuarantinedFiles>()
             .register_job_type::<jobs::SyncToGitIndex>()
             .register_job_type::<jobs::SyncToSparseIndex>()
             .register_job_type::<jobs::UpdateDownloads>()
+            .register_job_type::<jobs::VerifyVcsCommit>()
     }
 }
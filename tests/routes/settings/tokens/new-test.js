@@ -143,6 +143,34 @@ module('/settings/tokens/new', function (hooks) {
     assert.dom('[data-test-api-token="1"] [data-test-expired-at]').doesNotExist();
   });
 
+  test('version restricted crate scopes', async function (assert) {
+    prepare(this);
+
+    await visit('/settings/tokens/new');
+    await fillIn('[data-test-name]', 'token-name');
+    await click('[data-test-scope="publish-update"]');
+    await click('[data-test-add-crate-pattern]');
+
+    await fillIn('[data-test-crate-pattern="0"] input', 'serde@^1.0');
+    assert
+      .dom('[data-test-crate-pattern="0"] [data-test-description]')
+      .hasText('Matches only the serde crate, but only versions matching ^1.0');
+
+    await fillIn('[data-test-crate-pattern="0"] input', 'serde-*@prerelease');
+    assert
+      .dom('[data-test-crate-pattern="0"] [data-test-description]')
+      .hasText('Matches all crates starting with serde-, but only their prerelease versions');
+
+    await fillIn('[data-test-crate-pattern="0"] input', 'serde@latest');
+    assert.dom('[data-test-crate-pattern="0"] [data-test-description]').hasText('Invalid crate name pattern');
+
+    await fillIn('[data-test-crate-pattern="0"] input', 'serde@>=1.2, <2');
+    await click('[data-test-generate]');
+
+    let token = this.server.schema.apiTokens.findBy({ name: 'token-name' });
+    assert.deepEqual(token.crateScopes, ['serde@>=1.2, <2']);
+  });
+
   test('token expiry', async function (assert) {
     prepare(this);
 